@@ -0,0 +1,108 @@
+use std::hint::black_box;
+
+use chess_lab::{
+    logic::Game,
+    parsing::{parse_pgn, parse_pgn_with_options, PgnParseOptions},
+};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+/// A middlegame position with pieces of every type, castling rights and an
+/// en passant capture available
+const MIDDLEGAME: &str = "r1bqk2r/pp2bppp/2n1pn2/2ppP3/3P4/2PB1N2/PP1N1PPP/R1BQK2R w KQkq d6 0 8";
+
+/// The moves of a full game, to measure long histories
+const GAME: &str = "1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 \
+    8. c3 O-O 9. h3 Nb8 10. d4 Nbd7 11. c4 c6 12. cxb5 axb5 13. Nc3 Bb7 14. Bg5 b4 \
+    15. Nb1 h6 16. Bh4 c5 17. dxe5 Nxe4 18. Bxe7 Qxe7 19. exd6 Qf6 20. Nbd2 Nxd6 *";
+
+fn move_generation(c: &mut Criterion) {
+    let start = Game::default();
+    let middlegame = Game::from_fen(MIDDLEGAME);
+
+    c.bench_function("legal_moves/start", |b| {
+        b.iter(|| black_box(&start).legal_moves())
+    });
+    c.bench_function("legal_moves/middlegame", |b| {
+        b.iter(|| black_box(&middlegame).legal_moves())
+    });
+    c.bench_function("pseudo_legal_moves/middlegame", |b| {
+        b.iter(|| black_box(&middlegame).pseudo_legal_moves())
+    });
+}
+
+fn san_parsing(c: &mut Criterion) {
+    let middlegame = Game::from_fen(MIDDLEGAME);
+
+    for san in ["Nf1", "exd6", "O-O", "Qa4"] {
+        c.bench_function(&format!("parse_move/{}", san), |b| {
+            b.iter(|| black_box(&middlegame).parse_move(black_box(san)))
+        });
+    }
+}
+
+fn making_moves(c: &mut Criterion) {
+    let middlegame = Game::from_fen(MIDDLEGAME);
+
+    c.bench_function("move_piece/undo", |b| {
+        b.iter_batched_ref(
+            || middlegame.clone(),
+            |game| {
+                game.move_piece(black_box("exd6")).unwrap();
+                game.undo();
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    c.bench_function("make_move/undo", |b| {
+        let moves = middlegame.legal_moves();
+        b.iter_batched_ref(
+            || middlegame.clone(),
+            |game| {
+                for mov in &moves {
+                    game.make_move(mov).unwrap();
+                    game.undo();
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    c.bench_function("position_hash", |b| {
+        b.iter(|| black_box(&middlegame).position_hash())
+    });
+}
+
+fn fen_emission(c: &mut Criterion) {
+    let start = Game::default();
+    let middlegame = Game::from_fen(MIDDLEGAME);
+
+    c.bench_function("fen/start", |b| b.iter(|| black_box(&start).fen()));
+    c.bench_function("fen/middlegame", |b| {
+        b.iter(|| black_box(&middlegame).fen())
+    });
+    c.bench_function("from_fen/middlegame", |b| {
+        b.iter(|| Game::from_fen(black_box(MIDDLEGAME)))
+    });
+}
+
+fn pgn_parsing(c: &mut Criterion) {
+    let study = include_str!("../data/ex1.pgn");
+    let game = parse_pgn_with_options(GAME, &PgnParseOptions::lenient()).unwrap();
+
+    c.bench_function("parse_pgn/game", |b| {
+        b.iter(|| parse_pgn_with_options(black_box(GAME), &PgnParseOptions::lenient()))
+    });
+    c.bench_function("parse_pgn/study", |b| {
+        b.iter(|| parse_pgn(black_box(study)))
+    });
+    c.bench_function("pgn/game", |b| b.iter(|| black_box(&game).pgn()));
+}
+
+criterion_group!(
+    benches,
+    move_generation,
+    san_parsing,
+    making_moves,
+    fen_emission,
+    pgn_parsing
+);
+criterion_main!(benches);
@@ -0,0 +1,22 @@
+#![no_main]
+
+use chess_lab::{logic::Game, parsing::validate_fen};
+use libfuzzer_sys::fuzz_target;
+
+// Loads the FEN and plays every legal move of the position
+fuzz_target!(|data: &[u8]| {
+    let Ok(fen) = std::str::from_utf8(data) else {
+        return;
+    };
+    if validate_fen(fen).is_err() {
+        return;
+    }
+
+    let game = Game::from_fen(fen);
+    let _ = game.validate();
+    for mov in game.legal_moves() {
+        let mut game = game.clone();
+        let _ = game.move_piece(&mov.to_string());
+        let _ = game.fen();
+    }
+});
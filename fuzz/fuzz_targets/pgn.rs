@@ -0,0 +1,18 @@
+#![no_main]
+
+use chess_lab::parsing::{validate_pgn, PgnParseOptions};
+use libfuzzer_sys::fuzz_target;
+
+// Parses the PGN with the limits meant for untrusted input
+fuzz_target!(|data: &[u8]| {
+    let Ok(pgn) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let options = PgnParseOptions {
+        allow_missing_tags: true,
+        ..PgnParseOptions::limited()
+    };
+    let _ = validate_pgn(pgn, &options);
+    let _ = validate_pgn(pgn, &PgnParseOptions::lenient());
+});
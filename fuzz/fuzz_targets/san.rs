@@ -0,0 +1,21 @@
+#![no_main]
+
+use chess_lab::{
+    logic::Game,
+    parsing::{validate_san, Notation},
+};
+use libfuzzer_sys::fuzz_target;
+
+// Plays the moves, separated by whitespace, from the starting position
+fuzz_target!(|data: &[u8]| {
+    let Ok(moves) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let mut game = Game::default();
+    for san in moves.split_whitespace() {
+        let valid = validate_san(&game, san).is_ok();
+        assert_eq!(game.move_piece_with(san, Notation::San).is_ok(), valid);
+    }
+    let _ = game.pgn();
+});
@@ -0,0 +1,205 @@
+use crate::{
+    constants::{find_eval, DrawReason, Eval, GameStatus, WinReason},
+    logic::Game,
+};
+
+/// The score given to a forced mate, in centipawns
+const MATE_SCORE: i32 = 100_000;
+
+/// The rules used to adjudicate an engine game before it's over, as done by
+/// cutechess-cli
+/// The moves are counted for both sides, so a rule over 8 moves needs 16
+/// consecutive plies. A count of 0 disables the rule
+///
+/// # Fields
+/// * `draw_move_number`: The first move number where a draw can be adjudicated
+/// * `draw_move_count`: The number of consecutive moves the evaluation has to
+///   stay within `draw_score`
+/// * `draw_score`: The largest evaluation, in centipawns, considered a draw
+/// * `resign_move_count`: The number of consecutive moves the evaluation has
+///   to stay beyond `resign_score`
+/// * `resign_score`: The evaluation, in centipawns, from which a side is lost
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdjudicationOptions {
+    pub draw_move_number: u32,
+    pub draw_move_count: u32,
+    pub draw_score: i32,
+    pub resign_move_count: u32,
+    pub resign_score: i32,
+}
+
+impl Default for AdjudicationOptions {
+    /// Creates the usual options: a draw if the evaluation stays within 10
+    /// centipawns for 8 moves after move 40, and a loss if it stays beyond 6
+    /// pawns for 3 moves
+    ///
+    /// # Returns
+    /// The default options
+    ///
+    fn default() -> AdjudicationOptions {
+        AdjudicationOptions {
+            draw_move_number: 40,
+            draw_move_count: 8,
+            draw_score: 10,
+            resign_move_count: 3,
+            resign_score: 600,
+        }
+    }
+}
+
+/// The result of an adjudicated game
+///
+/// # Fields
+/// * `ply`: The number of half moves played when the game was adjudicated
+/// * `status`: The result, a draw by agreement or a win by resignation
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Adjudication {
+    pub ply: u32,
+    pub status: GameStatus,
+}
+
+/// Adjudicates a game from the evaluations stored on its moves
+///
+/// The evaluations are read from the `[%eval]` comments of the current line,
+/// written from the point of view of white, as done by
+/// [`par_analyze`](super::par_analyze) or exported by Lichess. A move without
+/// an evaluation breaks every streak
+///
+/// # Arguments
+/// * `game`: The game
+/// * `opts`: The adjudication rules
+///
+/// # Returns
+/// The first point where a rule applies, or `None` if the game can't be
+/// adjudicated
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::{adjudicate, AdjudicationOptions};
+/// use chess_lab::constants::{GameStatus, WinReason};
+/// use chess_lab::parsing::{parse_pgn_with_options, PgnParseOptions};
+///
+/// let game = parse_pgn_with_options(
+///     "1. f3 { [%eval -0.5] } e5 { [%eval -0.4] } 2. g4 { [%eval -8.2] } \
+///      Nc6 { [%eval -7.5] } 3. h3 { [%eval #-1] } *",
+///     &PgnParseOptions::lenient(),
+/// ).unwrap();
+///
+/// let opts = AdjudicationOptions { resign_move_count: 1, ..Default::default() };
+/// let adjudication = adjudicate(&game, &opts).unwrap();
+/// assert_eq!(adjudication.ply, 4);
+/// assert_eq!(adjudication.status, GameStatus::BlackWins(WinReason::Resignation));
+/// ```
+///
+pub fn adjudicate(game: &Game, opts: &AdjudicationOptions) -> Option<Adjudication> {
+    let mut draw_streak = 0;
+    let mut white_lost_streak = 0;
+    let mut black_lost_streak = 0;
+
+    for (ply, node) in game.history.line_nodes().iter().enumerate() {
+        let node = node.borrow();
+        let Some(eval) = node.comment.as_deref().and_then(eval) else {
+            draw_streak = 0;
+            white_lost_streak = 0;
+            black_lost_streak = 0;
+            continue;
+        };
+
+        let streak = |streak: u32, holds: bool| if holds { streak + 1 } else { 0 };
+        draw_streak = streak(
+            draw_streak,
+            node.fullmove_number >= opts.draw_move_number && eval.abs() <= opts.draw_score,
+        );
+        white_lost_streak = streak(white_lost_streak, eval <= -opts.resign_score);
+        black_lost_streak = streak(black_lost_streak, eval >= opts.resign_score);
+
+        let reached = |streak: u32, count: u32| count > 0 && streak >= count * 2;
+        let status = if reached(white_lost_streak, opts.resign_move_count) {
+            GameStatus::BlackWins(WinReason::Resignation)
+        } else if reached(black_lost_streak, opts.resign_move_count) {
+            GameStatus::WhiteWins(WinReason::Resignation)
+        } else if reached(draw_streak, opts.draw_move_count) {
+            GameStatus::Draw(DrawReason::Agreement)
+        } else {
+            continue;
+        };
+        return Some(Adjudication {
+            ply: ply as u32 + 1,
+            status,
+        });
+    }
+    None
+}
+
+/// Reads the evaluation of a comment
+///
+/// # Arguments
+/// * `comment`: The comment
+///
+/// # Returns
+/// The evaluation in centipawns from the point of view of white, with mates
+/// worth `MATE_SCORE`, or `None` if there is no `[%eval]` command
+///
+fn eval(comment: &str) -> Option<i32> {
+    match find_eval(comment)? {
+        Eval::Cp(cp) => Some(cp),
+        Eval::Mate(moves) if moves < 0 => Some(-MATE_SCORE),
+        Eval::Mate(_) => Some(MATE_SCORE),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adjudicate, eval, AdjudicationOptions};
+    use crate::{
+        constants::{DrawReason, GameStatus},
+        logic::Game,
+    };
+
+    #[test]
+    fn test_eval() {
+        assert_eq!(eval("[%clk 0:01:00] [%eval 0.25] Nice"), Some(25));
+        assert_eq!(eval("[%eval -1.256]"), Some(-126));
+        assert_eq!(eval("[%eval #-3]"), Some(-100_000));
+        assert_eq!(eval("[%eval #2]"), Some(100_000));
+        assert_eq!(eval("[%eval ?]"), None);
+        assert_eq!(eval("Book"), None);
+    }
+
+    #[test]
+    fn test_adjudicate_draw() {
+        let mut game = Game::default();
+        let opts = AdjudicationOptions {
+            draw_move_number: 2,
+            draw_move_count: 2,
+            ..Default::default()
+        };
+        for (mov, comment) in [
+            ("Nf3", "[%eval 0.05]"),
+            ("Nf6", "[%eval 0.1]"),
+            ("Ng1", "[%eval 0]"),
+            ("Ng8", "Book"),
+            ("Nf3", "[%eval -0.1]"),
+            ("Nf6", "[%eval 0.02]"),
+            ("Ng1", "[%eval 0.03]"),
+        ] {
+            game.move_piece(mov).unwrap();
+            game.history.set_comment(Some(comment.to_string()));
+            assert_eq!(adjudicate(&game, &opts), None);
+        }
+
+        game.move_piece("Ng8").unwrap();
+        game.history.set_comment(Some("[%eval 0.0]".to_string()));
+        let adjudication = adjudicate(&game, &opts).unwrap();
+        assert_eq!(adjudication.ply, 8);
+        assert_eq!(adjudication.status, GameStatus::Draw(DrawReason::Agreement));
+
+        let disabled = AdjudicationOptions {
+            draw_move_count: 0,
+            ..opts
+        };
+        assert_eq!(adjudicate(&game, &disabled), None);
+    }
+}
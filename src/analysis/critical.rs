@@ -0,0 +1,227 @@
+use crate::{constants::MoveType, database::OpeningTree, logic::Game};
+
+use super::{exchanges::balance, find_novelty};
+
+/// The smallest change of material, in pawns, that makes a moment critical
+const MATERIAL_SWING: i32 = 3;
+
+/// The smallest number of checks in a row by the same side that makes a
+/// forcing sequence
+const FORCING_CHECKS: usize = 3;
+
+/// Why a moment of a game is critical
+///
+/// # Variants
+/// * `MaterialSwing`: The material changed once the captures that started
+///   with the move were over
+///     - The change, from White's point of view, in pawns
+/// * `BookDeviation`: The move left the opening book
+/// * `ForcingSequence`: The move starts a series of checks by the same side
+///     - The number of checks
+/// * `OnlyMove`: The move was the only legal one
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CriticalReason {
+    MaterialSwing(i32),
+    BookDeviation,
+    ForcingSequence(usize),
+    OnlyMove,
+}
+
+/// A critical moment of a game
+///
+/// # Fields
+/// * `ply`: The ply of the move, counted from 1 as in `Game::goto_ply`, so the
+///   position the move was played from is at `ply - 1`
+/// * `reason`: Why the moment is critical
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CriticalPosition {
+    pub ply: usize,
+    pub reason: CriticalReason,
+}
+
+/// What a move of the main line did, as needed to find critical moments
+///
+/// # Fields
+/// * `material`: The material balance after the move
+/// * `capture`: Whether the move captured
+/// * `check`: Whether the move gave check
+/// * `only_move`: Whether the move was the only legal one
+///
+struct PlyInfo {
+    material: i32,
+    capture: bool,
+    check: bool,
+    only_move: bool,
+}
+
+/// Finds the critical moments of the main line of a game with heuristics, for
+/// reports when no engine is available: large material swings, series of
+/// checks and moves that were the only legal ones
+///
+/// # Arguments
+/// * `game`: The game
+///
+/// # Returns
+/// The critical moments, ordered by ply
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::{critical_positions, CriticalReason};
+/// use chess_lab::parsing::{parse_pgn_with_options, PgnParseOptions};
+///
+/// // Légal's mate: White gives up the queen to mate
+/// let game = parse_pgn_with_options(
+///     "1. e4 e5 2. Nf3 d6 3. Bc4 Bg4 4. Nc3 g6 5. Nxe5 Bxd1 6. Bxf7+ Ke7 7. Nd5# 1-0",
+///     &PgnParseOptions::lenient(),
+/// )
+/// .unwrap();
+/// let critical: Vec<(usize, CriticalReason)> = critical_positions(&game)
+///     .iter()
+///     .map(|position| (position.ply, position.reason))
+///     .collect();
+///
+/// assert_eq!(
+///     critical,
+///     [(9, CriticalReason::MaterialSwing(-7)), (12, CriticalReason::OnlyMove)]
+/// );
+/// ```
+///
+pub fn critical_positions(game: &Game) -> Vec<CriticalPosition> {
+    let mut replay = game.replay();
+    let start = balance(&replay.board);
+    let mut plies = Vec::new();
+    for node in game.history.main_line_nodes() {
+        let only_move = replay.legal_moves().len() == 1;
+        if replay.move_piece(&node.borrow().san()).is_err() {
+            break;
+        }
+        plies.push(PlyInfo {
+            material: balance(&replay.board),
+            capture: matches!(
+                node.borrow().mov.move_type,
+                MoveType::Normal { capture: true, .. } | MoveType::EnPassant
+            ),
+            check: replay.check(),
+            only_move,
+        });
+    }
+
+    let mut critical = Vec::new();
+    let moment = |ply, reason| CriticalPosition { ply, reason };
+
+    // The material is compared once the captures are over, so an exchange
+    // isn't a swing
+    let mut settled = (0, start);
+    for (ply, info) in (1..).zip(&plies) {
+        if info.capture && ply < plies.len() {
+            continue;
+        }
+        let change = info.material - settled.1;
+        if change.abs() >= MATERIAL_SWING {
+            critical.push(moment(settled.0 + 1, CriticalReason::MaterialSwing(change)));
+        }
+        settled = (ply, info.material);
+    }
+
+    // The moves of a side are two plies apart
+    for (index, info) in plies.iter().enumerate() {
+        if !info.check || (index >= 2 && plies[index - 2].check) {
+            continue;
+        }
+        let count = plies[index..]
+            .iter()
+            .step_by(2)
+            .take_while(|info| info.check)
+            .count();
+        if count >= FORCING_CHECKS {
+            critical.push(moment(index + 1, CriticalReason::ForcingSequence(count)));
+        }
+    }
+
+    for (ply, info) in (1..).zip(&plies) {
+        if info.only_move {
+            critical.push(moment(ply, CriticalReason::OnlyMove));
+        }
+    }
+
+    critical.sort_by_key(|position| position.ply);
+    critical
+}
+
+/// Finds the critical moments of the main line of a game with heuristics, as
+/// `critical_positions` does, adding the first move that left an opening book
+///
+/// # Arguments
+/// * `game`: The game
+/// * `book`: The opening tree of the reference games
+///
+/// # Returns
+/// The critical moments, ordered by ply
+///
+pub fn critical_positions_with_book(game: &Game, book: &OpeningTree) -> Vec<CriticalPosition> {
+    let mut critical = critical_positions(game);
+    if let Some((ply, _)) = find_novelty(game, book) {
+        critical.push(CriticalPosition {
+            ply,
+            reason: CriticalReason::BookDeviation,
+        });
+        critical.sort_by_key(|position| position.ply);
+    }
+    critical
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        critical_positions, critical_positions_with_book, CriticalPosition, CriticalReason,
+    };
+    use crate::{
+        database::OpeningTree,
+        parsing::{parse_pgn_with_options, PgnParseOptions},
+    };
+
+    #[test]
+    fn test_critical_positions() {
+        let options = PgnParseOptions::lenient();
+        let reasons = |critical: Vec<CriticalPosition>| {
+            critical
+                .iter()
+                .map(|position| (position.ply, position.reason))
+                .collect::<Vec<(usize, CriticalReason)>>()
+        };
+
+        // Winning a pawn back isn't a swing, but losing the queen for a
+        // knight and a pawn is
+        let game = parse_pgn_with_options(
+            "1. e4 d5 2. exd5 Nf6 3. c4 c6 4. dxc6 Nxc6 5. d4 Nxd4 6. Qxd4 Qxd4 7. Nc3 *",
+            &options,
+        )
+        .unwrap();
+        let mut book = OpeningTree::new(10);
+        book.add(&parse_pgn_with_options("1. e4 d5 2. exd5 Qxd5 *", &options).unwrap());
+        assert_eq!(
+            reasons(critical_positions_with_book(&game, &book)),
+            [
+                (4, CriticalReason::BookDeviation),
+                (10, CriticalReason::MaterialSwing(-7)),
+            ]
+        );
+
+        // Three checks in a row by White, the replies to two of them forced
+        let game = parse_pgn_with_options(
+            "[FEN \"k7/8/2K5/8/8/8/8/7R w - - 0 1\"]\n1. Rh8+ Ka7 2. Rh7+ Ka8 3. Rh8+ Ka7 *",
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            reasons(critical_positions(&game)),
+            [
+                (1, CriticalReason::ForcingSequence(3)),
+                (2, CriticalReason::OnlyMove),
+                (6, CriticalReason::OnlyMove),
+            ]
+        );
+    }
+}
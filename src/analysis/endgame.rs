@@ -0,0 +1,380 @@
+use std::collections::HashMap;
+
+use crate::{
+    constants::{Color, GameStatus, PieceType, Position},
+    logic::{Board, Game},
+};
+
+use super::search::material_signature;
+
+/// The pieces that count for the material of a side, from the most to the
+/// least valuable, with their values in pawns
+pub(super) const PIECE_VALUES: [(PieceType, u32); 5] = [
+    (PieceType::Queen, 9),
+    (PieceType::Rook, 5),
+    (PieceType::Bishop, 3),
+    (PieceType::Knight, 3),
+    (PieceType::Pawn, 1),
+];
+
+/// The results of the games that ended in a class of endgames, from the side
+/// written first in the class
+///
+/// # Fields
+/// * `games`: The number of games, finished or not
+/// * `stronger_wins`: The games won by the side written first
+/// * `draws`: The games drawn
+/// * `weaker_wins`: The games won by the side written second
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EndgameStats {
+    pub games: usize,
+    pub stronger_wins: usize,
+    pub draws: usize,
+    pub weaker_wins: usize,
+}
+
+/// Classifies a position by its material, as endgames are named
+/// The stronger side is written first, whatever its color: the side with
+/// more material, or with the more valuable pieces if both have the same
+/// material, and White if both sides have the same pieces
+///
+/// # Arguments
+/// * `board`: The board
+///
+/// # Returns
+/// The class of the position, such as `KRPvKR` or `KBNvK`
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::endgame_class;
+/// use chess_lab::logic::Board;
+///
+/// assert_eq!(endgame_class(&Board::new("8/8/4k3/8/3K4/8/8/2bn4")), "KBNvK");
+/// assert_eq!(endgame_class(&Board::new("8/3r4/4k3/8/3K4/4P3/8/7R")), "KRPvKR");
+/// assert_eq!(endgame_class(&Board::new("8/8/2b1k3/8/3K4/8/8/6N1")), "KBvKN");
+/// ```
+///
+pub fn endgame_class(board: &Board) -> String {
+    classify(board).0
+}
+
+/// Counts the results of a collection of games by the class of the endgame
+/// they ended in
+/// The result is read from the `Result` tag, or from the status of the game
+/// if the tag is missing
+///
+/// # Arguments
+/// * `games`: The games, at their final position, as read from a PGN
+///
+/// # Returns
+/// The results of the games for every class of endgame
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::endgame_statistics;
+/// use chess_lab::logic::Game;
+///
+/// let mut won = Game::from_fen("8/8/8/8/8/2k5/8/K1r5 w - - 0 60");
+/// won.history.result = Some(String::from("0-1"));
+/// let mut drawn = Game::from_fen("8/8/8/8/8/2K5/8/k1R5 b - - 0 60");
+/// drawn.history.result = Some(String::from("1/2-1/2"));
+///
+/// let statistics = endgame_statistics([&won, &drawn]);
+/// assert_eq!(statistics["KRvK"].games, 2);
+/// assert_eq!(statistics["KRvK"].stronger_wins, 1);
+/// assert_eq!(statistics["KRvK"].draws, 1);
+/// ```
+///
+pub fn endgame_statistics<'a>(
+    games: impl IntoIterator<Item = &'a Game>,
+) -> HashMap<String, EndgameStats> {
+    let mut statistics: HashMap<String, EndgameStats> = HashMap::new();
+    for game in games {
+        let (class, stronger) = classify(&game.board);
+        let winner = match game.history.result.as_deref() {
+            Some("1-0") => Some(Some(Color::White)),
+            Some("0-1") => Some(Some(Color::Black)),
+            Some("1/2-1/2") => Some(None),
+            Some(_) => None,
+            None => match game.game_status {
+                GameStatus::InProgress => None,
+                GameStatus::WhiteWins(_) => Some(Some(Color::White)),
+                GameStatus::BlackWins(_) => Some(Some(Color::Black)),
+                GameStatus::Draw(_) => Some(None),
+            },
+        };
+
+        let stats = statistics.entry(class).or_default();
+        stats.games += 1;
+        match winner {
+            Some(Some(color)) if color == stronger => stats.stronger_wins += 1,
+            Some(Some(_)) => stats.weaker_wins += 1,
+            Some(None) => stats.draws += 1,
+            None => {}
+        }
+    }
+    statistics
+}
+
+/// Checks if a side has the opposition: the kings stand an even number of
+/// files and ranks apart, so the other side, to move, has to give way
+/// This covers the direct, distant and diagonal oppositions
+///
+/// # Arguments
+/// * `board`: The board, with the other side to move
+/// * `color`: The side that would have the opposition
+///
+/// # Returns
+/// Whether the kings are in opposition, `false` if a king is missing
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::has_opposition;
+/// use chess_lab::constants::Color;
+/// use chess_lab::logic::Board;
+///
+/// assert!(has_opposition(&Board::new("8/8/4k3/8/4K3/8/8/8"), Color::White));
+/// assert!(has_opposition(&Board::new("8/4k3/8/8/8/8/8/4K3"), Color::Black));
+/// assert!(!has_opposition(&Board::new("8/8/4k3/4K3/8/8/8/8"), Color::White));
+/// ```
+///
+pub fn has_opposition(board: &Board, color: Color) -> bool {
+    let king = |color| board.find(PieceType::King, color).first().copied();
+    let (Some(own), Some(other)) = (king(color), king(color.opposite())) else {
+        return false;
+    };
+    let (files, ranks) = (own.col.abs_diff(other.col), own.row.abs_diff(other.row));
+    files % 2 == 0 && ranks % 2 == 0 && files + ranks > 0
+}
+
+/// Returns the key squares of a pawn: the squares its king must reach to
+/// promote it against the lone enemy king, whoever is to move
+///
+/// # Arguments
+/// * `pawn`: The square of the pawn
+/// * `color`: The color of the pawn
+///
+/// # Returns
+/// The key squares, from the lowest rank and file
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::key_squares;
+/// use chess_lab::constants::{Color, Position};
+///
+/// let squares: Vec<String> = key_squares(&Position::from_string("e4"), Color::White)
+///     .iter()
+///     .map(|square| square.to_string())
+///     .collect();
+/// assert_eq!(squares, vec!["d6", "e6", "f6"]);
+///
+/// let squares: Vec<String> = key_squares(&Position::from_string("a5"), Color::Black)
+///     .iter()
+///     .map(|square| square.to_string())
+///     .collect();
+/// assert_eq!(squares, vec!["b1", "b2"]);
+/// ```
+///
+pub fn key_squares(pawn: &Position, color: Color) -> Vec<Position> {
+    // Ranks are counted from the side of the pawn, both ways
+    let row = |rank: u8| match color {
+        Color::White => rank,
+        Color::Black => 7 - rank,
+    };
+    let rank = row(pawn.row);
+    let (files, ranks) = if pawn.col == 0 || pawn.col == 7 {
+        // A rook pawn only promotes if the king gets to the file next to it
+        let file = if pawn.col == 0 { 1 } else { 6 };
+        (file..=file, 6..=7)
+    } else {
+        let files = pawn.col - 1..=pawn.col + 1;
+        match rank {
+            0..=3 => (files, rank + 2..=rank + 2),
+            4 | 5 => (files, rank + 1..=rank + 2),
+            _ => (files, 6..=7),
+        }
+    };
+
+    let mut squares: Vec<Position> = ranks
+        .flat_map(|rank| {
+            files
+                .clone()
+                .map(move |file| Position::new(file, row(rank)))
+        })
+        .filter(|square| square != pawn)
+        .collect();
+    squares.sort_by_key(|square| (square.row, square.col));
+    squares
+}
+
+/// Applies the rule of the square: whether a king catches a passed pawn
+/// running to promote, with no other piece to help
+///
+/// # Arguments
+/// * `king`: The square of the king chasing the pawn
+/// * `pawn`: The square of the pawn
+/// * `color`: The color of the pawn
+/// * `king_to_move`: Whether the side of the king is to move
+///
+/// # Returns
+/// Whether the king is inside the square of the pawn
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::in_pawn_square;
+/// use chess_lab::constants::{Color, Position};
+///
+/// let pawn = Position::from_string("a4");
+/// assert!(in_pawn_square(&Position::from_string("e4"), &pawn, Color::White, true));
+/// assert!(!in_pawn_square(&Position::from_string("e4"), &pawn, Color::White, false));
+/// // The first move of a pawn can be a double step
+/// let pawn = Position::from_string("h2");
+/// assert!(!in_pawn_square(&Position::from_string("b6"), &pawn, Color::White, true));
+/// ```
+///
+pub fn in_pawn_square(king: &Position, pawn: &Position, color: Color, king_to_move: bool) -> bool {
+    let (promotion_row, start_row) = match color {
+        Color::White => (7, 1),
+        Color::Black => (0, 6),
+    };
+    let mut steps = pawn.row.abs_diff(promotion_row);
+    if pawn.row == start_row {
+        steps -= 1;
+    }
+    let promotion = Position::new(pawn.col, promotion_row);
+    let distance = king
+        .col
+        .abs_diff(promotion.col)
+        .max(king.row.abs_diff(promotion.row));
+    distance + u8::from(!king_to_move) <= steps
+}
+
+/// Classifies a position by its material
+///
+/// # Arguments
+/// * `board`: The board
+///
+/// # Returns
+/// The class of the position and the color of the side written first
+///
+fn classify(board: &Board) -> (String, Color) {
+    let material = |color: Color| {
+        let counts =
+            PIECE_VALUES.map(|(piece_type, _)| board.bitboard(piece_type, color).count_ones());
+        let value: u32 = counts
+            .iter()
+            .zip(PIECE_VALUES)
+            .map(|(count, (_, value))| count * value)
+            .sum();
+        (value, counts)
+    };
+
+    let signature = material_signature(board);
+    let (white, black) = signature.split_once('v').unwrap_or((&signature, ""));
+    if material(Color::Black) > material(Color::White) {
+        (format!("{}v{}", black, white), Color::Black)
+    } else {
+        (signature.clone(), Color::White)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        endgame_class, endgame_statistics, has_opposition, in_pawn_square, key_squares,
+        EndgameStats,
+    };
+    use crate::{
+        constants::{Color, GameStatus, Position, WinReason},
+        logic::{Board, Game},
+    };
+
+    #[test]
+    fn test_endgame_statistics() {
+        let game = |fen: &str, result: Option<&str>| {
+            let mut game = Game::from_fen(fen);
+            game.history.result = result.map(String::from);
+            game
+        };
+        let mut resigned = game("8/8/8/4k3/8/8/1Q6/K7 b - - 0 70", None);
+        resigned.resign(Color::Black);
+        assert_eq!(
+            resigned.game_status,
+            GameStatus::WhiteWins(WinReason::Resignation)
+        );
+        assert_eq!(endgame_class(&resigned.board), "KQvK");
+
+        let games = [
+            resigned,
+            game("8/8/8/4K3/8/8/1q6/k7 w - - 0 70", Some("1-0")),
+            game("8/8/8/4K3/8/8/1q6/k7 w - - 0 70", Some("*")),
+            game("8/8/8/4k3/8/8/1P6/K7 w - - 0 70", Some("1/2-1/2")),
+            game("4k3/8/8/8/8/8/8/4K3 w - - 0 70", Some("1/2-1/2")),
+        ];
+        let statistics = endgame_statistics(&games);
+
+        assert_eq!(statistics.len(), 3);
+        assert_eq!(
+            statistics["KQvK"],
+            EndgameStats {
+                games: 3,
+                stronger_wins: 1,
+                draws: 0,
+                weaker_wins: 1,
+            }
+        );
+        assert_eq!(statistics["KPvK"].draws, 1);
+        assert_eq!(statistics["KvK"].games, 1);
+    }
+
+    #[test]
+    fn test_king_and_pawn_rules() {
+        let squares = |pawn: &str, color| -> Vec<String> {
+            key_squares(&Position::from_string(pawn), color)
+                .iter()
+                .map(|square| square.to_string())
+                .collect()
+        };
+        assert_eq!(
+            squares("b5", Color::White),
+            ["a6", "b6", "c6", "a7", "b7", "c7"]
+        );
+        assert_eq!(squares("d2", Color::Black), ["c1", "d1", "e1", "c2", "e2"]);
+        assert_eq!(squares("h6", Color::White), ["g7", "g8"]);
+
+        // Diagonal opposition, and kings on the same color of squares only
+        assert!(has_opposition(
+            &Board::new("8/8/6k1/8/4K3/8/8/8"),
+            Color::Black
+        ));
+        assert!(!has_opposition(
+            &Board::new("8/8/5k2/8/4K3/8/8/8"),
+            Color::Black
+        ));
+        assert!(!has_opposition(
+            &Board::new("8/8/8/8/4K3/8/8/8"),
+            Color::White
+        ));
+
+        let pawn = Position::from_string("d5");
+        assert!(in_pawn_square(
+            &Position::from_string("g3"),
+            &pawn,
+            Color::Black,
+            true
+        ));
+        assert!(!in_pawn_square(
+            &Position::from_string("h6"),
+            &pawn,
+            Color::Black,
+            true
+        ));
+        assert!(in_pawn_square(
+            &Position::from_string("d2"),
+            &pawn,
+            Color::Black,
+            false
+        ));
+    }
+}
@@ -0,0 +1,49 @@
+use crate::logic::Game;
+
+/// A move suggested by an engine, with its evaluation
+///
+/// # Fields
+/// * `mov`: The move in algebraic notation
+/// * `score`: The evaluation after the move in centipawns, from the point of
+///   view of the side that plays it
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineMove {
+    pub mov: String,
+    pub score: i32,
+}
+
+/// A chess engine that can suggest the best moves of a position
+///
+/// This is the interface between the analysis and training tools of the crate
+/// and any engine, either written in Rust or driven through a protocol like UCI
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::{Engine, EngineMove};
+/// use chess_lab::logic::Game;
+///
+/// struct AlwaysE4;
+///
+/// impl Engine for AlwaysE4 {
+///     fn best_moves(&mut self, _game: &Game, _count: usize) -> Vec<EngineMove> {
+///         vec![EngineMove { mov: String::from("e4"), score: 30 }]
+///     }
+/// }
+///
+/// let mut engine = AlwaysE4;
+/// assert_eq!(engine.best_moves(&Game::default(), 3)[0].mov, "e4");
+/// ```
+///
+pub trait Engine {
+    /// Analyses a position and suggests its best moves
+    ///
+    /// # Arguments
+    /// * `game`: The game at the position to analyse
+    /// * `count`: The maximum number of moves to suggest
+    ///
+    /// # Returns
+    /// The suggested moves, from the best to the worst
+    ///
+    fn best_moves(&mut self, game: &Game, count: usize) -> Vec<EngineMove>;
+}
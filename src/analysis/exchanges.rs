@@ -0,0 +1,133 @@
+use crate::{
+    constants::{Color, Move, MoveType},
+    logic::{Board, Game},
+};
+
+use super::endgame::PIECE_VALUES;
+
+/// A capture of a game
+///
+/// # Fields
+/// * `ply`: The ply of the capture, counted from 1 as in `Game::goto_ply`
+/// * `mov`: The capturing move
+/// * `balance`: The material of White minus the one of Black after the
+///   capture, in pawns
+/// * `recapture`: Whether the move captured on the square of the capture just
+///   before it, continuing an exchange
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capture {
+    pub ply: usize,
+    pub mov: Move,
+    pub balance: i32,
+    pub recapture: bool,
+}
+
+/// Lists the captures of the main line of a game, with the material balance
+/// after each of them, as needed to draw the material of a game over time
+///
+/// # Arguments
+/// * `game`: The game
+///
+/// # Returns
+/// The captures, in the order they were played
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::captures;
+/// use chess_lab::parsing::{parse_pgn_with_options, PgnParseOptions};
+///
+/// let game = parse_pgn_with_options(
+///     "1. e4 d5 2. exd5 Qxd5 3. Nc3 Qa5 4. d4 c6 5. Bc4 Bf5 6. Bxf7+ Kxf7 *",
+///     &PgnParseOptions::lenient(),
+/// )
+/// .unwrap();
+///
+/// let captures = captures(&game);
+/// let summary: Vec<(usize, i32, bool)> = captures
+///     .iter()
+///     .map(|capture| (capture.ply, capture.balance, capture.recapture))
+///     .collect();
+/// assert_eq!(summary, [(3, 1, false), (4, 0, true), (11, 1, false), (12, -2, true)]);
+/// ```
+///
+pub fn captures(game: &Game) -> Vec<Capture> {
+    let mut captures: Vec<Capture> = Vec::new();
+    let mut replay = game.replay();
+    for (ply, node) in (1..).zip(game.history.main_line_nodes()) {
+        let mov = node.borrow().mov.clone();
+        if replay.move_piece(&node.borrow().san()).is_err() {
+            break;
+        }
+        let is_capture = match mov.move_type {
+            MoveType::Normal { capture, .. } => capture,
+            MoveType::EnPassant => true,
+            MoveType::Castle { .. } => false,
+        };
+        if !is_capture {
+            continue;
+        }
+
+        let recapture = captures
+            .last()
+            .is_some_and(|last| last.ply + 1 == ply && last.mov.to == mov.to);
+        captures.push(Capture {
+            ply,
+            mov,
+            balance: balance(&replay.board),
+            recapture,
+        });
+    }
+    captures
+}
+
+/// Computes the material balance of a board
+///
+/// # Arguments
+/// * `board`: The board
+///
+/// # Returns
+/// The material of White minus the one of Black, in pawns
+///
+pub(super) fn balance(board: &Board) -> i32 {
+    PIECE_VALUES
+        .iter()
+        .map(|&(piece_type, value)| {
+            let count = |color| board.bitboard(piece_type, color).count_ones() as i32;
+            (count(Color::White) - count(Color::Black)) * value as i32
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::captures;
+    use crate::parsing::{parse_pgn_with_options, PgnParseOptions};
+
+    #[test]
+    fn test_captures() {
+        let game = parse_pgn_with_options(
+            "1. e4 d5 2. e5 f5 3. exf6 Nc6 4. fxg7 Bg4 5. gxh8=Q Bxd1 6. Qxg8 (6. Kxd1) 6... Bxc2 *",
+            &PgnParseOptions::lenient(),
+        )
+        .unwrap();
+        let summary: Vec<(usize, i32, bool)> = captures(&game)
+            .iter()
+            .map(|capture| (capture.ply, capture.balance, capture.recapture))
+            .collect();
+
+        // En passant captures and promotions count, and only captures on the
+        // same square are recaptures
+        assert_eq!(
+            summary,
+            [
+                (5, 1, false),
+                (7, 2, false),
+                (9, 15, false),
+                (10, 6, false),
+                (11, 9, false),
+                (12, 8, false),
+            ]
+        );
+    }
+}
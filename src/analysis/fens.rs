@@ -0,0 +1,186 @@
+use std::{
+    cell::RefCell,
+    collections::{HashSet, VecDeque},
+    io::BufRead,
+    rc::Rc,
+};
+
+use crate::{
+    constants::{pgn::PgnLine, Move},
+    errors::PgnError,
+    logic::Game,
+    parsing::PgnReader,
+};
+
+/// Extracts every unique position of the games of a PGN stream, as FEN
+///
+/// Positions from the main line and from every variation are visited, and
+/// those that match the filter are emitted the first time they are reached.
+/// Two positions are the same if they only differ in the halfmove clock or the
+/// fullmove number. Games that can't be parsed are skipped, and the extraction
+/// stops if the stream can't be read
+///
+/// # Arguments
+/// * `pgn_reader`: The reader of the PGN stream
+/// * `filter`: A predicate on the game at each position, only the positions
+///   for which it returns `true` are emitted
+///
+/// # Returns
+/// A lazy iterator over the FEN of the positions
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::extract_fens;
+/// use chess_lab::constants::{Color, PieceType};
+/// use chess_lab::logic::Game;
+/// use chess_lab::parsing::{PgnParseOptions, PgnReader};
+///
+/// let pgn = "1. e4 e5 2. Nf3 *\n\n[Event \"?\"]\n\n1. e4 e5 2. Qh5 *\n";
+/// let reader = PgnReader::with_options(pgn.as_bytes(), PgnParseOptions::lenient());
+///
+/// let fens: Vec<String> = extract_fens(reader, |_| true).collect();
+/// assert_eq!(fens.len(), 5);
+///
+/// let reader = PgnReader::with_options(pgn.as_bytes(), PgnParseOptions::lenient());
+/// let queen_out = |game: &Game| {
+///     game.board.find(PieceType::Queen, Color::White)[0].to_string() != "d1"
+/// };
+///
+/// assert_eq!(extract_fens(reader, queen_out).count(), 1);
+/// ```
+///
+pub fn extract_fens<R, F>(pgn_reader: PgnReader<R>, filter: F) -> impl Iterator<Item = String>
+where
+    R: BufRead,
+    F: FnMut(&Game) -> bool,
+{
+    FenExtractor {
+        reader: pgn_reader,
+        filter,
+        seen: HashSet::new(),
+        pending: VecDeque::new(),
+    }
+}
+
+/// The iterator returned by `extract_fens`, reading one game at a time
+///
+struct FenExtractor<R: BufRead, F: FnMut(&Game) -> bool> {
+    reader: PgnReader<R>,
+    filter: F,
+    seen: HashSet<String>,
+    pending: VecDeque<String>,
+}
+
+impl<R: BufRead, F: FnMut(&Game) -> bool> FenExtractor<R, F> {
+    /// Replays a line of the game tree, collecting the new positions that
+    /// match the filter
+    /// The tree is walked with a stack instead of recursion, so long games
+    /// can't overflow it
+    ///
+    /// # Arguments
+    /// * `game`: The game at the position before the node
+    /// * `node`: The node of the tree to replay, with all its continuations
+    ///
+    fn visit(&mut self, game: &mut Game, node: &Rc<RefCell<PgnLine<Move>>>) {
+        // `None` takes back the move of a node once all its lines are done
+        let mut stack = vec![Some(node.clone())];
+        while let Some(step) = stack.pop() {
+            let Some(node) = step else {
+                game.undo();
+                continue;
+            };
+            let node = node.as_ref().borrow();
+            if game.move_piece(&node.san()).is_err() {
+                continue;
+            }
+            self.collect(game);
+            stack.push(None);
+            stack.extend(node.lines.iter().rev().cloned().map(Some));
+        }
+    }
+
+    /// Queues the current position of the game if it's new and matches the filter
+    ///
+    /// # Arguments
+    /// * `game`: The game at the position to collect
+    ///
+    fn collect(&mut self, game: &Game) {
+        let fen = game.fen();
+        let key = fen.rsplitn(3, ' ').last().unwrap_or(&fen).to_string();
+        if !self.seen.contains(&key) && (self.filter)(game) {
+            self.seen.insert(key);
+            self.pending.push_back(fen);
+        }
+    }
+}
+
+impl<R: BufRead, F: FnMut(&Game) -> bool> Iterator for FenExtractor<R, F> {
+    type Item = String;
+
+    /// Returns the next new position, reading more games when needed
+    ///
+    /// # Returns
+    /// The FEN of the position, or `None` when the stream is exhausted
+    ///
+    fn next(&mut self) -> Option<String> {
+        while self.pending.is_empty() {
+            let game = match self.reader.next()? {
+                Ok(game) => game,
+                Err(PgnError::ReadError) => return None,
+                Err(_) => continue,
+            };
+
+            let mut replay = game.replay();
+            self.collect(&replay);
+            for node in game.history.root_nodes() {
+                self.visit(&mut replay, node);
+            }
+        }
+        self.pending.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::extract_fens;
+    use crate::parsing::{PgnParseOptions, PgnReader};
+
+    #[test]
+    fn test_extract_fens_variations() {
+        let pgn = "1. e4 (1. d4 d5) 1... e5 2. Nf3 Nc6 3. Ng1 Nb8 4. Nf3 *";
+        let reader = PgnReader::with_options(pgn.as_bytes(), PgnParseOptions::lenient());
+        let fens: Vec<String> = extract_fens(reader, |_| true).collect();
+
+        assert_eq!(
+            fens,
+            vec![
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1",
+                "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2",
+                "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2",
+                "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+                "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 3 3",
+                "rnbqkbnr/pppppppp/8/8/3P4/8/PPP1PPPP/RNBQKBNR b KQkq - 0 1",
+                "rnbqkbnr/ppp1pppp/8/3p4/3P4/8/PPP1PPPP/RNBQKBNR w KQkq - 0 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_fens_filter() {
+        let pgn = "[FEN \"4k3/8/8/8/8/8/4P3/4K3 w - - 0 1\"]\n\n1. e4 Kd7 2. e5 *\n\n[Event \"?\"]\n\n1. d4 *";
+        let reader = PgnReader::with_options(pgn.as_bytes(), PgnParseOptions::lenient());
+        let pawn_endgames = |game: &crate::logic::Game| {
+            game.board
+                .to_string()
+                .chars()
+                .filter(|c| c.is_alphabetic())
+                .count()
+                == 3
+        };
+        let fens: Vec<String> = extract_fens(reader, pawn_endgames).collect();
+
+        assert_eq!(fens.len(), 4);
+        assert_eq!(fens[0], "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{
+    constants::{Color, PieceType},
+    logic::{Board, Game},
+};
+
+/// Every type of piece
+const PIECE_TYPES: [PieceType; 6] = [
+    PieceType::King,
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::Pawn,
+];
+
+/// Counts of what the pieces of a color did on every square, indexed like
+/// `Square::ALL` (a1 is 0, h1 is 7 and h8 is 63)
+///
+/// # Fields
+/// * `visits`: The times a piece moved to the square, including the rook of a
+///   castle and the piece a pawn promoted to
+/// * `captures`: The times a piece of the other color was captured on the
+///   square, which for an en passant capture is the square of the captured pawn
+/// * `checks`: The times a piece on the square gave check, directly or by
+///   discovery
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Heatmap {
+    pub visits: [u32; 64],
+    pub captures: [u32; 64],
+    pub checks: [u32; 64],
+}
+
+impl Default for Heatmap {
+    /// Creates a heatmap with every count at zero
+    fn default() -> Heatmap {
+        Heatmap {
+            visits: [0; 64],
+            captures: [0; 64],
+            checks: [0; 64],
+        }
+    }
+}
+
+/// The heatmaps of both colors of a game
+///
+/// # Fields
+/// * `white`: The heatmap of White
+/// * `black`: The heatmap of Black
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Heatmaps {
+    pub white: Heatmap,
+    pub black: Heatmap,
+}
+
+impl Heatmaps {
+    /// Returns the heatmap of a color
+    ///
+    /// # Arguments
+    /// * `color`: The color
+    ///
+    /// # Returns
+    /// The heatmap of the pieces of the color
+    ///
+    pub fn get(&self, color: Color) -> &Heatmap {
+        match color {
+            Color::White => &self.white,
+            Color::Black => &self.black,
+        }
+    }
+}
+
+/// Counts, for every square and color, the visits of the pieces, the captures
+/// and the checks over the main line of a game
+///
+/// # Arguments
+/// * `game`: The game
+///
+/// # Returns
+/// The heatmaps of both colors
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::heatmaps;
+/// use chess_lab::constants::{Color, Square};
+/// use chess_lab::parsing::{parse_pgn_with_options, PgnParseOptions};
+///
+/// let game = parse_pgn_with_options(
+///     "1. e4 d5 2. exd5 Qxd5 3. Nc3 Qe5+ 4. Qe2 Qxe2+ 5. Ngxe2 *",
+///     &PgnParseOptions::lenient(),
+/// )
+/// .unwrap();
+/// let heatmaps = heatmaps(&game);
+///
+/// assert_eq!(heatmaps.white.visits[Square::E2 as usize], 2);
+/// assert_eq!(heatmaps.black.captures[Square::E2 as usize], 1);
+/// assert_eq!(heatmaps.black.checks[Square::E5 as usize], 1);
+/// assert_eq!(heatmaps.get(Color::White).checks, [0; 64]);
+/// ```
+///
+pub fn heatmaps(game: &Game) -> Heatmaps {
+    let mut heatmaps = Heatmaps::default();
+    let mut replay = game.replay();
+    for node in game.history.main_line_nodes() {
+        let color = replay.side_to_move();
+        let before = replay.board.clone();
+        if replay.move_piece(&node.borrow().san()).is_err() {
+            break;
+        }
+        let after = &replay.board;
+        let heatmap = match color {
+            Color::White => &mut heatmaps.white,
+            Color::Black => &mut heatmaps.black,
+        };
+
+        for piece_type in PIECE_TYPES {
+            let arrived = after.bitboard(piece_type, color) & !before.bitboard(piece_type, color);
+            count(&mut heatmap.visits, arrived);
+        }
+        count(
+            &mut heatmap.captures,
+            before.occupancy(color.opposite()) & !after.occupancy(color.opposite()),
+        );
+        count(&mut heatmap.checks, checkers(after, color));
+    }
+    heatmaps
+}
+
+/// Adds one to the count of every square of a bitboard
+///
+/// # Arguments
+/// * `counts`: The counts of every square
+/// * `squares`: The bitboard of the squares
+///
+fn count(counts: &mut [u32; 64], mut squares: u64) {
+    while squares != 0 {
+        counts[squares.trailing_zeros() as usize] += 1;
+        squares &= squares - 1;
+    }
+}
+
+/// Finds the pieces of a color that give check
+///
+/// # Arguments
+/// * `board`: The board
+/// * `color`: The color of the checking pieces
+///
+/// # Returns
+/// The bitboard of the pieces attacking the king of the other color
+///
+fn checkers(board: &Board, color: Color) -> u64 {
+    let Some(king) = board
+        .find(PieceType::King, color.opposite())
+        .first()
+        .copied()
+    else {
+        return 0;
+    };
+    board
+        .find_all(color)
+        .iter()
+        .filter(|position| board.can_capture(position, &king))
+        .fold(0, |checkers, position| checkers | position.to_bitboard())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::heatmaps;
+    use crate::{
+        constants::Square,
+        parsing::{parse_pgn_with_options, PgnParseOptions},
+    };
+
+    #[test]
+    fn test_heatmaps() {
+        let game = parse_pgn_with_options(
+            "1. e4 d5 2. e5 f5 3. exf6 Nc6 4. fxg7 Bg4 5. gxh8=Q Qd7 6. Nf3 O-O-O (6... Kd8) 7. Qxg8 *",
+            &PgnParseOptions::lenient(),
+        )
+        .unwrap();
+        let heatmaps = heatmaps(&game);
+        let (white, black) = (heatmaps.white, heatmaps.black);
+
+        // En passant captures on the square of the captured pawn
+        assert_eq!(white.captures[Square::F5 as usize], 1);
+        assert_eq!(white.visits[Square::F6 as usize], 1);
+        // The promoted queen visits the promotion square
+        assert_eq!(white.visits[Square::H8 as usize], 1);
+        assert_eq!(white.captures[Square::H8 as usize], 1);
+        // Castling moves the king and the rook, and the variation is ignored
+        assert_eq!(black.visits[Square::C8 as usize], 1);
+        assert_eq!(black.visits[Square::D8 as usize], 1);
+        assert_eq!(black.visits.iter().sum::<u32>(), 7);
+        assert_eq!(white.visits.iter().sum::<u32>(), 7);
+        assert_eq!(white.checks, [0; 64]);
+        assert_eq!(black.checks, [0; 64]);
+    }
+}
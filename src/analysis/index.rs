@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::{
+    constants::{pgn::PlyPath, PositionKey},
+    logic::Game,
+};
+
+/// An index of the positions of a collection of games, to find where a
+/// position occurred without replaying the games
+/// Positions are indexed by their hash (`Game::position_hash`), from the main
+/// line and from every variation
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::PositionIndex;
+/// use chess_lab::parsing::{parse_pgn_with_options, PgnParseOptions};
+///
+/// let options = PgnParseOptions::lenient();
+/// let mut index = PositionIndex::new();
+/// index.add(&parse_pgn_with_options("1. e4 e5 2. Nf3 Nc6 *", &options).unwrap());
+/// index.add(&parse_pgn_with_options("1. Nf3 Nc6 2. e4 e5 *", &options).unwrap());
+///
+/// let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+/// assert_eq!(index.find(fen), [(0, vec![0, 0, 0, 0]), (1, vec![0, 0, 0, 0])]);
+/// assert_eq!(index.game_count(), 2);
+/// ```
+///
+#[derive(Debug, Clone, Default)]
+pub struct PositionIndex {
+    positions: HashMap<u64, Vec<(usize, PlyPath)>>,
+    games: usize,
+}
+
+impl PositionIndex {
+    /// Creates an empty index
+    ///
+    /// # Returns
+    /// A new index
+    ///
+    pub fn new() -> PositionIndex {
+        PositionIndex::default()
+    }
+
+    /// Adds the positions of a game to the index
+    ///
+    /// # Arguments
+    /// * `game`: The game
+    ///
+    /// # Returns
+    /// The number of the game in the index, in the order the games were added
+    ///
+    pub fn add(&mut self, game: &Game) -> usize {
+        let number = self.games;
+        for (hash, path) in game.positions() {
+            self.positions.entry(hash).or_default().push((number, path));
+        }
+        self.games += 1;
+        number
+    }
+
+    /// Finds the games a position occurred in
+    ///
+    /// # Arguments
+    /// * `position`: The FEN of the position or its hash
+    ///
+    /// # Returns
+    /// The number of every game the position occurred in, with the path to
+    /// the move that reached it (`Game::goto_path`), once for every time it
+    /// occurred
+    ///
+    pub fn find(&self, position: impl Into<PositionKey>) -> &[(usize, PlyPath)] {
+        position
+            .into()
+            .hash()
+            .and_then(|hash| self.positions.get(&hash))
+            .map_or(&[], |found| found.as_slice())
+    }
+
+    /// Returns the number of games in the index
+    ///
+    /// # Returns
+    /// The number of games added
+    ///
+    pub fn game_count(&self) -> usize {
+        self.games
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PositionIndex;
+    use crate::{
+        logic::Game,
+        parsing::{parse_pgn_with_options, PgnParseOptions},
+    };
+
+    #[test]
+    fn test_position_index() {
+        let options = PgnParseOptions::lenient();
+        let mut index = PositionIndex::new();
+        index.add(
+            &parse_pgn_with_options("1. e4 (1. d4 d5 2. e4) 1... d5 2. d4 *", &options).unwrap(),
+        );
+        index.add(&Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1"));
+
+        let fen = "rnbqkbnr/ppp1pppp/8/3p4/3PP3/8/PPP2PPP/RNBQKBNR b KQkq - 0 2";
+        assert_eq!(index.find(fen), [(0, vec![0, 0, 0]), (0, vec![1, 0, 0])]);
+        assert_eq!(index.find("4k3/8/8/8/8/8/8/4K3 w - - 10 60"), [(1, vec![])]);
+        assert!(index.find("4k3/8/8/8/8/8/8/4K3 b - - 0 1").is_empty());
+        assert!(index.find("not a fen").is_empty());
+
+        // The paths lead back to the position
+        let mut game =
+            parse_pgn_with_options("1. e4 (1. d4 d5 2. e4) 1... d5 2. d4 *", &options).unwrap();
+        for (_, path) in index.find(fen) {
+            assert!(game.goto_path(path));
+            assert_eq!(game.fen(), fen);
+        }
+    }
+}
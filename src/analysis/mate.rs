@@ -0,0 +1,194 @@
+use crate::{
+    constants::{GameStatus, Move},
+    logic::Game,
+};
+
+/// A forced mate, as a tree of moves
+///
+/// # Fields
+/// * `mov`: The move of the side that mates, in SAN
+/// * `replies`: Every legal reply of the defending side, with the move that
+///   goes on mating after it, empty if `mov` is mate
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MateLine {
+    pub mov: String,
+    pub replies: Vec<(String, MateLine)>,
+}
+
+impl MateLine {
+    /// Returns the number of moves of the side that mates, against the
+    /// longest defence
+    ///
+    /// # Returns
+    /// The length of the mate, 1 for a mate in one
+    ///
+    pub fn depth(&self) -> usize {
+        1 + self
+            .replies
+            .iter()
+            .map(|(_, line)| line.depth())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Searches for a forced mate of the side to move, trying every move of both
+/// sides up to a number of moves
+/// The draws by repetition and by the fifty-move rule are ignored, so the
+/// search is only meant for puzzles and short mates
+///
+/// # Arguments
+/// * `game`: The game at the position to solve
+/// * `moves`: The largest number of moves of the side to move
+///
+/// # Returns
+/// The shortest forced mate, or `None` if there is none in that many moves
+/// or the game is over
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::mate_in;
+/// use chess_lab::logic::Game;
+///
+/// let game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+/// let mate = mate_in(&game, 2).unwrap();
+/// assert_eq!(mate.mov, "Ra8#");
+/// assert_eq!(mate.depth(), 1);
+///
+/// assert_eq!(mate_in(&Game::default(), 2), None);
+/// ```
+///
+pub fn mate_in(game: &Game, moves: usize) -> Option<MateLine> {
+    if game.game_status != GameStatus::InProgress {
+        return None;
+    }
+    let mut position = Game::from_fen(&game.fen());
+    (1..=moves).find_map(|depth| attack(&mut position, depth))
+}
+
+/// Checks if a move forces mate within a number of moves
+///
+/// # Arguments
+/// * `position`: The position, with the side that mates to move
+/// * `mov`: A legal move of the side to move
+/// * `moves`: The largest number of moves of the side that mates, this one
+///   included
+///
+/// # Returns
+/// Whether every defence loses to a mate in time
+///
+pub(crate) fn forces_mate(position: &mut Game, mov: &Move, moves: usize) -> bool {
+    let state = position.play_unrecorded(mov);
+    let mates = position.checkmate() || (moves > 1 && defend(position, moves - 1).is_some());
+    position.take_back_unrecorded(state);
+    mates
+}
+
+/// Looks for a move that forces mate
+///
+/// # Arguments
+/// * `position`: The position, with the side that mates to move
+/// * `depth`: The largest number of moves of the side that mates
+///
+/// # Returns
+/// The first move found that forces mate, with its tree
+///
+fn attack(position: &mut Game, depth: usize) -> Option<MateLine> {
+    for mov in position.legal_moves() {
+        let state = position.play_unrecorded(&mov);
+        let found = if position.checkmate() {
+            Some(Vec::new())
+        } else if depth > 1 {
+            defend(position, depth - 1)
+        } else {
+            None
+        };
+        let check = position.check();
+        position.take_back_unrecorded(state);
+
+        if let Some(replies) = found {
+            return Some(MateLine {
+                mov: san(mov, check, replies.is_empty()),
+                replies,
+            });
+        }
+    }
+    None
+}
+
+/// Checks that every reply of the defending side still loses to a mate
+///
+/// # Arguments
+/// * `position`: The position, with the defending side to move
+/// * `depth`: The largest number of moves left to the side that mates
+///
+/// # Returns
+/// Every reply with the move that mates after it, or `None` if a reply
+/// escapes or the position is stalemate
+///
+fn defend(position: &mut Game, depth: usize) -> Option<Vec<(String, MateLine)>> {
+    let replies = position.legal_moves();
+    if replies.is_empty() {
+        return None;
+    }
+
+    let mut lines = Vec::with_capacity(replies.len());
+    for reply in replies {
+        let state = position.play_unrecorded(&reply);
+        let check = position.check();
+        let line = attack(position, depth);
+        position.take_back_unrecorded(state);
+        lines.push((san(reply, check, false), line?));
+    }
+    Some(lines)
+}
+
+/// Writes a move found by the search with its check flags
+///
+/// # Arguments
+/// * `mov`: The move, as generated
+/// * `check`: Whether the move gives check
+/// * `checkmate`: Whether the move mates
+///
+/// # Returns
+/// The SAN of the move
+///
+fn san(mut mov: Move, check: bool, checkmate: bool) -> String {
+    mov.check = check;
+    mov.checkmate = checkmate;
+    mov.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mate_in;
+    use crate::logic::Game;
+
+    #[test]
+    fn test_mate_in() {
+        // Mate in two on the back rank, the rook has to take
+        let game = Game::from_fen("2r3k1/5ppp/8/8/8/8/4RPPP/4R1K1 w - - 0 1");
+        let mate = mate_in(&game, 3).unwrap();
+        assert_eq!(mate.depth(), 2);
+        assert_eq!(mate.mov, "Re8+");
+        assert_eq!(mate.replies[0].0, "Rxe8");
+        assert_eq!(mate_in(&game, 1), None);
+        for (reply, line) in &mate.replies {
+            let mut replay = game.fork();
+            replay.move_piece(&mate.mov).unwrap();
+            replay.move_piece(reply).unwrap();
+            replay.move_piece(&line.mov).unwrap();
+            assert!(replay.checkmate());
+        }
+
+        // Stalemating is not mating
+        let game = Game::from_fen("k7/8/1K6/8/8/8/8/2Q5 w - - 0 1");
+        let mate = mate_in(&game, 1).unwrap();
+        assert!(mate.mov.ends_with('#'));
+        assert_eq!(
+            mate_in(&Game::from_fen("k7/8/2K5/8/8/8/8/8 w - - 0 1"), 3),
+            None
+        );
+    }
+}
@@ -0,0 +1,30 @@
+mod adjudication;
+mod critical;
+mod endgame;
+mod engine;
+mod exchanges;
+mod fens;
+mod heatmap;
+mod index;
+mod mate;
+mod novelty;
+mod parallel;
+mod reachability;
+pub mod search;
+mod timeline;
+mod trajectory;
+
+pub use adjudication::*;
+pub use critical::*;
+pub use endgame::*;
+pub use engine::*;
+pub use exchanges::*;
+pub use fens::*;
+pub use heatmap::*;
+pub use index::*;
+pub use mate::*;
+pub use novelty::*;
+pub use parallel::*;
+pub use reachability::*;
+pub use timeline::*;
+pub use trajectory::*;
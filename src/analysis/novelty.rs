@@ -0,0 +1,89 @@
+use crate::{constants::Move, database::OpeningTree, logic::Game};
+
+/// Finds the novelty of a game: the first move of its main line that reaches
+/// a position no game of a reference tree reached
+/// Since the tree is keyed by position, a move that transposes into a known
+/// position isn't a novelty, even if it was never played from the position
+/// before it. The tree knows nothing past its maximum plies, so the moves
+/// after them are never novelties
+///
+/// # Arguments
+/// * `game`: The game
+/// * `reference`: The opening tree of the reference games
+///
+/// # Returns
+/// The ply of the novelty, counted from 1 as in `Game::goto_ply`, and its
+/// move, or `None` if every position of the main line up to the maximum
+/// plies of the tree is in it
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::find_novelty;
+/// use chess_lab::database::OpeningTree;
+/// use chess_lab::parsing::{parse_pgn_with_options, PgnParseOptions};
+///
+/// let options = PgnParseOptions::lenient();
+/// let mut reference = OpeningTree::new(20);
+/// reference.add(&parse_pgn_with_options("1. e4 c5 2. Nf3 d6 3. d4 *", &options).unwrap());
+/// reference.add(&parse_pgn_with_options("1. Nf3 d6 2. e4 c5 3. c3 *", &options).unwrap());
+///
+/// let game = parse_pgn_with_options("1. e4 c5 2. Nf3 d6 3. c3 Nf6 4. Be2 *", &options).unwrap();
+/// let (ply, mov) = find_novelty(&game, &reference).unwrap();
+/// assert_eq!((ply, mov.to_string()), (6, String::from("Nf6")));
+///
+/// let game = parse_pgn_with_options("1. e4 c5 2. Nf3 *", &options).unwrap();
+/// assert_eq!(find_novelty(&game, &reference), None);
+/// ```
+///
+pub fn find_novelty(game: &Game, reference: &OpeningTree) -> Option<(usize, Move)> {
+    let mut replay = game.replay();
+    let nodes = game.history.main_line_nodes();
+    let nodes = &nodes[..nodes.len().min(reference.max_plies())];
+    if reference.node(replay.position_hash()).is_none() {
+        return nodes.first().map(|node| (1, node.borrow().mov.clone()));
+    }
+
+    for (ply, node) in nodes.iter().enumerate() {
+        let mov = node.borrow().mov.clone();
+        replay.move_piece(&node.borrow().san()).ok()?;
+        if reference.node(replay.position_hash()).is_none() {
+            return Some((ply + 1, mov));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_novelty;
+    use crate::{
+        database::OpeningTree,
+        logic::Game,
+        parsing::{parse_pgn_with_options, PgnParseOptions},
+    };
+
+    #[test]
+    fn test_find_novelty() {
+        let options = PgnParseOptions::lenient();
+        let parse = |pgn: &str| parse_pgn_with_options(pgn, &options).unwrap();
+        let mut reference = OpeningTree::new(4);
+        reference.add(&parse("1. d4 d5 2. c4 e6 3. Nc3 Nf6 *"));
+
+        // Variations are ignored
+        let game = parse("1. d4 d5 (1... Nf6) 2. c4 c6 3. Nc3 *");
+        let (ply, mov) = find_novelty(&game, &reference).unwrap();
+        assert_eq!((ply, mov.to_string()), (4, String::from("c6")));
+        assert_eq!(find_novelty(&parse("1. d4 d5 2. c4 *"), &reference), None);
+
+        // The tree knows nothing past its maximum plies
+        assert_eq!(reference.max_plies(), 4);
+        let game = parse("1. d4 d5 2. c4 e6 3. Nc3 *");
+        assert_eq!(find_novelty(&game, &reference), None);
+
+        // A game from another starting position is new from its first move
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+        assert_eq!(find_novelty(&game, &reference), None);
+        game.move_piece("e4").unwrap();
+        assert_eq!(find_novelty(&game, &reference).unwrap().0, 1);
+    }
+}
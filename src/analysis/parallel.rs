@@ -0,0 +1,306 @@
+use std::{
+    sync::{Mutex, PoisonError},
+    thread,
+};
+
+use crate::logic::{Game, RuleSet};
+
+use super::Engine;
+
+/// The options of a bulk analysis
+///
+/// # Fields
+/// * `lines`: The number of engine moves suggested for each position, listed
+///   in the comment of the moves that aren't the best one
+/// * `skip_plies`: The number of plies left without analysis at the start of
+///   every game, to skip the opening book
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnalysisOptions {
+    pub lines: usize,
+    pub skip_plies: usize,
+}
+
+impl Default for AnalysisOptions {
+    /// Creates the default options, which analyse every move and only
+    /// suggest the best one
+    ///
+    /// # Returns
+    /// The default options
+    ///
+    fn default() -> AnalysisOptions {
+        AnalysisOptions {
+            lines: 1,
+            skip_plies: 0,
+        }
+    }
+}
+
+/// A game to analyse, in a form that can be sent to another thread
+///
+/// # Fields
+/// * `index`: The position of the game in the batch
+/// * `start_position`: The FEN of the starting position
+/// * `rules`: The draw rules of the game
+/// * `capture_king`: Whether the king must be captured to win the game
+/// * `moves`: The moves of the current line, in SAN
+///
+struct Job {
+    index: usize,
+    start_position: String,
+    rules: RuleSet,
+    capture_king: bool,
+    moves: Vec<String>,
+}
+
+/// Analyses a batch of games, distributing them across a pool of engines
+///
+/// Every engine of the pool runs on its own thread and takes the next game
+/// left as soon as it's done with the previous one, so long and short games
+/// balance out. The moves of the current line of each game are annotated with
+/// the evaluation of the position they reach as a `[%eval]` comment, from the
+/// point of view of white and in pawns, followed by the engine suggestions
+/// when the move played isn't the best one. Existing comments are kept after
+/// the annotations
+///
+/// # Arguments
+/// * `games`: The games to analyse
+/// * `engine_pool`: The engines, one thread is used for each of them
+/// * `opts`: The options of the analysis
+///
+/// # Returns
+/// The annotated games, in the same order. If the pool is empty the games are
+/// returned untouched
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::{par_analyze, AnalysisOptions, Engine, EngineMove};
+/// use chess_lab::logic::Game;
+///
+/// struct AlwaysE4;
+///
+/// impl Engine for AlwaysE4 {
+///     fn best_moves(&mut self, _game: &Game, _count: usize) -> Vec<EngineMove> {
+///         vec![EngineMove { mov: String::from("e4"), score: 30 }]
+///     }
+/// }
+///
+/// let mut game = Game::default();
+/// game.move_piece("d4").unwrap();
+///
+/// let games = par_analyze(vec![game], &mut [AlwaysE4, AlwaysE4], &AnalysisOptions::default());
+/// assert_eq!(games[0].pgn(), "1. d4 { [%eval -0.30] Best: e4 } *");
+/// ```
+///
+pub fn par_analyze<E: Engine + Send>(
+    mut games: Vec<Game>,
+    engine_pool: &mut [E],
+    opts: &AnalysisOptions,
+) -> Vec<Game> {
+    if engine_pool.is_empty() {
+        return games;
+    }
+
+    let jobs = Mutex::new(
+        games
+            .iter()
+            .enumerate()
+            .map(|(index, game)| Job {
+                index,
+                start_position: game.start_position.clone(),
+                rules: game.rules,
+                capture_king: game.captures_king(),
+                moves: game
+                    .history_vec()
+                    .iter()
+                    .map(|mov| mov.to_string())
+                    .collect(),
+            })
+            .collect::<Vec<Job>>()
+            .into_iter(),
+    );
+
+    let results: Vec<(usize, Vec<Option<String>>)> = thread::scope(|scope| {
+        let workers: Vec<_> = engine_pool
+            .iter_mut()
+            .map(|engine| {
+                let jobs = &jobs;
+                scope.spawn(move || {
+                    let mut results = Vec::new();
+                    loop {
+                        let job = jobs.lock().unwrap_or_else(PoisonError::into_inner).next();
+                        match job {
+                            Some(job) => {
+                                results.push((job.index, analyze_line(engine, &job, opts)))
+                            }
+                            None => return results,
+                        }
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+
+    for (index, annotations) in results {
+        games[index].history.unshare();
+        for (node, annotation) in games[index].history.line_nodes().iter().zip(annotations) {
+            if let Some(annotation) = annotation {
+                let mut node = node.borrow_mut();
+                node.comment = Some(match &node.comment {
+                    Some(comment) => format!("{} {}", annotation, comment),
+                    None => annotation,
+                });
+            }
+        }
+    }
+    games
+}
+
+/// Replays a game with an engine, writing the annotation of each move
+///
+/// # Arguments
+/// * `engine`: The engine
+/// * `job`: The game to analyse
+/// * `opts`: The options of the analysis
+///
+/// # Returns
+/// The annotation of each move of the game, `None` for the moves left without
+/// analysis. The list stops early if a move can't be replayed
+///
+fn analyze_line<E: Engine>(
+    engine: &mut E,
+    job: &Job,
+    opts: &AnalysisOptions,
+) -> Vec<Option<String>> {
+    let mut game = Game::new(&job.start_position, job.capture_king);
+    game.rules = job.rules;
+    let mut annotations = Vec::new();
+    // The analysis of the position after a move gives both its evaluation and
+    // the suggestions for the next move, so each position is analysed once
+    let mut analysis = None;
+
+    for (ply, mov) in job.moves.iter().enumerate() {
+        if ply < opts.skip_plies {
+            if game.move_piece(mov).is_err() {
+                break;
+            }
+            annotations.push(None);
+            continue;
+        }
+
+        let suggestions = analysis
+            .take()
+            .unwrap_or_else(|| engine.best_moves(&game, opts.lines.max(1)));
+        if game.move_piece(mov).is_err() {
+            break;
+        }
+        let reply = engine.best_moves(&game, opts.lines.max(1));
+
+        let mut annotation = Vec::new();
+        if let Some(best) = reply.first() {
+            // The reply is scored for the side to move after the move
+            let score = if game.is_white_turn {
+                best.score
+            } else {
+                -best.score
+            };
+            annotation.push(format!("[%eval {:.2}]", score as f64 / 100.0));
+        }
+        let played = mov.trim_end_matches(['+', '#']);
+        if suggestions
+            .first()
+            .is_some_and(|best| best.mov.trim_end_matches(['+', '#']) != played)
+        {
+            let suggestions: Vec<&str> = suggestions.iter().map(|best| best.mov.as_str()).collect();
+            annotation.push(format!("Best: {}", suggestions.join(", ")));
+        }
+        annotations.push((!annotation.is_empty()).then(|| annotation.join(" ")));
+        analysis = Some(reply);
+    }
+    annotations
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{par_analyze, AnalysisOptions};
+    use crate::{
+        analysis::{Engine, EngineMove},
+        logic::Game,
+        parsing::{parse_pgn_with_options, PgnParseOptions},
+    };
+
+    /// An engine that knows a few positions and counts the ones it analyses
+    struct Book {
+        moves: HashMap<String, EngineMove>,
+        analysed: usize,
+    }
+
+    impl Book {
+        fn new() -> Book {
+            let mut moves = HashMap::new();
+            for (fen, mov, score) in [
+                (Game::default().fen(), "e4", 30),
+                (
+                    "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1".to_string(),
+                    "c5",
+                    -25,
+                ),
+            ] {
+                moves.insert(
+                    fen,
+                    EngineMove {
+                        mov: mov.to_string(),
+                        score,
+                    },
+                );
+            }
+            Book { moves, analysed: 0 }
+        }
+    }
+
+    impl Engine for Book {
+        fn best_moves(&mut self, game: &Game, _count: usize) -> Vec<EngineMove> {
+            self.analysed += 1;
+            self.moves.get(&game.fen()).cloned().into_iter().collect()
+        }
+    }
+
+    #[test]
+    fn test_par_analyze() {
+        let parse = |pgn: &str| parse_pgn_with_options(pgn, &PgnParseOptions::lenient()).unwrap();
+        let games = vec![
+            parse("[Event \"A\"]\n\n1. e4 { King's pawn } e5 *"),
+            parse("[Event \"B\"]\n\n1. d4 *"),
+            Game::default(),
+            parse("[Event \"C\"]\n\n1. e4 c5 *"),
+        ];
+        let mut pool = [Book::new(), Book::new(), Book::new()];
+
+        let games = par_analyze(games, &mut pool, &AnalysisOptions::default());
+        assert!(games[0]
+            .pgn()
+            .ends_with("1. e4 { [%eval 0.25] King's pawn } e5 { Best: c5 } *"));
+        assert!(games[1].pgn().ends_with("1. d4 { Best: e4 } *"));
+        assert_eq!(games[2].pgn(), "*");
+        assert!(games[3].pgn().ends_with("1. e4 { [%eval 0.25] } c5 *"));
+        // Every position is analysed once: 3 + 2 + 0 + 3
+        assert_eq!(pool.iter().map(|engine| engine.analysed).sum::<usize>(), 8);
+
+        let games = par_analyze(
+            games,
+            &mut pool,
+            &AnalysisOptions {
+                lines: 1,
+                skip_plies: 1,
+            },
+        );
+        assert!(games[3].pgn().ends_with("1. e4 { [%eval 0.25] } c5 *"));
+        assert!(par_analyze(games, &mut [] as &mut [Book], &AnalysisOptions::default()).len() == 4);
+    }
+}
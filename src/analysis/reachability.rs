@@ -0,0 +1,230 @@
+use crate::{
+    constants::{Color, PieceType, Position, ValidationIssue},
+    logic::{material_issues, Board},
+};
+
+/// Looks for the reasons why a composed position can't be reached in a legal
+/// game, from its pieces alone
+/// On top of the counts of kings, pawns and promoted pieces, the checks are
+/// retrograde: the captures the pawns needed to reach their files, the
+/// promotions the bishops on squares of one color needed and the checks no
+/// move could have given. They are best-effort, a position without issues
+/// may still be unreachable
+///
+/// # Arguments
+/// * `board`: The board
+///
+/// # Returns
+/// The issues found, empty if the position looks reachable
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::reachability_issues;
+/// use chess_lab::constants::{Color, ValidationIssue};
+/// use chess_lab::logic::Board;
+///
+/// // Two knights can't give check at once
+/// let board = Board::new("4k3/8/3N1N2/8/8/8/8/4K3");
+/// assert_eq!(
+///     reachability_issues(&board),
+///     vec![ValidationIssue::ImpossibleCheck(Color::Black)]
+/// );
+/// ```
+///
+pub fn reachability_issues(board: &Board) -> Vec<ValidationIssue> {
+    let mut issues = material_issues(board);
+
+    for color in [Color::White, Color::Black] {
+        if !issues.contains(&ValidationIssue::TooManyPieces(color))
+            && promotions(board, color) + board.find(PieceType::Pawn, color).len() > 8
+        {
+            issues.push(ValidationIssue::TooManyBishops(color));
+        }
+
+        let lost = 16usize.saturating_sub(board.find_all(color.opposite()).len());
+        if pawn_captures(board, color) > lost {
+            issues.push(ValidationIssue::TooManyPawnCaptures(color));
+        }
+    }
+
+    let checkers = [Color::White, Color::Black].map(|color| checkers(board, color));
+    if checkers.iter().all(|checkers| !checkers.is_empty()) {
+        issues.push(ValidationIssue::BothKingsInCheck);
+    }
+    for (color, checkers) in [Color::White, Color::Black].into_iter().zip(checkers) {
+        let uncovered = checkers.iter().any(|piece_type| {
+            matches!(
+                piece_type,
+                PieceType::Bishop | PieceType::Rook | PieceType::Queen
+            )
+        });
+        if checkers.len() > 2 || (checkers.len() == 2 && !uncovered) {
+            issues.push(ValidationIssue::ImpossibleCheck(color));
+        }
+    }
+
+    issues
+}
+
+/// Checks if a composed position can be reached in a legal game, as far as
+/// `reachability_issues` can tell
+///
+/// # Arguments
+/// * `board`: The board
+///
+/// # Returns
+/// Whether no issue was found
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::is_reachable;
+/// use chess_lab::logic::Board;
+///
+/// assert!(is_reachable(&Board::default()));
+///
+/// // The doubled pawns needed a capture, but Black has all its pieces
+/// assert!(!is_reachable(&Board::new(
+///     "rnbqkbnr/pppppppp/8/8/8/4P3/PPPPP1PP/RNBQKBNR"
+/// )));
+/// ```
+///
+pub fn is_reachable(board: &Board) -> bool {
+    reachability_issues(board).is_empty()
+}
+
+/// Counts the promotions a color needed for its pieces, with the bishops
+/// counted apart by the color of their squares
+///
+/// # Arguments
+/// * `board`: The board
+/// * `color`: The color of the pieces
+///
+/// # Returns
+/// The number of pieces beyond the ones of the starting position
+///
+fn promotions(board: &Board, color: Color) -> usize {
+    let bishops = board.find(PieceType::Bishop, color);
+    let light = bishops
+        .iter()
+        .filter(|bishop| (bishop.row + bishop.col) % 2 == 1)
+        .count();
+
+    [
+        (board.find(PieceType::Queen, color).len(), 1),
+        (board.find(PieceType::Rook, color).len(), 2),
+        (board.find(PieceType::Knight, color).len(), 2),
+        (light, 1),
+        (bishops.len() - light, 1),
+    ]
+    .iter()
+    .map(|(count, initial)| count.saturating_sub(*initial))
+    .sum()
+}
+
+/// Counts the captures the pawns of a color needed at least to reach their
+/// files, each capture moving a pawn to the next file
+/// Every pawn starts on a file of its own, so the count is the cheapest way
+/// to match the pawns to distinct starting files
+///
+/// # Arguments
+/// * `board`: The board
+/// * `color`: The color of the pawns
+///
+/// # Returns
+/// The fewest captures, 0 if there are more pawns than files
+///
+fn pawn_captures(board: &Board, color: Color) -> usize {
+    let mut files: Vec<usize> = board
+        .find(PieceType::Pawn, color)
+        .iter()
+        .map(|pawn| pawn.col as usize)
+        .collect();
+    if files.len() > 8 {
+        return 0;
+    }
+    files.sort_unstable();
+
+    // cheapest[start]: the cost of the pawns matched so far, to starting
+    // files before `start`
+    let mut cheapest = [0usize; 9];
+    for file in files {
+        let mut next = [usize::MAX; 9];
+        for start in 1..=8 {
+            let matched = cheapest[start - 1].saturating_add(file.abs_diff(start - 1));
+            next[start] = next[start - 1].min(matched);
+        }
+        cheapest = next;
+    }
+    cheapest[8]
+}
+
+/// Finds the pieces that check the king of a color
+///
+/// # Arguments
+/// * `board`: The board
+/// * `color`: The color of the king
+///
+/// # Returns
+/// The types of the checking pieces, empty if the color doesn't have exactly
+/// one king
+///
+fn checkers(board: &Board, color: Color) -> Vec<PieceType> {
+    let [king]: [Position; 1] = match board.find(PieceType::King, color).try_into() {
+        Ok(king) => king,
+        Err(_) => return Vec::new(),
+    };
+    board
+        .find_all(color.opposite())
+        .iter()
+        .filter(|piece| board.can_capture(piece, &king))
+        .filter_map(|piece| board.get_piece(piece))
+        .map(|piece| piece.piece_type)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_reachable, pawn_captures, reachability_issues};
+    use crate::{
+        constants::{Color, ValidationIssue},
+        logic::Board,
+    };
+
+    #[test]
+    fn test_reachability() {
+        assert_eq!(pawn_captures(&Board::default(), Color::White), 0);
+        // Three pawns on the a file need 1 + 2 captures, Black only lost two
+        // pieces
+        let board = Board::new("rnbqkbnr/pppppp2/8/P7/P7/P7/8/4K3");
+        assert_eq!(pawn_captures(&board, Color::White), 3);
+        assert_eq!(
+            reachability_issues(&board),
+            vec![ValidationIssue::TooManyPawnCaptures(Color::White)]
+        );
+        assert!(is_reachable(&Board::new(
+            "rnbqkbnr/pppppp2/8/P7/P7/8/8/4K3"
+        )));
+
+        // Two light squared bishops need a pawn to have promoted
+        let board = Board::new("rnbqkbnr/pppppppp/8/8/8/5B2/PPPPPPPP/RN1QKBNR");
+        assert_eq!(
+            reachability_issues(&board),
+            vec![ValidationIssue::TooManyBishops(Color::White)]
+        );
+        assert!(is_reachable(&Board::new(
+            "rnbqkbnr/pppppppp/8/8/8/5B2/PPPPPPP1/RN1QKBNR"
+        )));
+
+        // A rook can be uncovered by a move of the knight, not a knight by a
+        // move of the pawn
+        assert!(is_reachable(&Board::new("4k3/8/3N4/8/8/8/8/4RK2")));
+        assert_eq!(
+            reachability_issues(&Board::new("4k3/3P4/3N4/8/8/8/8/5K2")),
+            vec![ValidationIssue::ImpossibleCheck(Color::Black)]
+        );
+        assert_eq!(
+            reachability_issues(&Board::new("4k2R/8/8/8/8/8/8/r3K3")),
+            vec![ValidationIssue::BothKingsInCheck]
+        );
+    }
+}
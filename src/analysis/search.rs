@@ -0,0 +1,275 @@
+use crate::{
+    constants::{pgn::PlyPath, Color, File, PieceType},
+    logic::{Board, Game, Piece},
+};
+
+/// The order the pieces of a side are written in a material signature
+const SIGNATURE_ORDER: [PieceType; 6] = [
+    PieceType::King,
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::Pawn,
+];
+
+/// Writes the material on the board as a signature: the pieces of White and
+/// then the ones of Black, from the king to the pawns, separated by `v`
+///
+/// # Arguments
+/// * `board`: The board
+///
+/// # Returns
+/// The signature, such as `KRPvKR`
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::search::material_signature;
+/// use chess_lab::logic::Board;
+///
+/// assert_eq!(material_signature(&Board::new("8/8/4k3/8/3KP3/8/8/7R")), "KRPvK");
+/// assert_eq!(material_signature(&Board::default()), "KQRRBBNNPPPPPPPPvKQRRBBNNPPPPPPPP");
+/// ```
+///
+pub fn material_signature(board: &Board) -> String {
+    let side = |color: Color| -> String {
+        SIGNATURE_ORDER
+            .iter()
+            .map(|&piece_type| {
+                let count = board.bitboard(piece_type, color).count_ones() as usize;
+                piece_type.to_char().to_string().repeat(count)
+            })
+            .collect()
+    };
+    format!("{}v{}", side(Color::White), side(Color::Black))
+}
+
+/// A pawn structure to look for, as masks of squares
+///
+/// # Fields
+/// * `color`: The color of the pawns
+/// * `any`: At least one pawn must stand on these squares, no requirement
+///   if empty
+/// * `none`: No pawn may stand on these squares
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PawnMask {
+    pub color: Color,
+    pub any: u64,
+    pub none: u64,
+}
+
+impl PawnMask {
+    /// Creates the mask of an isolated pawn: a pawn on a file and none on
+    /// the files next to it
+    ///
+    /// # Arguments
+    /// * `color`: The color of the pawn
+    /// * `file`: The file of the pawn
+    ///
+    /// # Returns
+    /// The mask
+    ///
+    pub fn isolated(color: Color, file: File) -> PawnMask {
+        let file = file.bitboard();
+        PawnMask {
+            color,
+            any: file,
+            none: ((file << 1) & !0x0101010101010101) | ((file >> 1) & !0x8080808080808080),
+        }
+    }
+
+    /// Checks the pawns on a board against the mask
+    ///
+    /// # Arguments
+    /// * `board`: The board
+    ///
+    /// # Returns
+    /// Whether the pawns of the color match the mask
+    ///
+    pub fn matches(&self, board: &Board) -> bool {
+        let pawns = board.bitboard(PieceType::Pawn, self.color);
+        (self.any == 0 || pawns & self.any != 0) && pawns & self.none == 0
+    }
+}
+
+/// A pattern to look for in positions, made of constraints that must all be
+/// met
+///
+/// # Fields
+/// * `material`: The material signature of the position, as written by
+///   `material_signature`
+/// * `pieces`: Pieces that must stand on one of the squares of a bitboard
+/// * `pawns`: The pawn structure, as masks of squares
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::search::{PawnMask, Pattern};
+/// use chess_lab::constants::{Color, File, PieceType, Square};
+/// use chess_lab::logic::{Game, Piece};
+///
+/// // An isolated queen pawn for White, with White castled short and Black
+/// // castled long
+/// let pattern = Pattern {
+///     pieces: vec![
+///         (Piece::new(Color::White, PieceType::King), Square::G1.bitboard()),
+///         (Piece::new(Color::Black, PieceType::King), Square::C8.bitboard()),
+///     ],
+///     pawns: vec![PawnMask::isolated(Color::White, File::D)],
+///     ..Pattern::default()
+/// };
+///
+/// let game = Game::from_fen("2kr3r/pp3ppp/2n1b3/8/3P4/5N2/PP3PPP/R4RK1 w - - 0 15");
+/// assert!(pattern.matches(&game.board));
+/// assert!(!pattern.matches(&Game::default().board));
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Pattern {
+    pub material: Option<String>,
+    pub pieces: Vec<(Piece, u64)>,
+    pub pawns: Vec<PawnMask>,
+}
+
+impl Pattern {
+    /// Checks a position against the pattern
+    ///
+    /// # Arguments
+    /// * `board`: The board of the position
+    ///
+    /// # Returns
+    /// Whether the position meets every constraint of the pattern
+    ///
+    pub fn matches(&self, board: &Board) -> bool {
+        self.material
+            .as_ref()
+            .is_none_or(|material| *material == material_signature(board))
+            && self.pieces.iter().all(|(piece, squares)| {
+                board.bitboard(piece.piece_type, piece.color) & squares != 0
+            })
+            && self.pawns.iter().all(|mask| mask.matches(board))
+    }
+
+    /// Finds the positions of a game that match the pattern, in the main line
+    /// and in every variation
+    ///
+    /// # Arguments
+    /// * `game`: The game
+    ///
+    /// # Returns
+    /// The paths to the moves that reach the matching positions
+    /// (`Game::goto_path`), an empty path being the starting position
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::analysis::search::Pattern;
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 0 1");
+    /// for mov in ["Ra7", "Kf8", "e4", "Kg8"] {
+    ///     game.move_piece(mov).unwrap();
+    /// }
+    ///
+    /// let pattern = Pattern {
+    ///     material: Some(String::from("KRPvK")),
+    ///     ..Pattern::default()
+    /// };
+    /// assert_eq!(pattern.search(&game).len(), 5);
+    /// ```
+    ///
+    pub fn search(&self, game: &Game) -> Vec<PlyPath> {
+        let mut found = Vec::new();
+        game.visit_positions(&mut |position, path| {
+            if self.matches(&position.board) {
+                found.push(path.clone());
+            }
+        });
+        found
+    }
+
+    /// Finds the positions of a collection of games that match the pattern
+    ///
+    /// # Arguments
+    /// * `games`: The games
+    ///
+    /// # Returns
+    /// The number of every game with a matching position, in the order of
+    /// the collection, with the path to the position
+    ///
+    pub fn search_games<'a>(
+        &self,
+        games: impl IntoIterator<Item = &'a Game>,
+    ) -> Vec<(usize, PlyPath)> {
+        games
+            .into_iter()
+            .enumerate()
+            .flat_map(|(number, game)| {
+                self.search(game)
+                    .into_iter()
+                    .map(move |path| (number, path))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{material_signature, Pattern, PawnMask};
+    use crate::{
+        constants::{Color, File, PieceType, Square},
+        logic::{Game, Piece},
+        parsing::{parse_pgn_with_options, PgnParseOptions},
+    };
+
+    #[test]
+    fn test_pattern_search() {
+        let game = Game::from_fen("8/8/4k3/8/3KP3/8/8/7R w - - 0 1");
+        assert_eq!(material_signature(&game.board), "KRPvK");
+        assert_eq!(
+            material_signature(&Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").board),
+            "KvK"
+        );
+
+        // The a and h files only have one neighbour
+        let mask = PawnMask::isolated(Color::White, File::A);
+        assert_eq!(mask.none, File::B.bitboard());
+        let mask = PawnMask::isolated(Color::Black, File::H);
+        assert_eq!(mask.none, File::G.bitboard());
+
+        let options = PgnParseOptions::lenient();
+        let games = [
+            parse_pgn_with_options("1. d4 d5 2. c4 dxc4 3. e4 (3. e3 e5) 3... e5 *", &options)
+                .unwrap(),
+            parse_pgn_with_options("1. e4 e5 *", &options).unwrap(),
+        ];
+        let pattern = Pattern {
+            pieces: vec![(
+                Piece::new(Color::White, PieceType::Pawn),
+                Square::D4.bitboard() | Square::E4.bitboard(),
+            )],
+            pawns: vec![PawnMask {
+                color: Color::Black,
+                any: 0,
+                none: File::D.bitboard(),
+            }],
+            ..Pattern::default()
+        };
+        assert_eq!(
+            pattern.search_games(&games),
+            vec![
+                (0, vec![0, 0, 0, 0]),
+                (0, vec![0, 0, 0, 0, 0]),
+                (0, vec![0, 0, 0, 0, 0, 0]),
+                (0, vec![0, 0, 0, 0, 1]),
+                (0, vec![0, 0, 0, 0, 1, 0]),
+            ]
+        );
+
+        let pattern = Pattern {
+            material: Some(String::from("KQRRBBNNPPPPPPPvKQRRBBNNPPPPPPP")),
+            ..Pattern::default()
+        };
+        assert!(pattern.search_games(&games).is_empty());
+        assert_eq!(Pattern::default().search(&games[1]).len(), 3);
+    }
+}
@@ -0,0 +1,139 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::{find_eval, Eval},
+    logic::Game,
+};
+
+use super::exchanges::balance;
+
+/// The material and the evaluation of every position of the main line of a
+/// game, indexed by ply: the starting position is at 0 and the position after
+/// the first move at 1
+///
+/// # Fields
+/// * `material`: The material of White minus the one of Black, in pawns
+/// * `eval`: The evaluation of the position, if the comment of the move that
+///   reached it has an `[%eval]` command
+///
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Timeline {
+    pub material: Vec<i32>,
+    pub eval: Vec<Option<Eval>>,
+}
+
+#[cfg(feature = "serde")]
+impl Timeline {
+    /// Converts the timeline to JSON
+    ///
+    /// # Returns
+    /// The timeline as a JSON object with a `material` and an `eval` array
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::analysis::timeline;
+    /// use chess_lab::parsing::{parse_pgn_with_options, PgnParseOptions};
+    ///
+    /// let game = parse_pgn_with_options(
+    ///     "1. e4 { [%eval 0.3] } d5 2. exd5 { [%eval #4] } *",
+    ///     &PgnParseOptions::lenient(),
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(
+    ///     timeline(&game).to_json(),
+    ///     r#"{"material":[0,0,0,1],"eval":[null,{"cp":30},null,{"mate":4}]}"#
+    /// );
+    /// ```
+    ///
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("The timeline is serializable")
+    }
+}
+
+/// Builds the material and evaluation series of the main line of a game, to
+/// chart it
+///
+/// # Arguments
+/// * `game`: The game
+///
+/// # Returns
+/// The timeline of the game
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::timeline;
+/// use chess_lab::constants::Eval;
+/// use chess_lab::parsing::{parse_pgn_with_options, PgnParseOptions};
+///
+/// let game = parse_pgn_with_options(
+///     "1. e4 d5 2. exd5 { [%eval 0.45] } Qxd5 *",
+///     &PgnParseOptions::lenient(),
+/// )
+/// .unwrap();
+/// let timeline = timeline(&game);
+///
+/// assert_eq!(timeline.material, [0, 0, 0, 1, 0]);
+/// assert_eq!(timeline.eval[3], Some(Eval::Cp(45)));
+/// assert_eq!(timeline.eval[4], None);
+/// ```
+///
+pub fn timeline(game: &Game) -> Timeline {
+    let mut replay = game.replay();
+    let mut timeline = Timeline {
+        material: vec![balance(&replay.board)],
+        eval: vec![None],
+    };
+    for node in game.history.main_line_nodes() {
+        if replay.move_piece(&node.borrow().san()).is_err() {
+            break;
+        }
+        let eval = node.borrow().comment.as_deref().and_then(find_eval);
+        timeline.material.push(balance(&replay.board));
+        timeline.eval.push(eval);
+    }
+    timeline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::timeline;
+    use crate::{
+        constants::Eval,
+        logic::{Game, RuleSet},
+        parsing::{parse_pgn_with_options, PgnParseOptions},
+    };
+
+    #[test]
+    fn test_timeline() {
+        assert_eq!(Eval::parse("-1.25"), Some(Eval::Cp(-125)));
+        assert_eq!(Eval::parse("#-3"), Some(Eval::Mate(-3)));
+        assert_eq!(Eval::parse("NaN"), None);
+        assert_eq!(Eval::parse("#"), None);
+
+        let game = parse_pgn_with_options(
+            "1. e4 d5 2. exd5 { Pawn up [%eval 0.5] } (2. Nc3 { [%eval 0.2] }) 2... Qxd5 { [%eval bad] } *",
+            &PgnParseOptions::lenient(),
+        )
+        .unwrap();
+        let series = timeline(&game);
+        assert_eq!(series.material, [0, 0, 0, 1, 0]);
+        assert_eq!(series.eval, [None, None, None, Some(Eval::Cp(50)), None]);
+
+        let series = timeline(&Game::from_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1"));
+        assert_eq!(series.material, [9]);
+        assert_eq!(series.eval, [None]);
+
+        // The replay goes on past a repetition the rules of the game ignore
+        let mut game = Game::default();
+        game.rules = RuleSet::standard().with_repetition_limit(None);
+        for mov in [
+            "Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8", "e4", "d5", "exd5",
+        ] {
+            game.move_piece(mov).unwrap();
+        }
+        assert_eq!(timeline(&game).material.last(), Some(&1));
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{
+    constants::{CastleType, File, MoveType, PieceType, Position},
+    logic::{Game, Piece},
+};
+
+/// The path of a piece through a game
+///
+/// # Fields
+/// * `piece`: The piece, as it stood on its starting square
+/// * `squares`: The squares of the piece, each with the ply of the move that
+///   brought it there, from the starting square at ply 0
+/// * `promotion`: The ply a pawn promoted at and the piece it promoted to
+/// * `captured`: The ply the piece was captured at
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trajectory {
+    pub piece: Piece,
+    pub squares: Vec<(usize, Position)>,
+    pub promotion: Option<(usize, PieceType)>,
+    pub captured: Option<usize>,
+}
+
+/// Follows a piece through the main line of a game, from the square it stands
+/// on in the starting position
+/// A pawn that promotes is followed as the new piece, and the path ends when
+/// the piece is captured
+///
+/// # Arguments
+/// * `game`: The game
+/// * `start_square`: The square of the piece in the starting position
+///
+/// # Returns
+/// The path of the piece, or `None` if the square is empty
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::trajectory;
+/// use chess_lab::constants::Position;
+/// use chess_lab::parsing::{parse_pgn_with_options, PgnParseOptions};
+///
+/// let game = parse_pgn_with_options(
+///     "1. e4 d5 2. exd5 Qxd5 3. Nc3 *",
+///     &PgnParseOptions::lenient(),
+/// )
+/// .unwrap();
+///
+/// let pawn = trajectory(&game, Position::from_string("e2")).unwrap();
+/// let squares: Vec<(usize, String)> = pawn
+///     .squares
+///     .iter()
+///     .map(|(ply, square)| (*ply, square.to_string()))
+///     .collect();
+/// assert_eq!(squares, [(0, "e2".into()), (1, "e4".into()), (3, "d5".into())]);
+/// assert_eq!(pawn.captured, Some(4));
+///
+/// assert!(trajectory(&game, Position::from_string("e4")).is_none());
+/// ```
+///
+pub fn trajectory(game: &Game, start_square: Position) -> Option<Trajectory> {
+    let piece = game.replay().board.get_piece(&start_square)?;
+    let mut path = Trajectory {
+        piece,
+        squares: vec![(0, start_square)],
+        promotion: None,
+        captured: None,
+    };
+
+    let mut square = start_square;
+    for (ply, node) in (1..).zip(game.history.main_line_nodes()) {
+        let mov = node.borrow().mov.clone();
+        if mov.piece.color != piece.color {
+            let captured_square = match mov.move_type {
+                MoveType::EnPassant => Position::new(mov.to.col, mov.from.row),
+                _ => mov.to,
+            };
+            if captured_square == square {
+                path.captured = Some(ply);
+                break;
+            }
+            continue;
+        }
+
+        match mov.move_type {
+            _ if mov.from == square => {
+                square = mov.to;
+                if let MoveType::Normal {
+                    promotion: Some(piece_type),
+                    ..
+                } = mov.move_type
+                {
+                    path.promotion = Some((ply, piece_type));
+                }
+            }
+            MoveType::Castle { side } if mov.rook_from == Some(square) => {
+                let file = match side {
+                    CastleType::KingSide => File::F,
+                    CastleType::QueenSide => File::D,
+                };
+                square = Position::from_file_rank(file, mov.to.rank());
+            }
+            _ => continue,
+        }
+        path.squares.push((ply, square));
+    }
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::trajectory;
+    use crate::{
+        constants::{PieceType, Position},
+        parsing::{parse_pgn_with_options, PgnParseOptions},
+    };
+
+    #[test]
+    fn test_trajectory() {
+        let game = parse_pgn_with_options(
+            "1. e4 d5 2. e5 f5 3. exf6 Nc6 4. fxg7 Bg4 5. gxh8=Q Qd7 6. Qxh7 O-O-O (6... Qd6) 7. Qh5 *",
+            &PgnParseOptions::lenient(),
+        )
+        .unwrap();
+        let squares = |from: &str| {
+            trajectory(&game, Position::from_string(from))
+                .unwrap()
+                .squares
+                .iter()
+                .map(|(ply, square)| (*ply, square.to_string()))
+                .collect::<Vec<(usize, String)>>()
+        };
+
+        // The pawn promotes and goes on as a queen
+        let pawn = trajectory(&game, Position::from_string("e2")).unwrap();
+        assert_eq!(pawn.piece.piece_type, PieceType::Pawn);
+        assert_eq!(pawn.promotion, Some((9, PieceType::Queen)));
+        assert_eq!(
+            pawn.squares.last(),
+            Some(&(13, Position::from_string("h5")))
+        );
+        assert_eq!(pawn.captured, None);
+
+        // En passant and promotion captures
+        let pawn = trajectory(&game, Position::from_string("f7")).unwrap();
+        assert_eq!(pawn.captured, Some(5));
+        let rook = trajectory(&game, Position::from_string("h8")).unwrap();
+        assert_eq!(rook.captured, Some(9));
+        assert_eq!(rook.squares.len(), 1);
+
+        // Castling moves the king and the rook, and variations are ignored
+        assert_eq!(squares("e8"), [(0, "e8".into()), (12, "c8".into())]);
+        assert_eq!(squares("a8"), [(0, "a8".into()), (12, "d8".into())]);
+        assert_eq!(squares("d8"), [(0, "d8".into()), (10, "d7".into())]);
+    }
+}
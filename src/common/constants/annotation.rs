@@ -0,0 +1,580 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Error, Formatter},
+    time::Duration,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::Position;
+
+/// The color of a graphic annotation, as used by Lichess and ChessBase
+///
+/// # Variants
+/// * `Green`: Written as `G`
+/// * `Red`: Written as `R`
+/// * `Yellow`: Written as `Y`
+/// * `Blue`: Written as `B`
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnnotationColor {
+    Green,
+    Red,
+    Yellow,
+    Blue,
+}
+
+impl AnnotationColor {
+    /// Gets the color from its character
+    ///
+    /// # Arguments
+    /// * `c`: The character of the color
+    ///
+    /// # Returns
+    /// The color, or `None` if the character is not a color
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::AnnotationColor;
+    ///
+    /// assert_eq!(AnnotationColor::from_char('G'), Some(AnnotationColor::Green));
+    /// assert_eq!(AnnotationColor::from_char('X'), None);
+    /// ```
+    ///
+    pub fn from_char(c: char) -> Option<AnnotationColor> {
+        match c {
+            'G' => Some(AnnotationColor::Green),
+            'R' => Some(AnnotationColor::Red),
+            'Y' => Some(AnnotationColor::Yellow),
+            'B' => Some(AnnotationColor::Blue),
+            _ => None,
+        }
+    }
+
+    /// Gets the character of the color
+    ///
+    /// # Returns
+    /// The character of the color
+    ///
+    pub fn to_char(&self) -> char {
+        match self {
+            AnnotationColor::Green => 'G',
+            AnnotationColor::Red => 'R',
+            AnnotationColor::Yellow => 'Y',
+            AnnotationColor::Blue => 'B',
+        }
+    }
+}
+
+/// An arrow drawn on the board, stored in PGN as `[%cal Ge2e4]`
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Arrow {
+    pub from: Position,
+    pub to: Position,
+    pub color: AnnotationColor,
+}
+
+/// A circled square, stored in PGN as `[%csl Gd4]`
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Highlight {
+    pub square: Position,
+    pub color: AnnotationColor,
+}
+
+impl Display for Arrow {
+    /// Converts the arrow to the text used in `[%cal]` commands
+    ///
+    /// # Returns
+    /// The color followed by both squares, e.g. `Ge2e4`
+    ///
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}{}{}", self.color.to_char(), self.from, self.to)
+    }
+}
+
+impl Display for Highlight {
+    /// Converts the highlight to the text used in `[%csl]` commands
+    ///
+    /// # Returns
+    /// The color followed by the square, e.g. `Gd4`
+    ///
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}{}", self.color.to_char(), self.square)
+    }
+}
+
+/// Formats the graphic annotations as PGN comment commands
+///
+/// # Arguments
+/// * `arrows`: The arrows
+/// * `highlights`: The circled squares
+///
+/// # Returns
+/// The `[%csl]` and `[%cal]` commands, empty if there are no annotations
+///
+pub(crate) fn format_annotations(arrows: &[Arrow], highlights: &[Highlight]) -> String {
+    let join = |items: Vec<String>| items.join(",");
+    let mut text = String::new();
+    if !highlights.is_empty() {
+        text.push_str(&format!(
+            "[%csl {}]",
+            join(highlights.iter().map(|h| h.to_string()).collect())
+        ));
+    }
+    if !arrows.is_empty() {
+        text.push_str(&format!(
+            "[%cal {}]",
+            join(arrows.iter().map(|a| a.to_string()).collect())
+        ));
+    }
+    text
+}
+
+/// Splits the `[%cal]` and `[%csl]` commands out of a PGN comment
+/// Commands with malformed entries are left in the comment
+///
+/// # Arguments
+/// * `comment`: The comment text
+///
+/// # Returns
+/// The rest of the comment, the arrows and the circled squares
+///
+pub(crate) fn extract_annotations(comment: &str) -> (String, Vec<Arrow>, Vec<Highlight>) {
+    let mut rest = String::new();
+    let mut arrows = Vec::new();
+    let mut highlights = Vec::new();
+    let mut text = comment;
+
+    while let Some(start) = text.find("[%c") {
+        let Some(len) = text[start..].find(']') else {
+            break;
+        };
+        let command = &text[start + 1..start + len];
+        let parsed = match command.split_once(' ') {
+            Some(("%cal", entries)) => parse_entries(entries, parse_arrow).map(|mut a| {
+                arrows.append(&mut a);
+            }),
+            Some(("%csl", entries)) => parse_entries(entries, parse_highlight).map(|mut h| {
+                highlights.append(&mut h);
+            }),
+            _ => None,
+        };
+        rest.push_str(&text[..start]);
+        if parsed.is_none() {
+            rest.push_str(&text[start..start + len + 1]);
+        }
+        text = &text[start + len + 1..];
+    }
+    rest.push_str(text);
+
+    let rest = rest.split_whitespace().collect::<Vec<&str>>().join(" ");
+    (rest, arrows, highlights)
+}
+
+/// Formats the custom data of a node as `[%data key value]` PGN comment
+/// commands, sorted by key
+/// `%`, spaces, brackets and braces are percent-encoded, so any text fits
+/// in a command
+///
+/// # Arguments
+/// * `data`: The data of the node
+///
+/// # Returns
+/// The commands, empty if there is no data
+///
+pub(crate) fn format_node_data(data: &HashMap<String, String>) -> String {
+    let mut entries: Vec<(&String, &String)> = data.iter().collect();
+    entries.sort();
+    entries
+        .into_iter()
+        .map(|(key, value)| format!("[%data {} {}]", encode(key), encode(value)))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Splits the `[%data]` commands out of a PGN comment
+///
+/// # Arguments
+/// * `comment`: The comment text
+///
+/// # Returns
+/// The rest of the comment and the data, as key and value pairs
+///
+pub(crate) fn extract_node_data(comment: &str) -> (String, Vec<(String, String)>) {
+    let mut rest = comment.to_string();
+    let mut data = Vec::new();
+    while let Some(entry) = take_command(&mut rest, "data") {
+        let (key, value) = entry.split_once(' ').unwrap_or((&entry, ""));
+        data.push((decode(key), decode(value)));
+    }
+    let rest = rest.split_whitespace().collect::<Vec<&str>>().join(" ");
+    (rest, data)
+}
+
+/// Percent-encodes the characters that can't be written in a command
+///
+/// # Arguments
+/// * `text`: The text
+///
+/// # Returns
+/// The text with `%`, spaces, brackets and braces written as `%XX`
+///
+fn encode(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '%' | ' ' | '[' | ']' | '{' | '}' | '\n' => format!("%{:02X}", c as u8),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// Decodes the `%XX` escapes of a text, leaving malformed ones as they are
+///
+/// # Arguments
+/// * `text`: The encoded text
+///
+/// # Returns
+/// The decoded text
+///
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// An evaluation read from a `[%eval]` command, from White's point of view
+///
+/// # Variants
+/// * `Cp`: An advantage in centipawns, serialized as `{ "cp": 20 }`
+/// * `Mate`: A forced mate in a number of moves, negative if Black mates,
+///   serialized as `{ "mate": -3 }`
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::Eval;
+///
+/// assert_eq!(Eval::parse("-1.25"), Some(Eval::Cp(-125)));
+/// assert_eq!(Eval::parse("#-3"), Some(Eval::Mate(-3)));
+/// assert_eq!(Eval::Cp(20).to_string(), "0.20");
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Eval {
+    Cp(i32),
+    Mate(i32),
+}
+
+impl Eval {
+    /// Reads an evaluation as written in a `[%eval]` command
+    ///
+    /// # Arguments
+    /// * `text`: The value of the command, in pawns (`0.35`) or as a mate
+    ///   (`#-3`)
+    ///
+    /// # Returns
+    /// The evaluation, or `None` if the text isn't valid
+    ///
+    pub fn parse(text: &str) -> Option<Eval> {
+        match text.strip_prefix('#') {
+            Some(mate) => mate.parse().ok().map(Eval::Mate),
+            None => text
+                .parse::<f64>()
+                .ok()
+                .filter(|pawns| pawns.is_finite())
+                .map(|pawns| Eval::Cp((pawns * 100.0).round() as i32)),
+        }
+    }
+}
+
+impl Display for Eval {
+    /// Converts the evaluation to the text used in `[%eval]` commands
+    ///
+    /// # Returns
+    /// The evaluation in pawns with two decimals, e.g. `0.20`, or the mate
+    /// as `#-3`
+    ///
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            Eval::Cp(cp) => write!(f, "{:.2}", *cp as f64 / 100.0),
+            Eval::Mate(moves) => write!(f, "#{}", moves),
+        }
+    }
+}
+
+/// Reads the `[%eval]` command of a comment
+///
+/// # Arguments
+/// * `comment`: The comment
+///
+/// # Returns
+/// The evaluation, if the command is there and well formed
+///
+pub(crate) fn find_eval(comment: &str) -> Option<Eval> {
+    Eval::parse(&take_command(&mut comment.to_string(), "eval")?)
+}
+
+/// Removes the first `[%name value]` command from a comment
+///
+/// # Arguments
+/// * `comment`: The comment
+/// * `name`: The name of the command
+///
+/// # Returns
+/// The value of the command, if found
+///
+pub(crate) fn take_command(comment: &mut String, name: &str) -> Option<String> {
+    let start = comment.find(&format!("[%{} ", name))?;
+    let end = start + comment[start..].find(']')?;
+    let value = comment[start + name.len() + 3..end].trim().to_string();
+    comment.replace_range(start..=end, "");
+    Some(value)
+}
+
+/// Replaces the `[%name value]` commands of a comment, keeping the rest of it
+///
+/// # Arguments
+/// * `comment`: The comment, if any
+/// * `name`: The name of the command
+/// * `value`: The new value, or `None` to only remove the command
+///
+/// # Returns
+/// The comment with the command written first, or `None` if nothing is left
+///
+pub(crate) fn replace_command(
+    comment: Option<&str>,
+    name: &str,
+    value: Option<&str>,
+) -> Option<String> {
+    let mut rest = comment.unwrap_or("").to_string();
+    while take_command(&mut rest, name).is_some() {}
+    let rest = rest.split_whitespace().collect::<Vec<&str>>().join(" ");
+
+    match (value, rest.is_empty()) {
+        (Some(value), true) => Some(format!("[%{} {}]", name, value)),
+        (Some(value), false) => Some(format!("[%{} {}] {}", name, value, rest)),
+        (None, true) => None,
+        (None, false) => Some(rest),
+    }
+}
+
+/// Formats a time as used in `[%clk]` and `[%emt]` commands, with tenths of
+/// a second only when there are any
+///
+/// # Arguments
+/// * `time`: The time
+///
+/// # Returns
+/// The time formatted as `H:MM:SS` or `H:MM:SS.d`
+///
+pub(crate) fn format_clock_time(time: Duration) -> String {
+    let seconds = time.as_secs();
+    let tenths = time.subsec_millis() / 100;
+    let text = format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    );
+    match tenths {
+        0 => text,
+        tenths => format!("{}.{}", text, tenths),
+    }
+}
+
+/// Parses a time written in a `[%clk]` or `[%emt]` command
+///
+/// # Arguments
+/// * `text`: The time, as `H:MM:SS` with an optional fraction of a second
+///
+/// # Returns
+/// The time, or `None` if the format is invalid
+///
+pub(crate) fn parse_clock_time(text: &str) -> Option<Duration> {
+    let (whole, fraction) = match text.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (text, None),
+    };
+    let seconds = whole
+        .split(':')
+        .try_fold(0u64, |acc, part| Some(acc * 60 + part.parse::<u64>().ok()?))?;
+    let fraction = match fraction {
+        Some(fraction) => format!("0.{}", fraction).parse::<f64>().ok()?,
+        None => 0.0,
+    };
+    Some(Duration::from_secs(seconds) + Duration::from_secs_f64(fraction))
+}
+
+/// Reads the time of a `[%clk]` or `[%emt]` command of a comment
+///
+/// # Arguments
+/// * `comment`: The comment
+/// * `name`: The name of the command
+///
+/// # Returns
+/// The time, if the command is there and well formed
+///
+pub(crate) fn find_clock_time(comment: &str, name: &str) -> Option<Duration> {
+    parse_clock_time(&take_command(&mut comment.to_string(), name)?)
+}
+
+/// Parses the comma separated entries of a command
+///
+/// # Arguments
+/// * `entries`: The entries text
+/// * `parse`: The function that parses a single entry
+///
+/// # Returns
+/// The parsed entries, or `None` if any of them is malformed
+///
+fn parse_entries<T>(entries: &str, parse: fn(&str) -> Option<T>) -> Option<Vec<T>> {
+    entries
+        .split(',')
+        .map(|entry| parse(entry.trim()))
+        .collect()
+}
+
+/// Parses an arrow entry, like `Ge2e4`
+///
+/// # Arguments
+/// * `entry`: The entry text
+///
+/// # Returns
+/// The arrow, or `None` if the entry is malformed
+///
+fn parse_arrow(entry: &str) -> Option<Arrow> {
+    let color = AnnotationColor::from_char(entry.chars().next()?)?;
+    Some(Arrow {
+        from: entry.get(1..3)?.parse().ok()?,
+        to: entry.get(3..)?.parse().ok()?,
+        color,
+    })
+}
+
+/// Parses a highlight entry, like `Gd4`
+///
+/// # Arguments
+/// * `entry`: The entry text
+///
+/// # Returns
+/// The highlight, or `None` if the entry is malformed
+///
+fn parse_highlight(entry: &str) -> Option<Highlight> {
+    let color = AnnotationColor::from_char(entry.chars().next()?)?;
+    Some(Highlight {
+        square: entry.get(1..)?.parse().ok()?,
+        color,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use super::{
+        extract_annotations, extract_node_data, format_annotations, format_clock_time,
+        format_node_data, parse_clock_time, replace_command, AnnotationColor, Arrow, Highlight,
+    };
+    use crate::constants::Position;
+
+    #[test]
+    fn test_node_data_round_trip() {
+        let data = HashMap::from([
+            (String::from("conditional"), String::from("e5 Nf3;c5 Nf3")),
+            (String::from("odd key"), String::from("[1,3] {50%}")),
+        ]);
+        let text = format_node_data(&data);
+        assert_eq!(
+            text,
+            "[%data conditional e5%20Nf3;c5%20Nf3] [%data odd%20key %5B1,3%5D%20%7B50%25%7D]"
+        );
+
+        let (rest, entries) = extract_node_data(&format!("Sharp {} [%clk 0:01:00]", text));
+        assert_eq!(rest, "Sharp [%clk 0:01:00]");
+        assert_eq!(entries.into_iter().collect::<HashMap<_, _>>(), data);
+    }
+
+    #[test]
+    fn test_annotations_round_trip() {
+        let arrows = vec![
+            Arrow {
+                from: Position::from_string("e2"),
+                to: Position::from_string("e4"),
+                color: AnnotationColor::Green,
+            },
+            Arrow {
+                from: Position::from_string("d1"),
+                to: Position::from_string("h5"),
+                color: AnnotationColor::Red,
+            },
+        ];
+        let highlights = vec![Highlight {
+            square: Position::from_string("d4"),
+            color: AnnotationColor::Blue,
+        }];
+
+        let text = format_annotations(&arrows, &highlights);
+        assert_eq!(text, "[%csl Bd4][%cal Ge2e4,Rd1h5]");
+
+        let (rest, parsed_arrows, parsed_highlights) =
+            extract_annotations(&format!("Good move {} [%clk 0:01:00]", text));
+        assert_eq!(rest, "Good move [%clk 0:01:00]");
+        assert_eq!(parsed_arrows, arrows);
+        assert_eq!(parsed_highlights, highlights);
+    }
+
+    #[test]
+    fn test_extract_malformed() {
+        let (rest, arrows, highlights) = extract_annotations("[%cal Ge2e9] [%csl Xd4] [%cal");
+        assert_eq!(rest, "[%cal Ge2e9] [%csl Xd4] [%cal");
+        assert!(arrows.is_empty());
+        assert!(highlights.is_empty());
+    }
+
+    #[test]
+    fn test_clock_commands() {
+        assert_eq!(format_clock_time(Duration::from_secs(3725)), "1:02:05");
+        assert_eq!(format_clock_time(Duration::from_millis(4350)), "0:00:04.3");
+        assert_eq!(parse_clock_time("1:02:05"), Some(Duration::from_secs(3725)));
+        assert_eq!(
+            parse_clock_time("0:00:04.3"),
+            Some(Duration::from_millis(4300))
+        );
+        assert_eq!(parse_clock_time("0:x:04"), None);
+
+        assert_eq!(
+            replace_command(
+                Some("[%clk 0:01:00] Good [%eval 0.3]"),
+                "clk",
+                Some("0:00:59")
+            ),
+            Some(String::from("[%clk 0:00:59] Good [%eval 0.3]"))
+        );
+        assert_eq!(replace_command(Some("[%emt 0:00:01]"), "emt", None), None);
+        assert_eq!(
+            replace_command(None, "emt", Some("0:00:02")),
+            Some(String::from("[%emt 0:00:02]"))
+        );
+    }
+}
@@ -1,6 +1,9 @@
 use std::fmt::{Display, Error, Formatter};
 
-use crate::logic::Piece;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::logic::{Game, Piece};
 
 use super::Position;
 
@@ -11,6 +14,7 @@ use super::Position;
 /// * `Black`: The black color
 ///
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Color {
     White,
     Black,
@@ -122,6 +126,23 @@ impl PieceType {
             PieceType::King => 'K',
         }
     }
+
+    /// Gets the rank of the piece type, from the least (pawn) to the most
+    /// valuable (king), used to order captures
+    ///
+    /// # Returns
+    /// The rank of the piece type, between 1 and 6
+    ///
+    pub(crate) fn rank(&self) -> u32 {
+        match self {
+            PieceType::Pawn => 1,
+            PieceType::Knight => 2,
+            PieceType::Bishop => 3,
+            PieceType::Rook => 4,
+            PieceType::Queen => 5,
+            PieceType::King => 6,
+        }
+    }
 }
 
 /// Represents the status of a chess game
@@ -136,6 +157,7 @@ impl PieceType {
 ///     - `reason`: The reason for the win
 ///
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GameStatus {
     InProgress,
     Draw(DrawReason),
@@ -153,6 +175,7 @@ pub enum GameStatus {
 /// * `Agreement`: The game is a draw due to agreement
 ///
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum DrawReason {
     Stalemate,
     InsufficientMaterial,
@@ -167,12 +190,131 @@ pub enum DrawReason {
 /// * `Checkmate`: The game is a win due to checkmate
 /// * `Resignation`: The game is a win due to resignation
 /// * `Time`: The game is a win due to time
+/// * `DrawOdds`: The game was drawn and the player with draw odds wins, as in
+///   an armageddon game
 ///
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum WinReason {
     Checkmate,
     Resignation,
     Time,
+    DrawOdds,
+}
+
+/// Represents what made the status of a game change
+///
+/// # Variants
+/// * `Position`: The position reached by a move, like a checkmate or a
+///   stalemate
+/// * `Repetition`: A position was repeated
+///     - `key`: The repeated position, as its FEN without the move counters
+///     - `count`: The number of times the position was reached
+/// * `FiftyMoveRule`: The halfmove clock reached the limit
+///     - `halfmove_clock`: The halfmove clock after the move
+/// * `Declared`: The result was set without a move, like a resignation, a
+///   loss on time or a draw by agreement
+///
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum StatusTrigger {
+    Position,
+    Repetition { key: String, count: u32 },
+    FiftyMoveRule { halfmove_clock: u32 },
+    Declared,
+}
+
+/// Represents a step of a takeback negotiation
+///
+/// # Variants
+/// * `Requested`: A player asked to take back their last move
+///     - `color`: The color of the player asking
+/// * `Accepted`: The opponent accepted and the moves were taken back
+///     - `color`: The color of the player that asked
+///     - `plies`: The number of half moves taken back
+/// * `Declined`: The opponent declined and the game goes on
+///     - `color`: The color of the player that asked
+///
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TakebackEvent {
+    Requested { color: Color },
+    Accepted { color: Color, plies: u32 },
+    Declined { color: Color },
+}
+
+/// Represents a change of the status of a game
+///
+/// # Fields
+/// * `ply`: The number of half moves played when the status changed
+/// * `san`: The move that changed the status, `None` if it was declared
+/// * `from`: The status before the change
+/// * `to`: The status after the change
+/// * `trigger`: What made the status change
+///
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StatusChange {
+    pub ply: u32,
+    pub san: Option<String>,
+    pub from: GameStatus,
+    pub to: GameStatus,
+    pub trigger: StatusTrigger,
+}
+
+/// Represents the status of a position of the history
+///
+/// # Fields
+/// * `check`: Whether the side to move is in check
+/// * `checkmate`: Whether the side to move is checkmated
+/// * `stalemate`: Whether the side to move is stalemated
+/// * `game_status`: The status of the game at the position
+///
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PlyStatus {
+    pub check: bool,
+    pub checkmate: bool,
+    pub stalemate: bool,
+    pub game_status: GameStatus,
+}
+
+/// Represents a reason why a position can't be reached in a legal game
+///
+/// # Variants
+/// * `MissingKing`: A color has no king
+/// * `TooManyKings`: A color has more than one king
+/// * `TooManyPawns`: A color has more than 8 pawns
+/// * `TooManyPieces`: A color has more pieces than its pawns could have
+///   promoted to
+/// * `PawnOnBackRank`: A pawn is on the first or eighth rank
+/// * `BothKingsInCheck`: Both kings are in check
+/// * `OpponentInCheck`: The king of the player that just moved is in check
+/// * `InvalidEnPassant`: The en passant square doesn't follow a double pawn push
+/// * `InvalidCastlingRights`: A castling right is kept without the king and
+///   rook on their squares
+/// * `HalfmoveClockOutOfRange`: The halfmove clock is over 100, or not 0
+///   after a double pawn push
+/// * `InvalidFullmoveNumber`: The fullmove number is 0
+/// * `TooManyBishops`: A color has more bishops on squares of one color
+///   than its pawns could have promoted to
+/// * `TooManyPawnCaptures`: The pawns of a color need more captures to
+///   reach their files than the pieces the opponent has lost
+/// * `ImpossibleCheck`: The king of a color is checked by pieces no single
+///   move could have given check with
+///
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ValidationIssue {
+    MissingKing(Color),
+    TooManyKings(Color),
+    TooManyPawns(Color),
+    TooManyPieces(Color),
+    PawnOnBackRank(Position),
+    BothKingsInCheck,
+    OpponentInCheck,
+    InvalidEnPassant(Position),
+    InvalidCastlingRights,
+    HalfmoveClockOutOfRange(u32),
+    InvalidFullmoveNumber,
+    TooManyBishops(Color),
+    TooManyPawnCaptures(Color),
+    ImpossibleCheck(Color),
 }
 
 /// Represents the type of a move
@@ -210,12 +352,177 @@ pub enum CastleType {
     QueenSide,
 }
 
+/// The ways a FEN can write the castling rights and the en passant square
+///
+/// # Variants
+/// * `Standard`: Castling rights as `KQkq` and the en passant square after
+///   every double pawn push, as stored in the game
+/// * `XFen`: Castling rights as `KQkq`, dropping the ones whose king or rook
+///   left their squares, and the en passant square only when a pawn can
+///   capture on it
+/// * `ShredderFen`: Like `XFen`, but with the files of the castling rooks
+///   instead of `KQkq` (`HAha` for the starting position)
+///
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum FenStyle {
+    #[default]
+    Standard,
+    XFen,
+    ShredderFen,
+}
+
+/// A position to look for, given by its FEN or by its hash
+///
+/// # Variants
+/// * `Fen`: The FEN of the position
+/// * `Hash`: The hash of the position, as given by `Game::position_hash`
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::PositionKey;
+/// use chess_lab::logic::Game;
+///
+/// let game = Game::default();
+/// let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 4 9";
+///
+/// assert_eq!(PositionKey::from(fen).hash(), Some(game.position_hash()));
+/// assert_eq!(PositionKey::from(42).hash(), Some(42));
+/// assert_eq!(PositionKey::from("8/8 w - - 0 1").hash(), None);
+/// ```
+///
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PositionKey {
+    Fen(String),
+    Hash(u64),
+}
+
+impl PositionKey {
+    /// Returns the hash of the position, ignoring the halfmove clock and
+    /// fullmove number of a FEN
+    ///
+    /// # Returns
+    /// The hash, or `None` if the FEN is invalid
+    ///
+    pub fn hash(&self) -> Option<u64> {
+        match self {
+            PositionKey::Fen(fen) => Game::try_from_fen(fen)
+                .ok()
+                .map(|game| game.position_hash()),
+            PositionKey::Hash(hash) => Some(*hash),
+        }
+    }
+}
+
+impl From<&str> for PositionKey {
+    /// Creates a key from the FEN of a position
+    ///
+    /// # Arguments
+    /// * `fen`: The FEN of the position
+    ///
+    /// # Returns
+    /// The key
+    ///
+    fn from(fen: &str) -> PositionKey {
+        PositionKey::Fen(fen.to_string())
+    }
+}
+
+impl From<String> for PositionKey {
+    /// Creates a key from the FEN of a position
+    ///
+    /// # Arguments
+    /// * `fen`: The FEN of the position
+    ///
+    /// # Returns
+    /// The key
+    ///
+    fn from(fen: String) -> PositionKey {
+        PositionKey::Fen(fen)
+    }
+}
+
+impl From<u64> for PositionKey {
+    /// Creates a key from the hash of a position
+    ///
+    /// # Arguments
+    /// * `hash`: The hash of the position
+    ///
+    /// # Returns
+    /// The key
+    ///
+    fn from(hash: u64) -> PositionKey {
+        PositionKey::Hash(hash)
+    }
+}
+
+/// Represents a piece given as a handicap, removed from the starting position
+/// of the stronger player
+///
+/// # Variants
+/// * `Pawn`: The f-pawn
+/// * `Knight`: The queen side knight
+/// * `Rook`: The queen side rook
+/// * `Queen`: The queen
+///
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Odds {
+    Pawn,
+    Knight,
+    Rook,
+    Queen,
+}
+
+impl Odds {
+    /// Returns the starting square of the piece given
+    ///
+    /// # Arguments
+    /// * `color`: The color of the player giving the odds
+    ///
+    /// # Returns
+    /// The square the piece is removed from
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::{Color, Odds, Position};
+    ///
+    /// assert_eq!(Odds::Knight.square(Color::White), Position::from_string("b1"));
+    /// assert_eq!(Odds::Pawn.square(Color::Black), Position::from_string("f7"));
+    /// ```
+    ///
+    pub fn square(&self, color: Color) -> Position {
+        let col = match self {
+            Odds::Pawn => 5,
+            Odds::Knight => 1,
+            Odds::Rook => 0,
+            Odds::Queen => 3,
+        };
+        let row = match (self, color) {
+            (Odds::Pawn, Color::White) => 1,
+            (Odds::Pawn, Color::Black) => 6,
+            (_, Color::White) => 0,
+            (_, Color::Black) => 7,
+        };
+        Position::new(col, row)
+    }
+}
+
+impl Display for Odds {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        let name = match self {
+            Odds::Pawn => "pawn",
+            Odds::Knight => "knight",
+            Odds::Rook => "rook",
+            Odds::Queen => "queen",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 /// Represents a move in a chess game
 ///
 /// # Example
 /// ```
-/// use chess_lab::constants::{Color, PieceType, Position, Move, MoveType};
-/// use chess_lab::logic::Piece;
+/// use chess_lab::constants::{Color, Piece, PieceType, Position, Move, MoveType};
 ///
 /// let piece = Piece {
 ///     color: Color::White,
@@ -311,6 +618,7 @@ impl Move {
     /// );
     /// ```
     ///
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         piece: Piece,
         from: Position,
@@ -371,6 +679,37 @@ impl Move {
             checkmate,
         }
     }
+
+    /// Returns the move in UCI notation
+    ///
+    /// # Returns
+    /// The starting and ending squares, followed by the promotion piece, if any
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let game = Game::from_fen("4k3/P7/8/8/8/8/8/4K2R w K - 0 1");
+    /// let moves: Vec<String> = game.legal_moves().iter().map(|mov| mov.uci()).collect();
+    ///
+    /// assert!(moves.contains(&"a7a8q".to_string()));
+    /// assert!(moves.contains(&"e1g1".to_string()));
+    /// ```
+    ///
+    pub fn uci(&self) -> String {
+        match &self.move_type {
+            MoveType::Normal {
+                promotion: Some(promotion),
+                ..
+            } => format!(
+                "{}{}{}",
+                self.from,
+                self.to,
+                promotion.to_char().to_ascii_lowercase()
+            ),
+            _ => format!("{}{}", self.from, self.to),
+        }
+    }
 }
 
 impl Display for Move {
@@ -1,9 +1,26 @@
+mod annotation;
 mod game;
 pub(crate) mod movements;
 pub mod pgn;
 mod position;
+mod square;
 mod variant;
 
+pub use annotation::*;
 pub use game::*;
 pub use position::*;
+pub use square::*;
 pub use variant::*;
+
+/// The piece a `Move` is made with, the same type as `logic::Piece`, so the
+/// types of a move can all be imported from here
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::{Color, Piece, PieceType};
+///
+/// let piece: chess_lab::logic::Piece = Piece::new(Color::Black, PieceType::Knight);
+/// assert_eq!(piece.to_string(), "n");
+/// ```
+///
+pub use crate::logic::Piece;
@@ -72,7 +72,7 @@ pub fn movement_direction(start_pos: &Position, end_pos: &Position, direction: (
     } else if direction.1 == 0 {
         diff.0 * direction.0 > 0 && diff.1 == 0
     } else {
-        diff.0 * direction.0 == diff.1 * direction.1
+        diff.0 * direction.1 == diff.1 * direction.0 && diff.0 * direction.0 > 0
     }
 }
 
@@ -115,5 +115,6 @@ mod tests {
         let start_pos = Position::new(0, 2);
         let end_pos = Position::new(2, 0);
         assert!(movement_direction(&start_pos, &end_pos, (1, -1)));
+        assert!(!movement_direction(&start_pos, &end_pos, (-1, 1)));
     }
 }
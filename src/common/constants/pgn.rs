@@ -1,6 +1,69 @@
-use std::{cell::RefCell, fmt::Display, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, fmt::Display, rc::Rc, time::Duration};
 
-use super::{GameStatus, Position};
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Timelike, Utc};
+
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(feature = "serde")]
+use crate::errors::InteropError;
+use crate::errors::PgnError;
+
+use super::{
+    find_clock_time, format_annotations, format_clock_time, format_node_data, replace_command,
+    AnnotationColor, Arrow, GameStatus, Highlight, Move, Position,
+};
+
+/// The text a move was rendered to, along with the move it was rendered from
+type RenderCache<T> = RefCell<Option<(T, String)>>;
+
+/// A token held by the trees that share their nodes since a fork, so each
+/// one copies the nodes before changing them
+/// Cloning a tree doesn't share the token: the clone shares the nodes as it
+/// always did, and only forks copy on write
+#[derive(Debug, Default)]
+struct ShareToken(Rc<()>);
+
+impl Clone for ShareToken {
+    fn clone(&self) -> ShareToken {
+        ShareToken::default()
+    }
+}
+
+impl ShareToken {
+    /// Hands the token to a fork of the tree
+    ///
+    /// # Returns
+    /// The token, shared with this one
+    ///
+    fn share(&self) -> ShareToken {
+        ShareToken(Rc::clone(&self.0))
+    }
+
+    /// Checks if another tree holds the token
+    ///
+    /// # Returns
+    /// Whether the nodes are shared with a fork
+    ///
+    fn is_shared(&self) -> bool {
+        Rc::strong_count(&self.0) > 1
+    }
+}
+
+/// The position reached by a move, kept on its node while the position cache
+/// of the game is enabled
+///
+/// # Fields
+/// * `fen`: The FEN of the position
+/// * `prev_positions`: How many times each position was reached up to this
+///   one, as used for repetitions
+///
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CachedPosition {
+    pub(crate) fen: String,
+    pub(crate) prev_positions: Rc<HashMap<String, u32>>,
+}
 
 /// A struct representing a PGN line or variation
 /// Its also a tree node that contains a list of child nodes, the parent node,
@@ -16,6 +79,81 @@ pub struct PgnLine<T: PartialEq + Clone + Display> {
     pub castling_rights: u8,
     pub game_status: GameStatus,
     pub mov: T,
+    pub comment: Option<String>,
+    pub arrows: Vec<Arrow>,
+    pub highlights: Vec<Highlight>,
+    pub node_data: HashMap<String, String>,
+    pub(crate) position: Option<CachedPosition>,
+    san: RenderCache<T>,
+    uci: RenderCache<T>,
+}
+
+impl<T: PartialEq + Clone + Display> PgnLine<T> {
+    /// Returns the move of the node as written in the PGN, rendering it only
+    /// the first time
+    /// The text is rendered again if the move changed since, for example when
+    /// its disambiguation or its check flags are updated
+    ///
+    /// # Returns
+    /// The move in SAN
+    ///
+    pub fn san(&self) -> String {
+        PgnLine::cached(&self.san, &self.mov, T::to_string)
+    }
+
+    /// Returns the text of a move from a cache, rendering it if the cache is
+    /// empty or was filled from a different move
+    ///
+    /// # Arguments
+    /// * `cache`: The cache
+    /// * `mov`: The move
+    /// * `render`: The function that renders the move
+    ///
+    /// # Returns
+    /// The text of the move
+    ///
+    fn cached(cache: &RenderCache<T>, mov: &T, render: impl FnOnce(&T) -> String) -> String {
+        let mut cache = cache.borrow_mut();
+        match cache.as_ref() {
+            Some((cached, text)) if cached == mov => text.clone(),
+            _ => {
+                let text = render(mov);
+                *cache = Some((mov.clone(), text.clone()));
+                text
+            }
+        }
+    }
+
+    /// Returns the full comment of the node as written in the PGN,
+    /// the graphic annotations (`[%csl]` and `[%cal]`) and the custom data
+    /// (`[%data]`) followed by the comment
+    ///
+    /// # Returns
+    /// The comment text, or `None` if there is nothing to write
+    ///
+    pub fn comment_text(&self) -> Option<String> {
+        let parts: Vec<String> = [
+            format_annotations(&self.arrows, &self.highlights),
+            format_node_data(&self.node_data),
+        ]
+        .into_iter()
+        .chain(self.comment.clone())
+        .filter(|part| !part.is_empty())
+        .collect();
+        (!parts.is_empty()).then(|| parts.join(" "))
+    }
+}
+
+impl PgnLine<Move> {
+    /// Returns the move of the node in UCI notation, rendering it only the
+    /// first time
+    ///
+    /// # Returns
+    /// The move in UCI notation
+    ///
+    pub fn uci(&self) -> String {
+        PgnLine::cached(&self.uci, &self.mov, Move::uci)
+    }
 }
 
 impl<T: PartialEq + Clone + Display> PartialEq for PgnLine<T> {
@@ -51,8 +189,68 @@ pub struct PgnTree<T: PartialEq + Clone + Display> {
     pub black_elo: Option<u32>,
     pub time_control: Option<String>,
     pub termination: Option<String>,
+    pub white_clock: Option<String>,
+    pub black_clock: Option<String>,
+    pub handicap: Option<String>,
+    pub event_date: Option<String>,
+    pub utc_date: Option<String>,
+    pub utc_time: Option<String>,
     lines: Vec<Rc<RefCell<PgnLine<T>>>>,
     current_line: Option<Rc<RefCell<PgnLine<T>>>>,
+    shared: ShareToken,
+}
+
+/// The way from the starting position to a move of a PGN tree: the index of
+/// the variation taken at every ply, `0` being the main line
+/// An empty path is the starting position
+pub type PlyPath = Vec<usize>;
+
+/// The differences between two PGN trees, as found by `PgnTree::diff`
+/// Each move is written as the line of moves that leads to it from the
+/// starting position, the move itself last
+///
+/// # Fields
+/// * `added`: The first moves of the variations only the other tree has
+/// * `removed`: The first moves of the variations only this tree has
+/// * `changed_comments`: The moves both trees have with a different comment
+///   or different graphic annotations
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeDiff<T> {
+    pub added: Vec<Vec<T>>,
+    pub removed: Vec<Vec<T>>,
+    pub changed_comments: Vec<Vec<T>>,
+}
+
+impl<T> TreeDiff<T> {
+    /// Checks if the trees had no differences
+    ///
+    /// # Returns
+    /// Whether no move was added, removed or had its comment changed
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed_comments.is_empty()
+    }
+}
+
+/// The size and shape of a PGN tree, as given by `PgnTree::stats`
+///
+/// # Fields
+/// * `nodes`: The number of moves of the tree, in every line
+/// * `mainline_plies`: The number of moves of the main line
+/// * `variations`: The number of variations, the lines that are not the
+///   first continuation of a position
+/// * `branching_factor`: The average number of continuations of the
+///   positions that have at least one, 0 for an empty tree
+/// * `comments`: The number of moves with a comment
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TreeStats {
+    pub nodes: usize,
+    pub mainline_plies: usize,
+    pub variations: usize,
+    pub branching_factor: f64,
+    pub comments: usize,
 }
 
 impl<T: PartialEq + Clone + Display> Default for PgnTree<T> {
@@ -83,8 +281,15 @@ impl<T: PartialEq + Clone + Display> Default for PgnTree<T> {
             black_elo: None,
             time_control: None,
             termination: None,
+            white_clock: None,
+            black_clock: None,
+            handicap: None,
+            event_date: None,
+            utc_date: None,
+            utc_time: None,
             lines: Vec::new(),
             current_line: None,
+            shared: ShareToken::default(),
         }
     }
 }
@@ -104,6 +309,10 @@ impl<T: PartialEq + Clone + Display> PgnTree<T> {
     /// * `white_elo`: The white player ELO
     /// * `black_elo`: The black player ELO
     /// * `time_control`: The time control of the game
+    /// * `termination`: How the game ended
+    ///
+    /// The clocks of each color, the handicap and the timestamps are left
+    /// unset
     ///
     /// # Returns
     /// A new PgnTree
@@ -129,6 +338,7 @@ impl<T: PartialEq + Clone + Display> PgnTree<T> {
     /// );
     /// ```
     ///
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         event: Option<String>,
         site: Option<String>,
@@ -156,9 +366,119 @@ impl<T: PartialEq + Clone + Display> PgnTree<T> {
             black_elo,
             time_control,
             termination,
+            white_clock: None,
+            black_clock: None,
+            handicap: None,
+            event_date: None,
+            utc_date: None,
+            utc_time: None,
             lines: Vec::new(),
             current_line: None,
+            shared: ShareToken::default(),
+        }
+    }
+
+    /// Sets the names of the players
+    ///
+    /// # Arguments
+    /// * `white`: The name of the player with the white pieces
+    /// * `black`: The name of the player with the black pieces
+    ///
+    pub fn set_players(&mut self, white: &str, black: &str) {
+        self.white = Some(white.to_string());
+        self.black = Some(black.to_string());
+    }
+
+    /// Sets the date of the game, leaving the unknown parts as question marks
+    /// as the PGN standard writes them (`2024.??.??`)
+    ///
+    /// # Arguments
+    /// * `year`: The year, if known
+    /// * `month`: The month, from 1 to 12, if known
+    /// * `day`: The day of the month, if known
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.history.set_date_parts(Some(1851), Some(6), None);
+    /// assert_eq!(game.history.date, Some(String::from("1851.06.??")));
+    ///
+    /// game.history.set_date_parts(None, None, None);
+    /// assert_eq!(game.history.date, Some(String::from("????.??.??")));
+    /// ```
+    ///
+    pub fn set_date_parts(&mut self, year: Option<u32>, month: Option<u32>, day: Option<u32>) {
+        let part = |value: Option<u32>, width: usize| match value {
+            Some(value) => format!("{:0width$}", value, width = width),
+            None => "?".repeat(width),
+        };
+        self.date = Some(format!(
+            "{}.{}.{}",
+            part(year, 4),
+            part(month, 2),
+            part(day, 2)
+        ));
+    }
+
+    /// Sets the result of the game, checking that it's a game termination
+    /// marker
+    ///
+    /// # Arguments
+    /// * `result`: The result (`1-0`, `0-1`, `1/2-1/2` or `*`)
+    ///
+    /// # Returns
+    /// An error if the result is not a game termination marker, in which case
+    /// the result is left unchanged
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::errors::PgnError;
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// assert_eq!(game.history.set_result("1/2-1/2"), Ok(()));
+    /// assert_eq!(game.history.set_result("draw"), Err(PgnError::InvalidTag));
+    /// assert_eq!(game.history.result, Some(String::from("1/2-1/2")));
+    /// ```
+    ///
+    pub fn set_result(&mut self, result: &str) -> Result<(), PgnError> {
+        if !is_result(result) {
+            return Err(PgnError::InvalidTag);
+        }
+        self.result = Some(result.to_string());
+        Ok(())
+    }
+
+    /// Sets the tags of the Seven Tag Roster that are missing, other than the
+    /// result, to their unknown values: `?`, and `????.??.??` for the date
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.history.set_players("Anderssen", "Kieseritzky");
+    /// game.history.fill_seven_tag_roster();
+    ///
+    /// assert_eq!(
+    ///     game.pgn(),
+    ///     "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"Anderssen\"]\n[Black \"Kieseritzky\"]\n*"
+    /// );
+    /// ```
+    ///
+    pub fn fill_seven_tag_roster(&mut self) {
+        for tag in [
+            &mut self.event,
+            &mut self.site,
+            &mut self.round,
+            &mut self.white,
+            &mut self.black,
+        ] {
+            tag.get_or_insert_with(|| String::from("?"));
         }
+        self.date.get_or_insert_with(|| String::from("????.??.??"));
     }
 
     /// Adds a move to the current line
@@ -204,39 +524,39 @@ impl<T: PartialEq + Clone + Display> PgnTree<T> {
         castling_rights: u8,
         game_status: GameStatus,
     ) {
-        if let Some(current_line) = &self.current_line {
-            let new_line = Rc::new(RefCell::new(PgnLine {
-                lines: Vec::new(),
-                parent: Some(Rc::clone(&current_line)),
-                halfmove_clock,
-                fullmove_number,
-                en_passant,
-                castling_rights,
-                game_status,
-                mov,
-            }));
-            if !current_line.as_ref().borrow_mut().lines.contains(&new_line) {
-                current_line
-                    .as_ref()
-                    .borrow_mut()
-                    .lines
-                    .push(Rc::clone(&new_line));
-            }
-            self.current_line = Some(new_line);
-        } else {
-            let new_line = Rc::new(RefCell::new(PgnLine {
-                lines: Vec::new(),
-                parent: None,
-                halfmove_clock,
-                fullmove_number,
-                en_passant,
-                castling_rights,
-                game_status,
-                mov,
-            }));
-            self.lines.push(Rc::clone(&new_line));
-            self.current_line = Some(new_line);
+        let siblings = match &self.current_line {
+            Some(current_line) => current_line.borrow().lines.clone(),
+            None => self.lines.clone(),
+        };
+        // A move that was already played here is followed instead of repeated
+        if let Some(existing) = siblings.into_iter().find(|line| line.borrow().mov == mov) {
+            self.current_line = Some(existing);
+            return;
+        }
+
+        self.unshare();
+        let new_line = Rc::new(RefCell::new(PgnLine {
+            lines: Vec::new(),
+            parent: self.current_line.as_ref().map(Rc::clone),
+            halfmove_clock,
+            fullmove_number,
+            en_passant,
+            castling_rights,
+            game_status,
+            mov,
+            comment: None,
+            arrows: Vec::new(),
+            highlights: Vec::new(),
+            node_data: HashMap::new(),
+            position: None,
+            san: RefCell::new(None),
+            uci: RefCell::new(None),
+        }));
+        match &self.current_line {
+            Some(current_line) => current_line.borrow_mut().lines.push(Rc::clone(&new_line)),
+            None => self.lines.push(Rc::clone(&new_line)),
         }
+        self.current_line = Some(new_line);
     }
 
     /// Removes the current line
@@ -266,7 +586,8 @@ impl<T: PartialEq + Clone + Display> PgnTree<T> {
     /// ```
     ///
     pub fn rm_move(&mut self) {
-        if let None = &self.current_line {
+        self.unshare();
+        if self.current_line.is_none() {
             return;
         }
 
@@ -277,12 +598,12 @@ impl<T: PartialEq + Clone + Display> PgnTree<T> {
             return;
         }
 
-        let parent = Rc::clone(&current_line_borrowed.parent.as_ref().unwrap());
+        let parent = Rc::clone(current_line_borrowed.parent.as_ref().unwrap());
         let index = parent
             .borrow()
             .lines
             .iter()
-            .position(|x| Rc::ptr_eq(x, &self.current_line.as_ref().unwrap()))
+            .position(|x| Rc::ptr_eq(x, self.current_line.as_ref().unwrap()))
             .unwrap();
 
         parent.borrow_mut().lines.remove(index);
@@ -323,6 +644,281 @@ impl<T: PartialEq + Clone + Display> PgnTree<T> {
         Some(self.current_line.as_ref()?.borrow().mov.clone())
     }
 
+    /// Returns the comment of the current move
+    ///
+    /// # Returns
+    /// The comment of the current move, if any
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::{pgn::PgnTree, Move, PieceType, MoveType, Color, Position, GameStatus};
+    /// use chess_lab::logic::Piece;
+    ///
+    /// let mut tree = PgnTree::default();
+    /// let mov = Move::new(
+    ///     Piece::new(Color::White, PieceType::Pawn),
+    ///     Position::from_string("e2"),
+    ///     Position::from_string("e4"),
+    ///     MoveType::Normal {
+    ///         capture: false,
+    ///         promotion: None,
+    ///     },
+    ///     None,
+    ///     None,
+    ///     (false, false),
+    ///     false,
+    ///     false,
+    /// );
+    /// tree.add_move(mov, 0, 0, None, 0, GameStatus::InProgress);
+    /// assert_eq!(tree.get_comment(), None);
+    ///
+    /// tree.set_comment(Some("Best by test".to_string()));
+    /// assert_eq!(tree.get_comment(), Some("Best by test".to_string()));
+    /// ```
+    ///
+    pub fn get_comment(&self) -> Option<String> {
+        self.current_line.as_ref()?.borrow().comment.clone()
+    }
+
+    /// Sets the comment of the current move
+    /// Does nothing if there is no current move
+    ///
+    /// # Arguments
+    /// * `comment`: The comment to set, or `None` to remove it
+    ///
+    pub fn set_comment(&mut self, comment: Option<String>) {
+        self.unshare();
+        if let Some(current_line) = &self.current_line {
+            current_line.borrow_mut().comment = comment;
+        }
+    }
+
+    /// Returns the arrows drawn on the current move
+    ///
+    /// # Returns
+    /// The arrows of the current move, empty if there is no current move
+    ///
+    pub fn get_arrows(&self) -> Vec<Arrow> {
+        match &self.current_line {
+            Some(current_line) => current_line.borrow().arrows.clone(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Adds an arrow to the current move
+    /// Does nothing if there is no current move or the arrow is already drawn
+    ///
+    /// # Arguments
+    /// * `from`: The square the arrow starts from
+    /// * `to`: The square the arrow points to
+    /// * `color`: The color of the arrow
+    ///
+    pub fn add_arrow(&mut self, from: Position, to: Position, color: AnnotationColor) {
+        self.unshare();
+        if let Some(current_line) = &self.current_line {
+            let arrow = Arrow { from, to, color };
+            let arrows = &mut current_line.borrow_mut().arrows;
+            if !arrows.contains(&arrow) {
+                arrows.push(arrow);
+            }
+        }
+    }
+
+    /// Returns the squares circled on the current move
+    ///
+    /// # Returns
+    /// The highlights of the current move, empty if there is no current move
+    ///
+    pub fn get_highlights(&self) -> Vec<Highlight> {
+        match &self.current_line {
+            Some(current_line) => current_line.borrow().highlights.clone(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Circles a square on the current move
+    /// Does nothing if there is no current move or the square is already circled
+    ///
+    /// # Arguments
+    /// * `square`: The square to circle
+    /// * `color`: The color of the circle
+    ///
+    pub fn add_highlight(&mut self, square: Position, color: AnnotationColor) {
+        self.unshare();
+        if let Some(current_line) = &self.current_line {
+            let highlight = Highlight { square, color };
+            let highlights = &mut current_line.borrow_mut().highlights;
+            if !highlights.contains(&highlight) {
+                highlights.push(highlight);
+            }
+        }
+    }
+
+    /// Removes every arrow and circled square of the current move
+    /// Does nothing if there is no current move
+    ///
+    pub fn clear_annotations(&mut self) {
+        self.unshare();
+        if let Some(current_line) = &self.current_line {
+            let mut current_line = current_line.borrow_mut();
+            current_line.arrows.clear();
+            current_line.highlights.clear();
+        }
+    }
+
+    /// Returns the custom data stored on the current move under a key
+    ///
+    /// # Arguments
+    /// * `key`: The key of the data
+    ///
+    /// # Returns
+    /// The data, if there is a current move and it has data under the key
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// game.history.set_node_data("due", Some("2024-07-01".to_string()));
+    ///
+    /// assert_eq!(game.history.get_node_data("due"), Some("2024-07-01".to_string()));
+    /// assert_eq!(game.history.get_node_data("interval"), None);
+    /// ```
+    ///
+    pub fn get_node_data(&self, key: &str) -> Option<String> {
+        self.current_line
+            .as_ref()?
+            .borrow()
+            .node_data
+            .get(key)
+            .cloned()
+    }
+
+    /// Stores custom data on the current move under a key, replacing any
+    /// previous data under the same key
+    /// Does nothing if there is no current move
+    ///
+    /// # Arguments
+    /// * `key`: The key of the data
+    /// * `value`: The data to store, or `None` to remove it
+    ///
+    pub fn set_node_data(&mut self, key: &str, value: Option<String>) {
+        self.unshare();
+        if let Some(current_line) = &self.current_line {
+            let node_data = &mut current_line.borrow_mut().node_data;
+            match value {
+                Some(value) => node_data.insert(key.to_string(), value),
+                None => node_data.remove(key),
+            };
+        }
+    }
+
+    /// Returns the clock time left after the current move, stored as a
+    /// `[%clk]` command in its comment
+    ///
+    /// # Returns
+    /// The time left, if there is a current move and its comment has one
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// game.history.set_comment(Some("Fast".to_string()));
+    /// game.history.set_clock(Some(Duration::from_secs(178)));
+    /// game.history.set_elapsed(Some(Duration::from_millis(2500)));
+    ///
+    /// assert_eq!(game.history.get_clock(), Some(Duration::from_secs(178)));
+    /// assert_eq!(game.history.get_elapsed(), Some(Duration::from_millis(2500)));
+    /// assert_eq!(game.pgn(), "1. e4 { [%emt 0:00:02.5] [%clk 0:02:58] Fast } *");
+    /// ```
+    ///
+    pub fn get_clock(&self) -> Option<Duration> {
+        self.get_time_command("clk")
+    }
+
+    /// Stores the clock time left after the current move as a `[%clk]`
+    /// command in its comment, replacing the previous one
+    /// Does nothing if there is no current move
+    ///
+    /// # Arguments
+    /// * `clock`: The time left, or `None` to remove it
+    ///
+    pub fn set_clock(&mut self, clock: Option<Duration>) {
+        self.set_time_command("clk", clock);
+    }
+
+    /// Returns the time spent on the current move, stored as an `[%emt]`
+    /// command in its comment
+    ///
+    /// # Returns
+    /// The time spent, if there is a current move and its comment has one
+    ///
+    pub fn get_elapsed(&self) -> Option<Duration> {
+        self.get_time_command("emt")
+    }
+
+    /// Stores the time spent on the current move as an `[%emt]` command in its
+    /// comment, replacing the previous one
+    /// Does nothing if there is no current move
+    ///
+    /// # Arguments
+    /// * `elapsed`: The time spent, or `None` to remove it
+    ///
+    pub fn set_elapsed(&mut self, elapsed: Option<Duration>) {
+        self.set_time_command("emt", elapsed);
+    }
+
+    /// Returns the clock time and the time spent on every move of the current
+    /// line, to replay a game with its real timing
+    ///
+    /// # Returns
+    /// The time left and the time spent on each move, from the first one
+    ///
+    pub fn line_times(&self) -> Vec<(Option<Duration>, Option<Duration>)> {
+        self.line_nodes()
+            .iter()
+            .map(|node| {
+                let comment = node.borrow().comment.clone().unwrap_or_default();
+                (
+                    find_clock_time(&comment, "clk"),
+                    find_clock_time(&comment, "emt"),
+                )
+            })
+            .collect()
+    }
+
+    /// Reads a time command of the comment of the current move
+    ///
+    /// # Arguments
+    /// * `name`: The name of the command
+    ///
+    /// # Returns
+    /// The time, if the command is there and well formed
+    ///
+    fn get_time_command(&self, name: &str) -> Option<Duration> {
+        find_clock_time(&self.get_comment()?, name)
+    }
+
+    /// Writes a time command to the comment of the current move
+    ///
+    /// # Arguments
+    /// * `name`: The name of the command
+    /// * `time`: The time, or `None` to remove the command
+    ///
+    fn set_time_command(&mut self, name: &str, time: Option<Duration>) {
+        let comment = replace_command(
+            self.get_comment().as_deref(),
+            name,
+            time.map(format_clock_time).as_deref(),
+        );
+        self.set_comment(comment);
+    }
+
     /// Returns the move info
     ///
     /// # Returns
@@ -607,8 +1203,7 @@ impl<T: PartialEq + Clone + Display> PgnTree<T> {
         }
 
         let parent = Rc::clone(
-            &self
-                .current_line
+            self.current_line
                 .as_ref()?
                 .borrow()
                 .parent
@@ -619,57 +1214,625 @@ impl<T: PartialEq + Clone + Display> PgnTree<T> {
         Some(self.current_line.as_ref()?.borrow().mov.clone())
     }
 
-    pub fn pgn(&self) -> String {
-        let mut pgn = String::new();
-        pgn.push_str(&self.pgn_header());
-        pgn.push_str(&self.pgn_moves());
-        pgn
-    }
-
-    /// Returns the PGN header
+    /// Returns the moves that lead to the current position, from the first one
     ///
     /// # Returns
-    /// The PGN header
+    /// The moves of the current line, up to the current move
     ///
-    fn pgn_header(&self) -> String {
-        let mut header = String::new();
-        if let Some(event) = &self.event {
-            header.push_str(&format!("[Event \"{}\"]\n", event));
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// game.move_piece("e5").unwrap();
+    /// game.undo();
+    /// game.move_piece("c5").unwrap();
+    ///
+    /// let moves: Vec<String> = game.history.line_moves().iter().map(|mov| mov.to_string()).collect();
+    /// assert_eq!(moves, vec!["e4", "c5"]);
+    /// ```
+    ///
+    pub fn line_moves(&self) -> Vec<T> {
+        let mut moves = Vec::new();
+        let mut node = self.current_line.as_ref().map(Rc::clone);
+        while let Some(line) = node {
+            moves.push(line.borrow().mov.clone());
+            node = line.borrow().parent.as_ref().map(Rc::clone);
+        }
+        moves.reverse();
+        moves
+    }
+
+    /// Forks the tree: the copy has the same tags, moves and variations and
+    /// is positioned at the same move
+    /// The nodes are shared until either tree changes them, which copies the
+    /// nodes of that tree first, so forking is cheap and changes to one tree
+    /// never reach the other
+    ///
+    /// # Returns
+    /// The fork of the tree
+    ///
+    pub fn fork_line(&self) -> PgnTree<T> {
+        PgnTree {
+            shared: self.shared.share(),
+            ..self.clone()
+        }
+    }
+
+    /// Copies the nodes of the tree if they are shared with a fork, keeping
+    /// the tree at the same move, so they can be changed
+    pub(crate) fn unshare(&mut self) {
+        if !self.shared.is_shared() {
+            return;
+        }
+        self.shared = ShareToken::default();
+
+        let copy = |node: &Rc<RefCell<PgnLine<T>>>, parent: Option<&Rc<RefCell<PgnLine<T>>>>| {
+            Rc::new(RefCell::new(PgnLine {
+                lines: Vec::new(),
+                parent: parent.map(Rc::clone),
+                ..node.borrow().clone()
+            }))
+        };
+        let current = self.current_line.take();
+        let mut pending = Vec::new();
+        let roots: Vec<_> = self
+            .lines
+            .iter()
+            .map(|line| {
+                let root = copy(line, None);
+                pending.push((Rc::clone(line), Rc::clone(&root)));
+                root
+            })
+            .collect();
+        while let Some((original, node)) = pending.pop() {
+            if current
+                .as_ref()
+                .is_some_and(|current| Rc::ptr_eq(current, &original))
+            {
+                self.current_line = Some(Rc::clone(&node));
+            }
+            let lines = original
+                .borrow()
+                .lines
+                .iter()
+                .map(|line| {
+                    let child = copy(line, Some(&node));
+                    pending.push((Rc::clone(line), Rc::clone(&child)));
+                    child
+                })
+                .collect();
+            node.borrow_mut().lines = lines;
+        }
+        self.lines = roots;
+    }
+
+    /// Merges the moves of another tree into this one, as when games are
+    /// combined into a repertoire
+    /// Both trees must start from the same position. The moves they share are
+    /// kept once, with the comments of both and the arrows, highlights and
+    /// node data missing here; the moves only the other tree has are added
+    /// as variations after the ones already here. The tags and the current
+    /// move are left alone
+    ///
+    /// # Arguments
+    /// * `other`: The tree to merge
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut repertoire = Game::default();
+    /// for mov in ["e4", "e5", "Nf3"] {
+    ///     repertoire.move_piece(mov).unwrap();
+    /// }
+    /// repertoire.history.set_comment(Some(String::from("Main line")));
+    ///
+    /// let mut game = Game::default();
+    /// for mov in ["e4", "c5"] {
+    ///     game.move_piece(mov).unwrap();
+    /// }
+    /// game.history.set_comment(Some(String::from("Sicilian")));
+    ///
+    /// repertoire.history.merge(&game.history);
+    /// assert_eq!(repertoire.pgn(), "1. e4 e5 (1... c5 { Sicilian }) 2. Nf3 { Main line } *");
+    /// ```
+    ///
+    pub fn merge(&mut self, other: &PgnTree<T>) {
+        self.unshare();
+        type Node<T> = Rc<RefCell<PgnLine<T>>>;
+
+        let mut pending: Vec<(Option<Node<T>>, Node<T>)> = other
+            .lines
+            .iter()
+            .rev()
+            .map(|line| (None, Rc::clone(line)))
+            .collect();
+        while let Some((parent, theirs)) = pending.pop() {
+            let siblings = match &parent {
+                Some(parent) => parent.borrow().lines.clone(),
+                None => self.lines.clone(),
+            };
+            let existing = siblings
+                .into_iter()
+                .find(|line| line.borrow().mov == theirs.borrow().mov);
+
+            let ours = match existing {
+                Some(ours) => {
+                    merge_annotations(&mut ours.borrow_mut(), &theirs.borrow());
+                    ours
+                }
+                None => {
+                    let copy = Rc::new(RefCell::new(PgnLine {
+                        lines: Vec::new(),
+                        parent: parent.as_ref().map(Rc::clone),
+                        ..theirs.borrow().clone()
+                    }));
+                    match &parent {
+                        Some(parent) => parent.borrow_mut().lines.push(Rc::clone(&copy)),
+                        None => self.lines.push(Rc::clone(&copy)),
+                    }
+                    copy
+                }
+            };
+            pending.extend(
+                theirs
+                    .borrow()
+                    .lines
+                    .iter()
+                    .rev()
+                    .map(|line| (Some(Rc::clone(&ours)), Rc::clone(line))),
+            );
         }
-        if let Some(site) = &self.site {
-            header.push_str(&format!("[Site \"{}\"]\n", site));
+    }
+
+    /// Compares the moves of this tree with the ones of another, as when two
+    /// versions of a study are compared
+    /// Both trees must start from the same position. Moves are matched the
+    /// same way `merge` does, and a variation only one tree has is reported
+    /// by its first move. The tags are not compared
+    ///
+    /// # Arguments
+    /// * `other`: The newer version of the tree
+    ///
+    /// # Returns
+    /// The moves the other tree added, the ones it removed and the ones whose
+    /// comment it changed, in the order they are found in the trees
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut study = Game::default();
+    /// let mut new_study = Game::default();
+    /// for mov in ["e4", "e5", "Nf3"] {
+    ///     study.move_piece(mov).unwrap();
+    ///     new_study.move_piece(mov).unwrap();
+    /// }
+    ///
+    /// new_study.history.set_comment(Some(String::from("Main line")));
+    /// new_study.undo();
+    /// new_study.move_piece("Bc4").unwrap();
+    ///
+    /// let diff = study.history.diff(&new_study.history);
+    /// let added: Vec<String> = diff.added[0].iter().map(|mov| mov.to_string()).collect();
+    ///
+    /// assert_eq!(diff.added.len(), 1);
+    /// assert_eq!(added, ["e4", "e5", "Bc4"]);
+    /// assert!(diff.removed.is_empty());
+    /// assert_eq!(diff.changed_comments.len(), 1);
+    /// assert!(study.history.diff(&study.history).is_empty());
+    /// ```
+    ///
+    pub fn diff(&self, other: &PgnTree<T>) -> TreeDiff<T> {
+        type Node<T> = Rc<RefCell<PgnLine<T>>>;
+        type Lines<T> = Vec<Node<T>>;
+
+        let mut diff = TreeDiff {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed_comments: Vec::new(),
+        };
+        let mut pending: Vec<(Vec<T>, Lines<T>, Lines<T>)> =
+            vec![(Vec::new(), self.lines.clone(), other.lines.clone())];
+        while let Some((path, ours, theirs)) = pending.pop() {
+            let line = |node: &Node<T>| {
+                let mut line = path.clone();
+                line.push(node.borrow().mov.clone());
+                line
+            };
+            let mut children = Vec::new();
+            for node in &ours {
+                match theirs
+                    .iter()
+                    .find(|other| other.borrow().mov == node.borrow().mov)
+                {
+                    Some(other) => {
+                        let line = line(node);
+                        let (node, other) = (node.borrow(), other.borrow());
+                        if node.comment_text() != other.comment_text() {
+                            diff.changed_comments.push(line.clone());
+                        }
+                        children.push((line, node.lines.clone(), other.lines.clone()));
+                    }
+                    None => diff.removed.push(line(node)),
+                }
+            }
+            for node in &theirs {
+                if !ours
+                    .iter()
+                    .any(|other| other.borrow().mov == node.borrow().mov)
+                {
+                    diff.added.push(line(node));
+                }
+            }
+            pending.extend(children.into_iter().rev());
         }
-        if let Some(date) = &self.date {
-            header.push_str(&format!("[Date \"{}\"]\n", date));
+        diff
+    }
+
+    /// Returns the node the tree is currently positioned at
+    ///
+    /// # Returns
+    /// A shared reference to the current node, or `None` if the tree is at the root
+    ///
+    pub(crate) fn current_node(&self) -> Option<Rc<RefCell<PgnLine<T>>>> {
+        self.current_line.as_ref().map(Rc::clone)
+    }
+
+    /// Returns the nodes of the current line
+    ///
+    /// # Returns
+    /// The nodes of the moves played to reach the current move, from the
+    /// first one
+    ///
+    pub(crate) fn line_nodes(&self) -> Vec<Rc<RefCell<PgnLine<T>>>> {
+        let mut nodes = Vec::new();
+        let mut node = self.current_node();
+        while let Some(line) = node {
+            node = line.borrow().parent.as_ref().map(Rc::clone);
+            nodes.push(line);
         }
-        if let Some(round) = &self.round {
-            header.push_str(&format!("[Round \"{}\"]\n", round));
+        nodes.reverse();
+        nodes
+    }
+
+    /// Returns the nodes of the first moves of the game, the main line first
+    ///
+    /// # Returns
+    /// The first node of every line of the tree
+    ///
+    pub(crate) fn root_nodes(&self) -> &[Rc<RefCell<PgnLine<T>>>] {
+        &self.lines
+    }
+
+    /// Returns the nodes of the main line, whatever the current move is
+    ///
+    /// # Returns
+    /// The nodes of the main line, from the first move
+    ///
+    pub(crate) fn main_line_nodes(&self) -> Vec<Rc<RefCell<PgnLine<T>>>> {
+        let mut nodes = Vec::new();
+        let mut next = self.lines.first().map(Rc::clone);
+        while let Some(node) = next {
+            next = node.borrow().lines.first().map(Rc::clone);
+            nodes.push(node);
         }
-        if let Some(white) = &self.white {
-            header.push_str(&format!("[White \"{}\"]\n", white));
+        nodes
+    }
+
+    /// Removes a continuation of the current move, with every move after it
+    /// The tree must not be shared with a fork (`unshare`), or the node
+    /// wouldn't be found
+    ///
+    /// # Arguments
+    /// * `node`: The node of the continuation
+    ///
+    pub(crate) fn remove_next(&mut self, node: &Rc<RefCell<PgnLine<T>>>) {
+        match &self.current_line {
+            Some(current) => current
+                .borrow_mut()
+                .lines
+                .retain(|line| !Rc::ptr_eq(line, node)),
+            None => self.lines.retain(|line| !Rc::ptr_eq(line, node)),
         }
-        if let Some(black) = &self.black {
-            header.push_str(&format!("[Black \"{}\"]\n", black));
+    }
+
+    /// Moves the tree cursor to the given node
+    ///
+    /// # Arguments
+    /// * `node`: The node to position the tree at, or `None` for the root
+    ///
+    pub(crate) fn set_current_node(&mut self, node: Option<Rc<RefCell<PgnLine<T>>>>) {
+        self.current_line = node;
+    }
+
+    /// Returns the number of moves in the tree, counting every variation
+    ///
+    /// # Returns
+    /// The number of nodes of the tree
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// game.move_piece("e5").unwrap();
+    /// game.undo();
+    /// game.move_piece("c5").unwrap();
+    ///
+    /// assert_eq!(game.history.node_count(), 3);
+    /// ```
+    ///
+    pub fn node_count(&self) -> usize {
+        self.nodes().len()
+    }
+
+    /// Computes the size and shape of the tree, as shown in game lists
+    ///
+    /// # Returns
+    /// The statistics of the tree
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// for mov in ["e4", "e5", "Nf3"] {
+    ///     game.move_piece(mov).unwrap();
+    /// }
+    /// game.undo();
+    /// game.move_piece("Bc4").unwrap();
+    /// game.history.set_comment(Some(String::from("Bishop's opening")));
+    ///
+    /// let stats = game.history.stats();
+    /// assert_eq!(stats.nodes, 4);
+    /// assert_eq!(stats.mainline_plies, 3);
+    /// assert_eq!(stats.variations, 1);
+    /// assert_eq!(stats.branching_factor, 4.0 / 3.0);
+    /// assert_eq!(stats.comments, 1);
+    /// ```
+    ///
+    pub fn stats(&self) -> TreeStats {
+        let nodes = self.nodes();
+        let continuations = |lines: usize| (lines > 0).then_some(lines);
+        let branches: Vec<usize> = continuations(self.lines.len())
+            .into_iter()
+            .chain(
+                nodes
+                    .iter()
+                    .filter_map(|node| continuations(node.borrow().lines.len())),
+            )
+            .collect();
+
+        let mut mainline_plies = 0;
+        let mut next = self.lines.first().map(Rc::clone);
+        while let Some(node) = next {
+            mainline_plies += 1;
+            next = node.borrow().lines.first().map(Rc::clone);
         }
-        if let Some(result) = &self.result {
-            header.push_str(&format!("[Result \"{}\"]\n", result));
+
+        TreeStats {
+            nodes: nodes.len(),
+            mainline_plies,
+            variations: branches.iter().map(|lines| lines - 1).sum(),
+            branching_factor: match branches.len() {
+                0 => 0.0,
+                positions => branches.iter().sum::<usize>() as f64 / positions as f64,
+            },
+            comments: nodes
+                .iter()
+                .filter(|node| node.borrow().comment.is_some())
+                .count(),
         }
-        if let Some(white_elo) = &self.white_elo {
-            header.push_str(&format!("[WhiteElo \"{}\"]\n", white_elo));
+    }
+
+    /// Estimates the memory the moves of the tree use, including the
+    /// comments, annotations, node data and cached renderings
+    /// The estimate doesn't account for the allocator overhead
+    ///
+    /// # Returns
+    /// The estimated size, in bytes
+    ///
+    pub fn memory_estimate(&self) -> usize {
+        let pointer = size_of::<Rc<RefCell<PgnLine<T>>>>();
+        let strings = |cache: &RenderCache<T>| {
+            cache
+                .borrow()
+                .as_ref()
+                .map_or(0, |(_, text)| text.capacity())
+        };
+
+        self.lines.capacity() * pointer
+            + self
+                .nodes()
+                .iter()
+                .map(|node| {
+                    let node = node.borrow();
+                    // The node and the reference counts of its `Rc`
+                    size_of::<RefCell<PgnLine<T>>>()
+                        + 2 * size_of::<usize>()
+                        + node.lines.capacity() * pointer
+                        + node.comment.as_ref().map_or(0, String::capacity)
+                        + node.arrows.capacity() * size_of::<Arrow>()
+                        + node.highlights.capacity() * size_of::<Highlight>()
+                        + node
+                            .node_data
+                            .iter()
+                            .map(|(key, value)| key.capacity() + value.capacity())
+                            .sum::<usize>()
+                        + node.node_data.capacity() * size_of::<(String, String)>()
+                        + node.position.as_ref().map_or(0, |position| {
+                            let prev_positions = &position.prev_positions;
+                            position.fen.capacity()
+                                + size_of::<HashMap<String, u32>>()
+                                + prev_positions.capacity() * size_of::<(String, u32)>()
+                                + prev_positions.keys().map(String::capacity).sum::<usize>()
+                        })
+                        + strings(&node.san)
+                        + strings(&node.uci)
+                })
+                .sum::<usize>()
+    }
+
+    /// Frees the memory the tree doesn't need: the cached SAN and UCI of the
+    /// moves are dropped, to be rendered again when asked for, and the spare
+    /// capacity of every node is released
+    /// The positions kept by `Game::enable_position_cache` are left alone
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// game.move_piece("e5").unwrap();
+    /// let before = game.history.memory_estimate();
+    ///
+    /// game.history.compact();
+    /// assert!(game.history.memory_estimate() < before);
+    /// assert_eq!(game.pgn(), "1. e4 e5 *");
+    /// ```
+    ///
+    pub fn compact(&mut self) {
+        self.lines.shrink_to_fit();
+        for node in self.nodes() {
+            let mut node = node.borrow_mut();
+            node.lines.shrink_to_fit();
+            node.arrows.shrink_to_fit();
+            node.highlights.shrink_to_fit();
+            node.node_data.shrink_to_fit();
+            if let Some(comment) = &mut node.comment {
+                comment.shrink_to_fit();
+            }
+            node.san.take();
+            node.uci.take();
         }
-        if let Some(black_elo) = &self.black_elo {
-            header.push_str(&format!("[BlackElo \"{}\"]\n", black_elo));
+    }
+
+    /// Returns every node of the tree
+    ///
+    /// # Returns
+    /// The nodes, each one before the lines that follow it
+    ///
+    fn nodes(&self) -> Vec<Rc<RefCell<PgnLine<T>>>> {
+        let mut nodes = Vec::new();
+        let mut pending: Vec<_> = self.lines.iter().rev().map(Rc::clone).collect();
+        while let Some(node) = pending.pop() {
+            pending.extend(node.borrow().lines.iter().rev().map(Rc::clone));
+            nodes.push(node);
         }
-        if let Some(time_control) = &self.time_control {
-            header.push_str(&format!("[TimeControl \"{}\"]\n", time_control));
+        nodes
+    }
+
+    /// Returns the PGN of the game
+    /// The movetext ends with the `Result` tag, or with `*` if it's not set
+    ///
+    /// # Returns
+    /// The tag pairs followed by the movetext
+    ///
+    pub fn pgn(&self) -> String {
+        self.pgn_with_result(self.result.as_deref().unwrap_or("*"), None)
+    }
+
+    /// Returns the PGN of the game, ending the movetext with the given game
+    /// termination marker
+    ///
+    /// # Arguments
+    /// * `result`: The termination marker (`1-0`, `0-1`, `1/2-1/2` or `*`)
+    /// * `fen`: The starting position, written in the `SetUp` and `FEN` tags
+    ///   if given
+    ///
+    /// # Returns
+    /// The tag pairs followed by the movetext
+    ///
+    pub(crate) fn pgn_with_result(&self, result: &str, fen: Option<&str>) -> String {
+        let mut pgn = String::new();
+        pgn.push_str(&self.pgn_header());
+        if let Some(fen) = fen {
+            pgn.push_str("[SetUp \"1\"]\n");
+            pgn.push_str(&format_tag("FEN", fen));
         }
-        if let Some(variant) = &self.variant {
-            header.push_str(&format!("[Variant \"{}\"]\n", variant));
+        let moves = self.pgn_moves();
+        let moves = moves.trim_end();
+        if !moves.is_empty() {
+            pgn.push_str(moves);
+            pgn.push(' ');
         }
-        header
+        pgn.push_str(result);
+        pgn
+    }
+
+    /// Returns the PGN header
+    ///
+    /// # Returns
+    /// The PGN header
+    ///
+    fn pgn_header(&self) -> String {
+        self.tag_fields()
+            .into_iter()
+            .filter_map(|(tag, value)| Some(format_tag(tag, &value?)))
+            .collect()
+    }
+
+    /// Returns the tags stored in the fields of the tree, in the order they
+    /// are written, so every writer of tags agrees on it
+    ///
+    /// # Returns
+    /// The name of each tag and its value, `None` if it isn't set
+    ///
+    pub(crate) fn tag_fields(&self) -> [(&'static str, Option<String>); 18] {
+        [
+            ("Event", self.event.clone()),
+            ("Site", self.site.clone()),
+            ("Date", self.date.clone()),
+            ("Round", self.round.clone()),
+            ("White", self.white.clone()),
+            ("Black", self.black.clone()),
+            ("Result", self.result.clone()),
+            ("EventDate", self.event_date.clone()),
+            ("UTCDate", self.utc_date.clone()),
+            ("UTCTime", self.utc_time.clone()),
+            ("WhiteElo", self.white_elo.map(|elo| elo.to_string())),
+            ("BlackElo", self.black_elo.map(|elo| elo.to_string())),
+            ("TimeControl", self.time_control.clone()),
+            ("WhiteClock", self.white_clock.clone()),
+            ("BlackClock", self.black_clock.clone()),
+            ("Variant", self.variant.clone()),
+            ("Handicap", self.handicap.clone()),
+            ("Termination", self.termination.clone()),
+        ]
     }
 
     fn pgn_moves(&self) -> String {
+        self.pgn_moves_with(&Self::move_text)
+    }
+
+    /// Returns the PGN movetext, rendering every node with the given function
+    /// instead of the default move and comment text
+    ///
+    /// # Arguments
+    /// * `node_text`: The function that renders a single node of the tree
+    ///
+    /// # Returns
+    /// The PGN movetext, including variations
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// game.move_piece("e5").unwrap();
+    ///
+    /// let moves = game.history.pgn_moves_with(&|line| format!("{}!", line.mov));
+    ///
+    /// assert_eq!(moves, "1. e4! e5!");
+    /// ```
+    ///
+    pub fn pgn_moves_with(&self, node_text: &dyn Fn(&PgnLine<T>) -> String) -> String {
         let mut pgn = String::new();
 
         if self.lines.is_empty() {
@@ -677,40 +1840,57 @@ impl<T: PartialEq + Clone + Display> PgnTree<T> {
         }
 
         let line = self.lines[0].as_ref().borrow();
-        pgn.push_str(&format!("1. {}", line.mov));
+        pgn.push_str(&format!("1. {}", node_text(&line)));
 
         for next in self.lines.iter().skip(1) {
             pgn.push_str(&format!(
                 " {}",
-                self.pgn_line_moves(Rc::clone(next), 1, true)
+                self.pgn_line_moves(Rc::clone(next), 1, true, node_text)
             ));
         }
 
         pgn.push_str(&format!(
             " {}",
-            self.pgn_line_moves(Rc::clone(&self.lines[0]), 2, false)
+            self.pgn_line_moves(Rc::clone(&self.lines[0]), 2, false, node_text)
         ));
 
         pgn
     }
 
+    /// Returns the text of a node as it appears in the PGN movetext,
+    /// the move followed by its comment and graphic annotations, if any
+    ///
+    /// # Arguments
+    /// * `line`: The node to get the text from
+    ///
+    /// # Returns
+    /// The move and comment text
+    ///
+    fn move_text(line: &PgnLine<T>) -> String {
+        match &line.comment_text() {
+            Some(comment) => format!("{} {{ {} }}", line.san(), comment),
+            None => line.san(),
+        }
+    }
+
     fn pgn_line_moves(
         &self,
         line: Rc<RefCell<PgnLine<T>>>,
         move_number: u32,
         secondary: bool,
+        node_text: &dyn Fn(&PgnLine<T>) -> String,
     ) -> String {
         let mut pgn = String::new();
 
         let mut tmp_move_number = move_number;
 
         if secondary {
-            if tmp_move_number % 2 == 0 {
+            if tmp_move_number.is_multiple_of(2) {
                 pgn.push_str(&format!("{}... ", tmp_move_number / 2))
             } else {
                 pgn.push_str(&format!("{}. ", tmp_move_number / 2 + 1));
             };
-            pgn.push_str(&format!("{} ", line.as_ref().borrow().mov));
+            pgn.push_str(&format!("{} ", node_text(&line.as_ref().borrow())));
 
             tmp_move_number += 1;
         }
@@ -724,20 +1904,25 @@ impl<T: PartialEq + Clone + Display> PgnTree<T> {
                 pgn.pop();
                 continue;
             } else {
-                if tmp_move_number % 2 != 0 {
+                if !tmp_move_number.is_multiple_of(2) {
                     pgn.push_str(&format!("{}. ", tmp_move_number / 2 + 1));
                 };
                 tmp_move_number += 1;
 
                 let next = Rc::clone(&line.lines[0]);
-                pgn.push_str(&format!("{} ", next.as_ref().borrow().mov));
+                pgn.push_str(&format!("{} ", node_text(&next.as_ref().borrow())));
                 stack.push(Rc::clone(&next));
 
                 if line.lines.len() != 1 {
                     for next in line.lines.iter().skip(1) {
                         pgn.push_str(&format!(
                             "{} ",
-                            self.pgn_line_moves(Rc::clone(next), tmp_move_number - 1, true)
+                            self.pgn_line_moves(
+                                Rc::clone(next),
+                                tmp_move_number - 1,
+                                true,
+                                node_text,
+                            )
                         ));
                     }
                 }
@@ -859,11 +2044,282 @@ impl<T: PartialEq + Clone + Display> DoubleEndedIterator for PgnTree<T> {
     }
 }
 
+#[cfg(feature = "chrono")]
+impl<T: PartialEq + Clone + Display> PgnTree<T> {
+    /// Sets the date of the game
+    ///
+    /// # Arguments
+    /// * `date`: The date
+    ///
+    /// # Example
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.history.set_date(NaiveDate::from_ymd_opt(1851, 6, 21).unwrap());
+    ///
+    /// assert_eq!(game.history.date, Some(String::from("1851.06.21")));
+    /// assert_eq!(game.history.get_date(), NaiveDate::from_ymd_opt(1851, 6, 21));
+    /// ```
+    ///
+    pub fn set_date(&mut self, date: NaiveDate) {
+        self.date = Some(format_date(date));
+    }
+
+    /// Returns the date of the game
+    ///
+    /// # Returns
+    /// The date, or `None` if it's not set, any part of it is unknown or it
+    /// is not a valid date
+    ///
+    pub fn get_date(&self) -> Option<NaiveDate> {
+        parse_date(self.date.as_ref()?)
+    }
+
+    /// Sets the date the event started, the `EventDate` tag
+    ///
+    /// # Arguments
+    /// * `date`: The date
+    ///
+    pub fn set_event_date(&mut self, date: NaiveDate) {
+        self.event_date = Some(format_date(date));
+    }
+
+    /// Returns the date the event started, from the `EventDate` tag
+    ///
+    /// # Returns
+    /// The date, or `None` if it's not set, any part of it is unknown or it
+    /// is not a valid date
+    ///
+    pub fn get_event_date(&self) -> Option<NaiveDate> {
+        parse_date(self.event_date.as_ref()?)
+    }
+
+    /// Sets the moment the game started, the `UTCDate` and `UTCTime` tags
+    ///
+    /// # Arguments
+    /// * `timestamp`: The moment the game started
+    ///
+    /// # Example
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// let start = Utc.with_ymd_and_hms(2024, 3, 9, 18, 5, 42).unwrap();
+    /// game.history.set_utc(start);
+    ///
+    /// assert_eq!(game.history.utc_date, Some(String::from("2024.03.09")));
+    /// assert_eq!(game.history.utc_time, Some(String::from("18:05:42")));
+    /// assert_eq!(game.history.get_utc(), Some(start));
+    /// ```
+    ///
+    pub fn set_utc(&mut self, timestamp: DateTime<Utc>) {
+        let time = timestamp.time();
+        self.utc_date = Some(format_date(timestamp.date_naive()));
+        self.utc_time = Some(format!(
+            "{:02}:{:02}:{:02}",
+            time.hour(),
+            time.minute(),
+            time.second()
+        ));
+    }
+
+    /// Returns the moment the game started, from the `UTCDate` and `UTCTime`
+    /// tags
+    ///
+    /// # Returns
+    /// The moment, or `None` if any of the tags is missing or invalid
+    ///
+    pub fn get_utc(&self) -> Option<DateTime<Utc>> {
+        let date = parse_date(self.utc_date.as_ref()?)?;
+        let mut parts = self
+            .utc_time
+            .as_ref()?
+            .split(':')
+            .map(|part| part.parse().ok());
+        let (hour, minute, second) = (parts.next()??, parts.next()??, parts.next()??);
+        if parts.next().is_some() {
+            return None;
+        }
+        let time = NaiveTime::from_hms_opt(hour, minute, second)?;
+        Some(date.and_time(time).and_utc())
+    }
+
+    /// Stamps the game with the current date and time, in the `UTCDate` and
+    /// `UTCTime` tags
+    pub fn stamp_now(&mut self) {
+        self.set_utc(Utc::now());
+    }
+}
+
+/// Writes a date as the PGN standard does (`2024.03.09`)
+///
+/// # Arguments
+/// * `date`: The date
+///
+/// # Returns
+/// The date, with the year of dates before the common era unknown
+///
+#[cfg(feature = "chrono")]
+fn format_date(date: NaiveDate) -> String {
+    let year =
+        u32::try_from(date.year()).map_or(String::from("????"), |year| format!("{:04}", year));
+    format!("{}.{:02}.{:02}", year, date.month(), date.day())
+}
+
+/// Reads a date written as the PGN standard does (`2024.03.09`)
+///
+/// # Arguments
+/// * `date`: The date
+///
+/// # Returns
+/// The date, or `None` if any part of it is unknown or it is not a valid date
+///
+#[cfg(feature = "chrono")]
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    let mut parts = date.split('.').map(|part| part.parse().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    if parts.next().is_some() {
+        return None;
+    }
+    NaiveDate::from_ymd_opt(year as i32, month, day)
+}
+
+#[cfg(feature = "serde")]
+impl<T: PartialEq + Clone + Display> PgnTree<T> {
+    /// Returns the typed data stored on the current move under a key,
+    /// deserializing it from JSON
+    ///
+    /// # Arguments
+    /// * `key`: The key of the data
+    ///
+    /// # Returns
+    /// The data, or `None` if there is no current move, no data under the key
+    /// or the data can't be deserialized into `V`
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// game.history.set_node_value("reviews", &vec![1, 3, 7]).unwrap();
+    ///
+    /// assert_eq!(game.history.get_node_value::<Vec<u32>>("reviews"), Some(vec![1, 3, 7]));
+    /// assert_eq!(game.history.get_node_data("reviews"), Some("[1,3,7]".to_string()));
+    /// ```
+    ///
+    pub fn get_node_value<V: DeserializeOwned>(&self, key: &str) -> Option<V> {
+        serde_json::from_str(&self.get_node_data(key)?).ok()
+    }
+
+    /// Stores typed data on the current move under a key, serialized as JSON
+    /// Does nothing if there is no current move
+    ///
+    /// # Arguments
+    /// * `key`: The key of the data
+    /// * `value`: The data to store
+    ///
+    /// # Returns
+    /// An error if the data can't be serialized as JSON
+    ///
+    pub fn set_node_value<V: Serialize>(
+        &mut self,
+        key: &str,
+        value: &V,
+    ) -> Result<(), InteropError> {
+        let value = serde_json::to_string(value).map_err(|_| InteropError::InvalidJson)?;
+        self.set_node_data(key, Some(value));
+        Ok(())
+    }
+}
+
+/// Checks if a text is a game termination marker
+///
+/// # Arguments
+/// * `result`: The text
+///
+/// # Returns
+/// Whether the text is `1-0`, `0-1`, `1/2-1/2` or `*`
+///
+pub(crate) fn is_result(result: &str) -> bool {
+    matches!(result, "1-0" | "0-1" | "1/2-1/2" | "*")
+}
+
+/// Writes a tag pair of a PGN header, escaping the quotes and backslashes of
+/// its value as the PGN standard requires
+///
+/// # Arguments
+/// * `name`: The name of the tag
+/// * `value`: The value of the tag
+///
+/// # Returns
+/// The tag pair, followed by a line break
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::pgn::format_tag;
+///
+/// assert_eq!(format_tag("Event", "Casual"), "[Event \"Casual\"]\n");
+/// assert_eq!(
+///     format_tag("Annotator", "\"The Beast\" C:\\"),
+///     "[Annotator \"\\\"The Beast\\\" C:\\\\\"]\n"
+/// );
+/// ```
+///
+pub fn format_tag(name: &str, value: &str) -> String {
+    let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("[{} \"{}\"]\n", name, value)
+}
+
+/// Adds the annotations of a node to the node of the same move in another
+/// tree
+/// The comments are joined unless one already contains the other, and the
+/// node data already set is kept
+///
+/// # Arguments
+/// * `ours`: The node the annotations are added to
+/// * `theirs`: The node the annotations are taken from
+///
+fn merge_annotations<T: PartialEq + Clone + Display>(ours: &mut PgnLine<T>, theirs: &PgnLine<T>) {
+    ours.comment = match (ours.comment.take(), &theirs.comment) {
+        (Some(comment), Some(other)) if !comment.contains(other.as_str()) => {
+            if other.contains(comment.as_str()) {
+                Some(other.clone())
+            } else {
+                Some(format!("{} {}", comment, other))
+            }
+        }
+        (comment, other) => comment.or_else(|| other.clone()),
+    };
+    for arrow in &theirs.arrows {
+        if !ours.arrows.contains(arrow) {
+            ours.arrows.push(*arrow);
+        }
+    }
+    for highlight in &theirs.highlights {
+        if !ours.highlights.contains(highlight) {
+            ours.highlights.push(*highlight);
+        }
+    }
+    for (key, value) in &theirs.node_data {
+        ours.node_data
+            .entry(key.clone())
+            .or_insert_with(|| value.clone());
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use crate::constants::pgn::PgnTree;
-    use crate::constants::{Color, GameStatus, Move, MoveType, PieceType, Position};
-    use crate::logic::Piece;
+    use crate::constants::{
+        AnnotationColor, Color, GameStatus, Move, MoveType, PieceType, Position,
+    };
+    use crate::logic::{Game, Piece};
 
     #[test]
     fn test_add_move() {
@@ -1031,20 +2487,23 @@ mod tests {
 
     #[test]
     fn test_pgn_header() {
-        let mut tree: PgnTree<Move> = PgnTree::default();
-        tree.event = Some("Event".to_string());
-        tree.site = Some("Site".to_string());
-        tree.date = Some("Date".to_string());
-        tree.round = Some("Round".to_string());
-        tree.white = Some("White".to_string());
-        tree.black = Some("Black".to_string());
-        tree.result = Some("Result".to_string());
-        tree.white_elo = Some(1000);
-        tree.black_elo = Some(1000);
-        tree.time_control = Some("TimeControl".to_string());
-        tree.variant = Some("Variant".to_string());
-
-        assert_eq!(tree.pgn_header(), "[Event \"Event\"]\n[Site \"Site\"]\n[Date \"Date\"]\n[Round \"Round\"]\n[White \"White\"]\n[Black \"Black\"]\n[Result \"Result\"]\n[WhiteElo \"1000\"]\n[BlackElo \"1000\"]\n[TimeControl \"TimeControl\"]\n[Variant \"Variant\"]\n");
+        let tree: PgnTree<Move> = PgnTree {
+            event: Some("Event".to_string()),
+            site: Some("Site".to_string()),
+            date: Some("Date".to_string()),
+            round: Some("Round".to_string()),
+            white: Some("White".to_string()),
+            black: Some("Black".to_string()),
+            result: Some("Result".to_string()),
+            white_elo: Some(1000),
+            black_elo: Some(1000),
+            time_control: Some("TimeControl".to_string()),
+            variant: Some("Variant".to_string()),
+            termination: Some("Termination".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(tree.pgn_header(), "[Event \"Event\"]\n[Site \"Site\"]\n[Date \"Date\"]\n[Round \"Round\"]\n[White \"White\"]\n[Black \"Black\"]\n[Result \"Result\"]\n[WhiteElo \"1000\"]\n[BlackElo \"1000\"]\n[TimeControl \"TimeControl\"]\n[Variant \"Variant\"]\n[Termination \"Termination\"]\n");
     }
 
     #[test]
@@ -1123,6 +2582,281 @@ mod tests {
         pgn_tree.add_move(mov1.clone(), 0, 0, None, 0, GameStatus::InProgress);
         pgn_tree.add_move(mov2.clone(), 0, 0, None, 0, GameStatus::InProgress);
 
-        assert_eq!(pgn_tree.pgn(), "1. e4 e5");
+        assert_eq!(pgn_tree.pgn(), "1. e4 e5 *");
+    }
+
+    #[test]
+    fn test_pgn_comments() {
+        let mut pgn_tree = PgnTree::default();
+        let mov1 = Move::new(
+            Piece::new(Color::White, PieceType::Pawn),
+            Position::from_string("e2"),
+            Position::from_string("e4"),
+            MoveType::Normal {
+                capture: false,
+                promotion: None,
+            },
+            None,
+            None,
+            (false, false),
+            false,
+            false,
+        );
+        let mov2 = Move::new(
+            Piece::new(Color::Black, PieceType::Pawn),
+            Position::from_string("e7"),
+            Position::from_string("e5"),
+            MoveType::Normal {
+                capture: false,
+                promotion: None,
+            },
+            None,
+            None,
+            (false, false),
+            false,
+            false,
+        );
+        pgn_tree.add_move(mov1.clone(), 0, 0, None, 0, GameStatus::InProgress);
+        pgn_tree.set_comment(Some("[%clk 0:03:00]".to_string()));
+        pgn_tree.add_move(mov2.clone(), 0, 0, None, 0, GameStatus::InProgress);
+        pgn_tree.set_comment(Some("[%eval 0.2]".to_string()));
+
+        assert_eq!(
+            pgn_tree.pgn(),
+            "1. e4 { [%clk 0:03:00] } e5 { [%eval 0.2] } *"
+        );
+
+        pgn_tree.add_arrow(
+            Position::from_string("g1"),
+            Position::from_string("f3"),
+            AnnotationColor::Green,
+        );
+        pgn_tree.add_highlight(Position::from_string("e5"), AnnotationColor::Red);
+        pgn_tree.add_highlight(Position::from_string("e5"), AnnotationColor::Red);
+        assert_eq!(pgn_tree.get_arrows().len(), 1);
+        assert_eq!(pgn_tree.get_highlights().len(), 1);
+        assert_eq!(
+            pgn_tree.pgn(),
+            "1. e4 { [%clk 0:03:00] } e5 { [%csl Re5][%cal Gg1f3] [%eval 0.2] } *"
+        );
+
+        pgn_tree.clear_annotations();
+        pgn_tree.set_comment(None);
+        assert_eq!(pgn_tree.pgn(), "1. e4 { [%clk 0:03:00] } e5 *");
+    }
+
+    #[test]
+    fn test_cached_san() {
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/4K3/R6R w - - 0 1");
+        game.move_piece("Rad1").unwrap();
+        let node = game.history.current_node().unwrap();
+        assert_eq!(node.borrow().san(), "Rad1");
+        assert_eq!(node.borrow().uci(), "a1d1");
+
+        node.borrow_mut().mov.ambiguity = (false, false);
+        node.borrow_mut().mov.check = true;
+        assert_eq!(node.borrow().san(), "Rd1+");
+        assert_eq!(
+            game.pgn(),
+            "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4K3/R6R w - - 0 1\"]\n1. Rd1+ *"
+        );
+    }
+
+    #[test]
+    fn test_node_data() {
+        let mut game = Game::default();
+        game.history.set_node_data("seen", Some("true".to_string()));
+        assert_eq!(game.history.get_node_data("seen"), None);
+
+        game.move_piece("e4").unwrap();
+        game.history.set_node_data("seen", Some("true".to_string()));
+        game.move_piece("e5").unwrap();
+        assert_eq!(game.history.get_node_data("seen"), None);
+
+        game.undo();
+        assert_eq!(game.history.get_node_data("seen"), Some("true".to_string()));
+        game.history.set_node_data("seen", None);
+        assert_eq!(game.history.get_node_data("seen"), None);
+        assert_eq!(game.pgn(), "1. e4 e5 *");
+    }
+
+    #[test]
+    fn test_merge() {
+        let game = |moves: &[&str], comment: &str| {
+            let mut game = Game::default();
+            for mov in moves {
+                game.move_piece(mov).unwrap();
+            }
+            game.history.set_comment(Some(comment.to_string()));
+            game.history.add_arrow(
+                Position::from_string("g1"),
+                Position::from_string("f3"),
+                AnnotationColor::Green,
+            );
+            game.history
+                .set_node_data("source", Some(comment.to_string()));
+            game
+        };
+
+        let mut repertoire = game(&["e4", "e5", "Nf3"], "Open");
+        for other in [
+            game(&["e4", "e5", "Nf3"], "Main"),
+            game(&["e4", "e5", "Nf3"], "Open"),
+            game(&["e4", "c5", "Nf3", "d6"], "Najdorf soon"),
+            game(&["d4"], "Closed"),
+        ] {
+            repertoire.history.merge(&other.history);
+        }
+
+        assert_eq!(repertoire.history.node_count(), 7);
+        assert_eq!(
+            repertoire.history.get_comment(),
+            Some("Open Main".to_string())
+        );
+        assert_eq!(repertoire.history.get_arrows().len(), 1);
+        assert_eq!(
+            repertoire.history.get_node_data("source"),
+            Some("Open".to_string())
+        );
+        assert_eq!(
+            repertoire.pgn(),
+            "1. e4 (1. d4 { [%cal Gg1f3] [%data source Closed] Closed }) e5 (1... c5 2. Nf3 d6 { [%cal Gg1f3] [%data source Najdorf%20soon] Najdorf soon }) 2. Nf3 { [%cal Gg1f3] [%data source Open] Open Main } *"
+        );
+
+        // The merged moves are copies, the other tree is left alone
+        let mut other = game(&["e4", "c5"], "Sicilian");
+        repertoire.history.merge(&other.history);
+        other.history.set_comment(None);
+        repertoire.undo();
+        repertoire.undo();
+        assert_eq!(
+            repertoire.history.next_move_variant(1).unwrap().to_string(),
+            "c5"
+        );
+        assert_eq!(
+            repertoire.history.get_comment(),
+            Some("Sicilian".to_string())
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let game = |lines: &[&[&str]]| {
+            let mut game = Game::default();
+            for moves in lines {
+                while game.history.get_move().is_some() {
+                    game.undo();
+                }
+                for mov in *moves {
+                    game.move_piece(mov).unwrap();
+                }
+            }
+            game
+        };
+        let lines = |moves: &[Vec<Move>]| {
+            moves
+                .iter()
+                .map(|line| {
+                    line.iter()
+                        .map(|mov| mov.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let old = game(&[&["e4", "e5", "Nf3", "Nc6"], &["e4", "c5"], &["d4"]]);
+        let mut new = game(&[&["e4", "e5", "Nf3", "Nf6"], &["e4", "c5", "Nf3"]]);
+        new.undo();
+        new.history.add_arrow(
+            Position::from_string("g1"),
+            Position::from_string("f3"),
+            AnnotationColor::Green,
+        );
+
+        let diff = old.history.diff(&new.history);
+        assert_eq!(lines(&diff.added), vec!["e4 e5 Nf3 Nf6", "e4 c5 Nf3"]);
+        assert_eq!(lines(&diff.removed), vec!["d4", "e4 e5 Nf3 Nc6"]);
+        assert_eq!(lines(&diff.changed_comments), vec!["e4 c5"]);
+
+        let diff = new.history.diff(&old.history);
+        assert_eq!(lines(&diff.added), vec!["d4", "e4 e5 Nf3 Nc6"]);
+        assert!(!diff.is_empty());
+        assert!(old.history.diff(&old.history).is_empty());
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = PgnTree::<Move>::default().stats();
+        assert_eq!(stats.nodes, 0);
+        assert_eq!(stats.mainline_plies, 0);
+        assert_eq!(stats.branching_factor, 0.0);
+
+        let game = crate::parsing::parse_pgn_with_options(
+            "1. e4 { Best by test } (1. d4 d5 (1... Nf6) (1... f5)) (1. c4) 1... e5 *",
+            &crate::parsing::PgnParseOptions::lenient(),
+        )
+        .unwrap();
+        let stats = game.history.stats();
+
+        assert_eq!(stats.nodes, 7);
+        assert_eq!(stats.mainline_plies, 2);
+        assert_eq!(stats.variations, 4);
+        // The root has three moves, e4 and d4 one and three
+        assert_eq!(stats.branching_factor, 7.0 / 3.0);
+        assert_eq!(stats.comments, 1);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_utc_tags() {
+        use chrono::{NaiveDate, TimeZone, Utc};
+
+        let mut game = Game::default();
+        assert_eq!(game.history.get_utc(), None);
+        game.history
+            .set_utc(Utc.with_ymd_and_hms(2023, 12, 31, 23, 59, 7).unwrap());
+        game.history
+            .set_event_date(NaiveDate::from_ymd_opt(2023, 12, 28).unwrap());
+        assert!(game.pgn().contains(
+            "[EventDate \"2023.12.28\"]\n[UTCDate \"2023.12.31\"]\n[UTCTime \"23:59:07\"]"
+        ));
+
+        game.history.utc_time = Some(String::from("23:59"));
+        assert_eq!(game.history.get_utc(), None);
+        game.history.utc_time = Some(String::from("24:00:00"));
+        assert_eq!(game.history.get_utc(), None);
+        game.history.event_date = Some(String::from("2023.??.??"));
+        assert_eq!(game.history.get_event_date(), None);
+    }
+
+    #[test]
+    fn test_line_times() {
+        let mut game = Game::default();
+        assert_eq!(game.history.get_clock(), None);
+        game.history.set_clock(Some(Duration::from_secs(60)));
+        assert_eq!(game.history.get_comment(), None);
+
+        game.move_piece("e4").unwrap();
+        game.history.set_clock(Some(Duration::from_secs(60)));
+        game.move_piece("e5").unwrap();
+        game.history
+            .set_comment(Some("[%clk 0:00:55] [%emt 0:00:05]".to_string()));
+        game.history.set_clock(Some(Duration::from_secs(54)));
+        game.history.set_elapsed(None);
+        game.move_piece("Nf3").unwrap();
+
+        assert_eq!(
+            game.history.line_times(),
+            vec![
+                (Some(Duration::from_secs(60)), None),
+                (Some(Duration::from_secs(54)), None),
+                (None, None)
+            ]
+        );
+        assert_eq!(
+            game.pgn(),
+            "1. e4 { [%clk 0:01:00] } e5 { [%clk 0:00:54] } 2. Nf3 *"
+        );
     }
 }
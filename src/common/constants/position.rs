@@ -1,4 +1,256 @@
-use std::ops;
+use std::{
+    cmp::Ordering,
+    fmt::{Display, Error, Formatter},
+    ops,
+    str::FromStr,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::errors::PositionError;
+
+/// Represents a file (column) of the board
+///
+/// # Variants
+/// * `A` to `H`: The files from the queen side to the king side
+///
+/// # Examples
+/// ```
+/// use chess_lab::constants::File;
+///
+/// assert_eq!(File::from_char('e'), Some(File::E));
+/// assert_eq!(File::E.index(), 4);
+/// assert_eq!(File::E.to_string(), "e");
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum File {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+}
+
+impl File {
+    /// All the files, from a to h
+    pub const ALL: [File; 8] = [
+        File::A,
+        File::B,
+        File::C,
+        File::D,
+        File::E,
+        File::F,
+        File::G,
+        File::H,
+    ];
+
+    /// Gets the file from its index
+    ///
+    /// # Arguments
+    /// * `index`: The index of the file (between 0 and 7)
+    ///
+    /// # Returns
+    /// The file, or `None` if the index is out of bounds
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::File;
+    ///
+    /// assert_eq!(File::from_index(0), Some(File::A));
+    /// assert_eq!(File::from_index(8), None);
+    /// ```
+    ///
+    pub fn from_index(index: u8) -> Option<File> {
+        File::ALL.get(index as usize).copied()
+    }
+
+    /// Gets the file from its character
+    ///
+    /// # Arguments
+    /// * `c`: The character of the file (between 'a' and 'h')
+    ///
+    /// # Returns
+    /// The file, or `None` if the character is not a file
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::File;
+    ///
+    /// assert_eq!(File::from_char('h'), Some(File::H));
+    /// assert_eq!(File::from_char('H'), None);
+    /// ```
+    ///
+    pub fn from_char(c: char) -> Option<File> {
+        if c.is_ascii_lowercase() {
+            File::from_index(c as u8 - b'a')
+        } else {
+            None
+        }
+    }
+
+    /// Gets the index of the file
+    ///
+    /// # Returns
+    /// The index of the file, between 0 (a) and 7 (h)
+    ///
+    pub const fn index(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Gets the bitboard with every square of the file set
+    ///
+    /// # Returns
+    /// The bitboard of the file
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::File;
+    ///
+    /// assert_eq!(File::B.bitboard(), 0x0202020202020202);
+    /// ```
+    ///
+    pub fn bitboard(&self) -> u64 {
+        0x0101010101010101 << self.index()
+    }
+
+    /// Gets the character of the file
+    ///
+    /// # Returns
+    /// The character of the file, between 'a' and 'h'
+    ///
+    pub fn to_char(&self) -> char {
+        (b'a' + self.index()) as char
+    }
+}
+
+impl Display for File {
+    /// Converts the file to a string
+    ///
+    /// # Returns
+    /// The character of the file
+    ///
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+/// Represents a rank (row) of the board
+///
+/// # Variants
+/// * `First` to `Eighth`: The ranks from the white side to the black side
+///
+/// # Examples
+/// ```
+/// use chess_lab::constants::Rank;
+///
+/// assert_eq!(Rank::from_char('4'), Some(Rank::Fourth));
+/// assert_eq!(Rank::Fourth.index(), 3);
+/// assert_eq!(Rank::Fourth.to_string(), "4");
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Rank {
+    First,
+    Second,
+    Third,
+    Fourth,
+    Fifth,
+    Sixth,
+    Seventh,
+    Eighth,
+}
+
+impl Rank {
+    /// All the ranks, from 1 to 8
+    pub const ALL: [Rank; 8] = [
+        Rank::First,
+        Rank::Second,
+        Rank::Third,
+        Rank::Fourth,
+        Rank::Fifth,
+        Rank::Sixth,
+        Rank::Seventh,
+        Rank::Eighth,
+    ];
+
+    /// Gets the rank from its index
+    ///
+    /// # Arguments
+    /// * `index`: The index of the rank (between 0 and 7)
+    ///
+    /// # Returns
+    /// The rank, or `None` if the index is out of bounds
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::Rank;
+    ///
+    /// assert_eq!(Rank::from_index(7), Some(Rank::Eighth));
+    /// assert_eq!(Rank::from_index(8), None);
+    /// ```
+    ///
+    pub fn from_index(index: u8) -> Option<Rank> {
+        Rank::ALL.get(index as usize).copied()
+    }
+
+    /// Gets the rank from its character
+    ///
+    /// # Arguments
+    /// * `c`: The character of the rank (between '1' and '8')
+    ///
+    /// # Returns
+    /// The rank, or `None` if the character is not a rank
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::Rank;
+    ///
+    /// assert_eq!(Rank::from_char('1'), Some(Rank::First));
+    /// assert_eq!(Rank::from_char('9'), None);
+    /// ```
+    ///
+    pub fn from_char(c: char) -> Option<Rank> {
+        if c.is_ascii_digit() && c != '0' {
+            Rank::from_index(c as u8 - b'1')
+        } else {
+            None
+        }
+    }
+
+    /// Gets the index of the rank
+    ///
+    /// # Returns
+    /// The index of the rank, between 0 (1) and 7 (8)
+    ///
+    pub const fn index(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Gets the character of the rank
+    ///
+    /// # Returns
+    /// The character of the rank, between '1' and '8'
+    ///
+    pub fn to_char(&self) -> char {
+        (b'1' + self.index()) as char
+    }
+}
+
+impl Display for Rank {
+    /// Converts the rank to a string
+    ///
+    /// # Returns
+    /// The character of the rank
+    ///
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}", self.to_char())
+    }
+}
 
 /// Represents a position on the board.
 /// The position is represented by a column and a row.
@@ -17,7 +269,8 @@ use std::ops;
 /// assert_eq!(pos.row, 0);
 /// ```
 ///
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Position {
     pub col: u8,
     pub row: u8,
@@ -46,11 +299,36 @@ impl Position {
     /// assert_eq!(pos.row, 0);
     /// ```
     ///
-    pub fn new(col: u8, row: u8) -> Position {
+    pub const fn new(col: u8, row: u8) -> Position {
         assert!(col < 8 && row < 8, "Position out of bounds");
         Position { col, row }
     }
 
+    /// Creates a new position from a file and a rank
+    ///
+    /// # Arguments
+    /// * `file`: The file of the position
+    /// * `rank`: The rank of the position
+    ///
+    /// # Returns
+    /// A new position
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::{File, Position, Rank};
+    ///
+    /// let pos = Position::from_file_rank(File::E, Rank::Fourth);
+    ///
+    /// assert_eq!(pos.to_string(), "e4");
+    /// ```
+    ///
+    pub const fn from_file_rank(file: File, rank: Rank) -> Position {
+        Position {
+            col: file.index(),
+            row: rank.index(),
+        }
+    }
+
     /// Creates a new position from a string
     ///
     /// # Arguments
@@ -70,16 +348,51 @@ impl Position {
     ///
     /// assert_eq!(pos.col, 0);
     /// assert_eq!(pos.row, 0);
+    ///
+    /// const CENTER: [Position; 2] = [Position::from_string("d4"), Position::from_string("e5")];
+    /// assert_eq!(CENTER[1], Position::new(4, 4));
     /// ```
     ///
-    pub fn from_string(s: &str) -> Position {
-        assert!(s.len() == 2, "Invalid position string");
-        if s.len() != 2 {
-            assert!(false, "Invalid position string");
+    pub const fn from_string(s: &str) -> Position {
+        match s.as_bytes() {
+            [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => Position {
+                col: *file - b'a',
+                row: *rank - b'1',
+            },
+            _ => panic!("Invalid position string"),
         }
-        let col = s.chars().nth(0).unwrap() as u8 - 'a' as u8;
-        let row = s.chars().nth(1).unwrap() as u8 - '1' as u8;
-        Position::new(col, row)
+    }
+
+    /// Gets the file of the position
+    ///
+    /// # Returns
+    /// The file of the position
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::{File, Position};
+    ///
+    /// assert_eq!(Position::from_string("c7").file(), File::C);
+    /// ```
+    ///
+    pub fn file(&self) -> File {
+        File::from_index(self.col).unwrap()
+    }
+
+    /// Gets the rank of the position
+    ///
+    /// # Returns
+    /// The rank of the position
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::{Position, Rank};
+    ///
+    /// assert_eq!(Position::from_string("c7").rank(), Rank::Seventh);
+    /// ```
+    ///
+    pub fn rank(&self) -> Rank {
+        Rank::from_index(self.row).unwrap()
     }
 
     /// Converts the position to a string
@@ -96,7 +409,7 @@ impl Position {
     /// assert_eq!(pos.to_bitboard(), 0x0000000000000001);
     /// ```
     ///
-    pub fn to_bitboard(&self) -> u64 {
+    pub const fn to_bitboard(&self) -> u64 {
         1 << (self.row * 8 + self.col)
     }
 
@@ -156,6 +469,73 @@ impl Position {
         row = if row == 0 { 0 } else { row / row.abs() };
         (col, row)
     }
+
+    /// Adds an offset to the position, checking that it stays on the board
+    ///
+    /// # Arguments
+    /// * `offset`: The columns and rows to move
+    ///
+    /// # Returns
+    /// The new position, or `None` if it's off the board
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::Position;
+    ///
+    /// let pos = Position::from_string("g1");
+    ///
+    /// assert_eq!(pos.checked_add((-1, 2)), Some(Position::from_string("f3")));
+    /// assert_eq!(pos.checked_add((2, 1)), None);
+    /// ```
+    ///
+    pub fn checked_add(&self, offset: (i8, i8)) -> Option<Position> {
+        self.checked_offset(offset.0 as i16, offset.1 as i16)
+    }
+
+    /// Moves the position by a number of columns and rows, in a type wide
+    /// enough for any `i8` offset, even negated
+    ///
+    /// # Arguments
+    /// * `cols`: The columns to move
+    /// * `rows`: The rows to move
+    ///
+    /// # Returns
+    /// The new position, or `None` if it's off the board
+    ///
+    fn checked_offset(&self, cols: i16, rows: i16) -> Option<Position> {
+        let col = self.col as i16 + cols;
+        let row = self.row as i16 + rows;
+        if !(0..8).contains(&col) || !(0..8).contains(&row) {
+            return None;
+        }
+        Some(Position {
+            col: col as u8,
+            row: row as u8,
+        })
+    }
+}
+
+/// Creates a [`Position`] from the name of its square, parsed at compile time
+/// An invalid square is a compile error, so tables of squares can be consts
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::Position;
+/// use chess_lab::pos;
+///
+/// const KING_SQUARES: [Position; 2] = [pos!("e1"), pos!("e8")];
+///
+/// assert_eq!(KING_SQUARES[0], Position::new(4, 0));
+/// assert_eq!(pos!("h8").to_string(), "h8");
+/// ```
+///
+#[macro_export]
+macro_rules! pos {
+    ($square:literal) => {{
+        const POSITION: $crate::constants::Position =
+            $crate::constants::Position::from_string($square);
+        POSITION
+    }};
 }
 
 impl ops::Add<(i8, i8)> for &Position {
@@ -169,6 +549,10 @@ impl ops::Add<(i8, i8)> for &Position {
     /// # Returns
     /// The new position
     ///
+    /// # Panics
+    /// Panics if the new position is off the board, use
+    /// [`Position::checked_add`] when the offset isn't known to be valid
+    ///
     /// # Examples
     /// ```
     /// use chess_lab::constants::Position;
@@ -181,10 +565,7 @@ impl ops::Add<(i8, i8)> for &Position {
     /// ```
     ///
     fn add(self, other: (i8, i8)) -> Position {
-        Position::new(
-            (self.col as i8 + other.0) as u8,
-            (self.row as i8 + other.1) as u8,
-        )
+        self.checked_add(other).expect("Position out of bounds")
     }
 }
 
@@ -230,6 +611,9 @@ impl ops::Sub<(i8, i8)> for &Position {
     /// # Returns
     /// The new position
     ///
+    /// # Panics
+    /// Panics if the new position is off the board
+    ///
     /// # Examples
     /// ```
     /// use chess_lab::constants::Position;
@@ -242,14 +626,75 @@ impl ops::Sub<(i8, i8)> for &Position {
     /// ```
     ///
     fn sub(self, other: (i8, i8)) -> Position {
-        Position {
-            col: (self.col as i8 - other.0) as u8,
-            row: (self.row as i8 - other.1) as u8,
+        self.checked_offset(-(other.0 as i16), -(other.1 as i16))
+            .expect("Position out of bounds")
+    }
+}
+
+impl FromStr for Position {
+    type Err = PositionError;
+
+    /// Parses a position in algebraic notation
+    ///
+    /// # Arguments
+    /// * `s`: The string representation of the position
+    ///
+    /// # Returns
+    /// The position, or an error if the string is not a valid position
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::Position;
+    /// use chess_lab::errors::PositionError;
+    ///
+    /// assert_eq!("e4".parse(), Ok(Position::new(4, 3)));
+    /// assert_eq!("e9".parse::<Position>(), Err(PositionError::Invalid));
+    /// ```
+    ///
+    fn from_str(s: &str) -> Result<Position, PositionError> {
+        let mut chars = s.chars();
+        match (
+            chars.next().and_then(File::from_char),
+            chars.next().and_then(Rank::from_char),
+            chars.next(),
+        ) {
+            (Some(file), Some(rank), None) => Ok(Position::from_file_rank(file, rank)),
+            _ => Err(PositionError::Invalid),
         }
     }
 }
 
-impl ToString for Position {
+impl Ord for Position {
+    /// Compares two positions in rank-major order, the same order as the
+    /// bits of a bitboard (a1, b1, ..., h1, a2, ..., h8)
+    ///
+    /// # Arguments
+    /// * `other`: The other position
+    ///
+    /// # Returns
+    /// The ordering between the two positions
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::Position;
+    ///
+    /// assert!(Position::from_string("h1") < Position::from_string("a2"));
+    /// ```
+    ///
+    fn cmp(&self, other: &Position) -> Ordering {
+        (self.row, self.col).cmp(&(other.row, other.col))
+    }
+}
+
+impl PartialOrd for Position {
+    /// Compares two positions, see `Ord`
+    ///
+    fn partial_cmp(&self, other: &Position) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Position {
     /// Converts the position to a string
     ///
     /// # Returns
@@ -264,18 +709,22 @@ impl ToString for Position {
     /// assert_eq!(pos.to_string(), "a1");
     /// ```
     ///
-    fn to_string(&self) -> String {
-        format!(
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(
+            f,
             "{}{}",
-            ('a' as u8 + self.col) as char,
-            ('1' as u8 + self.row) as char
+            (b'a' + self.col) as char,
+            (b'1' + self.row) as char
         )
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Position;
+    use std::collections::HashSet;
+
+    use super::{File, Position, Rank};
+    use crate::errors::PositionError;
 
     #[test]
     fn test_position() {
@@ -291,6 +740,23 @@ mod tests {
         assert_eq!(pos.row, 7);
     }
 
+    #[test]
+    fn test_position_offsets() {
+        let pos = Position::from_string("b2");
+        assert_eq!(&pos + (1, 0), Position::from_string("c2"));
+        assert_eq!(&pos - (1, 1), Position::from_string("a1"));
+        assert_eq!(pos.checked_add((-2, 0)), None);
+        assert_eq!(pos.checked_add((0, 6)), Some(Position::from_string("b8")));
+        assert_eq!(pos.checked_add((0, 7)), None);
+        assert_eq!(pos.checked_add((i8::MAX, i8::MAX)), None);
+        assert_eq!(pos.checked_add((i8::MIN, 0)), None);
+        assert_eq!(
+            &Position::from_string("h8") - (7, 7),
+            Position::from_string("a1")
+        );
+        assert!(std::panic::catch_unwind(|| &pos - (i8::MIN, 0)).is_err());
+    }
+
     #[test]
     fn test_position_to_bitboard() {
         let pos = Position::new(0, 0);
@@ -343,4 +809,48 @@ mod tests {
         let dir = pos1.direction(&pos2);
         assert_eq!(dir, (1, 1));
     }
+
+    #[test]
+    fn test_position_from_str() {
+        assert_eq!("a1".parse(), Ok(Position::new(0, 0)));
+        assert_eq!("h8".parse(), Ok(Position::new(7, 7)));
+        for invalid in ["", "a", "a0", "i1", "a9", "A1", "a10"] {
+            assert_eq!(invalid.parse::<Position>(), Err(PositionError::Invalid));
+        }
+    }
+
+    #[test]
+    fn test_position_ord_hash() {
+        let mut positions = vec![
+            Position::from_string("a2"),
+            Position::from_string("h1"),
+            Position::from_string("a1"),
+        ];
+        positions.sort();
+        assert_eq!(
+            positions,
+            vec![
+                Position::from_string("a1"),
+                Position::from_string("h1"),
+                Position::from_string("a2"),
+            ]
+        );
+
+        let set: HashSet<Position> = positions.iter().chain(positions.iter()).copied().collect();
+        assert_eq!(set.len(), 3);
+    }
+
+    #[test]
+    fn test_file_rank() {
+        for (index, file) in File::ALL.iter().enumerate() {
+            assert_eq!(File::from_index(index as u8), Some(*file));
+            assert_eq!(File::from_char(file.to_char()), Some(*file));
+        }
+        for (index, rank) in Rank::ALL.iter().enumerate() {
+            assert_eq!(Rank::from_index(index as u8), Some(*rank));
+            assert_eq!(Rank::from_char(rank.to_char()), Some(*rank));
+        }
+        let pos = Position::from_string("f3");
+        assert_eq!(Position::from_file_rank(pos.file(), pos.rank()), pos);
+    }
 }
@@ -0,0 +1,223 @@
+use std::{
+    fmt::{Display, Error, Formatter},
+    str::FromStr,
+};
+
+use crate::errors::PositionError;
+
+use super::{File, Position, Rank};
+
+/// Represents a square of the board, an alternative to `Position` that can be
+/// used in `match` expressions and as an index into tables
+/// The squares are ordered in rank-major order, so `A1` is 0 and `H8` is 63,
+/// the same as the bits of a bitboard
+///
+/// # Examples
+/// ```
+/// use chess_lab::constants::{Position, Square};
+///
+/// let square = Square::E4;
+///
+/// assert_eq!(square.index(), 28);
+/// assert_eq!(Position::from(square), Position::from_string("e4"));
+/// assert_eq!(Square::from(Position::from_string("e4")), Square::E4);
+/// ```
+///
+#[rustfmt::skip]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Square {
+    A1, B1, C1, D1, E1, F1, G1, H1,
+    A2, B2, C2, D2, E2, F2, G2, H2,
+    A3, B3, C3, D3, E3, F3, G3, H3,
+    A4, B4, C4, D4, E4, F4, G4, H4,
+    A5, B5, C5, D5, E5, F5, G5, H5,
+    A6, B6, C6, D6, E6, F6, G6, H6,
+    A7, B7, C7, D7, E7, F7, G7, H7,
+    A8, B8, C8, D8, E8, F8, G8, H8,
+}
+
+impl Square {
+    /// All the squares, indexed by their bit index
+    #[rustfmt::skip]
+    pub const ALL: [Square; 64] = [
+        Square::A1, Square::B1, Square::C1, Square::D1, Square::E1, Square::F1, Square::G1, Square::H1,
+        Square::A2, Square::B2, Square::C2, Square::D2, Square::E2, Square::F2, Square::G2, Square::H2,
+        Square::A3, Square::B3, Square::C3, Square::D3, Square::E3, Square::F3, Square::G3, Square::H3,
+        Square::A4, Square::B4, Square::C4, Square::D4, Square::E4, Square::F4, Square::G4, Square::H4,
+        Square::A5, Square::B5, Square::C5, Square::D5, Square::E5, Square::F5, Square::G5, Square::H5,
+        Square::A6, Square::B6, Square::C6, Square::D6, Square::E6, Square::F6, Square::G6, Square::H6,
+        Square::A7, Square::B7, Square::C7, Square::D7, Square::E7, Square::F7, Square::G7, Square::H7,
+        Square::A8, Square::B8, Square::C8, Square::D8, Square::E8, Square::F8, Square::G8, Square::H8,
+    ];
+
+    /// Gets the square from its bit index
+    ///
+    /// # Arguments
+    /// * `index`: The bit index of the square (between 0 and 63)
+    ///
+    /// # Returns
+    /// The square, or `None` if the index is out of bounds
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::Square;
+    ///
+    /// assert_eq!(Square::from_index(0), Some(Square::A1));
+    /// assert_eq!(Square::from_index(63), Some(Square::H8));
+    /// assert_eq!(Square::from_index(64), None);
+    /// ```
+    ///
+    pub fn from_index(index: u8) -> Option<Square> {
+        Square::ALL.get(index as usize).copied()
+    }
+
+    /// Creates a square from a file and a rank
+    ///
+    /// # Arguments
+    /// * `file`: The file of the square
+    /// * `rank`: The rank of the square
+    ///
+    /// # Returns
+    /// The square
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::{File, Rank, Square};
+    ///
+    /// assert_eq!(Square::from_file_rank(File::G, Rank::Seventh), Square::G7);
+    /// ```
+    ///
+    pub fn from_file_rank(file: File, rank: Rank) -> Square {
+        Square::ALL[(rank.index() * 8 + file.index()) as usize]
+    }
+
+    /// Gets the bit index of the square
+    ///
+    /// # Returns
+    /// The bit index, between 0 (a1) and 63 (h8)
+    ///
+    pub fn index(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Gets the bitboard with only this square set
+    ///
+    /// # Returns
+    /// The bitboard of the square
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::Square;
+    ///
+    /// assert_eq!(Square::H1.bitboard(), 0x0000000000000080);
+    /// ```
+    ///
+    pub fn bitboard(&self) -> u64 {
+        1 << self.index()
+    }
+
+    /// Gets the file of the square
+    ///
+    /// # Returns
+    /// The file of the square
+    ///
+    pub fn file(&self) -> File {
+        File::ALL[(self.index() % 8) as usize]
+    }
+
+    /// Gets the rank of the square
+    ///
+    /// # Returns
+    /// The rank of the square
+    ///
+    pub fn rank(&self) -> Rank {
+        Rank::ALL[(self.index() / 8) as usize]
+    }
+}
+
+impl From<Square> for Position {
+    /// Converts a square into a position
+    ///
+    /// # Arguments
+    /// * `square`: The square to convert
+    ///
+    /// # Returns
+    /// The position of the square
+    ///
+    fn from(square: Square) -> Position {
+        Position::from_file_rank(square.file(), square.rank())
+    }
+}
+
+impl From<Position> for Square {
+    /// Converts a position into a square
+    ///
+    /// # Arguments
+    /// * `pos`: The position to convert
+    ///
+    /// # Returns
+    /// The square at the position
+    ///
+    fn from(pos: Position) -> Square {
+        Square::from_file_rank(pos.file(), pos.rank())
+    }
+}
+
+impl FromStr for Square {
+    type Err = PositionError;
+
+    /// Parses a square in algebraic notation
+    ///
+    /// # Arguments
+    /// * `s`: The string representation of the square
+    ///
+    /// # Returns
+    /// The square, or an error if the string is not a valid square
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::Square;
+    ///
+    /// assert_eq!("d5".parse(), Ok(Square::D5));
+    /// ```
+    ///
+    fn from_str(s: &str) -> Result<Square, PositionError> {
+        s.parse::<Position>().map(Square::from)
+    }
+}
+
+impl Display for Square {
+    /// Converts the square to a string
+    ///
+    /// # Returns
+    /// The square in algebraic notation
+    ///
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}{}", self.file(), self.rank())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Square;
+    use crate::constants::Position;
+
+    #[test]
+    fn test_square_index() {
+        for (index, square) in Square::ALL.iter().enumerate() {
+            assert_eq!(square.index() as usize, index);
+            assert_eq!(Square::from_index(index as u8), Some(*square));
+            assert_eq!(square.bitboard(), Position::from(*square).to_bitboard());
+        }
+    }
+
+    #[test]
+    fn test_square_position() {
+        for square in Square::ALL {
+            let pos = Position::from(square);
+            assert_eq!(Square::from(pos), square);
+            assert_eq!(pos.to_string(), square.to_string());
+            assert_eq!(square.to_string().parse(), Ok(square));
+        }
+    }
+}
@@ -1,11 +1,490 @@
-use crate::errors::MoveError;
+use std::{collections::HashMap, path::Path};
 
-use super::GameStatus;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
+use crate::{
+    errors::{ExportError, MoveError},
+    export::{save_pgn, SaveMode},
+    logic::{Board, Game, RuleSet},
+};
+
+use super::{pgn::PgnTree, Color, GameStatus, Move, Position};
+
+/// A read-only snapshot of the state of a game, as shown by a GUI
+///
+/// # Fields
+/// * `fen`: The FEN of the current position
+/// * `side_to_move`: The color of the side to move
+/// * `halfmove_clock`: The number of half moves since the last capture or
+///   pawn move
+/// * `fullmove_number`: The number of the current move
+/// * `castling_rights`: The castling rights, as stored on the game
+/// * `en_passant`: The en passant square, if any
+/// * `game_status`: The status of the game
+/// * `check`: Whether the side to move is in check
+/// * `last_move`: The SAN of the last move, if any
+/// * `ply`: The number of half moves of the current line
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameView {
+    pub fen: String,
+    pub side_to_move: Color,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+    pub castling_rights: u8,
+    pub en_passant: Option<Position>,
+    pub game_status: GameStatus,
+    pub check: bool,
+    pub last_move: Option<String>,
+    pub ply: usize,
+}
+
+/// A chess variant, played on top of a [`Game`]
+/// The analysis methods are wired to the game by default, so a variant only
+/// has to provide the game and the methods its rules change
+///
+/// The trait is object safe: games of different variants can be kept
+/// together as [`BoxedVariant`]s
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::Variant;
+/// use chess_lab::logic::Game;
+///
+/// let mut variant: Box<dyn Variant> = Box::new(Game::default());
+/// variant.move_piece("e4").unwrap();
+///
+/// assert_eq!(variant.legal_moves().len(), 20);
+/// assert!(!variant.is_check());
+/// ```
+///
 pub trait Variant {
-    fn move_piece(move_str: &str) -> Result<GameStatus, MoveError>;
-    fn undo();
-    fn redo();
-    fn pgn();
-    fn fen();
+    /// Returns the game the variant is played on
+    ///
+    /// # Returns
+    /// The game
+    ///
+    fn game(&self) -> &Game;
+
+    /// Returns the board of the game, without copying it
+    ///
+    /// # Returns
+    /// The board
+    ///
+    fn board(&self) -> &Board {
+        &self.game().board
+    }
+
+    /// Returns the history of the game, without copying it
+    ///
+    /// # Returns
+    /// The moves, variations and tags of the game
+    ///
+    fn history(&self) -> &PgnTree<Move> {
+        &self.game().history
+    }
+
+    /// Returns how many times every position occurred, without copying them
+    ///
+    /// # Returns
+    /// The number of occurrences of every position, by its reduced FEN
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::Variant;
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("Nf3").unwrap();
+    ///
+    /// assert_eq!(Variant::history(&game).line_moves().len(), 1);
+    /// assert_eq!(game.prev_positions().len(), 2);
+    /// assert!(Variant::board(&game).get_piece(&"f3".parse().unwrap()).is_some());
+    /// ```
+    ///
+    fn prev_positions(&self) -> &HashMap<String, u32> {
+        &self.game().prev_positions
+    }
+
+    /// Returns the name of the variant, as written in the `Variant` tag
+    ///
+    /// # Returns
+    /// The `Variant` tag of the game, or `Standard` if it's not set
+    ///
+    fn name(&self) -> &str {
+        self.game().history.variant.as_deref().unwrap_or("Standard")
+    }
+
+    /// Returns the draw rules the variant plays with
+    ///
+    /// # Returns
+    /// The rules of the game
+    ///
+    fn rules(&self) -> RuleSet {
+        self.game().rules
+    }
+
+    /// Returns a snapshot of the state of the game, to read it at once
+    /// instead of field by field
+    ///
+    /// # Returns
+    /// The state of the game
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::{Color, Variant};
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    ///
+    /// let view = game.view();
+    /// assert_eq!(view.side_to_move, Color::Black);
+    /// assert_eq!(view.last_move.as_deref(), Some("e4"));
+    /// assert_eq!(view.ply, 1);
+    /// ```
+    ///
+    fn view(&self) -> GameView {
+        let game = self.game();
+        GameView {
+            fen: self.fen(),
+            side_to_move: game.side_to_move(),
+            halfmove_clock: game.halfmove_clock,
+            fullmove_number: game.fullmove_number,
+            castling_rights: game.castling_rights,
+            en_passant: game.en_passant,
+            game_status: game.game_status,
+            check: self.is_check(),
+            last_move: game.last_move().map(|mov| mov.to_string()),
+            ply: game.history.line_nodes().len(),
+        }
+    }
+
+    /// Moves a piece
+    ///
+    /// # Arguments
+    /// * `move_str`: The move
+    ///
+    /// # Returns
+    /// The status of the game after the move, or an error if the move can't
+    /// be played
+    ///
+    fn move_piece(&mut self, move_str: &str) -> Result<GameStatus, MoveError>;
+
+    /// Takes back the last move
+    fn undo(&mut self);
+
+    /// Plays again the last move taken back
+    fn redo(&mut self);
+
+    /// Returns the PGN of the game
+    ///
+    /// # Returns
+    /// The PGN
+    ///
+    fn pgn(&self) -> String {
+        self.game().pgn()
+    }
+
+    /// Saves the PGN of the game to a file, atomically
+    ///
+    /// # Arguments
+    /// * `path`: The file to write
+    /// * `mode`: Whether an existing file is replaced or the game is added
+    ///   at its end
+    ///
+    /// # Returns
+    /// Ok if the file was written, or an error if it couldn't be
+    ///
+    fn save(&self, path: &Path, mode: SaveMode) -> Result<(), ExportError> {
+        save_pgn(path, &self.pgn(), mode)
+    }
+
+    /// Returns the FEN of the current position
+    ///
+    /// # Returns
+    /// The FEN
+    ///
+    fn fen(&self) -> String {
+        self.game().fen()
+    }
+
+    /// Returns the legal moves of the side to move
+    ///
+    /// # Returns
+    /// The legal moves
+    ///
+    fn legal_moves(&self) -> Vec<Move> {
+        self.game().legal_moves()
+    }
+
+    /// Returns whether the side to move is in check
+    ///
+    /// # Returns
+    /// Whether the king is in check
+    ///
+    fn is_check(&self) -> bool {
+        self.game().check()
+    }
+
+    /// Returns whether the side to move is checkmated
+    ///
+    /// # Returns
+    /// Whether the king is in checkmate
+    ///
+    fn is_checkmate(&self) -> bool {
+        self.game().checkmate()
+    }
+
+    /// Returns whether the side to move is stalemated
+    ///
+    /// # Returns
+    /// Whether the game is in stalemate
+    ///
+    fn is_stalemate(&self) -> bool {
+        self.game().stalemate()
+    }
+}
+
+/// A variant whose games can be read from a PGN, with
+/// [`parse_pgn_as`](crate::parsing::parse_pgn_as)
+///
+/// The PGN is read with the standard rules and all its lines are then played
+/// again on the variant, so a variant can only be stricter than standard
+/// chess
+///
+pub trait PgnVariant: Variant + Sized {
+    /// The values of the `Variant` tag of the games of the variant, compared
+    /// ignoring case
+    const TAGS: &'static [&'static str];
+
+    /// The draw rules the games of the variant are played with, which the
+    /// games read from a PGN get. Variants whose servers drop or change a
+    /// draw rule override it
+    const RULES: RuleSet = RuleSet::standard();
+
+    /// Creates the variant from a game read from a PGN
+    ///
+    /// # Arguments
+    /// * `game`: The game, with its tags, moves and variations
+    ///
+    /// # Returns
+    /// The variant
+    ///
+    fn from_game(game: Game) -> Self;
+}
+
+/// A game of any variant, to keep games of different variants together
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::{BoxedVariant, Variant};
+/// use chess_lab::logic::Game;
+///
+/// let mut games: Vec<BoxedVariant> = vec![
+///     Box::new(Game::default()),
+///     Box::new(Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1")),
+/// ];
+/// for game in games.iter_mut() {
+///     game.move_piece("Kd2").ok();
+/// }
+/// assert_eq!(games[1].fen(), "4k3/8/8/8/8/8/3K4/R7 b - - 1 1");
+/// assert_eq!(games[0].name(), "Standard");
+/// ```
+///
+pub type BoxedVariant = Box<dyn Variant>;
+
+impl<V: Variant + ?Sized> Variant for Box<V> {
+    fn game(&self) -> &Game {
+        (**self).game()
+    }
+
+    fn board(&self) -> &Board {
+        (**self).board()
+    }
+
+    fn history(&self) -> &PgnTree<Move> {
+        (**self).history()
+    }
+
+    fn prev_positions(&self) -> &HashMap<String, u32> {
+        (**self).prev_positions()
+    }
+
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn rules(&self) -> RuleSet {
+        (**self).rules()
+    }
+
+    fn view(&self) -> GameView {
+        (**self).view()
+    }
+
+    fn move_piece(&mut self, move_str: &str) -> Result<GameStatus, MoveError> {
+        (**self).move_piece(move_str)
+    }
+
+    fn undo(&mut self) {
+        (**self).undo()
+    }
+
+    fn redo(&mut self) {
+        (**self).redo()
+    }
+
+    fn pgn(&self) -> String {
+        (**self).pgn()
+    }
+
+    fn fen(&self) -> String {
+        (**self).fen()
+    }
+
+    fn save(&self, path: &Path, mode: SaveMode) -> Result<(), ExportError> {
+        (**self).save(path, mode)
+    }
+
+    fn legal_moves(&self) -> Vec<Move> {
+        (**self).legal_moves()
+    }
+
+    fn is_check(&self) -> bool {
+        (**self).is_check()
+    }
+
+    fn is_checkmate(&self) -> bool {
+        (**self).is_checkmate()
+    }
+
+    fn is_stalemate(&self) -> bool {
+        (**self).is_stalemate()
+    }
+}
+
+impl Variant for Game {
+    fn game(&self) -> &Game {
+        self
+    }
+
+    fn move_piece(&mut self, move_str: &str) -> Result<GameStatus, MoveError> {
+        Game::move_piece(self, move_str)
+    }
+
+    fn undo(&mut self) {
+        Game::undo(self)
+    }
+
+    fn redo(&mut self) {
+        Game::redo(self)
+    }
+}
+
+impl PgnVariant for Game {
+    const TAGS: &'static [&'static str] = &[
+        "Standard",
+        "From Position",
+        "Chess960",
+        "Chess 960",
+        "Fischerandom",
+        "Fischer Random",
+    ];
+
+    fn from_game(game: Game) -> Game {
+        game
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BoxedVariant, Variant};
+    use crate::{
+        constants::{GameStatus, Position},
+        errors::MoveError,
+        logic::Game,
+    };
+
+    /// A variant that only accepts pawn moves, to mix with standard games
+    struct PawnsOnly(Game);
+
+    impl Variant for PawnsOnly {
+        fn game(&self) -> &Game {
+            &self.0
+        }
+
+        fn name(&self) -> &str {
+            "Pawns only"
+        }
+
+        fn move_piece(&mut self, move_str: &str) -> Result<GameStatus, MoveError> {
+            if move_str.starts_with(|c: char| c.is_ascii_uppercase()) {
+                return Err(MoveError::Invalid {
+                    san: move_str.to_string(),
+                });
+            }
+            self.0.move_piece(move_str)
+        }
+
+        fn undo(&mut self) {
+            self.0.undo()
+        }
+
+        fn redo(&mut self) {
+            self.0.redo()
+        }
+    }
+
+    #[test]
+    fn test_boxed_variants() {
+        let mut games: Vec<BoxedVariant> = vec![
+            Box::new(Game::default()),
+            Box::new(PawnsOnly(Game::default())),
+        ];
+
+        let results: Vec<bool> = games
+            .iter_mut()
+            .map(|game| game.move_piece("Nf3").is_ok())
+            .collect();
+        assert_eq!(results, vec![true, false]);
+
+        let names: Vec<&str> = games.iter().map(|game| game.name()).collect();
+        assert_eq!(names, vec!["Standard", "Pawns only"]);
+
+        games[1].move_piece("e4").unwrap();
+        games[1].undo();
+        assert_eq!(games[1].fen(), Game::default().fen());
+        assert_eq!(games[1].legal_moves().len(), 20);
+    }
+
+    #[test]
+    fn test_view() {
+        let mut game: BoxedVariant = Box::<Game>::default();
+        for mov in ["e4", "d5", "e5", "f5"] {
+            game.move_piece(mov).unwrap();
+        }
+        let view = game.view();
+        assert_eq!(view.fen, game.fen());
+        assert_eq!(view.en_passant, Some(Position::from_string("f6")));
+        assert_eq!(view.fullmove_number, 3);
+        assert_eq!(view.ply, 4);
+        assert!(!view.check);
+
+        // The accessors borrow the state of the game
+        assert!(std::ptr::eq(game.board(), &game.game().board));
+        assert_eq!(game.history().line_moves().len(), 4);
+        assert_eq!(game.prev_positions().len(), 5);
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&view).unwrap();
+            assert_eq!(
+                serde_json::from_str::<super::GameView>(&json).unwrap(),
+                view
+            );
+        }
+    }
 }
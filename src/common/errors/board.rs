@@ -1,11 +1,22 @@
+use thiserror::Error;
+
 /// Enum for errors that can occur when interacting with the board
 ///
 /// # Variants
 /// * `Occupied`: The space is already occupied
 /// * `Empty`: The space is empty
+/// * `SameColor`: The space is occupied by a piece of the same color as the
+///   one moving there
+/// * `NotAligned`: The squares are not on the same rank, file or diagonal
 ///
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Error)]
 pub enum BoardError {
+    #[error("the square is already occupied")]
     Occupied,
+    #[error("the square is empty")]
     Empty,
+    #[error("the square is occupied by a piece of the same color")]
+    SameColor,
+    #[error("the squares are not on the same rank, file or diagonal")]
+    NotAligned,
 }
@@ -0,0 +1,66 @@
+use thiserror::Error;
+
+use super::{
+    BoardError, ExportError, FenError, InteropError, MoveError, PgnError, PositionError,
+    SessionError, TournamentError,
+};
+
+/// Any error produced by the crate
+/// Every specific error converts into it, so functions that mix FEN, PGN and
+/// move handling can propagate them all with `?`
+///
+/// # Variants
+/// * `Board`: An error when interacting with the board
+/// * `Export`: An error when exporting a game
+/// * `Fen`: An error when parsing a FEN
+/// * `Interop`: An error when converting games from other formats
+/// * `Move`: An error when moving a piece
+/// * `Pgn`: An error when parsing a PGN
+/// * `Position`: An error when parsing a position
+/// * `Session`: An error when playing in a session of several games
+/// * `Tournament`: An error when running a tournament
+///
+/// # Example
+/// ```
+/// use chess_lab::errors::{ChessError, IllegalReason, MoveError};
+/// use chess_lab::logic::Game;
+///
+/// fn play(fen: &str, moves: &[&str]) -> Result<Game, ChessError> {
+///     let mut game = Game::try_from_fen(fen)?;
+///     for mov in moves {
+///         game.move_piece(mov)?;
+///     }
+///     Ok(game)
+/// }
+///
+/// let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+///
+/// assert!(play(start, &["e4", "e5"]).is_ok());
+/// assert!(matches!(
+///     play(start, &["e5"]),
+///     Err(ChessError::Move(MoveError::Illegal { reason: IllegalReason::BadPattern, .. }))
+/// ));
+/// assert_eq!(play("8/8 w", &[]).unwrap_err().to_string(), "invalid FEN");
+/// ```
+///
+#[derive(Debug, PartialEq, Error)]
+pub enum ChessError {
+    #[error(transparent)]
+    Board(#[from] BoardError),
+    #[error(transparent)]
+    Export(#[from] ExportError),
+    #[error(transparent)]
+    Fen(#[from] FenError),
+    #[error(transparent)]
+    Interop(#[from] InteropError),
+    #[error(transparent)]
+    Move(#[from] MoveError),
+    #[error(transparent)]
+    Pgn(#[from] PgnError),
+    #[error(transparent)]
+    Position(#[from] PositionError),
+    #[error(transparent)]
+    Session(#[from] SessionError),
+    #[error(transparent)]
+    Tournament(#[from] TournamentError),
+}
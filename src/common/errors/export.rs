@@ -0,0 +1,21 @@
+use thiserror::Error;
+
+/// Errors that can occur when exporting games to other formats
+///
+/// # Variants
+/// * `TooLarge`: The requested image is larger than the format allows
+/// * `Encoding`: The output can't be encoded
+/// * `Write`: The file couldn't be written
+/// * `InvalidMove`: A move of the game can't be played again
+///
+#[derive(Debug, PartialEq, Error)]
+pub enum ExportError {
+    #[error("the image is too large")]
+    TooLarge,
+    #[error("the output can't be encoded")]
+    Encoding,
+    #[error("the file could not be written")]
+    Write,
+    #[error("the game contains a move that can't be played")]
+    InvalidMove,
+}
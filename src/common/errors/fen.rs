@@ -0,0 +1,12 @@
+use thiserror::Error;
+
+/// Errors that can occur when parsing a FEN
+///
+/// # Variants
+/// * `Invalid`: The string is not a well formed FEN
+///
+#[derive(Debug, PartialEq, Error)]
+pub enum FenError {
+    #[error("invalid FEN")]
+    Invalid,
+}
@@ -0,0 +1,15 @@
+use thiserror::Error;
+
+/// Errors that can occur when converting games from other formats
+///
+/// # Variants
+/// * `InvalidJson`: The input is not valid JSON or misses required fields
+/// * `InvalidMove`: The input contains a move that can't be played
+///
+#[derive(Debug, PartialEq, Error)]
+pub enum InteropError {
+    #[error("invalid JSON game")]
+    InvalidJson,
+    #[error("the game contains an illegal move")]
+    InvalidMove,
+}
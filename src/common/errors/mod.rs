@@ -1,5 +1,23 @@
 mod board;
+mod chess;
+mod export;
+mod fen;
+mod interop;
 mod movements;
+mod pgn;
+mod position;
+mod puzzle;
+mod session;
+mod tournament;
 
 pub use board::*;
+pub use chess::*;
+pub use export::*;
+pub use fen::*;
+pub use interop::*;
 pub use movements::*;
+pub use pgn::*;
+pub use position::*;
+pub use puzzle::*;
+pub use session::*;
+pub use tournament::*;
@@ -1,13 +1,87 @@
+use thiserror::Error;
+
+use crate::constants::{Color, Position};
+
 /// Errors that can occur when trying to move a piece
 ///
 /// # Variants
 /// * `Invalid`: The move is invalid
+///   * `san`: The move as it was given
 /// * `Illegal`: The move is illegal
+///   * `san`: The move as it was given
+///   * `to`: The destination square of the move
+///   * `reason`: Why the move can't be played
 /// * `Ambiguous`: The move is ambiguous
+///   * `san`: The move as it was given
+///   * `to`: The destination square of the move
+///   * `candidates`: The squares of the pieces that can make the move
+/// * `PromotionCancelled`: The promotion policy didn't pick a piece
+///   * `san`: The move as it was given
+/// * `IllegalEnPassant`: The move is an en passant capture, but there is no
+///   pawn that can be captured en passant on that square
+///   * `san`: The move as it was given
+///   * `to`: The destination square of the move
+/// * `WrongColor`: The piece to move belongs to the side that is not to move
+///   * `san`: The move as it was given
+///   * `color`: The color of the piece
+/// * `Finished`: The game, drill or session the move was played in is
+///   already finished
+///   * `san`: The move as it was given
+/// * `IncompleteSequence`: A conditional move sequence doesn't have both a
+///   move of the opponent and a reply
+///   * `san`: The moves as they were given
+/// * `NoMovePlayed`: There is no move to keep a conditional move sequence on
 ///
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Error)]
 pub enum MoveError {
-    Invalid,
-    Illegal,
-    Ambiguous,
+    #[error("invalid move notation: {san}")]
+    Invalid { san: String },
+    #[error("illegal move {san}: {reason}")]
+    Illegal {
+        san: String,
+        to: Position,
+        reason: IllegalReason,
+    },
+    #[error("ambiguous move {san}: {} pieces can move to {to}", candidates.len())]
+    Ambiguous {
+        san: String,
+        to: Position,
+        candidates: Vec<Position>,
+    },
+    #[error("the promotion of {san} was cancelled")]
+    PromotionCancelled { san: String },
+    #[error("illegal en passant capture {san}: no pawn can be captured en passant on {to}")]
+    IllegalEnPassant { san: String, to: Position },
+    #[error("wrong color for {san}: it's not {color:?}'s turn")]
+    WrongColor { san: String, color: Color },
+    #[error("can't play {san}: it's already finished")]
+    Finished { san: String },
+    #[error("the conditional sequence {san:?} needs a move and a reply")]
+    IncompleteSequence { san: String },
+    #[error("no move has been played to keep the conditional sequence on")]
+    NoMovePlayed,
+}
+
+/// The reason why a move is illegal
+///
+/// # Variants
+/// * `SelfCheck`: The move leaves (or, when castling, moves the king through)
+///   an attacked square
+///   * `from`: The square of a piece that attacks it
+/// * `BlockedPath`: There is a piece in the way
+///   * `at`: The square of the first piece in the way
+/// * `BadPattern`: No piece can move that way, or castling is not allowed
+/// * `WrongTurn`: Only a piece of the side that is not to move can make the
+///   move
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum IllegalReason {
+    #[error("the king would be in check from {from}")]
+    SelfCheck { from: Position },
+    #[error("the path is blocked at {at}")]
+    BlockedPath { at: Position },
+    #[error("no piece can move that way")]
+    BadPattern,
+    #[error("it's the other side's turn")]
+    WrongTurn,
 }
@@ -0,0 +1,51 @@
+use thiserror::Error;
+
+/// Errors that can occur when parsing a PGN
+///
+/// # Variants
+/// * `InvalidTag`: A tag pair is malformed
+/// * `MissingTag`: A tag of the Seven Tag Roster is missing
+/// * `InvalidFen`: The FEN tag doesn't hold a valid FEN
+/// * `InvalidToken`: The movetext contains an unexpected token
+/// * `InvalidMove`: A move can't be played in the current position
+/// * `UnterminatedComment`: A comment is never closed
+/// * `UnbalancedVariation`: A variation is never closed, or closed without being opened
+/// * `LimitExceeded`: The game is larger than the limits of the parse
+///   options: too many moves, tags or variations, or too long a comment
+/// * `MissingResult`: The movetext doesn't end with a game termination marker
+/// * `ReadError`: The input couldn't be read
+/// * `Empty`: The input doesn't contain any game
+/// * `UnsupportedCompression`: The input is compressed in a format whose
+///   feature isn't enabled
+/// * `UnsupportedVariant`: The `Variant` tag names a variant the game can't
+///   be read as
+///
+#[derive(Debug, PartialEq, Error)]
+pub enum PgnError {
+    #[error("malformed tag pair")]
+    InvalidTag,
+    #[error("missing tag of the Seven Tag Roster")]
+    MissingTag,
+    #[error("invalid FEN tag")]
+    InvalidFen,
+    #[error("unexpected token in the movetext")]
+    InvalidToken,
+    #[error("the movetext contains an illegal move")]
+    InvalidMove,
+    #[error("unterminated comment")]
+    UnterminatedComment,
+    #[error("unbalanced variation")]
+    UnbalancedVariation,
+    #[error("the PGN exceeds the limits of the parse options")]
+    LimitExceeded,
+    #[error("missing game termination marker")]
+    MissingResult,
+    #[error("the PGN could not be read")]
+    ReadError,
+    #[error("the PGN doesn't contain any game")]
+    Empty,
+    #[error("the PGN is compressed in an unsupported format")]
+    UnsupportedCompression,
+    #[error("unsupported variant: {0}")]
+    UnsupportedVariant(String),
+}
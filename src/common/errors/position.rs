@@ -0,0 +1,12 @@
+use thiserror::Error;
+
+/// Errors that can occur when parsing a position
+///
+/// # Variants
+/// * `Invalid`: The string is not a square in algebraic notation (`a1` to `h8`)
+///
+#[derive(Debug, PartialEq, Error)]
+pub enum PositionError {
+    #[error("invalid square")]
+    Invalid,
+}
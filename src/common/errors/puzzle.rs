@@ -0,0 +1,39 @@
+use thiserror::Error;
+
+/// Errors that can occur when checking a puzzle
+///
+/// # Variants
+/// * `InvalidFen`: The FEN of the puzzle is not valid
+/// * `IllegalMove`: A move of the puzzle can't be played
+///   * `ply`: The index of the move in the moves of the puzzle
+///   * `mov`: The move, in UCI
+/// * `Incomplete`: The puzzle doesn't end with a move of the solver
+/// * `NotWinning`: A move of the solution doesn't win
+///   * `ply`: The index of the move in the moves of the puzzle
+/// * `NotForced`: Another move wins as well as the one of the solution
+///   * `ply`: The index of the move in the moves of the puzzle
+///   * `alternative`: The other move
+/// * `Unverifiable`: The solution doesn't mate and no engine was given
+/// * `InvalidRecord`: A line of a puzzle CSV file is malformed
+///   * `0`: The line
+/// * `ReadError`: The input couldn't be read
+///
+#[derive(Debug, PartialEq, Error)]
+pub enum PuzzleError {
+    #[error("invalid FEN")]
+    InvalidFen,
+    #[error("move {ply} can't be played: {mov}")]
+    IllegalMove { ply: usize, mov: String },
+    #[error("the puzzle doesn't end with a move of the solver")]
+    Incomplete,
+    #[error("move {ply} of the solution doesn't win")]
+    NotWinning { ply: usize },
+    #[error("move {ply} of the solution isn't the only one, {alternative} wins too")]
+    NotForced { ply: usize, alternative: String },
+    #[error("the solution doesn't mate and there is no engine to check it")]
+    Unverifiable,
+    #[error("malformed puzzle record: {0}")]
+    InvalidRecord(String),
+    #[error("the puzzles could not be read")]
+    ReadError,
+}
@@ -0,0 +1,17 @@
+use thiserror::Error;
+
+use super::MoveError;
+
+/// Errors that can occur when playing in a session over several games
+///
+/// # Variants
+/// * `UnknownBoard`: There is no board with that index
+/// * `Move`: The move can't be played on the board
+///
+#[derive(Debug, PartialEq, Error)]
+pub enum SessionError {
+    #[error("there is no board {0}")]
+    UnknownBoard(usize),
+    #[error(transparent)]
+    Move(#[from] MoveError),
+}
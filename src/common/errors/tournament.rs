@@ -0,0 +1,28 @@
+use thiserror::Error;
+
+/// Errors that can occur when running a tournament
+///
+/// # Variants
+/// * `NotEnoughPlayers`: A tournament needs at least two players
+/// * `NoRoundsLeft`: Every round of the tournament was already paired
+/// * `RoundNotFinished`: A Swiss round can't be paired before the results of
+///   the previous one are known
+/// * `UnknownGame`: There is no game on the board of the round
+/// * `GameInProgress`: The game being recorded hasn't finished
+/// * `WrongPlayers`: The players of the game don't match the pairing
+///
+#[derive(Debug, PartialEq, Error)]
+pub enum TournamentError {
+    #[error("a tournament needs at least two players")]
+    NotEnoughPlayers,
+    #[error("every round was already paired")]
+    NoRoundsLeft,
+    #[error("the previous round isn't finished")]
+    RoundNotFinished,
+    #[error("there is no game on board {board} of round {round}")]
+    UnknownGame { round: u32, board: u32 },
+    #[error("the game is still in progress")]
+    GameInProgress,
+    #[error("the players of the game don't match the pairing")]
+    WrongPlayers,
+}
@@ -0,0 +1,87 @@
+mod opening;
+
+pub use opening::*;
+
+use std::collections::HashSet;
+
+use crate::logic::Game;
+
+/// Removes the games that are repeated in a collection, such as the same game
+/// imported from several sources
+/// Two games are the same if they have the same identifier
+/// (`Game::canonical_id`)
+///
+/// # Arguments
+/// * `games`: The games
+///
+/// # Returns
+/// The first occurrence of every game, in the order of the collection
+///
+/// # Example
+/// ```
+/// use chess_lab::database::dedupe;
+/// use chess_lab::parsing::{parse_pgn_with_options, PgnParseOptions};
+///
+/// let options = PgnParseOptions::lenient();
+/// let games = [
+///     "[Site \"lichess.org\"]\n[White \"Anna\"]\n1. d4 d5 *",
+///     "[Site \"chess.com\"]\n[White \"anna\"]\n1. d4 d5 *",
+///     "[White \"Anna\"]\n1. d4 Nf6 *",
+/// ]
+/// .map(|pgn| parse_pgn_with_options(pgn, &options).unwrap());
+///
+/// let unique = dedupe(games);
+/// assert_eq!(unique.len(), 2);
+/// assert_eq!(unique[0].history.site, Some(String::from("lichess.org")));
+/// ```
+///
+pub fn dedupe(games: impl IntoIterator<Item = Game>) -> Vec<Game> {
+    let mut seen = HashSet::new();
+    games
+        .into_iter()
+        .filter(|game| seen.insert(game.canonical_id()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::dedupe;
+    use crate::{
+        logic::Game,
+        parsing::{parse_pgn_with_options, PgnParseOptions},
+    };
+
+    #[test]
+    fn test_dedupe() {
+        let options = PgnParseOptions::lenient();
+        let parse = |pgn: &str| parse_pgn_with_options(pgn, &options).unwrap();
+
+        let game = parse("[White \"Anna\"]\n[Date \"2024.05.01\"]\n1. e4 e5 2. Nf3 1-0");
+        // Unknown tags count as missing, other tags and annotations are ignored
+        assert_eq!(
+            parse("[White \"Anna\"]\n[Black \"?\"]\n[Date \"2024.05.01\"]\n[Round \"3\"]\n1. e4 e5 { Solid } (1... c5) 2. Nf3 1-0")
+                .canonical_id(),
+            game.canonical_id()
+        );
+        for other in [
+            "[White \"Anna\"]\n[Date \"2024.05.02\"]\n1. e4 e5 2. Nf3 1-0",
+            "[White \"Anna\"]\n[Date \"2024.05.01\"]\n1. e4 e5 2. Nf3 0-1",
+            "[White \"Anna\"]\n[Date \"2024.05.01\"]\n1. e4 e5 1-0",
+        ] {
+            assert_ne!(parse(other).canonical_id(), game.canonical_id());
+        }
+        assert_ne!(
+            Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").canonical_id(),
+            Game::default().canonical_id()
+        );
+
+        let games = dedupe([
+            Game::default(),
+            parse("1. e4 *"),
+            Game::default(),
+            parse("1. e4 *"),
+        ]);
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[1].history.line_moves().len(), 1);
+    }
+}
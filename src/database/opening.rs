@@ -0,0 +1,282 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    constants::{Color, PositionKey},
+    logic::Game,
+    rating::score,
+};
+
+/// The most move orders kept for a position, so a position reached in many
+/// ways (the starting position of a big database) doesn't grow without bound
+const MAX_MOVE_ORDERS: usize = 8;
+
+/// A position of an opening tree, with the results of the games that reached
+/// it
+///
+/// # Fields
+/// * `fen`: The FEN of the position, as first reached
+/// * `games`: The number of games that reached the position
+/// * `white_wins`: The games won by White
+/// * `draws`: The games drawn
+/// * `black_wins`: The games won by Black
+/// * `moves`: The moves played from the position, in SAN, with the hash of the
+///   position they reach, in the order they were first played
+/// * `move_orders`: The different move orders that reached the position, from
+///   the starting position, in the order they were first played. Only the
+///   first 8 are kept
+///
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct OpeningNode {
+    pub fen: String,
+    pub games: usize,
+    pub white_wins: usize,
+    pub draws: usize,
+    pub black_wins: usize,
+    pub moves: Vec<(String, u64)>,
+    pub move_orders: Vec<Vec<String>>,
+}
+
+/// A tree of the openings played in a collection of games
+/// The nodes are positions, keyed by their hash (`Game::position_hash`), so
+/// the move orders that transpose into the same position share its node and
+/// its results
+///
+/// # Example
+/// ```
+/// use chess_lab::database::OpeningTree;
+/// use chess_lab::parsing::{parse_pgn_with_options, PgnParseOptions};
+///
+/// let options = PgnParseOptions::lenient();
+/// let mut tree = OpeningTree::new(10);
+/// tree.add(&parse_pgn_with_options("1. d4 Nf6 2. c4 e6 1-0", &options).unwrap());
+/// tree.add(&parse_pgn_with_options("1. c4 e6 2. d4 Nf6 1/2-1/2", &options).unwrap());
+///
+/// let fen = "rnbqkb1r/pppp1ppp/4pn2/8/2PP4/8/PP2PPPP/RNBQKBNR w KQkq - 0 3";
+/// let node = tree.node(fen).unwrap();
+/// assert_eq!((node.games, node.white_wins, node.draws), (2, 1, 1));
+/// assert_eq!(
+///     tree.transpositions_of(fen),
+///     [vec!["d4", "Nf6", "c4", "e6"], vec!["c4", "e6", "d4", "Nf6"]]
+/// );
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct OpeningTree {
+    nodes: HashMap<u64, OpeningNode>,
+    max_plies: usize,
+    games: usize,
+}
+
+impl OpeningTree {
+    /// Creates an empty opening tree
+    ///
+    /// # Arguments
+    /// * `max_plies`: The number of moves of every game added to the tree
+    ///
+    /// # Returns
+    /// A new opening tree
+    ///
+    pub fn new(max_plies: usize) -> OpeningTree {
+        OpeningTree {
+            nodes: HashMap::new(),
+            max_plies,
+            games: 0,
+        }
+    }
+
+    /// Adds the main line of a game to the tree, up to the maximum number of
+    /// plies
+    /// The result is read from the `Result` tag, or from the status of the
+    /// game if the tag is missing
+    ///
+    /// # Arguments
+    /// * `game`: The game
+    ///
+    pub fn add(&mut self, game: &Game) {
+        let white_score = match game.history.result.as_deref() {
+            Some("1-0") => Some(1.0),
+            Some("0-1") => Some(0.0),
+            Some("1/2-1/2") => Some(0.5),
+            Some(_) => None,
+            None => score(game.game_status, Color::White),
+        };
+
+        let mut replay = game.replay();
+        let mut order = Vec::new();
+        let mut reached = HashSet::new();
+        let mut hash = replay.position_hash();
+        self.visit(hash, &replay, &order, &mut reached, white_score);
+
+        for node in game.history.main_line_nodes() {
+            let san = node.borrow().san();
+            if order.len() == self.max_plies || replay.move_piece(&san).is_err() {
+                break;
+            }
+            let child = replay.position_hash();
+            let moves = &mut self.nodes.get_mut(&hash).unwrap().moves;
+            if !moves.iter().any(|(mov, _)| *mov == san) {
+                moves.push((san.clone(), child));
+            }
+
+            order.push(san);
+            self.visit(child, &replay, &order, &mut reached, white_score);
+            hash = child;
+        }
+        self.games += 1;
+    }
+
+    /// Records a position reached by a game
+    ///
+    /// # Arguments
+    /// * `hash`: The hash of the position
+    /// * `replay`: The game at the position
+    /// * `order`: The moves that reached the position
+    /// * `reached`: The positions the game already reached, so a repeated
+    ///   position counts the game once
+    /// * `white_score`: The score of White in the game, if it's finished
+    ///
+    fn visit(
+        &mut self,
+        hash: u64,
+        replay: &Game,
+        order: &[String],
+        reached: &mut HashSet<u64>,
+        white_score: Option<f64>,
+    ) {
+        let node = self.nodes.entry(hash).or_insert_with(|| OpeningNode {
+            fen: replay.fen(),
+            ..OpeningNode::default()
+        });
+        if node.move_orders.len() < MAX_MOVE_ORDERS
+            && !node.move_orders.iter().any(|known| known == order)
+        {
+            node.move_orders.push(order.to_vec());
+        }
+        if !reached.insert(hash) {
+            return;
+        }
+
+        node.games += 1;
+        match white_score {
+            Some(1.0) => node.white_wins += 1,
+            Some(0.0) => node.black_wins += 1,
+            Some(_) => node.draws += 1,
+            None => {}
+        }
+    }
+
+    /// Returns a position of the tree
+    ///
+    /// # Arguments
+    /// * `position`: The FEN of the position or its hash
+    ///
+    /// # Returns
+    /// The node of the position, or `None` if no game reached it
+    ///
+    pub fn node(&self, position: impl Into<PositionKey>) -> Option<&OpeningNode> {
+        self.nodes.get(&position.into().hash()?)
+    }
+
+    /// Lists the move orders that reach a position
+    /// A position with more than one move order is reached by transposition
+    ///
+    /// # Arguments
+    /// * `position`: The FEN of the position or its hash
+    ///
+    /// # Returns
+    /// The moves of every move order, in SAN, up to 8 of them, or
+    /// none if no game reached the position
+    ///
+    pub fn transpositions_of(&self, position: impl Into<PositionKey>) -> &[Vec<String>] {
+        self.node(position)
+            .map_or(&[], |node| node.move_orders.as_slice())
+    }
+
+    /// Returns the number of games in the tree
+    ///
+    /// # Returns
+    /// The number of games added
+    ///
+    pub fn game_count(&self) -> usize {
+        self.games
+    }
+
+    /// Returns the number of moves of every game added to the tree
+    ///
+    /// # Returns
+    /// The maximum number of plies, past which the tree knows no position
+    ///
+    pub fn max_plies(&self) -> usize {
+        self.max_plies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OpeningTree, MAX_MOVE_ORDERS};
+    use crate::{
+        logic::Game,
+        parsing::{parse_pgn_with_options, PgnParseOptions},
+    };
+
+    #[test]
+    fn test_opening_tree() {
+        let options = PgnParseOptions::lenient();
+        let mut tree = OpeningTree::new(4);
+        for pgn in [
+            "1. e4 e5 2. Nf3 Nc6 3. Bb5 1-0",
+            "1. Nf3 Nc6 2. e4 e5 (2... d5) 3. Bc4 0-1",
+            "1. Nf3 Nf6 2. Ng1 Ng8 3. e4 *",
+        ] {
+            tree.add(&parse_pgn_with_options(pgn, &options).unwrap());
+        }
+        assert_eq!(tree.game_count(), 3);
+
+        // The position repeated in the third game counts it once
+        let start = tree.node(Game::default().position_hash()).unwrap();
+        assert_eq!(start.games, 3);
+        assert_eq!((start.white_wins, start.draws, start.black_wins), (1, 0, 1));
+        assert_eq!(start.moves.len(), 2);
+        assert_eq!(
+            tree.transpositions_of(Game::default().position_hash()),
+            [vec![], vec!["Nf3", "Nf6", "Ng1", "Ng8"]]
+        );
+
+        // Only the main line, up to the maximum plies, is added
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+        let node = tree.node(fen).unwrap();
+        assert_eq!(node.games, 2);
+        assert!(node.moves.is_empty());
+        assert_eq!(tree.transpositions_of(fen).len(), 2);
+        assert!(tree
+            .node("rnbqkbnr/ppp1pppp/8/3p4/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 0 3")
+            .is_none());
+        assert!(tree.transpositions_of("not a fen").is_empty());
+
+        // Every way back to the starting position is a move order of it, only
+        // the first ones are kept
+        let mut tree = OpeningTree::new(4);
+        for white in [
+            ["Nf3", "Ng1"],
+            ["Nh3", "Ng1"],
+            ["Nc3", "Nb1"],
+            ["Na3", "Nb1"],
+        ] {
+            for black in [
+                ["Nf6", "Ng8"],
+                ["Nh6", "Ng8"],
+                ["Nc6", "Nb8"],
+                ["Na6", "Nb8"],
+            ] {
+                let pgn = format!(
+                    "1. {} {} 2. {} {} *",
+                    white[0], black[0], white[1], black[1]
+                );
+                tree.add(&parse_pgn_with_options(&pgn, &options).unwrap());
+            }
+        }
+        let orders = tree.transpositions_of(Game::default().position_hash());
+        assert_eq!(orders.len(), MAX_MOVE_ORDERS);
+        assert_eq!(orders[1], ["Nf3", "Nf6", "Ng1", "Ng8"]);
+    }
+}
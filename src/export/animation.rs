@@ -0,0 +1,255 @@
+use gif::{Encoder, Frame, Repeat};
+
+use crate::{
+    constants::{Color, PieceType, Position},
+    errors::ExportError,
+    logic::Game,
+};
+
+use super::{frames, BoardSnapshot};
+
+/// The colors of the frames, as RGB triplets
+const PALETTE: [u8; 24] = [
+    240, 217, 181, // Light square
+    181, 136, 99, // Dark square
+    205, 210, 106, // Light square of the last move
+    170, 162, 58, // Dark square of the last move
+    255, 255, 255, // White piece
+    0, 0, 0, // Black piece
+    0, 0, 0, // Unused
+    0, 0, 0, // Unused
+];
+
+const LIGHT: u8 = 0;
+const DARK: u8 = 1;
+const LIGHT_MOVE: u8 = 2;
+const DARK_MOVE: u8 = 3;
+const WHITE: u8 = 4;
+const BLACK: u8 = 5;
+
+/// The number of sprite pixels per side of a square, including the margin
+const SPRITE_CELLS: u16 = 10;
+
+/// Options that control how a game is animated
+///
+/// # Fields
+/// * `delay`: The time each frame is shown, in hundredths of a second
+/// * `square_size`: The size of a square, in pixels
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GifOptions {
+    pub delay: u16,
+    pub square_size: u16,
+}
+
+impl Default for GifOptions {
+    /// One frame per second with squares of 40 pixels
+    ///
+    /// # Returns
+    /// The default options
+    ///
+    fn default() -> GifOptions {
+        GifOptions {
+            delay: 100,
+            square_size: 40,
+        }
+    }
+}
+
+/// Encodes the main line of a game as an animated GIF, looping forever
+/// White is at the bottom and the squares of the last move are highlighted
+///
+/// # Arguments
+/// * `game`: The game to animate
+/// * `options`: The delay between frames and the size of the squares
+///
+/// # Returns
+/// The bytes of the GIF, or an error if the image is too large or can't be
+/// encoded
+///
+/// # Example
+/// ```
+/// use chess_lab::export::{to_gif, GifOptions};
+/// use chess_lab::logic::Game;
+///
+/// let mut game = Game::default();
+/// game.move_piece("e4").unwrap();
+///
+/// let gif = to_gif(&game, &GifOptions { delay: 50, square_size: 20 }).unwrap();
+/// assert!(gif.starts_with(b"GIF89a"));
+/// ```
+///
+pub fn to_gif(game: &Game, options: &GifOptions) -> Result<Vec<u8>, ExportError> {
+    let size = options
+        .square_size
+        .checked_mul(8)
+        .filter(|size| *size > 0)
+        .ok_or(ExportError::TooLarge)?;
+
+    let mut bytes = Vec::new();
+    {
+        let mut encoder =
+            Encoder::new(&mut bytes, size, size, &PALETTE).map_err(|_| ExportError::Encoding)?;
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(|_| ExportError::Encoding)?;
+        for snapshot in frames(game) {
+            let pixels = render(&snapshot, options.square_size);
+            let mut frame = Frame::from_indexed_pixels(size, size, pixels, None);
+            frame.delay = options.delay;
+            encoder
+                .write_frame(&frame)
+                .map_err(|_| ExportError::Encoding)?;
+        }
+    }
+    Ok(bytes)
+}
+
+/// Draws a frame as indexes into the palette, row by row from the top
+///
+/// # Arguments
+/// * `snapshot`: The frame to draw
+/// * `square_size`: The size of a square, in pixels
+///
+/// # Returns
+/// The pixels of the frame
+///
+fn render(snapshot: &BoardSnapshot, square_size: u16) -> Vec<u8> {
+    let square_size = square_size as usize;
+    let size = square_size * 8;
+    let mut pixels = vec![LIGHT; size * size];
+
+    for row in 0..8u8 {
+        for col in 0..8u8 {
+            let position = Position::new(col, row);
+            let highlighted = snapshot
+                .last_move
+                .is_some_and(|(from, to)| from == position || to == position);
+            let color = match ((col + row) % 2 == 1, highlighted) {
+                (true, false) => LIGHT,
+                (false, false) => DARK,
+                (true, true) => LIGHT_MOVE,
+                (false, true) => DARK_MOVE,
+            };
+            let x = col as usize * square_size;
+            let y = (7 - row as usize) * square_size;
+            for dy in 0..square_size {
+                let start = (y + dy) * size + x;
+                pixels[start..start + square_size].fill(color);
+            }
+        }
+    }
+
+    let cell = (square_size / SPRITE_CELLS as usize).max(1);
+    let margin = square_size.saturating_sub(cell * 8) / 2;
+    for (position, piece) in &snapshot.board {
+        let (fill, outline) = match piece.color {
+            Color::White => (WHITE, BLACK),
+            Color::Black => (BLACK, WHITE),
+        };
+        let sprite = sprite(piece.piece_type);
+        let filled = |x: i32, y: i32| {
+            (0..8).contains(&x) && (0..8).contains(&y) && sprite[y as usize] >> (7 - x) & 1 == 1
+        };
+        let left = position.col as usize * square_size + margin;
+        let top = (7 - position.row as usize) * square_size + margin;
+
+        for sy in 0..8 {
+            for sx in 0..8 {
+                let color = if filled(sx, sy) {
+                    fill
+                } else if filled(sx - 1, sy)
+                    || filled(sx + 1, sy)
+                    || filled(sx, sy - 1)
+                    || filled(sx, sy + 1)
+                {
+                    outline
+                } else {
+                    continue;
+                };
+                for dy in 0..cell {
+                    let start = (top + sy as usize * cell + dy) * size + left + sx as usize * cell;
+                    pixels[start..start + cell].fill(color);
+                }
+            }
+        }
+    }
+    pixels
+}
+
+/// Gets the 8x8 silhouette of a piece, one byte per row from the top
+///
+/// # Arguments
+/// * `piece_type`: The type of the piece
+///
+/// # Returns
+/// The rows of the silhouette, the most significant bit being the left pixel
+///
+fn sprite(piece_type: PieceType) -> [u8; 8] {
+    match piece_type {
+        PieceType::Pawn => [
+            0b00000000, 0b00011000, 0b00111100, 0b00011000, 0b00111100, 0b01111110, 0b01111110,
+            0b00000000,
+        ],
+        PieceType::Knight => [
+            0b00000000, 0b00111000, 0b01111100, 0b11111100, 0b00011100, 0b00111100, 0b01111110,
+            0b00000000,
+        ],
+        PieceType::Bishop => [
+            0b00010000, 0b00111000, 0b00110100, 0b00111100, 0b00011000, 0b00111100, 0b01111110,
+            0b00000000,
+        ],
+        PieceType::Rook => [
+            0b00000000, 0b01011010, 0b01111110, 0b00111100, 0b00111100, 0b00111100, 0b01111110,
+            0b00000000,
+        ],
+        PieceType::Queen => [
+            0b10011001, 0b01011010, 0b01111110, 0b00111100, 0b00011000, 0b00111100, 0b01111110,
+            0b00000000,
+        ],
+        PieceType::King => [
+            0b00011000, 0b00111100, 0b00011000, 0b01111110, 0b01111110, 0b00111100, 0b01111110,
+            0b00000000,
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, to_gif, GifOptions, BLACK, DARK, LIGHT, LIGHT_MOVE, WHITE};
+    use crate::{errors::ExportError, export::frames, logic::Game};
+
+    #[test]
+    fn test_render() {
+        let mut game = Game::default();
+        game.move_piece("e4").unwrap();
+        let frames = frames(&game);
+        let pixels = render(&frames[1], 10);
+        let pixel = |x: usize, y: usize| pixels[y * 80 + x];
+
+        // The corners of the squares are empty, e2 and e4 are highlighted
+        assert_eq!(pixel(0, 0), LIGHT);
+        assert_eq!(pixel(0, 70), DARK);
+        assert_eq!(pixel(40, 60), LIGHT_MOVE);
+        assert_eq!(pixel(40, 40), LIGHT_MOVE);
+        // The center of the black rook on a8 and of the white pawn on e4
+        assert_eq!(pixel(4, 5), BLACK);
+        assert_eq!(pixel(44, 45), WHITE);
+    }
+
+    #[test]
+    fn test_to_gif_errors() {
+        let game = Game::default();
+        let options = GifOptions {
+            square_size: 0,
+            ..GifOptions::default()
+        };
+        assert_eq!(to_gif(&game, &options), Err(ExportError::TooLarge));
+
+        let options = GifOptions {
+            square_size: 10000,
+            ..GifOptions::default()
+        };
+        assert_eq!(to_gif(&game, &options), Err(ExportError::TooLarge));
+    }
+}
@@ -0,0 +1,231 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::errors::ExportError;
+
+/// How a file is written when it already exists
+///
+/// # Variants
+/// * `Overwrite`: The file is replaced
+/// * `Append`: The text is added at the end of the file, to build a database
+///   of games
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SaveMode {
+    #[default]
+    Overwrite,
+    Append,
+}
+
+/// Saves a PGN to a file
+/// A replaced file is written to a temporary file next to the destination,
+/// which then takes its place, so a crash never leaves a half written file
+/// behind. Appended games are added at the end of the file, separated from
+/// the previous ones by a blank line
+///
+/// # Arguments
+/// * `path`: The file to write
+/// * `pgn`: The PGN to save
+/// * `mode`: Whether an existing file is replaced or extended
+///
+/// # Returns
+/// Ok if the file was written, or an error if it couldn't be
+///
+/// # Example
+/// ```
+/// use chess_lab::export::{save_pgn, SaveMode};
+///
+/// let path = std::env::temp_dir().join("chess_lab_save_pgn_example.pgn");
+/// save_pgn(&path, "1. e4 e5 *", SaveMode::Overwrite).unwrap();
+/// save_pgn(&path, "1. d4 d5 *", SaveMode::Append).unwrap();
+///
+/// assert_eq!(
+///     std::fs::read_to_string(&path).unwrap(),
+///     "1. e4 e5 *\n\n1. d4 d5 *\n"
+/// );
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+///
+pub fn save_pgn(path: &Path, pgn: &str, mode: SaveMode) -> Result<(), ExportError> {
+    let result = match mode {
+        SaveMode::Overwrite => replace(path, pgn),
+        SaveMode::Append => append(path, pgn),
+    };
+    result.map_err(|_| ExportError::Write)
+}
+
+/// Saves a PGN to a file without blocking the async runtime, as done by
+/// [`save_pgn`]
+///
+/// # Arguments
+/// * `path`: The file to write
+/// * `pgn`: The PGN to save
+/// * `mode`: Whether an existing file is replaced or extended
+///
+/// # Returns
+/// Ok if the file was written, or an error if it couldn't be
+///
+#[cfg(feature = "tokio")]
+pub async fn save_pgn_async(
+    path: impl AsRef<Path>,
+    pgn: &str,
+    mode: SaveMode,
+) -> Result<(), ExportError> {
+    let path = path.as_ref().to_path_buf();
+    let pgn = pgn.to_string();
+    tokio::task::spawn_blocking(move || save_pgn(&path, &pgn, mode))
+        .await
+        .map_err(|_| ExportError::Write)?
+}
+
+/// Replaces a file through a temporary file, which is renamed over it once
+/// it is flushed to disk
+///
+/// # Arguments
+/// * `path`: The file to write
+/// * `pgn`: The PGN to save
+///
+/// # Returns
+/// Ok if the file was replaced
+///
+fn replace(path: &Path, pgn: &str) -> std::io::Result<()> {
+    let temp = temp_path(path);
+    let result = write_temp(&temp, pgn).and_then(|_| fs::rename(&temp, path));
+    if result.is_err() {
+        fs::remove_file(&temp).ok();
+        return result;
+    }
+
+    // Make the rename itself durable where directories can be synced
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        if let Ok(dir) = File::open(dir) {
+            dir.sync_all().ok();
+        }
+    }
+    Ok(())
+}
+
+/// Writes a PGN to a new temporary file
+///
+/// # Arguments
+/// * `temp`: The temporary file
+/// * `pgn`: The PGN to save
+///
+/// # Returns
+/// Ok if the temporary file was written and flushed to disk
+///
+fn write_temp(temp: &Path, pgn: &str) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().write(true).create_new(true).open(temp)?;
+    file.write_all(pgn.trim_end().as_bytes())?;
+    file.write_all(b"\n")?;
+    file.sync_all()
+}
+
+/// Adds a PGN at the end of a file, creating it if needed
+/// The separator is picked from the last bytes of the file, so that there is
+/// exactly one blank line before the new game
+///
+/// # Arguments
+/// * `path`: The file to extend
+/// * `pgn`: The PGN to save
+///
+/// # Returns
+/// Ok if the PGN was added and flushed to disk
+///
+fn append(path: &Path, pgn: &str) -> std::io::Result<()> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(path)?;
+
+    let len = file.metadata()?.len();
+    let mut tail = [0u8; 2];
+    let tail = &mut tail[..len.min(2) as usize];
+    if !tail.is_empty() {
+        file.seek(SeekFrom::End(-(tail.len() as i64)))?;
+        file.read_exact(tail)?;
+    }
+    let separator: &[u8] = match tail {
+        [] | [b'\n', b'\n'] => b"",
+        [.., b'\n'] => b"\n",
+        _ => b"\n\n",
+    };
+
+    file.write_all(separator)?;
+    file.write_all(pgn.trim_end().as_bytes())?;
+    file.write_all(b"\n")?;
+    file.sync_all()
+}
+
+/// Returns a new temporary file to write a file
+/// The name holds the process and a counter, so that concurrent saves of the
+/// same file don't share it
+///
+/// # Arguments
+/// * `path`: The file to write
+///
+/// # Returns
+/// A hidden file in the same directory, so that renaming it is atomic
+///
+fn temp_path(path: &Path) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        name,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{save_pgn, temp_path, SaveMode};
+    use crate::errors::ExportError;
+
+    #[test]
+    fn test_save_pgn() {
+        let path = std::env::temp_dir().join(format!("chess_lab_test_{}.pgn", std::process::id()));
+        fs::remove_file(&path).ok();
+
+        save_pgn(&path, "1. e4 *\n", SaveMode::Append).unwrap();
+        save_pgn(&path, "1. d4 *", SaveMode::Append).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1. e4 *\n\n1. d4 *\n");
+
+        fs::write(&path, "1. e4 *").unwrap();
+        save_pgn(&path, "1. d4 *", SaveMode::Append).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1. e4 *\n\n1. d4 *\n");
+
+        save_pgn(&path, "1. c4 *", SaveMode::Overwrite).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1. c4 *\n");
+        assert_ne!(temp_path(&path), temp_path(&path));
+        let prefix = format!(".{}", path.file_name().unwrap().to_string_lossy());
+        assert!(!fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .any(|entry| entry
+                .unwrap()
+                .file_name()
+                .to_string_lossy()
+                .starts_with(&prefix)));
+        fs::remove_file(&path).unwrap();
+
+        let missing = std::env::temp_dir()
+            .join("chess_lab_missing_dir")
+            .join("game.pgn");
+        assert_eq!(
+            save_pgn(&missing, "*", SaveMode::Overwrite),
+            Err(ExportError::Write)
+        );
+    }
+}
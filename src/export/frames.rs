@@ -0,0 +1,78 @@
+use crate::{
+    constants::Position,
+    logic::{Board, Game},
+};
+
+/// A frame of the animation of a game: the board at a given moment
+///
+/// # Fields
+/// * `board`: The board
+/// * `fen`: The FEN of the position
+/// * `last_move`: The starting and ending squares of the move that led to
+///   the position, if any
+/// * `san`: The move that led to the position in algebraic notation, if any
+///
+#[derive(Debug, Clone)]
+pub struct BoardSnapshot {
+    pub board: Board,
+    pub fen: String,
+    pub last_move: Option<(Position, Position)>,
+    pub san: Option<String>,
+}
+
+/// Returns the frames of the main line of a game, to animate it
+///
+/// # Arguments
+/// * `game`: The game to animate
+///
+/// # Returns
+/// The board at the start position, followed by the board after every move
+/// of the main line
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::Position;
+/// use chess_lab::export::frames;
+/// use chess_lab::logic::Game;
+///
+/// let mut game = Game::default();
+/// game.move_piece("e4").unwrap();
+/// game.move_piece("e5").unwrap();
+/// game.undo();
+///
+/// let frames = frames(&game);
+/// assert_eq!(frames.len(), 3);
+/// assert_eq!(frames[0].san, None);
+/// assert_eq!(frames[2].san, Some("e5".to_string()));
+/// assert_eq!(
+///     frames[1].last_move,
+///     Some((Position::from_string("e2"), Position::from_string("e4")))
+/// );
+/// ```
+///
+pub fn frames(game: &Game) -> Vec<BoardSnapshot> {
+    let mut replay = game.replay();
+    let mut frames = vec![BoardSnapshot {
+        board: replay.board.clone(),
+        fen: replay.fen(),
+        last_move: None,
+        san: None,
+    }];
+
+    let mut node = game.history.root_nodes().first().cloned();
+    while let Some(line) = node {
+        let mov = line.borrow().mov.clone();
+        let san = line.borrow().san();
+        replay
+            .move_piece(&san)
+            .expect("The moves of the game are legal");
+        frames.push(BoardSnapshot {
+            board: replay.board.clone(),
+            fen: replay.fen(),
+            last_move: Some((mov.from, mov.to)),
+            san: Some(san),
+        });
+        node = line.borrow().lines.first().cloned();
+    }
+    frames
+}
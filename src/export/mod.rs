@@ -0,0 +1,12 @@
+#[cfg(feature = "gif")]
+mod animation;
+mod file;
+mod frames;
+mod report;
+pub mod scid;
+
+#[cfg(feature = "gif")]
+pub use animation::*;
+pub use file::*;
+pub use frames::*;
+pub use report::*;
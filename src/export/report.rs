@@ -0,0 +1,524 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    constants::{pgn::PgnLine, Move},
+    errors::ExportError,
+    logic::Game,
+};
+
+/// Where diagram placeholders are inserted in a report
+///
+/// # Variants
+/// * `Never`: No diagrams
+/// * `AfterComments`: After every commented move of the main line
+/// * `Every`: After every given number of moves (plies) of the main line
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Diagrams {
+    #[default]
+    Never,
+    AfterComments,
+    Every(u32),
+}
+
+/// Options that control how a game is written as a report
+///
+/// # Fields
+/// * `diagrams`: Where diagram placeholders are inserted
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReportOptions {
+    pub diagrams: Diagrams,
+}
+
+/// Writes a game as Markdown, for blogs and reports
+///
+/// # Arguments
+/// * `game`: The game to write
+///
+/// # Returns
+/// The game in Markdown, or an error if a move of the game can't be played
+///
+/// # Example
+/// ```
+/// use chess_lab::export::to_markdown;
+/// use chess_lab::parsing::{parse_pgn_with_options, PgnParseOptions};
+///
+/// let game = parse_pgn_with_options(
+///     "[White \"Morphy\"]\n[Black \"Duke\"]\n\n1. e4 e5 (1... c5 { Sicilian }) 2. Nf3 *",
+///     &PgnParseOptions::lenient(),
+/// )
+/// .unwrap();
+///
+/// assert_eq!(
+///     to_markdown(&game).unwrap(),
+///     "# Morphy - Duke\n\n1. e4 e5\n\n- 1... c5 *Sicilian*\n\n2. Nf3\n\n**\\***\n"
+/// );
+/// ```
+///
+pub fn to_markdown(game: &Game) -> Result<String, ExportError> {
+    to_markdown_with(game, &ReportOptions::default())
+}
+
+/// Writes a game as Markdown with the given options
+/// Diagram placeholders are fenced code blocks with the `fen` language
+///
+/// # Arguments
+/// * `game`: The game to write
+/// * `options`: The report options
+///
+/// # Returns
+/// The game in Markdown, or an error if a move of the game can't be played
+///
+/// # Example
+/// ```
+/// use chess_lab::export::{to_markdown_with, Diagrams, ReportOptions};
+/// use chess_lab::logic::Game;
+///
+/// let mut game = Game::default();
+/// game.move_piece("e4").unwrap();
+/// game.move_piece("e5").unwrap();
+///
+/// let options = ReportOptions { diagrams: Diagrams::Every(2) };
+/// assert!(to_markdown_with(&game, &options).unwrap().contains(
+///     "```fen\nrnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2\n```"
+/// ));
+/// ```
+///
+pub fn to_markdown_with(game: &Game, options: &ReportOptions) -> Result<String, ExportError> {
+    let mut writer = MarkdownWriter::default();
+    write_report(game, options, &mut writer)?;
+    Ok(writer.out)
+}
+
+/// Writes a game as an HTML fragment, for blogs and reports
+/// Every move carries the FEN of the position after it in a `data-fen` attribute
+///
+/// # Arguments
+/// * `game`: The game to write
+///
+/// # Returns
+/// The game as HTML, or an error if a move of the game can't be played
+///
+/// # Example
+/// ```
+/// use chess_lab::export::to_html;
+/// use chess_lab::logic::Game;
+///
+/// let mut game = Game::default();
+/// game.move_piece("e4").unwrap();
+///
+/// assert_eq!(
+///     to_html(&game).unwrap(),
+///     "<div class=\"game\">\n<p class=\"moves\">1. <span class=\"move\" data-fen=\"rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1\">e4</span></p>\n<p class=\"result\">*</p>\n</div>\n"
+/// );
+/// ```
+///
+pub fn to_html(game: &Game) -> Result<String, ExportError> {
+    to_html_with(game, &ReportOptions::default())
+}
+
+/// Writes a game as an HTML fragment with the given options
+/// Diagram placeholders are empty `div` elements with a `data-fen` attribute
+///
+/// # Arguments
+/// * `game`: The game to write
+/// * `options`: The report options
+///
+/// # Returns
+/// The game as HTML, or an error if a move of the game can't be played
+///
+pub fn to_html_with(game: &Game, options: &ReportOptions) -> Result<String, ExportError> {
+    let mut writer = HtmlWriter::default();
+    writer.out.push_str("<div class=\"game\">\n");
+    write_report(game, options, &mut writer)?;
+    writer.out.push_str("</div>\n");
+    Ok(writer.out)
+}
+
+/// The output format of a report
+trait ReportWriter {
+    /// Writes the title and the information of the game
+    fn header(&mut self, title: &str, info: &str);
+
+    /// Writes a move, preceded by its number, if any
+    fn mov(&mut self, number: Option<&str>, san: &str, fen: &str);
+
+    /// Writes the comment of the last move
+    fn comment(&mut self, comment: &str);
+
+    /// Writes a diagram placeholder of a position
+    fn diagram(&mut self, fen: &str);
+
+    /// Starts a variation, nested at the given depth
+    fn start_line(&mut self, depth: usize);
+
+    /// Writes the result, ending the report
+    fn result(&mut self, result: &str);
+}
+
+/// Writes the header, the moves and the result of a game
+///
+/// # Arguments
+/// * `game`: The game to write
+/// * `options`: The report options
+/// * `writer`: The output format
+///
+/// # Returns
+/// An error if a move of the game can't be played
+///
+fn write_report(
+    game: &Game,
+    options: &ReportOptions,
+    writer: &mut impl ReportWriter,
+) -> Result<(), ExportError> {
+    let history = &game.history;
+    if history.white.is_some() || history.black.is_some() {
+        let info = [&history.event, &history.site, &history.date]
+            .into_iter()
+            .flatten()
+            .map(|value| value.as_str())
+            .collect::<Vec<&str>>()
+            .join(", ");
+        let title = format!(
+            "{} - {}",
+            history.white.as_deref().unwrap_or("?"),
+            history.black.as_deref().unwrap_or("?")
+        );
+        writer.header(&title, &info);
+    }
+
+    writer.start_line(0);
+    if let Some((first, alternatives)) = history.root_nodes().split_first() {
+        let mut walker = Walker {
+            options,
+            writer,
+            ply: 0,
+        };
+        walker.line(Rc::clone(first), alternatives, game.replay(), 0)?;
+    }
+    writer.result(history.result.as_deref().unwrap_or("*"));
+    Ok(())
+}
+
+/// Walks the move tree of a game, sending it to a writer
+struct Walker<'a, W: ReportWriter> {
+    options: &'a ReportOptions,
+    writer: &'a mut W,
+    ply: u32,
+}
+
+impl<W: ReportWriter> Walker<'_, W> {
+    /// Writes a line starting at a node, with the alternatives to its first move
+    ///
+    /// # Arguments
+    /// * `node`: The first node of the line
+    /// * `alternatives`: The nodes that can be played instead of the first one
+    /// * `game`: The game at the position before the first move, with the
+    ///   settings of the written one
+    /// * `depth`: The nesting depth of the line, 0 for the main line
+    ///
+    /// # Returns
+    /// An error if a move of the line can't be played
+    ///
+    fn line(
+        &mut self,
+        node: Rc<RefCell<PgnLine<Move>>>,
+        alternatives: &[Rc<RefCell<PgnLine<Move>>>],
+        mut game: Game,
+        depth: usize,
+    ) -> Result<(), ExportError> {
+        let mut show_number = true;
+        let mut node = node;
+        let mut alternatives = alternatives.to_vec();
+
+        loop {
+            // The variations are replayed on a copy, keeping the settings of the game
+            let before = (!alternatives.is_empty()).then(|| game.clone());
+            let number = if game.is_white_turn {
+                Some(format!("{}.", game.fullmove_number))
+            } else if show_number {
+                Some(format!("{}...", game.fullmove_number))
+            } else {
+                None
+            };
+
+            let line = node.borrow();
+            game.move_piece(&line.san())
+                .map_err(|_| ExportError::InvalidMove)?;
+            let fen = game.fen();
+            self.writer.mov(number.as_deref(), &line.san(), &fen);
+            show_number = false;
+
+            let comment = line.comment_text();
+            if let Some(comment) = &comment {
+                self.writer.comment(comment);
+                show_number = true;
+            }
+            if depth == 0 {
+                self.ply += 1;
+                let diagram = match self.options.diagrams {
+                    Diagrams::Never => false,
+                    Diagrams::AfterComments => comment.is_some(),
+                    Diagrams::Every(plies) => plies > 0 && self.ply.is_multiple_of(plies),
+                };
+                if diagram {
+                    self.writer.diagram(&fen);
+                    show_number = true;
+                }
+            }
+
+            if let Some(before) = &before {
+                for alternative in &alternatives {
+                    self.writer.start_line(depth + 1);
+                    self.line(Rc::clone(alternative), &[], before.clone(), depth + 1)?;
+                    self.writer.start_line(depth);
+                    show_number = true;
+                }
+            }
+
+            let Some((next, rest)) = line.lines.split_first() else {
+                break;
+            };
+            let next = Rc::clone(next);
+            alternatives = rest.to_vec();
+            drop(line);
+            node = next;
+        }
+        Ok(())
+    }
+}
+
+/// Writes reports as Markdown, variations as nested list items
+#[derive(Default)]
+struct MarkdownWriter {
+    out: String,
+    tokens: Vec<String>,
+    depth: usize,
+    in_list: bool,
+}
+
+impl MarkdownWriter {
+    /// Writes the pending tokens as a paragraph or a list item
+    fn flush(&mut self) {
+        if self.tokens.is_empty() {
+            return;
+        }
+        let text = self.tokens.join(" ");
+        self.tokens.clear();
+        if self.depth == 0 {
+            if self.in_list {
+                self.out.push('\n');
+                self.in_list = false;
+            }
+            self.out.push_str(&format!("{}\n\n", text));
+        } else {
+            self.out
+                .push_str(&format!("{}- {}\n", "  ".repeat(self.depth - 1), text));
+            self.in_list = true;
+        }
+    }
+
+    /// Escapes the characters that Markdown would read as formatting
+    fn escape(text: &str) -> String {
+        text.chars().fold(String::new(), |mut escaped, c| {
+            if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '#') {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+            escaped
+        })
+    }
+}
+
+impl ReportWriter for MarkdownWriter {
+    fn header(&mut self, title: &str, info: &str) {
+        self.out
+            .push_str(&format!("# {}\n\n", MarkdownWriter::escape(title)));
+        if !info.is_empty() {
+            self.out
+                .push_str(&format!("{}\n\n", MarkdownWriter::escape(info)));
+        }
+    }
+
+    fn mov(&mut self, number: Option<&str>, san: &str, _fen: &str) {
+        match number {
+            Some(number) => self.tokens.push(format!("{} {}", number, san)),
+            None => self.tokens.push(san.to_string()),
+        }
+    }
+
+    fn comment(&mut self, comment: &str) {
+        self.tokens
+            .push(format!("*{}*", MarkdownWriter::escape(comment)));
+    }
+
+    fn diagram(&mut self, fen: &str) {
+        self.flush();
+        if self.in_list {
+            self.out.push('\n');
+            self.in_list = false;
+        }
+        self.out.push_str(&format!("```fen\n{}\n```\n\n", fen));
+    }
+
+    fn start_line(&mut self, depth: usize) {
+        self.flush();
+        self.depth = depth;
+    }
+
+    fn result(&mut self, result: &str) {
+        self.flush();
+        if self.in_list {
+            self.out.push('\n');
+        }
+        self.out
+            .push_str(&format!("**{}**\n", MarkdownWriter::escape(result)));
+    }
+}
+
+/// Writes reports as HTML, variations as indented `div` elements
+#[derive(Default)]
+struct HtmlWriter {
+    out: String,
+    tokens: Vec<String>,
+    depth: usize,
+}
+
+impl HtmlWriter {
+    /// Writes the pending tokens as a paragraph or a variation
+    fn flush(&mut self) {
+        if self.tokens.is_empty() {
+            return;
+        }
+        let text = self.tokens.join(" ");
+        self.tokens.clear();
+        if self.depth == 0 {
+            self.out
+                .push_str(&format!("<p class=\"moves\">{}</p>\n", text));
+        } else {
+            self.out.push_str(&format!(
+                "<div class=\"variation\" style=\"margin-left: {}em\">{}</div>\n",
+                self.depth * 2,
+                text
+            ));
+        }
+    }
+
+    /// Escapes the characters that have a meaning in HTML
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+}
+
+impl ReportWriter for HtmlWriter {
+    fn header(&mut self, title: &str, info: &str) {
+        self.out
+            .push_str(&format!("<h1>{}</h1>\n", HtmlWriter::escape(title)));
+        if !info.is_empty() {
+            self.out.push_str(&format!(
+                "<p class=\"info\">{}</p>\n",
+                HtmlWriter::escape(info)
+            ));
+        }
+    }
+
+    fn mov(&mut self, number: Option<&str>, san: &str, fen: &str) {
+        let mov = format!(
+            "<span class=\"move\" data-fen=\"{}\">{}</span>",
+            fen,
+            HtmlWriter::escape(san)
+        );
+        match number {
+            Some(number) => self.tokens.push(format!("{} {}", number, mov)),
+            None => self.tokens.push(mov),
+        }
+    }
+
+    fn comment(&mut self, comment: &str) {
+        self.tokens.push(format!(
+            "<span class=\"comment\">{}</span>",
+            HtmlWriter::escape(comment)
+        ));
+    }
+
+    fn diagram(&mut self, fen: &str) {
+        self.flush();
+        self.out.push_str(&format!(
+            "<div class=\"diagram\" data-fen=\"{}\"></div>\n",
+            fen
+        ));
+    }
+
+    fn start_line(&mut self, depth: usize) {
+        self.flush();
+        self.depth = depth;
+    }
+
+    fn result(&mut self, result: &str) {
+        self.flush();
+        self.out.push_str(&format!(
+            "<p class=\"result\">{}</p>\n",
+            HtmlWriter::escape(result)
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_html, to_html_with, to_markdown, to_markdown_with, Diagrams, ReportOptions};
+    use crate::{
+        logic::{Game, STARTING_FEN},
+        parsing::{parse_pgn_with_options, PgnParseOptions},
+    };
+
+    #[test]
+    fn test_report_variations() {
+        let game = parse_pgn_with_options(
+            "[Event \"Club <1>\"]\n[White \"A\"]\n\n1. e4 { Best } e5 (1... c5 2. Nf3 (2. c3 d5) 2... d6) 2. Nf3 1-0",
+            &PgnParseOptions::lenient(),
+        )
+        .unwrap();
+        let options = ReportOptions {
+            diagrams: Diagrams::AfterComments,
+        };
+
+        assert_eq!(
+            to_markdown_with(&game, &options).unwrap(),
+            "# A - ?\n\nClub <1>\n\n1. e4 *Best*\n\n```fen\nrnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1\n```\n\n1... e5\n\n- 1... c5 2. Nf3\n  - 2. c3 d5\n- 2... d6\n\n2. Nf3\n\n**1-0**\n"
+        );
+
+        let html = to_html_with(&game, &options).unwrap();
+        assert!(html.starts_with(
+            "<div class=\"game\">\n<h1>A - ?</h1>\n<p class=\"info\">Club &lt;1&gt;</p>\n"
+        ));
+        assert!(html.contains(
+            "<div class=\"variation\" style=\"margin-left: 4em\">2. <span class=\"move\""
+        ));
+        assert_eq!(html.matches("class=\"diagram\"").count(), 1);
+        assert!(html.ends_with("<p class=\"result\">1-0</p>\n</div>\n"));
+    }
+
+    #[test]
+    fn test_report_variation_settings() {
+        // The variation leaves the king in check, which only a game where the
+        // king must be captured allows
+        let mut game = Game::new(STARTING_FEN, true);
+        for mov in ["e4", "e5"] {
+            game.move_piece(mov).unwrap();
+        }
+        game.undo();
+        for mov in ["f6", "Qh5", "Nc6"] {
+            game.move_piece(mov).unwrap();
+        }
+
+        assert_eq!(
+            to_markdown(&game).unwrap(),
+            "1. e4 e5\n\n- 1... f6 2. Qh5 Nc6\n\n**\\***\n"
+        );
+        assert!(to_html(&game).unwrap().contains(">Nc6</span>"));
+    }
+}
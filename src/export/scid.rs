@@ -0,0 +1,262 @@
+use std::time::Duration;
+
+use crate::{
+    constants::{
+        find_clock_time, format_annotations, format_clock_time, format_node_data,
+        pgn::{format_tag, PgnLine},
+        Color, Move,
+    },
+    logic::{Game, STARTING_FEN},
+};
+
+/// Serializes a game as SCID-flavored PGN, ready to be imported by SCID and
+/// other desktop database tools
+///
+/// The Seven Tag Roster is always written (missing tags are set to `?`), the
+/// header is separated from the movetext by a blank line and the movetext is
+/// terminated by the game result. Every move with a `[%clk]` comment also gets
+/// an `[%emt]` comment with the time spent on it, using the increment of the
+/// `WhiteClock` or `BlackClock` tag of the player, or else of the
+/// `TimeControl` tag, if any
+///
+/// # Arguments
+/// * `game`: The game to serialize
+///
+/// # Returns
+/// The game in SCID-flavored PGN
+///
+/// # Example
+/// ```
+/// use chess_lab::export::scid::to_scid_pgn;
+/// use chess_lab::logic::Game;
+///
+/// let mut game = Game::default();
+/// game.history.time_control = Some(String::from("60+1"));
+/// game.move_piece("e4").unwrap();
+/// game.history.set_comment(Some(String::from("[%clk 0:00:58]")));
+///
+/// assert_eq!(
+///     to_scid_pgn(&game),
+///     "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"?\"]\n[Black \"?\"]\n[Result \"*\"]\n[TimeControl \"60+1\"]\n\n1. e4 { [%clk 0:00:58] [%emt 0:00:03] } *\n"
+/// );
+/// ```
+///
+pub fn to_scid_pgn(game: &Game) -> String {
+    let history = &game.history;
+    let result = history.result.clone().unwrap_or(String::from("*"));
+    let clocks = [&history.white_clock, &history.black_clock].map(|clock| {
+        clock
+            .as_deref()
+            .or(history.time_control.as_deref())
+            .and_then(parse_time_control)
+            .map_or((None, 0), |(base, increment)| (Some(base), increment))
+    });
+
+    // The Seven Tag Roster is always written, with the placeholders of the
+    // PGN standard for the tags that aren't set
+    let mut pgn = String::new();
+    for (tag, value) in history.tag_fields() {
+        let default = match tag {
+            "Event" | "Site" | "Round" | "White" | "Black" => Some("?"),
+            "Date" => Some("????.??.??"),
+            "Result" => Some("*"),
+            _ => None,
+        };
+        if let Some(value) = value.as_deref().or(default) {
+            pgn.push_str(&format_tag(tag, value));
+        }
+    }
+    if game.start_position != STARTING_FEN {
+        pgn.push_str("[SetUp \"1\"]\n");
+        pgn.push_str(&format_tag("FEN", &game.start_position));
+    }
+    pgn.push('\n');
+
+    let moves = history.pgn_moves_with(&|line| {
+        let (base, increment) = match line.mov.piece.color {
+            Color::White => clocks[0],
+            Color::Black => clocks[1],
+        };
+        move_text(line, base, increment)
+    });
+    let moves = moves.trim_end();
+    if !moves.is_empty() {
+        pgn.push_str(moves);
+        pgn.push(' ');
+    }
+    pgn.push_str(&result);
+    pgn.push('\n');
+    pgn
+}
+
+/// Serializes a collection of games as a single SCID-flavored PGN database
+///
+/// # Arguments
+/// * `games`: The games to serialize
+///
+/// # Returns
+/// The games in SCID-flavored PGN, separated by blank lines
+///
+/// # Example
+/// ```
+/// use chess_lab::export::scid::to_scid_database;
+/// use chess_lab::logic::Game;
+///
+/// let database = to_scid_database(&[Game::default(), Game::default()]);
+///
+/// assert_eq!(database.matches("[Event \"?\"]").count(), 2);
+/// ```
+///
+pub fn to_scid_database(games: &[Game]) -> String {
+    games
+        .iter()
+        .map(to_scid_pgn)
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Returns the text of a node with its comment extended by an `[%emt]` tag
+///
+/// # Arguments
+/// * `line`: The node to render
+/// * `base`: The initial time of each player in seconds, if known
+/// * `increment`: The increment per move in seconds
+///
+/// # Returns
+/// The move followed by its graphic annotations, custom data and comment,
+/// if any
+///
+fn move_text(line: &PgnLine<Move>, base: Option<u32>, increment: u32) -> String {
+    let mut comment = line.comment.clone();
+    let clock_of = |line: &PgnLine<Move>| find_clock_time(line.comment.as_deref()?, "clk");
+
+    // A time spent set on the move is kept as it is
+    let elapsed = comment
+        .as_deref()
+        .and_then(|comment| find_clock_time(comment, "emt"));
+    if let (None, Some(clock)) = (elapsed, clock_of(line)) {
+        // The previous move of the same player is two plies up the tree
+        let previous = match &line.parent {
+            Some(parent) => match &parent.as_ref().borrow().parent {
+                Some(grandparent) => clock_of(&grandparent.as_ref().borrow()),
+                None => base.map(|base| Duration::from_secs(base as u64)),
+            },
+            None => base.map(|base| Duration::from_secs(base as u64)),
+        };
+        if let Some(previous) = previous {
+            let elapsed = (previous + Duration::from_secs(increment as u64)).saturating_sub(clock);
+            comment = comment.map(|c| format!("{} [%emt {}]", c, format_clock_time(elapsed)));
+        }
+    }
+
+    let annotations = [
+        format_annotations(&line.arrows, &line.highlights),
+        format_node_data(&line.node_data),
+    ]
+    .into_iter()
+    .filter(|part| !part.is_empty())
+    .collect::<Vec<String>>()
+    .join(" ");
+    match (comment, annotations.is_empty()) {
+        (Some(comment), true) => format!("{} {{ {} }}", line.san(), comment),
+        (Some(comment), false) => format!("{} {{ {} {} }}", line.san(), annotations, comment),
+        (None, false) => format!("{} {{ {} }}", line.san(), annotations),
+        (None, true) => line.san(),
+    }
+}
+
+/// Parses a `TimeControl` tag of the form `base+increment` or `base`
+///
+/// # Arguments
+/// * `time_control`: The value of the tag
+///
+/// # Returns
+/// The base time and the increment in seconds, or `None` if the tag is not a
+/// simple sudden death or increment time control
+///
+fn parse_time_control(time_control: &str) -> Option<(u32, u32)> {
+    let mut parts = time_control.split('+');
+    let base = parts.next()?.parse().ok()?;
+    let increment = match parts.next() {
+        Some(increment) => increment.parse().ok()?,
+        None => 0,
+    };
+    match parts.next() {
+        Some(_) => None,
+        None => Some((base, increment)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{parse_time_control, to_scid_database, to_scid_pgn};
+    use crate::{logic::Game, parsing::parse_pgn};
+
+    #[test]
+    fn test_parse_time_control() {
+        assert_eq!(parse_time_control("180+2"), Some((180, 2)));
+        assert_eq!(parse_time_control("300"), Some((300, 0)));
+        assert_eq!(parse_time_control("40/7200:3600"), None);
+        assert_eq!(parse_time_control("-"), None);
+    }
+
+    #[test]
+    fn test_to_scid_pgn_emt() {
+        let game = parse_pgn(
+            "[Event \"Blitz\"]\n[Site \"?\"]\n[Date \"2024.01.01\"]\n[Round \"1\"]\n[White \"A\"]\n[Black \"B\"]\n[Result \"1-0\"]\n[TimeControl \"180+2\"]\n\n1. e4 { [%clk 0:02:59] } e5 { [%clk 0:02:50] } 2. Nf3 { [%clk 0:02:40] } (2. d4 { [%clk 0:02:01] }) Nc6 { [%clk 0:02:52] } 1-0",
+        )
+        .unwrap();
+
+        assert_eq!(
+            to_scid_pgn(&game),
+            "[Event \"Blitz\"]\n[Site \"?\"]\n[Date \"2024.01.01\"]\n[Round \"1\"]\n[White \"A\"]\n[Black \"B\"]\n[Result \"1-0\"]\n[TimeControl \"180+2\"]\n\n1. e4 { [%clk 0:02:59] [%emt 0:00:03] } e5 { [%clk 0:02:50] [%emt 0:00:12] } 2. Nf3 { [%clk 0:02:40] [%emt 0:00:21] } (2. d4 { [%clk 0:02:01] [%emt 0:01:00] }) Nc6 { [%clk 0:02:52] [%emt 0:00:00] } 1-0\n"
+        );
+    }
+
+    #[test]
+    fn test_to_scid_pgn_time_odds() {
+        let mut game = Game::default();
+        game.history.white_clock = Some(String::from("60"));
+        game.history.black_clock = Some(String::from("300+2"));
+        game.move_piece("e4").unwrap();
+        game.history
+            .set_comment(Some(String::from("[%clk 0:00:55]")));
+        game.move_piece("e5").unwrap();
+        game.history
+            .set_comment(Some(String::from("[%clk 0:04:52]")));
+
+        assert!(to_scid_pgn(&game).ends_with(
+            "[WhiteClock \"60\"]\n[BlackClock \"300+2\"]\n\n1. e4 { [%clk 0:00:55] [%emt 0:00:05] } e5 { [%clk 0:04:52] [%emt 0:00:10] } *\n"
+        ));
+
+        // A time spent already set is not written twice
+        game.history.set_elapsed(Some(Duration::from_millis(9500)));
+        assert!(to_scid_pgn(&game).ends_with("e5 { [%emt 0:00:09.5] [%clk 0:04:52] } *\n"));
+    }
+
+    #[test]
+    fn test_to_scid_pgn_setup() {
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+        game.move_piece("e4").unwrap();
+
+        assert_eq!(
+            to_scid_pgn(&game),
+            "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"?\"]\n[Black \"?\"]\n[Result \"*\"]\n[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 w - - 0 1\"]\n\n1. e4 *\n"
+        );
+    }
+
+    #[test]
+    fn test_to_scid_database() {
+        let mut game = Game::default();
+        game.move_piece("d4").unwrap();
+
+        let database = to_scid_database(&[Game::default(), game]);
+        let games: Vec<&str> = database.split("\n\n[Event").collect();
+
+        assert_eq!(games.len(), 2);
+        assert!(games[0].ends_with("\n\n*"));
+        assert!(games[1].ends_with("\n\n1. d4 *\n"));
+    }
+}
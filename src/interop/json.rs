@@ -0,0 +1,396 @@
+//! A stable JSON schema to exchange games with web frontends
+//!
+//! A game is an object with its `tags`, its starting FEN (`startFen`) and its
+//! main line (`moves`). Every move carries its SAN, UCI, squares and the FEN
+//! after it, so clients don't have to replay the game. Clock times (`[%clk]`)
+//! and evaluations (`[%eval]`) are read out of the comments into their own
+//! fields. The variations of a move are the lines that can be played instead
+//! of it
+//!
+//! ```json
+//! {
+//!   "tags": { "Event": "Casual game", "Result": "*" },
+//!   "startFen": "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+//!   "moves": [
+//!     {
+//!       "san": "e4", "uci": "e2e4", "from": "e2", "to": "e4",
+//!       "fen": "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1",
+//!       "clock": 180, "eval": { "cp": 20 }, "comment": "Best by test",
+//!       "variations": [[{ "san": "d4", "...": "..." }]]
+//!     }
+//!   ]
+//! }
+//! ```
+
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::{
+        extract_annotations, extract_node_data, format_clock_time, parse_clock_time, pgn::PgnLine,
+        take_command, Eval, Move,
+    },
+    errors::InteropError,
+    logic::Game,
+};
+
+/// A game in the JSON interchange schema
+///
+/// # Fields
+/// * `tags`: The PGN tags of the game
+/// * `start_fen`: The FEN of the starting position
+/// * `moves`: The main line
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonGame {
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+    pub start_fen: String,
+    #[serde(default)]
+    pub moves: Vec<JsonMove>,
+}
+
+/// A move in the JSON interchange schema
+/// Only the SAN is read back, the other derived fields are ignored on input
+///
+/// # Fields
+/// * `san`: The move in standard algebraic notation
+/// * `uci`: The move in UCI notation (`e2e4`, `e7e8q`)
+/// * `from`: The starting square
+/// * `to`: The ending square
+/// * `fen`: The FEN of the position after the move
+/// * `clock`: The remaining time after the move in seconds, if known
+/// * `eval`: The evaluation of the position after the move, if known
+/// * `comment`: The comment of the move, without the clock and evaluation
+/// * `data`: The custom data stored on the move
+/// * `variations`: The lines that can be played instead of this move
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonMove {
+    pub san: String,
+    #[serde(default)]
+    pub uci: String,
+    #[serde(default)]
+    pub from: String,
+    #[serde(default)]
+    pub to: String,
+    #[serde(default)]
+    pub fen: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eval: Option<Eval>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub data: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variations: Vec<Vec<JsonMove>>,
+}
+
+impl Game {
+    /// Converts the game to the JSON interchange schema (see `interop::json`)
+    ///
+    /// # Returns
+    /// The game as a JSON string, or an error if a move of the history can't
+    /// be played
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// game.history.set_comment(Some("[%clk 0:03:00] [%eval 0.2] Best".to_string()));
+    ///
+    /// let json = game.to_json().unwrap();
+    /// assert!(json.contains(r#""san":"e4","uci":"e2e4","from":"e2","to":"e4""#));
+    /// assert!(json.contains(r#""clock":180,"eval":{"cp":20},"comment":"Best""#));
+    ///
+    /// let restored = Game::from_json(&json).unwrap();
+    /// assert_eq!(restored.to_json().unwrap(), json);
+    /// ```
+    ///
+    pub fn to_json(&self) -> Result<String, InteropError> {
+        Ok(serde_json::to_string(&self.to_json_game()?).expect("The schema is serializable"))
+    }
+
+    /// Converts the game to the structs of the JSON interchange schema
+    ///
+    /// # Returns
+    /// The game in the JSON interchange schema, or an error if a move of the
+    /// history can't be played
+    ///
+    pub fn to_json_game(&self) -> Result<JsonGame, InteropError> {
+        let history = &self.history;
+        let tags = history
+            .tag_fields()
+            .into_iter()
+            .filter_map(|(tag, value)| Some((tag.to_string(), value?)))
+            .collect();
+
+        let mut replay = self.replay();
+        Ok(JsonGame {
+            tags,
+            start_fen: self.start_position.clone(),
+            moves: json_line(history.root_nodes(), &mut replay)?,
+        })
+    }
+
+    /// Parses a game in the JSON interchange schema (see `interop::json`)
+    ///
+    /// # Arguments
+    /// * `json`: The game as a JSON string
+    ///
+    /// # Returns
+    /// The game, or an error if the JSON doesn't follow the schema or
+    /// contains an illegal move
+    ///
+    pub fn from_json(json: &str) -> Result<Game, InteropError> {
+        let json_game: JsonGame =
+            serde_json::from_str(json).map_err(|_| InteropError::InvalidJson)?;
+        Game::from_json_game(&json_game)
+    }
+
+    /// Builds a game from the structs of the JSON interchange schema
+    ///
+    /// # Arguments
+    /// * `json_game`: The game in the JSON interchange schema
+    ///
+    /// # Returns
+    /// The game, or an error if the starting FEN is invalid or a move is illegal
+    ///
+    pub fn from_json_game(json_game: &JsonGame) -> Result<Game, InteropError> {
+        let mut game =
+            Game::try_from_fen(&json_game.start_fen).map_err(|_| InteropError::InvalidJson)?;
+
+        let tag = |name: &str| json_game.tags.get(name).cloned();
+        game.history.event = tag("Event");
+        game.history.site = tag("Site");
+        game.history.date = tag("Date");
+        game.history.round = tag("Round");
+        game.history.white = tag("White");
+        game.history.black = tag("Black");
+        game.history.result = tag("Result");
+        game.history.white_elo = tag("WhiteElo").and_then(|elo| elo.parse().ok());
+        game.history.black_elo = tag("BlackElo").and_then(|elo| elo.parse().ok());
+        game.history.time_control = tag("TimeControl");
+        game.history.variant = tag("Variant");
+        game.history.termination = tag("Termination");
+        game.history.white_clock = tag("WhiteClock");
+        game.history.black_clock = tag("BlackClock");
+        game.history.handicap = tag("Handicap");
+        game.history.event_date = tag("EventDate");
+        game.history.utc_date = tag("UTCDate");
+        game.history.utc_time = tag("UTCTime");
+
+        play_line(&mut game, &json_game.moves)?;
+        Ok(game)
+    }
+}
+
+/// Converts a line of the move tree, and its variations, to the schema
+///
+/// # Arguments
+/// * `nodes`: The first node of the line followed by its alternatives
+/// * `replay`: A game at the position before the line, with the settings of
+///   the exported one
+///
+/// # Returns
+/// The moves of the line, or an error if a move of the tree can't be played
+///
+fn json_line(
+    nodes: &[Rc<RefCell<PgnLine<Move>>>],
+    replay: &mut Game,
+) -> Result<Vec<JsonMove>, InteropError> {
+    let mut moves = Vec::new();
+    let mut nodes = nodes.to_vec();
+
+    while let Some((node, alternatives)) = nodes.split_first() {
+        let variations = alternatives
+            .iter()
+            .map(|alternative| json_line(std::slice::from_ref(alternative), &mut replay.clone()))
+            .collect::<Result<_, _>>()?;
+        let line = node.borrow();
+        replay
+            .move_piece(&line.san())
+            .map_err(|_| InteropError::InvalidMove)?;
+
+        let (clock, eval, comment) = match line.comment_text() {
+            Some(comment) => split_comment(&extract_node_data(&comment).0),
+            None => (None, None, None),
+        };
+
+        moves.push(JsonMove {
+            san: line.san(),
+            uci: line.uci(),
+            from: line.mov.from.to_string(),
+            to: line.mov.to.to_string(),
+            fen: replay.fen(),
+            clock,
+            eval,
+            comment,
+            data: line.node_data.clone().into_iter().collect(),
+            variations,
+        });
+        let next = line.lines.clone();
+        drop(line);
+        nodes = next;
+    }
+    Ok(moves)
+}
+
+/// Plays a line of the schema, and its variations, on a game
+///
+/// # Arguments
+/// * `game`: The game at the position before the line
+/// * `moves`: The moves of the line
+///
+/// # Returns
+/// An error if a move is illegal
+///
+fn play_line(game: &mut Game, moves: &[JsonMove]) -> Result<(), InteropError> {
+    for mov in moves {
+        game.move_piece(&mov.san)
+            .map_err(|_| InteropError::InvalidMove)?;
+
+        let mut comment = Vec::new();
+        if let Some(eval) = mov.eval {
+            comment.push(format!("[%eval {}]", eval));
+        }
+        if let Some(clock) = mov.clock {
+            let clock = Duration::from_secs(clock as u64);
+            comment.push(format!("[%clk {}]", format_clock_time(clock)));
+        }
+        let (text, arrows, highlights) = extract_annotations(mov.comment.as_deref().unwrap_or(""));
+        if !text.is_empty() {
+            comment.push(text);
+        }
+        if !comment.is_empty() {
+            game.history.set_comment(Some(comment.join(" ")));
+        }
+        for arrow in arrows {
+            game.history.add_arrow(arrow.from, arrow.to, arrow.color);
+        }
+        for highlight in highlights {
+            game.history
+                .add_highlight(highlight.square, highlight.color);
+        }
+        for (key, value) in &mov.data {
+            game.history.set_node_data(key, Some(value.clone()));
+        }
+
+        for variation in &mov.variations {
+            let snapshot = game.snapshot();
+            game.undo();
+            play_line(game, variation)?;
+            game.restore(&snapshot);
+        }
+    }
+    Ok(())
+}
+
+/// Splits the clock time and the evaluation out of a comment
+///
+/// # Arguments
+/// * `comment`: The comment
+///
+/// # Returns
+/// The clock time in seconds, the evaluation and the rest of the comment
+///
+fn split_comment(comment: &str) -> (Option<u32>, Option<Eval>, Option<String>) {
+    let mut rest = comment.to_string();
+
+    let clock = take_command(&mut rest, "clk")
+        .and_then(|clock| parse_clock_time(&clock))
+        .map(|clock| clock.as_secs() as u32);
+    let eval = take_command(&mut rest, "eval").and_then(|eval| Eval::parse(&eval));
+
+    let rest = rest.split_whitespace().collect::<Vec<&str>>().join(" ");
+    (clock, eval, Some(rest).filter(|rest| !rest.is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_comment;
+    use crate::{
+        constants::Eval,
+        errors::InteropError,
+        logic::{Game, STARTING_FEN},
+        parsing::{parse_pgn_with_options, PgnParseOptions},
+    };
+
+    #[test]
+    fn test_json_round_trip() {
+        let game = parse_pgn_with_options(
+            "[White \"A\"]\n[FEN \"4k3/P7/8/8/8/8/8/4K2R w K - 0 1\"]\n\n1. Kf1 { [%csl Ga8] Queen } (1. O-O { [%eval #-2] } Kd7 (1... Ke7)) Kd7 2. a8=Q { [%data seen true] } *",
+            &PgnParseOptions::lenient(),
+        )
+        .unwrap();
+
+        let json_game = game.to_json_game().unwrap();
+        assert_eq!(json_game.tags.get("White"), Some(&"A".to_string()));
+        assert_eq!(json_game.moves.len(), 3);
+        assert_eq!(json_game.moves[2].uci, "a7a8q");
+        assert_eq!(json_game.moves[2].comment, None);
+        assert_eq!(
+            json_game.moves[2].data.get("seen"),
+            Some(&"true".to_string())
+        );
+        assert_eq!(json_game.moves[0].variations[0][0].uci, "e1g1");
+        assert_eq!(
+            json_game.moves[0].variations[0][0].eval,
+            Some(Eval::Mate(-2))
+        );
+        assert_eq!(json_game.moves[0].variations[0][1].variations.len(), 1);
+
+        let restored = Game::from_json(&game.to_json().unwrap()).unwrap();
+        assert_eq!(restored.pgn(), game.pgn());
+        assert_eq!(restored.fen(), game.fen());
+
+        // The variations are replayed with the settings of the game, here
+        // leaving the king in check
+        let mut game = Game::new(STARTING_FEN, true);
+        for mov in ["e4", "e5"] {
+            game.move_piece(mov).unwrap();
+        }
+        game.undo();
+        for mov in ["f6", "Qh5", "Nc6"] {
+            game.move_piece(mov).unwrap();
+        }
+        let json_game = game.to_json_game().unwrap();
+        let variation = &json_game.moves[1].variations[0];
+        assert_eq!(variation[2].san, "Nc6");
+        assert_eq!(
+            variation[2].fen,
+            "r1bqkbnr/ppppp1pp/2n2p2/7Q/4P3/8/PPPP1PPP/RNB1KBNR w KQkq - 2 3"
+        );
+    }
+
+    #[test]
+    fn test_json_errors() {
+        assert_eq!(Game::from_json("{}").err(), Some(InteropError::InvalidJson));
+        assert_eq!(
+            Game::from_json(r#"{"startFen": "8/8 w"}"#).err(),
+            Some(InteropError::InvalidJson)
+        );
+        assert_eq!(
+            Game::from_json(
+                r#"{"startFen": "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "moves": [{"san": "e5"}]}"#
+            )
+            .err(),
+            Some(InteropError::InvalidMove)
+        );
+    }
+
+    #[test]
+    fn test_split_comment() {
+        assert_eq!(
+            split_comment("[%clk 1:02:03.4] text [%eval -1.25]"),
+            (Some(3723), Some(Eval::Cp(-125)), Some("text".to_string()))
+        );
+        assert_eq!(split_comment("[%clk x]"), (None, None, None));
+    }
+}
@@ -0,0 +1,351 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    constants::{format_clock_time, Color, Eval},
+    errors::InteropError,
+    logic::Game,
+};
+
+/// A game as exported by the Lichess API (`application/x-ndjson`)
+/// Only the fields needed to rebuild the game are read
+///
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LichessGame {
+    id: Option<String>,
+    rated: Option<bool>,
+    variant: Option<String>,
+    speed: Option<String>,
+    status: Option<String>,
+    players: Option<LichessPlayers>,
+    winner: Option<String>,
+    moves: Option<String>,
+    clocks: Option<Vec<u32>>,
+    analysis: Option<Vec<LichessAnalysis>>,
+    clock: Option<LichessClock>,
+    initial_fen: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LichessPlayers {
+    white: Option<LichessPlayer>,
+    black: Option<LichessPlayer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LichessPlayer {
+    user: Option<LichessUser>,
+    rating: Option<u32>,
+    ai_level: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LichessUser {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LichessAnalysis {
+    eval: Option<i32>,
+    mate: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LichessClock {
+    initial: u32,
+    increment: u32,
+}
+
+impl LichessPlayer {
+    /// Returns the display name of the player as Lichess shows it
+    ///
+    /// # Returns
+    /// The user name, `lichess AI level N` for the AI or `Anonymous`
+    ///
+    fn name(&self) -> String {
+        if let Some(user) = &self.user {
+            user.name.clone()
+        } else if let Some(level) = self.ai_level {
+            format!("lichess AI level {}", level)
+        } else {
+            String::from("Anonymous")
+        }
+    }
+}
+
+/// Serializes a game into the JSON body accepted by the Lichess game import
+/// endpoint (`POST /api/import`)
+///
+/// # Arguments
+/// * `game`: The game to serialize
+///
+/// # Returns
+/// The JSON body, containing the PGN of the game
+///
+/// # Example
+/// ```
+/// use chess_lab::interop::lichess::to_import_json;
+/// use chess_lab::logic::Game;
+///
+/// let mut game = Game::default();
+/// game.move_piece("e4").unwrap();
+/// game.move_piece("e5").unwrap();
+///
+/// assert_eq!(to_import_json(&game), r#"{"pgn":"1. e4 e5 *"}"#);
+/// ```
+///
+pub fn to_import_json(game: &Game) -> String {
+    json!({ "pgn": game.pgn() }).to_string()
+}
+
+/// Parses a game exported by the Lichess API in JSON format
+/// Clock times and engine evaluations are stored as `[%clk]` and `[%eval]`
+/// comments on each move, the same way Lichess does in its PGN exports
+///
+/// # Arguments
+/// * `json`: A JSON object representing a Lichess game
+///
+/// # Returns
+/// The game, or an error if the JSON is invalid or contains an illegal move
+///
+/// # Example
+/// ```
+/// use chess_lab::interop::lichess::from_json;
+///
+/// let game = from_json(r#"{
+///     "id": "q7ZvsdUF",
+///     "players": {
+///         "white": { "user": { "name": "Alice" }, "rating": 1500 },
+///         "black": { "user": { "name": "Bob" }, "rating": 1600 }
+///     },
+///     "moves": "e4 e5 Qh5 Nc6 Bc4 Nf6 Qxf7#",
+///     "status": "mate",
+///     "winner": "white"
+/// }"#).unwrap();
+///
+/// assert_eq!(game.history.white, Some("Alice".to_string()));
+/// assert_eq!(game.history.black_elo, Some(1600));
+/// assert_eq!(game.history.result, Some("1-0".to_string()));
+/// assert!(game.checkmate());
+/// ```
+///
+pub fn from_json(json: &str) -> Result<Game, InteropError> {
+    let lichess_game: LichessGame =
+        serde_json::from_str(json).map_err(|_| InteropError::InvalidJson)?;
+
+    let mut game = match &lichess_game.initial_fen {
+        Some(fen) => Game::try_from_fen(fen).map_err(|_| InteropError::InvalidJson)?,
+        None => Game::default(),
+    };
+
+    if let Some(moves) = &lichess_game.moves {
+        for (ply, mov) in moves.split_whitespace().enumerate() {
+            game.move_piece(mov)
+                .map_err(|_| InteropError::InvalidMove)?;
+
+            let mut comment = Vec::new();
+            if let Some(analysis) = lichess_game.analysis.as_ref().and_then(|a| a.get(ply)) {
+                let eval = analysis
+                    .mate
+                    .map(Eval::Mate)
+                    .or(analysis.eval.map(Eval::Cp));
+                if let Some(eval) = eval {
+                    comment.push(format!("[%eval {}]", eval));
+                }
+            }
+            if let Some(clock) = lichess_game.clocks.as_ref().and_then(|c| c.get(ply)) {
+                let clock = Duration::from_millis(*clock as u64 * 10);
+                comment.push(format!("[%clk {}]", format_clock_time(clock)));
+            }
+            if !comment.is_empty() {
+                game.history.set_comment(Some(comment.join(" ")));
+            }
+        }
+    }
+
+    if let Some(id) = &lichess_game.id {
+        game.history.site = Some(format!("https://lichess.org/{}", id));
+    }
+    if let Some(speed) = &lichess_game.speed {
+        let mut speed = speed.clone();
+        if let Some(first) = speed.get_mut(0..1) {
+            first.make_ascii_uppercase();
+        }
+        game.history.event = Some(match lichess_game.rated {
+            Some(true) => format!("Rated {} game", speed),
+            _ => format!("Casual {} game", speed),
+        });
+    }
+    if let Some(players) = &lichess_game.players {
+        if let Some(white) = &players.white {
+            game.history.white = Some(white.name());
+            game.history.white_elo = white.rating;
+        }
+        if let Some(black) = &players.black {
+            game.history.black = Some(black.name());
+            game.history.black_elo = black.rating;
+        }
+    }
+    if let Some(variant) = &lichess_game.variant {
+        if variant != "standard" {
+            game.history.variant = Some(variant.clone());
+        }
+    }
+    if let Some(clock) = &lichess_game.clock {
+        game.history.time_control = Some(format!("{}+{}", clock.initial, clock.increment));
+    }
+
+    let winner = match lichess_game.winner.as_deref() {
+        Some("white") => Some(Color::White),
+        Some("black") => Some(Color::Black),
+        _ => None,
+    };
+    match lichess_game.status.as_deref() {
+        Some("resign") => {
+            if let Some(winner) = winner {
+                game.resign(winner.opposite());
+            }
+        }
+        Some("outoftime") | Some("timeout") => {
+            if let Some(winner) = winner {
+                game.set_lost_in_time(winner.opposite());
+            }
+        }
+        Some("draw") if winner.is_none() => game.set_draw_by_agreement(),
+        _ => {}
+    }
+    game.history.termination = match lichess_game.status.as_deref() {
+        Some("outoftime") => Some(String::from("Time forfeit")),
+        Some("created") | Some("started") | None => None,
+        Some(_) => Some(String::from("Normal")),
+    };
+    // Aborted, unfinished and unknown games have no result, not a draw
+    game.history.result = Some(String::from(
+        match (lichess_game.status.as_deref(), winner) {
+            (_, Some(Color::White)) => "1-0",
+            (_, Some(Color::Black)) => "0-1",
+            (
+                Some("draw") | Some("stalemate") | Some("outoftime") | Some("timeout")
+                | Some("variantEnd"),
+                None,
+            ) => "1/2-1/2",
+            (_, None) => "*",
+        },
+    ));
+
+    Ok(game)
+}
+
+/// Parses the NDJSON stream returned by the Lichess game export endpoints,
+/// one game per line
+///
+/// # Arguments
+/// * `ndjson`: The NDJSON text, empty lines are ignored
+///
+/// # Returns
+/// The games in the same order as the input, or the first error found
+///
+/// # Example
+/// ```
+/// use chess_lab::interop::lichess::from_ndjson;
+///
+/// let games = from_ndjson(
+///     "{\"moves\": \"e4 e5\", \"clocks\": [18003, 18003]}\n{\"moves\": \"d4\"}\n",
+/// ).unwrap();
+///
+/// assert_eq!(games.len(), 2);
+/// assert_eq!(games[0].pgn(), "[Result \"*\"]\n1. e4 { [%clk 0:03:00] } e5 { [%clk 0:03:00] } *");
+/// ```
+///
+pub fn from_ndjson(ndjson: &str) -> Result<Vec<Game>, InteropError> {
+    ndjson
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(from_json)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_json, from_ndjson, to_import_json};
+    use crate::{
+        constants::{GameStatus, WinReason},
+        errors::InteropError,
+    };
+
+    #[test]
+    fn test_from_json() {
+        let game = from_json(
+            r#"{
+                "id": "abcdefgh",
+                "rated": true,
+                "variant": "standard",
+                "speed": "blitz",
+                "status": "resign",
+                "winner": "black",
+                "players": {
+                    "white": { "aiLevel": 3 },
+                    "black": { "user": { "name": "Bob" }, "rating": 1712 }
+                },
+                "moves": "e4 e5 Nf3",
+                "clocks": [18003, 18003, 17503],
+                "analysis": [{ "eval": 18 }, { "eval": -25 }, { "mate": -4 }],
+                "clock": { "initial": 180, "increment": 2, "totalTime": 260 }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            game.pgn(),
+            "[Event \"Rated Blitz game\"]\n[Site \"https://lichess.org/abcdefgh\"]\n[White \"lichess AI level 3\"]\n[Black \"Bob\"]\n[Result \"0-1\"]\n[BlackElo \"1712\"]\n[TimeControl \"180+2\"]\n[Termination \"Normal\"]\n1. e4 { [%eval 0.18] [%clk 0:03:00] } e5 { [%eval -0.25] [%clk 0:03:00] } 2. Nf3 { [%eval #-4] [%clk 0:02:55] } 0-1"
+        );
+        assert_eq!(
+            game.game_status,
+            GameStatus::BlackWins(WinReason::Resignation)
+        );
+    }
+
+    #[test]
+    fn test_from_json_results() {
+        for (status, result) in [
+            ("stalemate", "1/2-1/2"),
+            ("outoftime", "1/2-1/2"),
+            ("aborted", "*"),
+            ("noStart", "*"),
+            ("unknownFinish", "*"),
+            ("mate", "*"),
+            ("started", "*"),
+        ] {
+            let json = format!(r#"{{"moves": "e4", "status": "{}"}}"#, status);
+            assert_eq!(
+                from_json(&json).unwrap().history.result,
+                Some(String::from(result))
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_ndjson_errors() {
+        assert_eq!(
+            from_ndjson("{\"moves\": \"e5\"}").err(),
+            Some(InteropError::InvalidMove)
+        );
+        assert_eq!(
+            from_ndjson("not json").err(),
+            Some(InteropError::InvalidJson)
+        );
+    }
+
+    #[test]
+    fn test_to_import_json() {
+        let game = from_json(r#"{ "moves": "d4 d5", "clocks": [6000, 6000] }"#).unwrap();
+        assert_eq!(
+            to_import_json(&game),
+            r#"{"pgn":"[Result \"*\"]\n1. d4 { [%clk 0:01:00] } d5 { [%clk 0:01:00] } *"}"#
+        );
+    }
+}
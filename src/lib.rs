@@ -1,5 +1,19 @@
+pub mod analysis;
 mod common;
+pub mod database;
+pub mod export;
+#[cfg(feature = "serde")]
+pub mod interop;
 pub mod logic;
+pub mod parsing;
+pub mod prelude;
+pub mod puzzles;
+pub mod rating;
+pub mod session;
+pub mod tournament;
+pub mod training;
+pub mod utils;
+pub mod variants;
 
 pub use common::constants;
 pub use common::errors;
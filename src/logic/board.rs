@@ -1,3 +1,5 @@
+use std::fmt::{Display, Error, Formatter};
+
 use regex::Regex;
 
 use crate::{
@@ -6,6 +8,7 @@ use crate::{
         Color, PieceType, Position,
     },
     errors::BoardError,
+    utils::rays::between,
 };
 
 use super::pieces::{piece_movement, Piece};
@@ -109,7 +112,7 @@ impl Board {
 
             let mut col = 0;
             for c in rank.chars() {
-                if c.is_digit(10) {
+                if c.is_ascii_digit() {
                     col += c.to_digit(10).unwrap() as u8;
                     assert!(col <= 8, "Invalid FEN");
                     continue;
@@ -134,21 +137,7 @@ impl Board {
     /// Whether the position is occupied by a piece
     ///
     pub fn is_ocupied(&self, pos: &Position) -> bool {
-        let bit = pos.to_bitboard();
-        (self.wpawns
-            | self.bpawns
-            | self.wknights
-            | self.bknights
-            | self.wbishops
-            | self.bbishops
-            | self.wrooks
-            | self.brooks
-            | self.wqueens
-            | self.bqueens
-            | self.wkings
-            | self.bkings)
-            & bit
-            != 0
+        self.all_occupancy() & pos.to_bitboard() != 0
     }
 
     /// Gets the piece at a position
@@ -252,7 +241,7 @@ impl Board {
     /// The piece that was deleted or Err if the position is empty
     ///
     pub fn delete_piece(&mut self, pos: &Position) -> Result<Piece, BoardError> {
-        let piece = self.get_piece(&pos);
+        let piece = self.get_piece(pos);
         if piece.is_none() {
             return Err(BoardError::Empty);
         }
@@ -297,34 +286,142 @@ impl Board {
     /// A vector of positions of the pieces
     ///
     pub fn find(&self, piece_type: PieceType, color: Color) -> Vec<Position> {
-        let bitboard;
+        Position::from_bitboard(self.bitboard(piece_type, color))
+    }
+
+    /// Gets the bitboard of the pieces of a certain type and color
+    /// Bit `row * 8 + col` is set if there is a piece at that position
+    ///
+    /// # Arguments
+    /// * `piece_type`: The type of the pieces
+    /// * `color`: The color of the pieces
+    ///
+    /// # Returns
+    /// The bitboard of the pieces
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::{Color, PieceType};
+    /// use chess_lab::logic::Board;
+    ///
+    /// let board = Board::default();
+    ///
+    /// assert_eq!(board.bitboard(PieceType::Pawn, Color::White), 0x000000000000FF00);
+    /// assert_eq!(board.bitboard(PieceType::King, Color::Black), 0x1000000000000000);
+    /// ```
+    ///
+    pub fn bitboard(&self, piece_type: PieceType, color: Color) -> u64 {
         match piece_type {
             PieceType::Pawn => match color {
-                Color::White => bitboard = self.wpawns,
-                Color::Black => bitboard = self.bpawns,
+                Color::White => self.wpawns,
+                Color::Black => self.bpawns,
             },
             PieceType::Knight => match color {
-                Color::White => bitboard = self.wknights,
-                Color::Black => bitboard = self.bknights,
+                Color::White => self.wknights,
+                Color::Black => self.bknights,
             },
             PieceType::Bishop => match color {
-                Color::White => bitboard = self.wbishops,
-                Color::Black => bitboard = self.bbishops,
+                Color::White => self.wbishops,
+                Color::Black => self.bbishops,
             },
             PieceType::Rook => match color {
-                Color::White => bitboard = self.wrooks,
-                Color::Black => bitboard = self.brooks,
+                Color::White => self.wrooks,
+                Color::Black => self.brooks,
             },
             PieceType::Queen => match color {
-                Color::White => bitboard = self.wqueens,
-                Color::Black => bitboard = self.bqueens,
+                Color::White => self.wqueens,
+                Color::Black => self.bqueens,
             },
             PieceType::King => match color {
-                Color::White => bitboard = self.wkings,
-                Color::Black => bitboard = self.bkings,
+                Color::White => self.wkings,
+                Color::Black => self.bkings,
             },
         }
-        Position::from_bitboard(bitboard)
+    }
+
+    /// Gets the bitboard of all the pieces of a color
+    ///
+    /// # Arguments
+    /// * `color`: The color of the pieces
+    ///
+    /// # Returns
+    /// The bitboard of the pieces
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::Color;
+    /// use chess_lab::logic::Board;
+    ///
+    /// let board = Board::default();
+    ///
+    /// assert_eq!(board.occupancy(Color::White), 0x000000000000FFFF);
+    /// assert_eq!(board.occupancy(Color::Black), 0xFFFF000000000000);
+    /// ```
+    ///
+    pub fn occupancy(&self, color: Color) -> u64 {
+        match color {
+            Color::White => {
+                self.wpawns
+                    | self.wknights
+                    | self.wbishops
+                    | self.wrooks
+                    | self.wqueens
+                    | self.wkings
+            }
+            Color::Black => {
+                self.bpawns
+                    | self.bknights
+                    | self.bbishops
+                    | self.brooks
+                    | self.bqueens
+                    | self.bkings
+            }
+        }
+    }
+
+    /// Gets the bitboard of all the pieces on the board
+    ///
+    /// # Returns
+    /// The bitboard of the pieces
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::logic::Board;
+    ///
+    /// let board = Board::default();
+    ///
+    /// assert_eq!(board.all_occupancy(), 0xFFFF00000000FFFF);
+    /// ```
+    ///
+    pub fn all_occupancy(&self) -> u64 {
+        self.occupancy(Color::White) | self.occupancy(Color::Black)
+    }
+
+    /// Iterates over the pieces on the board in rank-major order, from a1 to
+    /// h1 and then up to a8 to h8
+    ///
+    /// # Returns
+    /// An iterator over the positions and the pieces
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::{Color, PieceType};
+    /// use chess_lab::logic::Board;
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3");
+    /// let pieces: Vec<String> = board
+    ///     .pieces()
+    ///     .map(|(pos, piece)| format!("{}{}", piece, pos))
+    ///     .collect();
+    ///
+    /// assert_eq!(pieces, vec!["Ke1", "Pe2", "ke8"]);
+    /// ```
+    ///
+    pub fn pieces(&self) -> Pieces<'_> {
+        Pieces {
+            board: self,
+            remaining: self.all_occupancy(),
+        }
     }
 
     /// Finds all pieces of a certain color
@@ -346,24 +443,59 @@ impl Board {
         pieces
     }
 
-    /// Moves a piece from one position to another
+    /// Moves a piece from one position to another, capturing the piece on the
+    /// destination if there is one
     ///
     /// # Arguments
     /// * `from`: The position to move the piece from
     /// * `to`: The position to move the piece to
     ///
     /// # Returns
-    /// Ok if the move was successful, Err if the from position is empty
+    /// The captured piece, if any, or Err if the from position is empty or the
+    /// destination holds a piece of the same color. The board is left untouched
+    /// on error
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::{Color, PieceType, Position};
+    /// use chess_lab::errors::BoardError;
+    /// use chess_lab::logic::{Board, Piece};
     ///
-    pub fn move_piece(&mut self, from: &Position, to: &Position) -> Result<(), BoardError> {
-        let piece = self.delete_piece(from)?;
+    /// let mut board = Board::from_fen("4k3/8/8/3p4/4P3/8/8/4K3");
+    /// let e4 = Position::from_string("e4");
+    /// let d5 = Position::from_string("d5");
+    ///
+    /// assert_eq!(
+    ///     board.move_piece(&Position::from_string("e1"), &Position::from_string("e2")),
+    ///     Ok(None)
+    /// );
+    /// assert_eq!(
+    ///     board.move_piece(&Position::from_string("e2"), &e4),
+    ///     Err(BoardError::SameColor)
+    /// );
+    /// assert_eq!(
+    ///     board.move_piece(&e4, &d5),
+    ///     Ok(Some(Piece::new(Color::Black, PieceType::Pawn)))
+    /// );
+    /// ```
+    ///
+    pub fn move_piece(
+        &mut self,
+        from: &Position,
+        to: &Position,
+    ) -> Result<Option<Piece>, BoardError> {
+        let piece = self.get_piece(from).ok_or(BoardError::Empty)?;
+        let captured = self.get_piece(to);
+        if captured.is_some_and(|captured| captured.color == piece.color) {
+            return Err(BoardError::SameColor);
+        }
 
-        if self.is_ocupied(to) {
+        self.delete_piece(from).unwrap();
+        if captured.is_some() {
             self.delete_piece(to).unwrap();
         }
-
         self.set_piece(piece, to).unwrap();
-        Ok(())
+        Ok(captured)
     }
 
     /// Checks if a position is attacked by a certain color
@@ -376,13 +508,37 @@ impl Board {
     /// Whether the position is attacked or not
     ///
     pub fn is_attacked(&self, pos: Position, color: Color) -> bool {
-        let pieces = self.find_all(color);
-        for piece in pieces {
-            if self.can_capture(&piece, &pos) {
-                return true;
-            }
-        }
-        false
+        self.attacker(pos, color).is_some()
+    }
+
+    /// Finds a piece of a certain color that attacks a position
+    ///
+    /// # Arguments
+    /// * `pos`: The position to check
+    /// * `color`: The color of the attacking pieces
+    ///
+    /// # Returns
+    /// The position of the first attacking piece, in the order of `find_all`,
+    /// or `None` if the position is not attacked
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::{Color, Position};
+    /// use chess_lab::logic::Board;
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/8/r3K3");
+    ///
+    /// assert_eq!(
+    ///     board.attacker(Position::from_string("e1"), Color::Black),
+    ///     Some(Position::from_string("a1"))
+    /// );
+    /// assert_eq!(board.attacker(Position::from_string("e2"), Color::Black), None);
+    /// ```
+    ///
+    pub fn attacker(&self, pos: Position, color: Color) -> Option<Position> {
+        self.find_all(color)
+            .into_iter()
+            .find(|piece| self.can_capture(piece, &pos))
     }
 
     pub fn can_capture(&self, start_pos: &Position, end_pos: &Position) -> bool {
@@ -400,7 +556,7 @@ impl Board {
                 PieceType::Pawn => diagonal_movement(start_pos, end_pos),
                 PieceType::Knight | PieceType::King => true,
                 PieceType::Bishop | PieceType::Rook | PieceType::Queen => {
-                    !self.piece_between(start_pos, end_pos)
+                    self.piece_between(start_pos, end_pos) == Ok(false)
                 }
             };
         }
@@ -414,46 +570,304 @@ impl Board {
     /// * `to`: The ending position
     ///
     /// # Returns
-    /// Whether there is a piece between the two positions
+    /// Whether there is a piece between the two positions, or Err if they are
+    /// not on the same rank, file or diagonal
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::Position;
+    /// use chess_lab::errors::BoardError;
+    /// use chess_lab::logic::Board;
+    ///
+    /// let board = Board::default();
+    /// let a1 = Position::from_string("a1");
+    ///
+    /// assert_eq!(board.piece_between(&a1, &Position::from_string("a8")), Ok(true));
+    /// assert_eq!(board.piece_between(&a1, &Position::from_string("b2")), Ok(false));
+    /// assert_eq!(
+    ///     board.piece_between(&a1, &Position::from_string("b3")),
+    ///     Err(BoardError::NotAligned)
+    /// );
+    /// ```
+    ///
+    pub fn piece_between(&self, from: &Position, to: &Position) -> Result<bool, BoardError> {
+        if !linear_movement(from, to) && !diagonal_movement(from, to) {
+            return Err(BoardError::NotAligned);
+        }
+        Ok(self.all_occupancy() & between(from, to) != 0)
+    }
+
+    /// Finds the first piece between two positions, going from the first one
+    ///
+    /// # Arguments
+    /// * `from`: The starting position
+    /// * `to`: The ending position
+    ///
+    /// # Returns
+    /// The position of the piece closest to `from`, or `None` if there is no
+    /// piece between them or they are not on the same rank, file or diagonal
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::Position;
+    /// use chess_lab::logic::Board;
+    ///
+    /// let board = Board::default();
+    /// let a8 = Position::from_string("a8");
+    ///
+    /// assert_eq!(
+    ///     board.first_piece_between(&a8, &Position::from_string("a1")),
+    ///     Some(Position::from_string("a7"))
+    /// );
+    /// assert_eq!(
+    ///     board.first_piece_between(&Position::from_string("a6"), &Position::from_string("a3")),
+    ///     None
+    /// );
+    /// ```
+    ///
+    pub fn first_piece_between(&self, from: &Position, to: &Position) -> Option<Position> {
+        let pieces = self.all_occupancy() & between(from, to);
+        if pieces == 0 {
+            return None;
+        }
+        let index = if (to.row, to.col) > (from.row, from.col) {
+            pieces.trailing_zeros()
+        } else {
+            63 - pieces.leading_zeros()
+        } as u8;
+        Some(Position::new(index % 8, index / 8))
+    }
+
+    /// Gets all the captures available to a color, in the order of `find_all`
+    /// Captures that leave the own king attacked are excluded. En passant is
+    /// not included, since the board doesn't know the previous move
+    ///
+    /// # Arguments
+    /// * `color`: The color of the capturing pieces
+    ///
+    /// # Returns
+    /// The captures, as pairs of starting and ending positions
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::{Color, Position};
+    /// use chess_lab::logic::Board;
+    ///
+    /// let board = Board::from_fen("4k3/8/8/3p4/4P3/8/8/4K3");
+    ///
+    /// assert_eq!(
+    ///     board.captures_for(Color::White),
+    ///     vec![(Position::from_string("e4"), Position::from_string("d5"))]
+    /// );
+    /// ```
+    ///
+    pub fn captures_for(&self, color: Color) -> Vec<(Position, Position)> {
+        self.moves_for(color)
+            .into_iter()
+            .filter(|(_, to)| self.is_ocupied(to))
+            .collect()
+    }
+
+    /// Gets all the moves of a color that give check to the opposite king
+    /// Castling and en passant are not included, since the board doesn't know
+    /// the castling rights nor the previous move
+    ///
+    /// # Arguments
+    /// * `color`: The color of the moving pieces
+    ///
+    /// # Returns
+    /// The checking moves, as pairs of starting and ending positions
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::{Color, Position};
+    /// use chess_lab::logic::Board;
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3");
+    ///
+    /// assert_eq!(
+    ///     board.checks_for(Color::White),
+    ///     vec![(Position::from_string("a1"), Position::from_string("a8"))]
+    /// );
+    /// ```
+    ///
+    pub fn checks_for(&self, color: Color) -> Vec<(Position, Position)> {
+        self.moves_for(color)
+            .into_iter()
+            .filter(|(from, to)| {
+                let mut board = self.clone();
+                board.move_piece(from, to).unwrap();
+                board
+                    .find(PieceType::King, color.opposite())
+                    .first()
+                    .is_some_and(|king| board.is_attacked(*king, color))
+            })
+            .collect()
+    }
+
+    /// Gets the MVV-LVA (most valuable victim, least valuable attacker) score
+    /// of a capture, higher scores should be searched first
+    ///
+    /// # Arguments
+    /// * `from`: The position of the capturing piece
+    /// * `to`: The position of the captured piece
+    ///
+    /// # Returns
+    /// The score of the capture, or 0 if it's not a capture
     ///
     /// # Panics
-    /// If the positions are not in a straight line
+    /// Panics if there is no piece at the starting position
     ///
-    pub fn piece_between(&self, from: &Position, to: &Position) -> bool {
-        assert!(
-            linear_movement(from, to) || diagonal_movement(from, to),
-            "The positions are not in a straight line"
-        );
-        let direction = from.direction(to);
-        let mut pos = from.to_owned();
-
-        loop {
-            if pos.col as i8 + direction.0 < 0
-                || pos.col as i8 + direction.0 > 7
-                || pos.row as i8 + direction.1 < 0
-                || pos.row as i8 + direction.1 > 7
-            {
-                panic!("Position out of bounds :(");
-            }
-            pos = &pos + direction;
-            if pos == *to {
-                break;
-            }
-            if self.is_ocupied(&pos) {
-                return true;
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::Position;
+    /// use chess_lab::logic::Board;
+    ///
+    /// let board = Board::from_fen("4k3/8/8/3q4/4P3/8/8/3QK3");
+    /// let pxq = board.mvv_lva(&Position::from_string("e4"), &Position::from_string("d5"));
+    /// let qxq = board.mvv_lva(&Position::from_string("d1"), &Position::from_string("d5"));
+    ///
+    /// assert!(pxq > qxq);
+    /// ```
+    ///
+    pub fn mvv_lva(&self, from: &Position, to: &Position) -> u32 {
+        let attacker = self.get_piece(from).unwrap();
+        match self.get_piece(to) {
+            Some(victim) => victim.piece_type.rank() * 8 + 7 - attacker.piece_type.rank(),
+            None => 0,
+        }
+    }
+
+    /// Sorts moves by their MVV-LVA score, best first
+    /// The sort is stable, so moves with the same score keep their order
+    ///
+    /// # Arguments
+    /// * `moves`: The moves to sort, as pairs of starting and ending positions
+    ///
+    /// # Examples
+    /// ```
+    /// use chess_lab::constants::{Color, Position};
+    /// use chess_lab::logic::Board;
+    ///
+    /// let board = Board::from_fen("4k3/8/8/1n1q4/2P5/8/8/4K3");
+    /// let mut captures = board.captures_for(Color::White);
+    /// board.sort_mvv_lva(&mut captures);
+    ///
+    /// assert_eq!(captures[0].1, Position::from_string("d5"));
+    /// ```
+    ///
+    pub fn sort_mvv_lva(&self, moves: &mut [(Position, Position)]) {
+        moves.sort_by_key(|(from, to)| std::cmp::Reverse(self.mvv_lva(from, to)));
+    }
+
+    /// Gets all the moves of a color that don't leave its king attacked,
+    /// except castling and en passant
+    ///
+    /// # Arguments
+    /// * `color`: The color of the moving pieces
+    ///
+    /// # Returns
+    /// The moves, as pairs of starting and ending positions
+    ///
+    fn moves_for(&self, color: Color) -> Vec<(Position, Position)> {
+        let mut moves = Vec::new();
+        for from in self.find_all(color) {
+            let piece = self.get_piece(&from).unwrap();
+            for to in Position::from_bitboard(u64::MAX) {
+                let reachable = if self.is_ocupied(&to) {
+                    self.can_capture(&from, &to)
+                } else if !piece_movement(&piece, &from, &to) {
+                    false
+                } else {
+                    match piece.piece_type {
+                        PieceType::Pawn => {
+                            from.col == to.col && self.piece_between(&from, &to) == Ok(false)
+                        }
+                        PieceType::Knight | PieceType::King => true,
+                        PieceType::Bishop | PieceType::Rook | PieceType::Queen => {
+                            self.piece_between(&from, &to) == Ok(false)
+                        }
+                    }
+                };
+                if !reachable {
+                    continue;
+                }
+
+                let mut board = self.clone();
+                board.move_piece(&from, &to).unwrap();
+                let king_safe = board
+                    .find(PieceType::King, color)
+                    .first()
+                    .is_none_or(|king| !board.is_attacked(*king, color.opposite()));
+                if king_safe {
+                    moves.push((from, to));
+                }
             }
         }
-        false
+        moves
+    }
+}
+
+/// An iterator over the pieces of a board, created by `Board::pieces`
+///
+pub struct Pieces<'a> {
+    board: &'a Board,
+    remaining: u64,
+}
+
+impl Iterator for Pieces<'_> {
+    type Item = (Position, Piece);
+
+    /// Returns the next piece of the board
+    ///
+    /// # Returns
+    /// The position and the piece, or `None` if there are no more pieces
+    ///
+    fn next(&mut self) -> Option<(Position, Piece)> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let index = self.remaining.trailing_zeros() as u8;
+        self.remaining &= self.remaining - 1;
+
+        let pos = Position::new(index % 8, index / 8);
+        Some((pos, self.board.get_piece(&pos).unwrap()))
+    }
+
+    /// Returns the exact number of pieces left
+    ///
+    /// # Returns
+    /// The lower and upper bounds, both equal to the pieces left
+    ///
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.remaining.count_ones() as usize;
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for Pieces<'_> {}
+
+impl<'a> IntoIterator for &'a Board {
+    type Item = (Position, Piece);
+    type IntoIter = Pieces<'a>;
+
+    /// Iterates over the pieces on the board, the same as `Board::pieces`
+    ///
+    /// # Returns
+    /// An iterator over the positions and the pieces
+    ///
+    fn into_iter(self) -> Pieces<'a> {
+        self.pieces()
     }
 }
 
-impl ToString for Board {
+impl Display for Board {
     /// Converts the board to a string
     ///
     /// # Returns
     /// A string representation of the board in FEN format
     ///
-    fn to_string(&self) -> String {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         let mut board = String::new();
         for row in (0..8).rev() {
             let mut empty = 0;
@@ -478,7 +892,7 @@ impl ToString for Board {
                 board.push('/');
             }
         }
-        board
+        write!(f, "{}", board)
     }
 }
 
@@ -487,6 +901,7 @@ mod tests {
 
     use super::Board;
     use crate::constants::{Color, PieceType, Position};
+    use crate::errors::BoardError;
     use crate::logic::pieces::Piece;
 
     #[test]
@@ -576,11 +991,32 @@ mod tests {
         let mut board = Board::default();
         let from = Position::new(4, 1);
         let to = Position::new(4, 3);
-        board.move_piece(&from, &to).unwrap();
+        assert_eq!(board.move_piece(&from, &to), Ok(None));
         assert_eq!(
             board.to_string(),
             "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR"
         );
+
+        assert_eq!(
+            board.move_piece(&Position::new(3, 0), &Position::new(3, 1)),
+            Err(BoardError::SameColor)
+        );
+        assert_eq!(
+            board.move_piece(&Position::new(4, 4), &Position::new(4, 5)),
+            Err(BoardError::Empty)
+        );
+        assert_eq!(
+            board.move_piece(&Position::new(3, 0), &Position::new(7, 4)),
+            Ok(None)
+        );
+        assert_eq!(
+            board.move_piece(&Position::new(7, 4), &Position::new(5, 6)),
+            Ok(Some(Piece::new(Color::Black, PieceType::Pawn)))
+        );
+        assert_eq!(
+            board.to_string(),
+            "rnbqkbnr/pppppQpp/8/8/4P3/8/PPPP1PPP/RNB1KBNR"
+        );
     }
 
     #[test]
@@ -594,11 +1030,67 @@ mod tests {
         assert!(!board.is_attacked(pos, Color::White));
     }
 
+    #[test]
+    fn test_occupancy() {
+        let board = Board::from_fen("4k3/8/8/3p4/4P3/8/8/4K3");
+        assert_eq!(board.bitboard(PieceType::Pawn, Color::White), 1 << 28);
+        assert_eq!(board.bitboard(PieceType::Pawn, Color::Black), 1 << 35);
+        assert_eq!(board.occupancy(Color::White), 1 << 28 | 1 << 4);
+        assert_eq!(board.occupancy(Color::Black), 1 << 35 | 1 << 60);
+        assert_eq!(board.all_occupancy().count_ones(), 4);
+    }
+
+    #[test]
+    fn test_pieces() {
+        let board = Board::default();
+        assert_eq!(board.pieces().len(), 32);
+
+        let (pos, piece) = board.pieces().next().unwrap();
+        assert_eq!(pos, Position::from_string("a1"));
+        assert_eq!(piece, Piece::new(Color::White, PieceType::Rook));
+
+        let mut count = 0;
+        for (pos, piece) in &board {
+            assert_eq!(board.get_piece(&pos), Some(piece));
+            count += 1;
+        }
+        assert_eq!(count, 32);
+        assert_eq!(Board::empty().pieces().next(), None);
+    }
+
+    #[test]
+    fn test_captures_for() {
+        let board = Board::from_fen("4k3/8/8/1n1q4/2P5/8/3r4/3QK3");
+        let mut captures = board.captures_for(Color::White);
+        assert_eq!(captures.len(), 3);
+
+        board.sort_mvv_lva(&mut captures);
+        let captures: Vec<String> = captures
+            .iter()
+            .map(|(from, to)| format!("{}{}", from, to))
+            .collect();
+        assert_eq!(captures, vec!["c4d5", "d1d2", "c4b5"]);
+
+        let pinned = Board::from_fen("4k3/4r3/8/3p4/4N3/8/8/4K3");
+        assert!(pinned.captures_for(Color::White).is_empty());
+    }
+
+    #[test]
+    fn test_checks_for() {
+        let board = Board::from_fen("4k3/8/3P4/3N4/8/8/8/4K3");
+        let checks: Vec<String> = board
+            .checks_for(Color::White)
+            .iter()
+            .map(|(from, to)| format!("{}{}", from, to))
+            .collect();
+        assert_eq!(checks, vec!["d6d7", "d5f6", "d5c7"]);
+    }
+
     #[test]
     fn test_piece_between() {
         let board = Board::default();
         let from = Position::new(0, 0);
         let to = Position::new(0, 6);
-        assert!(board.piece_between(&from, &to));
+        assert_eq!(board.piece_between(&from, &to), Ok(true));
     }
 }
@@ -0,0 +1,236 @@
+use crate::errors::MoveError;
+
+use super::Game;
+
+/// The node data key the conditional moves of a node are stored under
+const CONDITIONAL_KEY: &str = "conditional";
+
+impl Game {
+    /// Stores a conditional move sequence on the last move, as in
+    /// correspondence chess: "if the opponent plays X, I reply Y, then if
+    /// they play Z, I reply W"
+    /// The sequence is checked by playing it on a fork of the game. Sequences
+    /// are tried in the order they were added, so a later one starting with
+    /// the same moves never overrides an earlier one
+    ///
+    /// # Arguments
+    /// * `moves`: The moves, starting with a move of the opponent and
+    ///   alternating with the replies
+    ///
+    /// # Returns
+    /// Ok if the sequence was stored, or an error if a move can't be played.
+    /// The sequence needs at least a move and a reply, and a move must have
+    /// been played to keep it on
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// game.add_conditional_moves(&["e5", "Nf3", "Nc6", "Bb5"]).unwrap();
+    /// game.add_conditional_moves(&["c5", "Nf3"]).unwrap();
+    ///
+    /// assert_eq!(game.receive_move("e5"), Ok(Some(String::from("Nf3"))));
+    /// assert_eq!(game.conditional_moves(), vec![vec!["Nc6", "Bb5"]]);
+    ///
+    /// assert_eq!(game.receive_move("d6"), Ok(None));
+    /// assert_eq!(game.pgn(), "1. e4 e5 2. Nf3 d6 *");
+    /// ```
+    ///
+    pub fn add_conditional_moves(&mut self, moves: &[&str]) -> Result<(), MoveError> {
+        if moves.len() < 2 {
+            return Err(MoveError::IncompleteSequence {
+                san: moves.join(" "),
+            });
+        }
+        if self.history.current_node().is_none() {
+            return Err(MoveError::NoMovePlayed);
+        }
+
+        let mut line = self.fork();
+        let mut sequence = Vec::new();
+        for mov in moves {
+            if !line.is_ongoing() {
+                return Err(MoveError::Finished {
+                    san: mov.to_string(),
+                });
+            }
+            line.move_piece(mov)?;
+            sequence.push(line.history.current_node().unwrap().borrow().san());
+        }
+
+        let mut sequences = self.conditional_moves();
+        if !sequences.contains(&sequence) {
+            sequences.push(sequence);
+        }
+        self.set_conditional_moves(&sequences);
+        Ok(())
+    }
+
+    /// Returns the conditional move sequences stored on the last move
+    ///
+    /// # Returns
+    /// The sequences, in SAN, starting with a move of the opponent
+    ///
+    pub fn conditional_moves(&self) -> Vec<Vec<String>> {
+        self.history
+            .get_node_data(CONDITIONAL_KEY)
+            .map(|data| {
+                data.split(';')
+                    .map(|sequence| sequence.split(' ').map(String::from).collect())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Removes the conditional move sequences stored on the last move
+    pub fn clear_conditional_moves(&mut self) {
+        self.history.set_node_data(CONDITIONAL_KEY, None);
+    }
+
+    /// Plays a move of the opponent and, if a conditional sequence starts
+    /// with it, plays the reply
+    /// The sequences that follow the reply are kept on it, waiting for the
+    /// next move of the opponent, and every other sequence is dropped
+    ///
+    /// # Arguments
+    /// * `mov`: The move of the opponent
+    ///
+    /// # Returns
+    /// The reply that was played, if any, or an error if the move of the
+    /// opponent can't be played
+    ///
+    pub fn receive_move(&mut self, mov: &str) -> Result<Option<String>, MoveError> {
+        if !self.is_ongoing() {
+            self.move_piece(mov)?;
+            return Ok(None);
+        }
+
+        let sequences = self.conditional_moves();
+        self.clear_conditional_moves();
+        if let Err(error) = self.move_piece(mov) {
+            self.set_conditional_moves(&sequences);
+            return Err(error);
+        }
+
+        let played = self.history.current_node().unwrap().borrow().san();
+        let Some(reply) = sequences
+            .iter()
+            .find(|sequence| sequence[0] == played)
+            .map(|sequence| sequence[1].clone())
+        else {
+            return Ok(None);
+        };
+        if !self.is_ongoing() || self.move_piece(&reply).is_err() {
+            return Ok(None);
+        }
+
+        let rest: Vec<Vec<String>> = sequences
+            .iter()
+            .filter(|sequence| sequence.len() >= 4 && sequence[0] == played && sequence[1] == reply)
+            .map(|sequence| sequence[2..].to_vec())
+            .collect();
+        self.set_conditional_moves(&rest);
+        Ok(Some(reply))
+    }
+
+    /// Stores conditional move sequences on the last move, replacing the
+    /// previous ones
+    ///
+    /// # Arguments
+    /// * `sequences`: The sequences, removed from the node if empty
+    ///
+    fn set_conditional_moves(&mut self, sequences: &[Vec<String>]) {
+        let data = (!sequences.is_empty()).then(|| {
+            sequences
+                .iter()
+                .map(|sequence| sequence.join(" "))
+                .collect::<Vec<String>>()
+                .join(";")
+        });
+        self.history.set_node_data(CONDITIONAL_KEY, data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        constants::{DrawReason, GameStatus},
+        errors::MoveError,
+        logic::Game,
+        parsing::{parse_pgn_with_options, PgnParseOptions},
+    };
+
+    #[test]
+    fn test_conditional_moves() {
+        let mut game = Game::default();
+        assert_eq!(
+            game.add_conditional_moves(&["e4", "e5"]),
+            Err(MoveError::NoMovePlayed)
+        );
+
+        game.move_piece("Nf3").unwrap();
+        assert_eq!(
+            game.add_conditional_moves(&["Nf6"]),
+            Err(MoveError::IncompleteSequence {
+                san: String::from("Nf6")
+            })
+        );
+        assert!(game.add_conditional_moves(&["Nf6", "Nf3"]).is_err());
+        assert!(game.conditional_moves().is_empty());
+
+        for sequence in [
+            ["Nf6", "Ng1", "Ng8", "Nf3"],
+            ["Nf6", "Ng1", "Nc6", "Nf3"],
+            ["d5", "d4", "Nf6", "c4"],
+        ] {
+            game.add_conditional_moves(&sequence).unwrap();
+        }
+        game.add_conditional_moves(&["d5", "g3"]).unwrap();
+
+        // An illegal move keeps the sequences
+        assert!(game.receive_move("Ke7").is_err());
+        assert_eq!(game.conditional_moves().len(), 4);
+
+        assert_eq!(game.receive_move("Nf6"), Ok(Some(String::from("Ng1"))));
+        assert_eq!(
+            game.conditional_moves(),
+            vec![vec!["Ng8", "Nf3"], vec!["Nc6", "Nf3"]]
+        );
+        assert_eq!(game.receive_move("Ng8"), Ok(Some(String::from("Nf3"))));
+        assert!(game.conditional_moves().is_empty());
+
+        // The third repetition ends the game before the last reply
+        assert_eq!(
+            game.add_conditional_moves(&["Nf6", "Ng1", "Ng8", "Nf3"]),
+            Err(MoveError::Finished {
+                san: String::from("Nf3")
+            })
+        );
+        game.add_conditional_moves(&["Nf6", "Ng1"]).unwrap();
+        assert_eq!(game.receive_move("Nf6"), Ok(Some(String::from("Ng1"))));
+        assert_eq!(game.receive_move("Ng8"), Ok(None));
+        assert_eq!(
+            game.game_status,
+            GameStatus::Draw(DrawReason::ThreefoldRepetition)
+        );
+    }
+
+    #[test]
+    fn test_conditional_moves_saved() {
+        let mut game = Game::default();
+        game.move_piece("e4").unwrap();
+        game.add_conditional_moves(&["e5", "Nf3"]).unwrap();
+        game.add_conditional_moves(&["c5", "Nf3", "d6", "d4"])
+            .unwrap();
+        assert_eq!(
+            game.pgn(),
+            "1. e4 { [%data conditional e5%20Nf3;c5%20Nf3%20d6%20d4] } *"
+        );
+
+        let mut saved = parse_pgn_with_options(&game.pgn(), &PgnParseOptions::lenient()).unwrap();
+        assert_eq!(saved.conditional_moves(), game.conditional_moves());
+        assert_eq!(saved.receive_move("c5"), Ok(Some(String::from("Nf3"))));
+    }
+}
@@ -1,34 +1,56 @@
-use std::collections::HashMap;
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::{Debug, Display, Error, Formatter},
+    rc::Rc,
+};
 
 use regex::Regex;
 
 use crate::{
     constants::{
-        movements::{diagonal_movement, linear_movement},
-        pgn::PgnTree,
-        CastleType, Color, DrawReason, GameStatus, Move, MoveType, PieceType, Position, WinReason,
+        pgn::{CachedPosition, PgnLine, PgnTree, PlyPath},
+        AnnotationColor, CastleType, Color, DrawReason, FenStyle, File, GameStatus, Move, MoveType,
+        Odds, PieceType, PlyStatus, Position, PositionKey, Rank, StatusChange, StatusTrigger,
+        TakebackEvent, ValidationIssue, WinReason,
     },
-    errors::MoveError,
+    errors::{FenError, IllegalReason, MoveError},
     logic::pieces::{piece_movement, Piece},
+    parsing::{parse_long_algebraic, parse_smith, parse_uci, Notation},
 };
 
-use super::board::Board;
+use super::{board::Board, PromotionPolicy, RuleSet};
+
+/// The largest number of positions `Game::is_dead_position` searches before
+/// giving up
+const DEAD_POSITION_SEARCH_LIMIT: usize = 4096;
+
+/// The FEN of the standard starting position
+pub(crate) const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// The result of parsing a move string: the piece type, the start position
+/// criteria (column and row, if given), the end position and the move type
+///
+pub type ParsedMove = (PieceType, (Option<u8>, Option<u8>), Position, MoveType);
 
 /// Represents a game of chess
 /// It contains the board, the turn, the halfmove clock, the fullmove number,
 /// the en passant square, the castling rights, the start position, the history,
-/// a flag to indicate if the king needs to be captured, the previous positions
-/// and the game status
+/// a flag to indicate if the king needs to be captured, the previous positions,
+/// the game status, the promotion policy, the draw rules and whether the PGN
+/// gets the `SetUp` and `FEN` tags when the game doesn't start from the
+/// initial position
 ///
 /// # Example
 /// ```
 /// use chess_lab::logic::Game;
 ///
 /// let game = Game::default();
-/// assert_eq!(game.to_string(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+/// assert_eq!(game.fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
 /// ```
 ///
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Game {
     capture_king: bool,
     pub board: Board,
@@ -41,6 +63,64 @@ pub struct Game {
     pub history: PgnTree<Move>,
     pub prev_positions: HashMap<String, u32>,
     pub game_status: GameStatus,
+    pub promotion_policy: PromotionPolicy,
+    pub rules: RuleSet,
+    pub setup_tags: bool,
+    takeback_request: Option<(Color, usize)>,
+    position_cache: bool,
+    castling_files: [Option<u8>; 4],
+}
+
+/// A snapshot of the state of a game at a given moment
+/// It contains the board, the turn, the halfmove clock, the fullmove number,
+/// the en passant square, the castling rights, the game status, the previous
+/// positions and the position of the history cursor
+///
+/// Cloning a snapshot is cheap: the previous positions and the history cursor
+/// are shared, not copied
+///
+/// # Example
+/// ```
+/// use chess_lab::logic::Game;
+///
+/// let mut game = Game::default();
+/// let snapshot = game.snapshot();
+///
+/// game.move_piece("e4").unwrap();
+/// game.restore(&snapshot);
+///
+/// assert_eq!(game.fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct GameSnapshot {
+    pub board: Board,
+    pub is_white_turn: bool,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+    pub en_passant: Option<Position>,
+    pub castling_rights: u8,
+    pub game_status: GameStatus,
+    prev_positions: Rc<HashMap<String, u32>>,
+    history_node: Option<Rc<RefCell<PgnLine<Move>>>>,
+}
+
+/// The state of a position before a move played with
+/// `Game::play_unrecorded`
+pub(crate) struct Unrecorded {
+    board: Board,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    en_passant: Option<Position>,
+    castling_rights: u8,
+}
+
+/// A step of `Game::visit_positions`: a node to replay with its index among
+/// its siblings and its depth, or the position to go back to once the lines
+/// of a node are done
+enum ReplayStep {
+    Enter(Rc<RefCell<PgnLine<Move>>>, usize, usize),
+    Leave(GameSnapshot),
 }
 
 impl Default for Game {
@@ -51,11 +131,11 @@ impl Default for Game {
     /// use chess_lab::logic::Game;
     ///
     /// let game = Game::default();
-    /// assert_eq!(game.to_string(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    /// assert_eq!(game.fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
     /// ```
     ///
     fn default() -> Game {
-        let fen = String::from("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let fen = String::from(STARTING_FEN);
         let mut map = HashMap::new();
         map.insert(
             String::from("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -"),
@@ -74,6 +154,12 @@ impl Default for Game {
             capture_king: false,
             prev_positions: map,
             game_status: GameStatus::InProgress,
+            promotion_policy: PromotionPolicy::default(),
+            rules: RuleSet::default(),
+            setup_tags: true,
+            takeback_request: None,
+            position_cache: false,
+            castling_files: [Some(7), Some(0), Some(7), Some(0)],
         }
     }
 }
@@ -96,7 +182,7 @@ impl Game {
     /// use chess_lab::logic::Game;
     ///
     /// let game = Game::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", true);
-    /// assert_eq!(game.to_string(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    /// assert_eq!(game.fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
     /// ```
     ///
     pub fn new(fen: &str, capture_king: bool) -> Game {
@@ -107,6 +193,43 @@ impl Game {
         game
     }
 
+    /// Creates a game at the starting position of this one, to play its moves
+    /// again
+    /// The replay keeps the draw rules, the promotion policy and whether the
+    /// king must be captured, so it ends where this game ends
+    ///
+    /// # Returns
+    /// The game at the starting position, with no moves
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::{Game, RuleSet};
+    ///
+    /// let mut game = Game::default();
+    /// game.rules = RuleSet::standard().with_repetition_limit(None);
+    /// game.move_piece("e4").unwrap();
+    ///
+    /// let replay = game.replay();
+    /// assert_eq!(replay.fen(), Game::default().fen());
+    /// assert_eq!(replay.rules, game.rules);
+    /// ```
+    ///
+    pub fn replay(&self) -> Game {
+        let mut replay = Game::new(&self.start_position, self.capture_king);
+        replay.promotion_policy = self.promotion_policy.clone();
+        replay.rules = self.rules;
+        replay
+    }
+
+    /// Returns whether the king must be captured to win the game
+    ///
+    /// # Returns
+    /// Whether the game is played by capturing the king
+    ///
+    pub(crate) fn captures_king(&self) -> bool {
+        self.capture_king
+    }
+
     /// Creates a new game from a FEN string
     ///
     /// # Arguments
@@ -123,41 +246,247 @@ impl Game {
     /// use chess_lab::logic::Game;
     ///
     /// let game = Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
-    /// assert_eq!(game.to_string(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    /// assert_eq!(game.fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
     /// ```
     ///
     pub fn from_fen(fen: &str) -> Game {
-        let re = Regex::new(r"^([1-8PpNnBbRrQqKk]{1,8}/){7}[1-8PpNnBbRrQqKk]{1,8} [wb] (-|[KQkq]{1,4}) (-|[a-h][1-8]) \d+ ([1-9]\d*)$").unwrap();
-        assert!(re.is_match(fen), "Invalid FEN");
+        Game::try_from_fen(fen).expect("Invalid FEN")
+    }
 
+    /// Creates a new game from the starting position, stamped with the
+    /// current date and time in its `UTCDate` and `UTCTime` tags
+    ///
+    /// # Returns
+    /// A new game
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let game = Game::stamped();
+    /// assert!(game.history.get_utc().is_some());
+    /// assert!(game.pgn().contains("[UTCDate "));
+    /// ```
+    ///
+    #[cfg(feature = "chrono")]
+    pub fn stamped() -> Game {
         let mut game = Game::default();
-        game.start_position = fen.to_string();
+        game.history.stamp_now();
+        game
+    }
 
-        game.prev_positions.clear();
-        game.prev_positions.insert(game.get_fen_reduced(), 1);
+    /// Creates a new game from a FEN string, without panicking
+    /// The castling rights can also be written as rook files, as in X-FEN and
+    /// Shredder-FEN
+    ///
+    /// # Arguments
+    /// * `fen`: A string slice that holds the FEN representation of the game
+    ///
+    /// # Returns
+    /// A new game, or an error if the FEN is invalid
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::errors::FenError;
+    /// use chess_lab::logic::Game;
+    ///
+    /// assert!(Game::try_from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").is_ok());
+    /// assert_eq!(Game::try_from_fen("4k3/8/8/8/8/8/8/4K4 w - - 0 1").err(), Some(FenError::Invalid));
+    /// ```
+    ///
+    pub fn try_from_fen(fen: &str) -> Result<Game, FenError> {
+        if !Game::is_valid_fen(fen) {
+            return Err(FenError::Invalid);
+        }
+
+        let mut game = Game {
+            start_position: fen.to_string(),
+            ..Default::default()
+        };
 
         let parts = fen.split(' ').collect::<Vec<&str>>();
         game.board = Board::new(parts[0]);
         game.is_white_turn = parts[1] == "w";
-        game.castling_rights = parts[2].chars().fold(0, |acc, c| match c {
-            'K' => acc | 0b1000,
-            'Q' => acc | 0b0100,
-            'k' => acc | 0b0010,
-            'q' => acc | 0b0001,
-            _ => 0,
-        });
+        game.castling_rights = 0;
+        game.castling_files = [None; 4];
+        for c in parts[2].chars().filter(|c| *c != '-') {
+            let (color, side, file) = match c {
+                'K' => (Color::White, CastleType::KingSide, None),
+                'Q' => (Color::White, CastleType::QueenSide, None),
+                'k' => (Color::Black, CastleType::KingSide, None),
+                'q' => (Color::Black, CastleType::QueenSide, None),
+                // X-FEN and Shredder-FEN name the file of the rook, which
+                // gives the side from where the king stands
+                _ => {
+                    let (color, row) = if c.is_ascii_uppercase() {
+                        (Color::White, 0)
+                    } else {
+                        (Color::Black, 7)
+                    };
+                    let col = c.to_ascii_lowercase() as u8 - b'a';
+                    let king = game
+                        .board
+                        .find(PieceType::King, color)
+                        .into_iter()
+                        .find(|king| king.row == row)
+                        .ok_or(FenError::Invalid)?;
+                    match col.cmp(&king.col) {
+                        Ordering::Equal => return Err(FenError::Invalid),
+                        Ordering::Greater => (color, CastleType::KingSide, Some(col)),
+                        Ordering::Less => (color, CastleType::QueenSide, Some(col)),
+                    }
+                }
+            };
+            let index = castling_index(color, &side);
+            game.castling_rights |= 0b1000 >> index;
+            // `KQkq` castle with the outermost rook of the side
+            let file = file.or_else(|| game.outermost_rook(color, &side).map(|rook| rook.col));
+            game.castling_files[index] = file;
+        }
 
         game.en_passant = if parts[3] == "-" {
             None
         } else {
             Some(Position::from_string(parts[3]))
         };
-        game.halfmove_clock = parts[4].parse::<u32>().unwrap();
-        game.fullmove_number = parts[5].parse::<u32>().unwrap();
+        // Counters too big to be stored are clamped
+        game.halfmove_clock = parts[4].parse::<u32>().unwrap_or(u32::MAX);
+        game.fullmove_number = parts[5].parse::<u32>().unwrap_or(u32::MAX);
+
+        game.prev_positions.clear();
+        game.prev_positions.insert(game.get_fen_reduced(), 1);
+        Ok(game)
+    }
+
+    /// Creates a new game from a FEN string, repairing the fields that don't
+    /// match the position: impossible en passant squares and castling rights
+    /// are dropped, and the halfmove clock and fullmove number are brought
+    /// into range
+    ///
+    /// # Arguments
+    /// * `fen`: A string slice that holds the FEN representation of the game
+    ///
+    /// # Returns
+    /// A new game, or an error if the FEN is not well formed
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let game = Game::try_from_fen_repaired("4k3/8/8/8/8/8/8/4K3 w KQ e6 250 0").unwrap();
+    /// assert_eq!(game.fen(), "4k3/8/8/8/8/8/8/4K3 w - - 100 1");
+    /// assert!(game.validate().is_empty());
+    /// ```
+    ///
+    pub fn try_from_fen_repaired(fen: &str) -> Result<Game, FenError> {
+        let mut game = Game::try_from_fen(fen)?;
+
+        for issue in game.validate() {
+            match issue {
+                ValidationIssue::InvalidEnPassant(_) => game.en_passant = None,
+                ValidationIssue::InvalidCastlingRights => {
+                    for (bit, color, side) in [
+                        (0b1000, Color::White, CastleType::KingSide),
+                        (0b0100, Color::White, CastleType::QueenSide),
+                        (0b0010, Color::Black, CastleType::KingSide),
+                        (0b0001, Color::Black, CastleType::QueenSide),
+                    ] {
+                        if game.castling_rook(color, &side).is_none() {
+                            game.castling_rights &= !bit;
+                        }
+                    }
+                }
+                ValidationIssue::HalfmoveClockOutOfRange(halfmove_clock) => {
+                    game.halfmove_clock = if game.en_passant.is_some() {
+                        0
+                    } else {
+                        halfmove_clock.min(100)
+                    };
+                }
+                ValidationIssue::InvalidFullmoveNumber => game.fullmove_number = 1,
+                _ => {}
+            }
+        }
+
+        game.start_position = game.fen();
+        game.prev_positions.clear();
+        game.prev_positions.insert(game.get_fen_reduced(), 1);
+        Ok(game)
+    }
+
+    /// Creates a game from the standard starting position with material odds:
+    /// the pieces given are removed from the side of `color`, its castling
+    /// rights are dropped where the rook is gone, and the odds are written in
+    /// the `Handicap` tag
+    ///
+    /// # Arguments
+    /// * `color`: The color of the player giving the odds
+    /// * `odds`: The pieces given
+    ///
+    /// # Returns
+    /// The game at its starting position
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::{Color, Odds};
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::with_odds(Color::White, &[Odds::Rook]);
+    /// game.history.white_clock = Some(String::from("60+0"));
+    /// game.history.black_clock = Some(String::from("300+5"));
+    /// game.move_piece("e4").unwrap();
+    ///
+    /// assert_eq!(game.start_position, "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/1NBQKBNR w Kkq - 0 1");
+    /// assert_eq!(
+    ///     game.pgn(),
+    ///     "[WhiteClock \"60+0\"]\n[BlackClock \"300+5\"]\n[Handicap \"White gives rook odds\"]\n[SetUp \"1\"]\n[FEN \"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/1NBQKBNR w Kkq - 0 1\"]\n1. e4 *"
+    /// );
+    /// ```
+    ///
+    pub fn with_odds(color: Color, odds: &[Odds]) -> Game {
+        let mut board = Board::default();
+        for piece in odds {
+            // Giving the same piece twice removes it only once
+            let _ = board.delete_piece(&piece.square(color));
+        }
+        let mut game =
+            Game::try_from_fen_repaired(&format!("{} w KQkq - 0 1", board)).expect("Invalid FEN");
+
+        if !odds.is_empty() {
+            let pieces: Vec<String> = odds.iter().map(|piece| piece.to_string()).collect();
+            let color = match color {
+                Color::White => "White",
+                Color::Black => "Black",
+            };
+            game.history.handicap = Some(format!("{} gives {} odds", color, pieces.join(" and ")));
+        }
         game
     }
 
+    /// Checks if a FEN string is well formed
+    ///
+    /// # Arguments
+    /// * `fen`: A string slice that holds the FEN representation of the game
+    ///
+    /// # Returns
+    /// Whether the FEN string can be given to `from_fen`
+    ///
+    fn is_valid_fen(fen: &str) -> bool {
+        let re = Regex::new(r"^([1-8PpNnBbRrQqKk]{1,8}/){7}[1-8PpNnBbRrQqKk]{1,8} [wb] (-|[KQkqA-Ha-h]{1,4}) (-|[a-h][1-8]) \d+ \d+$").unwrap();
+        if !re.is_match(fen) {
+            return false;
+        }
+        fen.split(' ').next().unwrap().split('/').all(|rank| {
+            rank.chars()
+                .map(|c| c.to_digit(10).unwrap_or(1))
+                .sum::<u32>()
+                == 8
+        })
+    }
+
     /// Moves a piece on the board
+    /// The notation of the move (SAN, long algebraic or Smith) is detected
+    /// automatically
     ///
     /// # Arguments
     /// * `move_str`: A string slice that holds the move
@@ -171,72 +500,111 @@ impl Game {
     ///
     /// let mut game = Game::default();
     /// game.move_piece("e4").unwrap();
-    /// assert_eq!(game.to_string(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+    /// assert_eq!(game.fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+    ///
+    /// game.move_piece("e7-e5").unwrap();
+    /// game.move_piece("g1f3").unwrap();
+    /// assert_eq!(game.pgn(), "1. e4 e5 2. Nf3 *");
     /// ```
     ///
     pub fn move_piece(&mut self, move_str: &str) -> Result<GameStatus, MoveError> {
+        self.move_piece_with(
+            move_str,
+            Notation::detect(move_str).unwrap_or(Notation::San),
+        )
+    }
+
+    /// Moves a piece on the board, with the move written in the given notation
+    ///
+    /// # Arguments
+    /// * `move_str`: A string slice that holds the move
+    /// * `notation`: The notation the move is written in
+    ///
+    /// # Returns
+    /// The game status if the move was successful, otherwise an error
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    /// use chess_lab::parsing::Notation;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece_with("Ng1-f3", Notation::LongAlgebraic).unwrap();
+    /// game.move_piece_with("d7d5", Notation::Smith).unwrap();
+    ///
+    /// assert_eq!(game.pgn(), "1. Nf3 d5 *");
+    /// assert!(game.move_piece_with("Nb1-c3", Notation::San).is_err());
+    /// ```
+    ///
+    pub fn move_piece_with(
+        &mut self,
+        move_str: &str,
+        notation: Notation,
+    ) -> Result<GameStatus, MoveError> {
         if self.game_status != GameStatus::InProgress {
             return Ok(self.game_status);
         }
 
-        let (piece_type, start_pos_info, end_pos, move_type) = self.parse_move(move_str)?;
-        let color = if self.is_white_turn {
-            Color::White
-        } else {
-            Color::Black
-        };
+        let (piece_type, start_pos_info, end_pos, move_type) =
+            self.parse_move_with(move_str, notation)?;
+        if let MoveType::Normal {
+            capture: _,
+            promotion: Some(promotion),
+        } = move_type
+        {
+            if !self.promotion_policy.allows(promotion) {
+                return Err(MoveError::Illegal {
+                    san: move_str.to_string(),
+                    to: end_pos,
+                    reason: IllegalReason::BadPattern,
+                });
+            }
+        }
+        let color = self.side_to_move();
+        if let (Some(col), Some(row)) = start_pos_info {
+            if let Some(piece) = self.board.get_piece(&Position::new(col, row)) {
+                if piece.color != color {
+                    return Err(MoveError::WrongColor {
+                        san: move_str.to_string(),
+                        color: piece.color,
+                    });
+                }
+            }
+        }
 
-        let start_pos = self.find_piece(piece_type, color, start_pos_info, &end_pos, &move_type)?;
+        let start_pos = self.find_piece(
+            move_str,
+            piece_type,
+            color,
+            start_pos_info,
+            &end_pos,
+            &move_type,
+        )?;
 
+        let ambiguity =
+            self.move_ambiguity(piece_type, color, start_pos_info, &end_pos, &move_type);
         let mut rook_start: Option<Position> = None;
-        let mut captured_piece: Option<PieceType> =
-            self.board.get_piece(&end_pos).map(|p| p.piece_type);
-
-        match self.board.move_piece(&start_pos, &end_pos) {
-            Ok(_) => {
-                match &move_type {
-                    MoveType::Castle { side } => {
-                        let rook_end = match side {
-                            CastleType::KingSide => Position {
-                                col: 5,
-                                row: start_pos.row,
-                            },
-                            CastleType::QueenSide => Position {
-                                col: 3,
-                                row: start_pos.row,
-                            },
-                        };
-
-                        let rooks = self.board.find(PieceType::Rook, color);
+        let moved = match &move_type {
+            MoveType::Castle { side } => {
+                let rook = self.castling_rook(color, side).unwrap();
+                let rook_to = castled_rook(side, start_pos.rank());
+                castle_on(&mut self.board, [start_pos, end_pos], [rook, rook_to]);
+                rook_start = Some(rook);
+                Ok(None)
+            }
+            _ => self.board.move_piece(&start_pos, &end_pos),
+        };
 
-                        for rook in rooks {
-                            match side {
-                                CastleType::KingSide => {
-                                    if rook.col > start_pos.col && rook.row == start_pos.row {
-                                        rook_start = Some(rook);
-                                        self.board.move_piece(&rook, &rook_end).unwrap();
-                                        break;
-                                    }
-                                }
-                                CastleType::QueenSide => {
-                                    if rook.col < start_pos.col && rook.row == start_pos.row {
-                                        rook_start = Some(rook);
-                                        self.board.move_piece(&rook, &rook_end).unwrap();
-                                        break;
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    MoveType::EnPassant => {
-                        let captured_pos = Position {
-                            col: end_pos.col,
-                            row: start_pos.row,
-                        };
-                        captured_piece =
-                            Some(self.board.delete_piece(&captured_pos).unwrap().piece_type);
-                    }
-                    _ => {}
+        match moved {
+            Ok(captured) => {
+                let mut captured_piece = captured.map(|piece| piece.piece_type);
+                if let MoveType::EnPassant = &move_type {
+                    let captured_pos = Position {
+                        col: end_pos.col,
+                        row: start_pos.row,
+                    };
+                    captured_piece =
+                        Some(self.board.delete_piece(&captured_pos).unwrap().piece_type);
                 }
                 if let MoveType::Normal {
                     capture: _,
@@ -248,8 +616,6 @@ impl Game {
                         .set_piece(Piece::new(color, piece_type), &end_pos)
                         .unwrap();
                 }
-                let ambiguity =
-                    self.move_ambiguity(piece_type, color, start_pos_info, &end_pos, &move_type);
 
                 self.update_rules(Move::new(
                     Piece::new(color, piece_type),
@@ -259,79 +625,265 @@ impl Game {
                     captured_piece,
                     rook_start,
                     ambiguity,
-                    self.check(),
-                    self.checkmate(),
+                    false,
+                    false,
                 ));
 
                 Ok(self.game_status)
             }
-            Err(_) => Err(MoveError::Illegal),
+            Err(_) => Err(MoveError::Illegal {
+                san: move_str.to_string(),
+                to: end_pos,
+                reason: IllegalReason::BadPattern,
+            }),
         }
     }
 
-    /// Parses a move string
+    /// Moves a piece from one square to another, as done by dragging it in a GUI
+    /// Castling is done by moving the king two squares, and the promotion
+    /// piece is picked by the promotion policy
     ///
     /// # Arguments
-    /// * `mov`: A move that holds the piece type, start and end position, the move type, the captured piece and the rook start position
+    /// * `from`: The square of the piece to move
+    /// * `to`: The square to move the piece to
     ///
-    fn update_rules(&mut self, mov: Move) {
-        self.history.add_move(
-            mov.clone(),
-            self.halfmove_clock,
-            self.fullmove_number,
-            self.en_passant,
-            self.castling_rights,
-            self.game_status,
-        );
-
-        if matches!(mov.move_type, MoveType::Castle { .. })
-            || mov.piece.piece_type == PieceType::King
-        {
-            self.castling_rights &= match mov.piece.color {
-                Color::White => 0b0011,
-                Color::Black => 0b1100,
-            };
+    /// # Returns
+    /// The game status if the move was successful, otherwise an error, where
+    /// the move is written with both squares (`e2e4`, `e4xd5`, `Ng1f3`)
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::Position;
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece_coords(&Position::from_string("g1"), &Position::from_string("f3")).unwrap();
+    /// game.move_piece_coords(&Position::from_string("e7"), &Position::from_string("e5")).unwrap();
+    ///
+    /// assert_eq!(game.pgn(), "1. Nf3 e5 *");
+    /// ```
+    ///
+    pub fn move_piece_coords(
+        &mut self,
+        from: &Position,
+        to: &Position,
+    ) -> Result<GameStatus, MoveError> {
+        let piece = match self.board.get_piece(from) {
+            Some(piece) => piece,
+            None => {
+                return Err(MoveError::Illegal {
+                    san: format!("{}{}", from, to),
+                    to: *to,
+                    reason: IllegalReason::BadPattern,
+                })
+            }
+        };
+        if piece.color != self.side_to_move() {
+            return Err(MoveError::WrongColor {
+                san: format!("{}{}", from, to),
+                color: piece.color,
+            });
         }
-        if mov.piece.piece_type == PieceType::Rook {
-            let king = self.board.find(PieceType::King, mov.piece.color)[0];
-            match mov.piece.color {
-                Color::White => {
-                    if mov.from.col < king.col {
-                        self.castling_rights &= 0b1011;
-                    } else if mov.from.col > king.col {
-                        self.castling_rights &= 0b0111;
-                    }
-                }
-                Color::Black => {
-                    if mov.from.col < king.col {
-                        self.castling_rights &= 0b1110;
-                    } else if mov.from.col > king.col {
-                        self.castling_rights &= 0b1101;
-                    }
+        let capture = if self.board.is_ocupied(to) || Some(*to) == self.en_passant {
+            "x"
+        } else {
+            ""
+        };
+
+        let move_str = match piece.piece_type {
+            PieceType::King if from.row == to.row && from.col.abs_diff(to.col) == 2 => {
+                String::from(if to.col > from.col { "O-O" } else { "O-O-O" })
+            }
+            PieceType::Pawn => {
+                let mut move_str = format!("{}{}{}", from, capture, to);
+                if to.rank() == Rank::First || to.rank() == Rank::Eighth {
+                    let promotion = self.promotion_policy.choose(piece.color).ok_or(
+                        MoveError::PromotionCancelled {
+                            san: move_str.clone(),
+                        },
+                    )?;
+                    move_str.push('=');
+                    move_str.push(promotion.to_char());
                 }
+                move_str
             }
+            _ => format!("{}{}{}{}", piece.piece_type.to_char(), from, capture, to),
+        };
+
+        self.move_piece(&move_str)
+    }
+
+    /// Plays a move that was already generated, like one of [`Game::legal_moves`],
+    /// without writing and parsing it again
+    /// This is the fast path for searches, which play a move with this method
+    /// and take it back with [`Game::undo`]
+    ///
+    /// # Arguments
+    /// * `mov`: The move to play
+    ///
+    /// # Returns
+    /// The game status if the move was successful, otherwise an error if the
+    /// move doesn't match the position or is illegal
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// for mov in game.legal_moves() {
+    ///     game.make_move(&mov).unwrap();
+    ///     game.undo();
+    /// }
+    ///
+    /// let mov = game.legal_moves().into_iter().find(|mov| mov.to_string() == "Nf3").unwrap();
+    /// game.make_move(&mov).unwrap();
+    /// assert_eq!(game.fen(), "rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1");
+    /// assert!(game.make_move(&mov).is_err());
+    /// ```
+    ///
+    pub fn make_move(&mut self, mov: &Move) -> Result<GameStatus, MoveError> {
+        if self.game_status != GameStatus::InProgress {
+            return Ok(self.game_status);
         }
-        if let Some(PieceType::Rook) = mov.captured_piece {
-            let king = self.board.find(PieceType::King, mov.piece.color)[0];
-            match mov.piece.color.opposite() {
-                Color::White => {
-                    if mov.to.col < king.col {
-                        self.castling_rights &= 0b1011;
-                    } else if mov.to.col > king.col {
-                        self.castling_rights &= 0b0111;
-                    }
+
+        let illegal = |reason| MoveError::Illegal {
+            san: mov.to_string(),
+            to: mov.to,
+            reason,
+        };
+        if mov.piece.color != self.side_to_move() {
+            return Err(MoveError::WrongColor {
+                san: mov.to_string(),
+                color: mov.piece.color,
+            });
+        }
+        let captured = match &mov.move_type {
+            MoveType::EnPassant => Some(PieceType::Pawn),
+            MoveType::Castle { .. } => None,
+            _ => self.board.get_piece(&mov.to).map(|piece| piece.piece_type),
+        };
+        let consistent = self.board.get_piece(&mov.from) == Some(mov.piece)
+            && mov.captured_piece == captured
+            && match &mov.move_type {
+                MoveType::Normal { capture, promotion } => {
+                    *capture == captured.is_some()
+                        && promotion.is_none_or(|piece| self.promotion_policy.allows(piece))
                 }
-                Color::Black => {
-                    if mov.to.col < king.col {
-                        self.castling_rights &= 0b1110;
-                    } else if mov.to.col > king.col {
-                        self.castling_rights &= 0b1101;
-                    }
+                MoveType::EnPassant => self.en_passant == Some(mov.to),
+                MoveType::Castle { side } => {
+                    mov.rook_from.is_some()
+                        && mov.rook_from == self.castling_rook(mov.piece.color, side)
                 }
-            }
+            };
+        if !consistent {
+            return Err(illegal(IllegalReason::BadPattern));
         }
-        if matches!(mov.move_type, MoveType::Normal { capture: true, .. })
-            || mov.piece.piece_type == PieceType::Pawn
+        if let Some(reason) = self.illegal_reason(&mov.piece, &mov.from, &mov.to, &mov.move_type) {
+            return Err(illegal(reason));
+        }
+
+        self.apply_move(mov);
+        self.update_rules(mov.clone());
+        Ok(self.game_status)
+    }
+
+    /// Records a move in the history and updates the state of the game
+    ///
+    /// # Arguments
+    /// * `mov`: A move that holds the piece type, start and end position, the move type, the captured piece and the rook start position
+    ///
+    fn update_rules(&mut self, mut mov: Move) {
+        self.takeback_request = None;
+        // The node keeps the state before the move, the flags are read after
+        // it with the opponent to move
+        let halfmove_clock = self.halfmove_clock;
+        let fullmove_number = self.fullmove_number;
+        let en_passant = self.en_passant;
+        let castling_rights = self.castling_rights;
+
+        self.update_position(&mov);
+        mov.check = self.check();
+        mov.checkmate = self.checkmate();
+        self.history.add_move(
+            mov.clone(),
+            halfmove_clock,
+            fullmove_number,
+            en_passant,
+            castling_rights,
+            self.game_status,
+        );
+        self.record_position(mov.checkmate);
+    }
+
+    /// Updates the castling rights, counters, en passant square, turn,
+    /// repetition count and status of the game after a move
+    ///
+    /// # Arguments
+    /// * `mov`: The move that was just played on the board
+    ///
+    fn update_state(&mut self, mov: &Move) {
+        self.update_position(mov);
+        self.record_position(mov.checkmate);
+    }
+
+    /// Counts the repetition of the current position, keeps it in the cache
+    /// when enabled and updates the status of the game
+    ///
+    /// # Arguments
+    /// * `checkmate`: Whether the move that reached the position mates
+    ///
+    fn record_position(&mut self, checkmate: bool) {
+        let current_pos = self.get_fen_reduced();
+        let posistions = *self.prev_positions.get(&current_pos).unwrap_or(&0);
+        self.prev_positions.insert(current_pos, posistions + 1);
+
+        if self.position_cache {
+            if let Some(node) = self.history.current_node() {
+                node.borrow_mut().position = Some(CachedPosition {
+                    fen: self.fen(),
+                    prev_positions: Rc::new(self.prev_positions.clone()),
+                });
+            }
+        }
+
+        self.game_status = self.rules_status(checkmate, posistions + 1);
+    }
+
+    /// Updates the castling rights, counters, en passant square and turn
+    /// after a move
+    ///
+    /// # Arguments
+    /// * `mov`: The move that was just played on the board
+    ///
+    fn update_position(&mut self, mov: &Move) {
+        if matches!(mov.move_type, MoveType::Castle { .. })
+            || mov.piece.piece_type == PieceType::King
+        {
+            self.castling_rights &= match mov.piece.color {
+                Color::White => 0b0011,
+                Color::Black => 0b1100,
+            };
+        }
+        // A right is lost once its rook leaves its square or is captured
+        let rooks = [
+            (mov.piece.piece_type == PieceType::Rook).then_some((mov.piece.color, mov.from)),
+            (mov.captured_piece == Some(PieceType::Rook))
+                .then_some((mov.piece.color.opposite(), mov.to)),
+        ];
+        for (color, square) in rooks.into_iter().flatten() {
+            for side in [CastleType::KingSide, CastleType::QueenSide] {
+                let index = castling_index(color, &side);
+                let back_rank = match color {
+                    Color::White => 0,
+                    Color::Black => 7,
+                };
+                if square.row == back_rank && self.castling_files[index] == Some(square.col) {
+                    self.castling_rights &= !(0b1000 >> index);
+                }
+            }
+        }
+        if matches!(mov.move_type, MoveType::Normal { capture: true, .. })
+            || mov.piece.piece_type == PieceType::Pawn
         {
             self.halfmove_clock = 0;
         } else {
@@ -345,8 +897,8 @@ impl Game {
             };
 
             let can_en_passant = positions.iter().any(|pos| {
-                let piece = self.board.get_piece(&pos).unwrap();
-                piece_movement(&piece, &pos, &en_passant_pos)
+                let piece = self.board.get_piece(pos).unwrap();
+                piece_movement(&piece, pos, &en_passant_pos)
             });
 
             if can_en_passant {
@@ -361,27 +913,34 @@ impl Game {
         if self.is_white_turn {
             self.fullmove_number += 1;
         }
+    }
 
-        let current_pos = self.get_fen_reduced();
-        let posistions = *self.prev_positions.get(&current_pos).unwrap_or(&0);
-
-        self.prev_positions.insert(current_pos, posistions + 1);
-
-        if mov.checkmate {
-            self.game_status = if self.is_white_turn {
+    /// Returns the status the rules give to the position after a move, with
+    /// the draws by repetition and move count of the game rules (`rules`)
+    ///
+    /// # Arguments
+    /// * `checkmate`: Whether the move was a checkmate
+    /// * `repetitions`: How many times the position has been reached
+    ///
+    /// # Returns
+    /// The status of the game
+    ///
+    fn rules_status(&self, checkmate: bool, repetitions: u32) -> GameStatus {
+        if checkmate {
+            if self.is_white_turn {
                 GameStatus::BlackWins(WinReason::Checkmate)
             } else {
                 GameStatus::WhiteWins(WinReason::Checkmate)
-            };
+            }
         } else if self.stalemate() {
-            self.game_status = GameStatus::Draw(DrawReason::Stalemate);
-        } else if posistions == 2 {
-            self.game_status = GameStatus::Draw(DrawReason::ThreefoldRepetition);
-        } else if self.halfmove_clock >= 100 {
-            self.game_status = GameStatus::Draw(DrawReason::FiftyMoveRule);
+            GameStatus::Draw(DrawReason::Stalemate)
+        } else if self.rules.is_repetition_draw(repetitions) {
+            GameStatus::Draw(DrawReason::ThreefoldRepetition)
+        } else if self.rules.is_move_limit_draw(self.halfmove_clock) {
+            GameStatus::Draw(DrawReason::FiftyMoveRule)
         } else {
-            self.game_status = GameStatus::InProgress;
-        };
+            GameStatus::InProgress
+        }
     }
 
     /// Returns the FEN representation of the game
@@ -398,40 +957,55 @@ impl Game {
     /// ```
     ///
     pub fn fen(&self) -> String {
-        let mut fen = String::new();
-        fen.push_str(&self.board.to_string());
-        fen.push(' ');
-        fen.push(if self.is_white_turn { 'w' } else { 'b' });
-        fen.push(' ');
-        if self.castling_rights == 0 {
-            fen.push('-');
-        } else {
-            if self.castling_rights & 0b1000 != 0 {
-                fen.push('K');
-            }
-            if self.castling_rights & 0b0100 != 0 {
-                fen.push('Q');
-            }
-            if self.castling_rights & 0b0010 != 0 {
-                fen.push('k');
-            }
-            if self.castling_rights & 0b0001 != 0 {
-                fen.push('q');
-            }
-        }
-        fen.push(' ');
-        fen.push_str(
-            &self
-                .en_passant
-                .as_ref()
-                .map_or(String::from("-"), |pos| pos.to_string()),
-        );
-        fen.push(' ');
-        fen.push_str(&self.halfmove_clock.to_string());
-        fen.push(' ');
-        fen.push_str(&self.fullmove_number.to_string());
+        self.fen_with_style(FenStyle::Standard)
+    }
 
-        fen
+    /// Returns the FEN representation of the game in the given style
+    /// X-FEN only writes the file of a castling rook when another rook
+    /// stands further out on its side
+    ///
+    /// # Arguments
+    /// * `style`: How the castling rights and the en passant square are
+    ///   written
+    ///
+    /// # Returns
+    /// A string that holds the FEN representation of the game
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::FenStyle;
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// assert_eq!(
+    ///     game.fen_with_style(FenStyle::XFen),
+    ///     "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"
+    /// );
+    /// assert_eq!(
+    ///     game.fen_with_style(FenStyle::ShredderFen),
+    ///     "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b HAha - 0 1"
+    /// );
+    ///
+    /// let game = Game::from_fen("bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1");
+    /// assert_eq!(game.fen(), "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1");
+    /// ```
+    ///
+    pub fn fen_with_style(&self, style: FenStyle) -> String {
+        let en_passant = match (style, self.en_passant) {
+            (FenStyle::Standard, Some(pos)) => pos.to_string(),
+            (_, Some(pos)) if self.en_passant_legal(&pos) => pos.to_string(),
+            _ => String::from("-"),
+        };
+        format!(
+            "{} {} {} {} {} {}",
+            self.board,
+            if self.is_white_turn { 'w' } else { 'b' },
+            self.castling_field(style),
+            en_passant,
+            self.halfmove_clock,
+            self.fullmove_number
+        )
     }
 
     /// Undoes the last move
@@ -447,34 +1021,54 @@ impl Game {
     /// ```
     ///
     pub fn undo(&mut self) {
+        self.takeback_request = None;
         let mov = self.history.get_move();
         let info = self.history.get_prev_move_info();
 
-        if let None = mov {
+        if mov.is_none() {
             return;
         }
 
         let mov = mov.unwrap();
 
-        self.board.move_piece(&mov.to, &mov.from).unwrap();
+        let current_pos = self.get_fen_reduced();
+        if let Some(positions) = self.prev_positions.get_mut(&current_pos) {
+            *positions -= 1;
+            if *positions == 0 {
+                self.prev_positions.remove(&current_pos);
+            }
+        }
+
+        if let MoveType::Castle { side } = &mov.move_type {
+            let rook_to = castled_rook(side, mov.to.rank());
+            castle_on(
+                &mut self.board,
+                [mov.to, mov.from],
+                [rook_to, mov.rook_from.unwrap()],
+            );
+        } else {
+            self.board.move_piece(&mov.to, &mov.from).unwrap();
+        }
+
+        if let MoveType::Normal {
+            capture: _,
+            promotion: Some(_),
+        } = mov.move_type
+        {
+            self.board.delete_piece(&mov.from).unwrap();
+            self.board
+                .set_piece(Piece::new(mov.piece.color, PieceType::Pawn), &mov.from)
+                .unwrap();
+        }
 
         match mov.move_type {
-            MoveType::Normal {
-                capture: true,
-                promotion,
-            } => {
+            MoveType::Normal { capture: true, .. } => {
                 self.board
                     .set_piece(
                         Piece::new(mov.piece.color.opposite(), mov.captured_piece.unwrap()),
                         &mov.to,
                     )
                     .unwrap();
-                if let Some(_) = promotion {
-                    self.board.delete_piece(&mov.from).unwrap();
-                    self.board
-                        .set_piece(Piece::new(mov.piece.color, PieceType::Pawn), &mov.from)
-                        .unwrap();
-                }
             }
             MoveType::EnPassant => {
                 let captured_pos = Position {
@@ -488,20 +1082,6 @@ impl Game {
                     )
                     .unwrap();
             }
-            MoveType::Castle { side } => {
-                let rook_from = mov.rook_from.unwrap();
-                let rook_to = match side {
-                    CastleType::KingSide => Position {
-                        col: 5,
-                        row: mov.to.row,
-                    },
-                    CastleType::QueenSide => Position {
-                        col: 3,
-                        row: mov.to.row,
-                    },
-                };
-                self.board.move_piece(&rook_to, &rook_from).unwrap();
-            }
             _ => {}
         }
 
@@ -531,20 +1111,141 @@ impl Game {
     /// ```
     ///
     pub fn redo(&mut self) {
+        self.takeback_request = None;
         let mov = self.history.next_move();
 
-        if let None = mov {
+        if mov.is_none() {
             return;
         }
 
         let mov = mov.unwrap();
 
-        self.move_piece(mov.to_string().as_str()).unwrap();
+        self.apply_move(&mov);
+        self.update_state(&mov);
+    }
+
+    /// Plays a move from the history on the board, without checking it
+    ///
+    /// # Arguments
+    /// * `mov`: The move, as recorded when it was first played
+    ///
+    fn apply_move(&mut self, mov: &Move) {
+        play_on(&mut self.board, mov);
+    }
+
+    /// Plays a move on the position only, leaving the history, the
+    /// repetitions and the status untouched, for searches
+    ///
+    /// # Arguments
+    /// * `mov`: A legal move of the side to move
+    ///
+    /// # Returns
+    /// The state to give to `take_back_unrecorded` to take the move back
+    ///
+    pub(crate) fn play_unrecorded(&mut self, mov: &Move) -> Unrecorded {
+        let state = Unrecorded {
+            board: self.board.clone(),
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            en_passant: self.en_passant,
+            castling_rights: self.castling_rights,
+        };
+        play_on(&mut self.board, mov);
+        self.update_position(mov);
+        state
+    }
+
+    /// Takes back a move played with `play_unrecorded`
+    ///
+    /// # Arguments
+    /// * `state`: The state returned when the move was played
+    ///
+    pub(crate) fn take_back_unrecorded(&mut self, state: Unrecorded) {
+        self.board = state.board;
+        self.halfmove_clock = state.halfmove_clock;
+        self.fullmove_number = state.fullmove_number;
+        self.en_passant = state.en_passant;
+        self.castling_rights = state.castling_rights;
+        self.is_white_turn = !self.is_white_turn;
+    }
+
+    /// Returns the last move played to reach the current position
+    ///
+    /// # Returns
+    /// The last move, or `None` at the starting position
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::Position;
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// assert!(game.last_move().is_none());
+    ///
+    /// game.move_piece("Nf3").unwrap();
+    /// let last_move = game.last_move().unwrap();
+    /// assert_eq!(last_move.from, Position::from_string("g1"));
+    /// assert_eq!(last_move.to, Position::from_string("f3"));
+    /// ```
+    ///
+    pub fn last_move(&self) -> Option<Move> {
+        self.history.get_move()
+    }
+
+    /// Returns the moves played to reach the current position
+    /// Only the current line is followed, variations and undone moves are left out
+    ///
+    /// # Returns
+    /// The moves of the current line, from the first one
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// game.move_piece("e5").unwrap();
+    /// game.move_piece("Nf3").unwrap();
+    /// game.undo();
+    ///
+    /// let moves: Vec<String> = game.history_vec().iter().map(|mov| mov.to_string()).collect();
+    /// assert_eq!(moves, vec!["e4", "e5"]);
+    /// ```
+    ///
+    pub fn history_vec(&self) -> Vec<Move> {
+        self.history.line_moves()
+    }
 
-        self.history.next_move();
+    /// Returns the color of the side to move
+    ///
+    /// # Returns
+    /// The color of the player whose turn it is
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::Color;
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// assert_eq!(game.side_to_move(), Color::White);
+    ///
+    /// game.move_piece("e4").unwrap();
+    /// assert_eq!(game.side_to_move(), Color::Black);
+    /// ```
+    ///
+    pub fn side_to_move(&self) -> Color {
+        if self.is_white_turn {
+            Color::White
+        } else {
+            Color::Black
+        }
     }
 
     /// Returns the PGN of the game
+    /// The movetext ends with the `Result` tag if it's set, or with the result
+    /// of the game otherwise (`*` while it's ongoing). Games that don't start
+    /// from the initial position get the `SetUp` and `FEN` tags, unless
+    /// `setup_tags` is turned off
     ///
     /// # Returns
     /// A string containing the PGN of the game
@@ -556,45 +1257,705 @@ impl Game {
     /// let mut game = Game::default();
     /// game.move_piece("e4").unwrap();
     /// game.move_piece("e5").unwrap();
-    /// assert_eq!(game.pgn(), "1. e4 e5");
+    /// assert_eq!(game.pgn(), "1. e4 e5 *");
+    ///
+    /// game.resign(chess_lab::constants::Color::Black);
+    /// assert_eq!(game.pgn(), "1. e4 e5 1-0");
     /// ```
     ///
     pub fn pgn(&self) -> String {
-        self.history.pgn()
+        let result = match &self.history.result {
+            Some(result) => result.as_str(),
+            None => match self.game_status {
+                GameStatus::InProgress => "*",
+                GameStatus::WhiteWins(_) => "1-0",
+                GameStatus::BlackWins(_) => "0-1",
+                GameStatus::Draw(_) => "1/2-1/2",
+            },
+        };
+        let fen = (self.setup_tags && self.start_position != STARTING_FEN)
+            .then_some(self.start_position.as_str());
+        self.history.pgn_with_result(result, fen)
     }
 
-    /// Parse a move string and return the start and end positions
+    /// Returns whether the game is still being played
+    ///
+    /// # Returns
+    /// Whether the game hasn't ended yet
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::Color;
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// assert!(game.is_ongoing());
+    ///
+    /// game.resign(Color::Black);
+    /// assert!(!game.is_ongoing());
+    /// ```
+    ///
+    pub fn is_ongoing(&self) -> bool {
+        self.game_status == GameStatus::InProgress
+    }
+
+    /// Draws an arrow on the last move, written to the PGN as a `[%cal]` comment
+    /// Does nothing if no move has been made
     ///
     /// # Arguments
-    /// * `move_str`: A string slice that holds the move to be parsed
+    /// * `from`: The square the arrow starts from
+    /// * `to`: The square the arrow points to
+    /// * `color`: The color of the arrow
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::{AnnotationColor, Position};
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// game.move_piece("e5").unwrap();
+    /// game.add_arrow(
+    ///     Position::from_string("g1"),
+    ///     Position::from_string("f3"),
+    ///     AnnotationColor::Green,
+    /// );
+    /// assert_eq!(game.pgn(), "1. e4 e5 { [%cal Gg1f3] } *");
+    /// ```
+    ///
+    pub fn add_arrow(&mut self, from: Position, to: Position, color: AnnotationColor) {
+        self.history.add_arrow(from, to, color);
+    }
+
+    /// Circles a square on the last move, written to the PGN as a `[%csl]` comment
+    /// Does nothing if no move has been made
+    ///
+    /// # Arguments
+    /// * `square`: The square to circle
+    /// * `color`: The color of the circle
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::{AnnotationColor, Position};
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// game.move_piece("d5").unwrap();
+    /// game.add_highlight(Position::from_string("d5"), AnnotationColor::Red);
+    /// assert_eq!(game.pgn(), "1. e4 d5 { [%csl Rd5] } *");
+    /// ```
+    ///
+    pub fn add_highlight(&mut self, square: Position, color: AnnotationColor) {
+        self.history.add_highlight(square, color);
+    }
+
+    /// Takes a snapshot of the current state of the game
     ///
     /// # Returns
-    /// A tuple containing the piece type, start position, end position and the move type
-    /// If the move is invalid, a MoveError is returned
+    /// A snapshot that can be given to `restore` to roll the game back to this state
     ///
-    pub fn parse_move(
-        &self,
-        move_str: &str,
-    ) -> Result<(PieceType, (Option<u8>, Option<u8>), Position, MoveType), MoveError> {
-        let mut move_str = move_str.to_string();
-        let re =
-            Regex::new(r"^([NBRQK]?[a-h]?[1-8]?x?[a-h][1-8](=[NBRQ])?|O(-O){1,2})[+#]?").unwrap();
-        if !re.is_match(move_str.as_str()) || move_str.starts_with('x') {
-            return Err(MoveError::Invalid);
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    ///
+    /// let snapshot = game.snapshot();
+    /// assert_eq!(snapshot.board.to_string(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR");
+    /// assert!(!snapshot.is_white_turn);
+    /// ```
+    ///
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            board: self.board.clone(),
+            is_white_turn: self.is_white_turn,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            en_passant: self.en_passant,
+            castling_rights: self.castling_rights,
+            game_status: self.game_status,
+            prev_positions: Rc::new(self.prev_positions.clone()),
+            history_node: self.history.current_node(),
         }
+    }
 
-        if move_str.chars().last().unwrap() == '+' || move_str.chars().last().unwrap() == '#' {
-            move_str.remove(move_str.len() - 1);
-        }
+    /// Restores the game to the state of a snapshot
+    /// The moves played after the snapshot was taken are kept in the history as
+    /// a line, so they can still be navigated with `redo`, and a pending
+    /// takeback request is dropped
+    ///
+    /// # Arguments
+    /// * `snapshot`: A snapshot previously taken from this game (or a clone of it)
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// let snapshot = game.snapshot();
+    ///
+    /// game.move_piece("e5").unwrap();
+    /// game.move_piece("Nf3").unwrap();
+    /// game.restore(&snapshot);
+    ///
+    /// assert_eq!(game.fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+    /// assert_eq!(game.pgn(), "1. e4 e5 2. Nf3 *");
+    /// ```
+    ///
+    pub fn restore(&mut self, snapshot: &GameSnapshot) {
+        self.board = snapshot.board.clone();
+        self.is_white_turn = snapshot.is_white_turn;
+        self.halfmove_clock = snapshot.halfmove_clock;
+        self.fullmove_number = snapshot.fullmove_number;
+        self.en_passant = snapshot.en_passant;
+        self.castling_rights = snapshot.castling_rights;
+        self.game_status = snapshot.game_status;
+        self.prev_positions = snapshot.prev_positions.as_ref().clone();
+        self.history
+            .set_current_node(snapshot.history_node.as_ref().map(Rc::clone));
+        self.takeback_request = None;
+    }
 
-        if move_str.starts_with('O') {
-            let castle_side;
-            let end_pos;
-            if move_str == "O-O" {
-                if (self.castling_rights & 0b1000 == 0 || !self.is_white_turn)
+    /// Opens an analysis board on the current position
+    /// Unlike `clone`, whose history shares its nodes with the original game,
+    /// the fork is independent: the histories share their nodes until one of
+    /// them changes, which copies its nodes first, so forking a long game with
+    /// many variations stays cheap and the moves tried on the fork never reach
+    /// the original
+    ///
+    /// # Returns
+    /// A new game at the current position, with the tags, moves and variations
+    /// of this one
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// game.move_piece("e5").unwrap();
+    /// game.undo();
+    /// game.move_piece("c5").unwrap();
+    ///
+    /// let mut analysis = game.fork();
+    /// analysis.move_piece("Nf3").unwrap();
+    ///
+    /// assert_eq!(analysis.pgn(), "1. e4 e5 (1... c5 2. Nf3) *");
+    /// assert_eq!(game.pgn(), "1. e4 e5 (1... c5) *");
+    /// ```
+    ///
+    pub fn fork(&self) -> Game {
+        Game {
+            history: self.history.fork_line(),
+            ..self.clone()
+        }
+    }
+
+    /// Keeps the position reached by every move on its node of the history,
+    /// with the repetitions counted up to it, so `goto_ply` and `fen_at_ply`
+    /// read it instead of replaying the moves
+    /// It trades memory for speed: the positions of the moves already in the
+    /// history are computed once here, and the new ones as they are played
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// game.enable_position_cache();
+    /// game.move_piece("e5").unwrap();
+    ///
+    /// assert!(game.position_cache_enabled());
+    /// assert_eq!(
+    ///     game.fen_at_ply(1),
+    ///     Some("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1".to_string())
+    /// );
+    /// ```
+    ///
+    pub fn enable_position_cache(&mut self) {
+        self.position_cache = true;
+
+        let mut replay = self.replay();
+        replay.position_cache = true;
+        for node in self.history.root_nodes() {
+            replay.cache_positions(node);
+        }
+    }
+
+    /// Stops keeping the positions on the history and frees the ones already
+    /// kept
+    pub fn disable_position_cache(&mut self) {
+        self.position_cache = false;
+
+        let mut nodes = self.history.root_nodes().to_vec();
+        while let Some(node) = nodes.pop() {
+            let mut node = node.borrow_mut();
+            node.position = None;
+            nodes.extend(node.lines.iter().map(Rc::clone));
+        }
+    }
+
+    /// Returns whether the positions are kept on the history
+    ///
+    /// # Returns
+    /// Whether the position cache is enabled
+    ///
+    pub fn position_cache_enabled(&self) -> bool {
+        self.position_cache
+    }
+
+    /// Returns the FEN of the position after a number of plies of the current
+    /// line, which continues past the current move along the main line
+    ///
+    /// # Arguments
+    /// * `ply`: The number of plies from the starting position
+    ///
+    /// # Returns
+    /// The FEN of the position, or `None` if the line is shorter
+    ///
+    pub fn fen_at_ply(&self, ply: usize) -> Option<String> {
+        let nodes = self.ply_nodes(ply)?;
+        if ply == 0 {
+            return Some(self.start_position.clone());
+        }
+        if let Some(position) = &nodes[ply - 1].borrow().position {
+            return Some(position.fen.clone());
+        }
+
+        let mut replay = self.clone();
+        replay.goto_ply(ply);
+        Some(replay.fen())
+    }
+
+    /// Returns the status of the position after a number of plies of the
+    /// current line, which continues past the current move along the main
+    /// line
+    /// The check flags are read from the moves of the history and the status
+    /// from the node that follows, so the game is only replayed for the last
+    /// position of a line that isn't the current one
+    ///
+    /// # Arguments
+    /// * `ply`: The number of plies from the starting position
+    ///
+    /// # Returns
+    /// The status of the position, or `None` if the line is shorter
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::{GameStatus, WinReason};
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// for mov in ["f3", "e5", "g4", "Qh4#"] {
+    ///     game.move_piece(mov).unwrap();
+    /// }
+    /// game.goto_ply(0);
+    ///
+    /// assert!(!game.status_at_ply(3).unwrap().check);
+    /// let status = game.status_at_ply(4).unwrap();
+    /// assert!(status.checkmate);
+    /// assert_eq!(status.game_status, GameStatus::BlackWins(WinReason::Checkmate));
+    /// assert_eq!(game.status_at_ply(5), None);
+    /// ```
+    ///
+    pub fn status_at_ply(&self, ply: usize) -> Option<PlyStatus> {
+        let current = self.history.line_nodes().len() == ply;
+        let mut nodes = self.ply_nodes(ply)?;
+        nodes.truncate(ply);
+
+        let (check, checkmate) = match nodes.last() {
+            Some(node) => {
+                let node = node.borrow();
+                (node.mov.check, node.mov.checkmate)
+            }
+            None => {
+                let start = Game::new(&self.start_position, self.capture_king);
+                (start.check(), start.checkmate())
+            }
+        };
+        let next = match nodes.last() {
+            Some(node) => node.borrow().lines.first().map(Rc::clone),
+            None => self.history.root_nodes().first().map(Rc::clone),
+        };
+        let game_status = match next {
+            Some(next) => next.borrow().game_status,
+            None if current => self.game_status,
+            None => {
+                let mut replay = self.clone();
+                replay.goto_ply(ply);
+                replay.game_status
+            }
+        };
+
+        Some(PlyStatus {
+            check,
+            checkmate,
+            stalemate: game_status == GameStatus::Draw(DrawReason::Stalemate),
+            game_status,
+        })
+    }
+
+    /// Moves to the position after a number of plies of the current line,
+    /// which continues past the current move along the main line
+    /// With the position cache enabled, the position is read from the history
+    /// instead of undoing or redoing every move in between. Either way, a
+    /// pending takeback request is dropped
+    ///
+    /// # Arguments
+    /// * `ply`: The number of plies from the starting position
+    ///
+    /// # Returns
+    /// Whether the line is long enough to reach the ply
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// for mov in ["e4", "e5", "Nf3", "Nc6"] {
+    ///     game.move_piece(mov).unwrap();
+    /// }
+    ///
+    /// assert!(game.goto_ply(1));
+    /// assert_eq!(game.fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+    /// assert!(game.goto_ply(3));
+    /// assert_eq!(game.last_move().unwrap().to_string(), "Nf3");
+    /// assert!(!game.goto_ply(5));
+    /// ```
+    ///
+    pub fn goto_ply(&mut self, ply: usize) -> bool {
+        let current = self.history.line_nodes().len();
+        let Some(nodes) = self.ply_nodes(ply) else {
+            return false;
+        };
+        if ply == current {
+            return true;
+        }
+        self.takeback_request = None;
+
+        // The cached position keeps the repetitions of its whole line, so
+        // only the node of the ply is read
+        if ply
+            .checked_sub(1)
+            .is_some_and(|last| nodes[last].borrow().position.is_none())
+        {
+            for _ in ply..current {
+                self.undo();
+            }
+            for _ in current..ply {
+                self.redo();
+            }
+            return true;
+        }
+
+        let (position, checkmate, prev_positions) = match ply.checked_sub(1) {
+            Some(last) => {
+                let node = nodes[last].borrow();
+                let cached = node.position.as_ref().unwrap();
+                (
+                    Game::new(&cached.fen, self.capture_king),
+                    node.mov.checkmate,
+                    Some(Rc::clone(&cached.prev_positions)),
+                )
+            }
+            None => (
+                Game::new(&self.start_position, self.capture_king),
+                false,
+                None,
+            ),
+        };
+        self.prev_positions = match prev_positions {
+            Some(prev_positions) => prev_positions.as_ref().clone(),
+            None => position.prev_positions,
+        };
+        self.board = position.board;
+        self.is_white_turn = position.is_white_turn;
+        self.halfmove_clock = position.halfmove_clock;
+        self.fullmove_number = position.fullmove_number;
+        self.en_passant = position.en_passant;
+        self.castling_rights = position.castling_rights;
+
+        self.game_status = match nodes.get(ply) {
+            Some(next) => next.borrow().game_status,
+            None => {
+                let repetitions = self.prev_positions[&self.get_fen_reduced()];
+                self.rules_status(checkmate, repetitions)
+            }
+        };
+        self.history
+            .set_current_node(ply.checked_sub(1).map(|last| Rc::clone(&nodes[last])));
+        true
+    }
+
+    /// Finds where a position occurs in the history, in the main line and in
+    /// every variation
+    /// Positions are compared the way repetitions are, without the halfmove
+    /// clock and fullmove number
+    ///
+    /// # Arguments
+    /// * `position`: The FEN of the position or its hash (`position_hash`)
+    ///
+    /// # Returns
+    /// The paths to the moves that reach the position, in the order of the
+    /// history, or none if the FEN is invalid
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// for mov in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+    ///     game.move_piece(mov).unwrap();
+    /// }
+    /// game.goto_ply(0);
+    /// game.move_piece("e4").unwrap();
+    ///
+    /// let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    /// assert_eq!(game.find_position(start), vec![vec![], vec![0, 0, 0, 0]]);
+    ///
+    /// let paths = game.find_position(game.position_hash());
+    /// assert_eq!(paths, vec![vec![1]]);
+    /// ```
+    ///
+    pub fn find_position(&self, position: impl Into<PositionKey>) -> Vec<PlyPath> {
+        let Some(hash) = position.into().hash() else {
+            return Vec::new();
+        };
+        self.positions()
+            .into_iter()
+            .filter(|(key, _)| *key == hash)
+            .map(|(_, path)| path)
+            .collect()
+    }
+
+    /// Moves to the position reached by a path of the history, as found by
+    /// `find_position`
+    ///
+    /// # Arguments
+    /// * `path`: The index of the variation to take at every ply
+    ///
+    /// # Returns
+    /// Whether the path exists in the history. If it doesn't, the game stays
+    /// at the last move of the path that exists
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// game.move_piece("e5").unwrap();
+    /// game.undo();
+    /// game.move_piece("c5").unwrap();
+    /// game.goto_ply(0);
+    ///
+    /// assert!(game.goto_path(&[0, 1]));
+    /// assert_eq!(game.last_move().unwrap().to_string(), "c5");
+    /// assert!(!game.goto_path(&[0, 2]));
+    /// ```
+    ///
+    pub fn goto_path(&mut self, path: &[usize]) -> bool {
+        self.goto_ply(0);
+        for variant in path {
+            let Some(mov) = self.history.next_move_variant(*variant as u32) else {
+                return false;
+            };
+            self.apply_move(&mov);
+            self.update_state(&mov);
+        }
+        true
+    }
+
+    /// Returns the nodes of the current line, continued along the main line
+    /// until it has the given number of plies
+    ///
+    /// # Arguments
+    /// * `ply`: The number of plies the line needs
+    ///
+    /// # Returns
+    /// The nodes, from the first move, or `None` if the line is shorter
+    ///
+    fn ply_nodes(&self, ply: usize) -> Option<Vec<Rc<RefCell<PgnLine<Move>>>>> {
+        let mut nodes = self.history.line_nodes();
+        while nodes.len() < ply {
+            let next = match nodes.last() {
+                Some(node) => node.borrow().lines.first().map(Rc::clone),
+                None => self.history.root_nodes().first().map(Rc::clone),
+            };
+            nodes.push(next?);
+        }
+        Some(nodes)
+    }
+
+    /// Replays a node of the history and the lines that follow it, keeping
+    /// the position reached by every move on its node
+    ///
+    /// # Arguments
+    /// * `node`: The node to replay, from the position before its move
+    ///
+    fn cache_positions(&mut self, node: &Rc<RefCell<PgnLine<Move>>>) {
+        let snapshot = self.snapshot();
+        let mov = node.borrow().mov.clone();
+
+        self.history.set_current_node(Some(Rc::clone(node)));
+        self.apply_move(&mov);
+        self.update_state(&mov);
+        let lines = node.borrow().lines.clone();
+        for line in &lines {
+            self.cache_positions(line);
+        }
+
+        self.restore(&snapshot);
+    }
+
+    /// Returns the hash of every position of the history, in the main line
+    /// and in every variation
+    ///
+    /// # Returns
+    /// The hash of every position along with the path that reaches it, from
+    /// the starting position
+    ///
+    pub(crate) fn positions(&self) -> Vec<(u64, PlyPath)> {
+        let mut positions = Vec::new();
+        self.visit_positions(&mut |game, path| {
+            positions.push((game.position_hash(), path.clone()))
+        });
+        positions
+    }
+
+    /// Replays every position of the history, in the main line and in every
+    /// variation, from the starting position
+    ///
+    /// # Arguments
+    /// * `visit`: The function called with the game at every position and the
+    ///   path that reaches it
+    ///
+    pub(crate) fn visit_positions(&self, visit: &mut dyn FnMut(&Game, &PlyPath)) {
+        let mut replay = self.replay();
+        let mut path = Vec::new();
+        visit(&replay, &path);
+
+        // The lines are walked with a stack instead of recursion, so long
+        // games can't overflow it. Each node is entered with its index among
+        // its siblings and its depth, and the position before it is restored
+        // once all its lines are done
+        let mut stack: Vec<ReplayStep> = self
+            .history
+            .root_nodes()
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(variant, node)| ReplayStep::Enter(node.clone(), variant, 0))
+            .collect();
+        while let Some(step) = stack.pop() {
+            let (node, variant, depth) = match step {
+                ReplayStep::Enter(node, variant, depth) => (node, variant, depth),
+                ReplayStep::Leave(snapshot) => {
+                    replay.restore(&snapshot);
+                    continue;
+                }
+            };
+            let snapshot = replay.snapshot();
+            let mov = node.borrow().mov.clone();
+
+            replay.apply_move(&mov);
+            replay.update_position(&mov);
+            path.truncate(depth);
+            path.push(variant);
+            visit(&replay, &path);
+
+            stack.push(ReplayStep::Leave(snapshot));
+            for (variant, line) in node.borrow().lines.iter().enumerate().rev() {
+                stack.push(ReplayStep::Enter(line.clone(), variant, depth + 1));
+            }
+        }
+    }
+
+    /// Parse a move string and return the start and end positions
+    /// The notation of the move (SAN, long algebraic or Smith) is detected
+    /// automatically
+    ///
+    /// # Arguments
+    /// * `move_str`: A string slice that holds the move to be parsed
+    ///
+    /// # Returns
+    /// A tuple containing the piece type, start position, end position and the move type
+    /// If the move is invalid, a MoveError is returned
+    ///
+    pub fn parse_move(&self, move_str: &str) -> Result<ParsedMove, MoveError> {
+        self.parse_move_with(
+            move_str,
+            Notation::detect(move_str).unwrap_or(Notation::San),
+        )
+    }
+
+    /// Parse a move string written in the given notation
+    ///
+    /// # Arguments
+    /// * `move_str`: A string slice that holds the move to be parsed
+    /// * `notation`: The notation the move is written in
+    ///
+    /// # Returns
+    /// A tuple containing the piece type, start position, end position and the move type
+    /// If the move is invalid in that notation, a MoveError is returned
+    ///
+    pub fn parse_move_with(
+        &self,
+        move_str: &str,
+        notation: Notation,
+    ) -> Result<ParsedMove, MoveError> {
+        match notation {
+            Notation::San => self.parse_san(move_str),
+            Notation::LongAlgebraic => parse_long_algebraic(self, move_str),
+            Notation::Smith => parse_smith(self, move_str),
+            Notation::Uci => parse_uci(self, move_str),
+        }
+    }
+
+    /// Parse a move string in standard algebraic notation
+    ///
+    /// # Arguments
+    /// * `move_str`: A string slice that holds the move to be parsed
+    ///
+    /// # Returns
+    /// A tuple containing the piece type, start position, end position and the move type
+    /// If the move is invalid, a MoveError is returned
+    ///
+    fn parse_san(&self, move_str: &str) -> Result<ParsedMove, MoveError> {
+        let invalid = || MoveError::Invalid {
+            san: move_str.to_string(),
+        };
+        let san = move_str;
+        let mut move_str = move_str.to_string();
+        let re = Regex::new(
+            r"^([NBRQK]?[a-h]?[1-8]?x?[a-h][1-8](=[NBRQ])?( ?e\.p\.)?|O(-O){1,2})[+#]?$",
+        )
+        .unwrap();
+        if !re.is_match(move_str.as_str()) || move_str.starts_with('x') {
+            return Err(invalid());
+        }
+
+        if move_str.ends_with('+') || move_str.ends_with('#') {
+            move_str.remove(move_str.len() - 1);
+        }
+        let en_passant_suffix = move_str.ends_with("e.p.");
+        if en_passant_suffix {
+            move_str = move_str.trim_end_matches("e.p.").trim_end().to_string();
+        }
+
+        if move_str.starts_with('O') {
+            let castle_side;
+            let end_pos;
+            if move_str == "O-O" {
+                if (self.castling_rights & 0b1000 == 0 || !self.is_white_turn)
                     && (self.castling_rights & 0b0010 == 0 || self.is_white_turn)
                 {
-                    return Err(MoveError::Invalid);
+                    return Err(invalid());
                 }
                 castle_side = CastleType::KingSide;
                 end_pos = if self.is_white_turn {
@@ -606,7 +1967,7 @@ impl Game {
                 if (self.castling_rights & 0b0100 == 0 || !self.is_white_turn)
                     && (self.castling_rights & 0b0001 == 0 || self.is_white_turn)
                 {
-                    return Err(MoveError::Invalid);
+                    return Err(invalid());
                 }
                 castle_side = CastleType::QueenSide;
 
@@ -616,14 +1977,14 @@ impl Game {
                     Position::from_string("c8")
                 };
             } else {
-                return Err(MoveError::Invalid);
+                return Err(invalid());
             }
-            return Ok((
+            Ok((
                 PieceType::King,
                 (None, None),
                 end_pos,
                 MoveType::Castle { side: castle_side },
-            ));
+            ))
         } else {
             let start_col;
             let start_row;
@@ -645,15 +2006,15 @@ impl Game {
 
             if move_str.contains('=') {
                 if "NBRQK".contains(move_str.chars().next().unwrap()) {
-                    return Err(MoveError::Invalid);
+                    return Err(invalid());
                 }
 
                 promotion = Some(PieceType::from_char(move_str.chars().last().unwrap()).unwrap());
                 end_pos = Position::from_string(&move_str[move_str.len() - 4..move_str.len() - 2]);
                 end_pos_index = move_str.len() - 4;
 
-                if end_pos.row != 0 && end_pos.row != 7 {
-                    return Err(MoveError::Invalid);
+                if end_pos.rank() != Rank::First && end_pos.rank() != Rank::Eighth {
+                    return Err(invalid());
                 }
             } else {
                 end_pos = Position::from_string(&move_str[move_str.len() - 2..]);
@@ -662,27 +2023,20 @@ impl Game {
             }
 
             if end_pos_index > 1 {
-                if "abcdefgh".contains(move_str.chars().nth(1).unwrap()) {
-                    start_col = Some(move_str.chars().nth(1).unwrap() as u8 - 'a' as u8);
-                    if "12345678".contains(move_str.chars().nth(2).unwrap()) {
-                        start_row = Some(move_str.chars().nth(2).unwrap() as u8 - '1' as u8);
-                    } else {
-                        start_row = None;
-                    }
-                } else if "12345678".contains(move_str.chars().nth(1).unwrap()) {
-                    start_col = None;
-                    start_row = Some(move_str.chars().nth(1).unwrap() as u8 - '1' as u8);
-                } else {
-                    start_col = None;
-                    start_row = None;
-                }
+                let file = move_str.chars().nth(1).and_then(File::from_char);
+                let rank = match file {
+                    Some(_) => move_str.chars().nth(2).and_then(Rank::from_char),
+                    None => move_str.chars().nth(1).and_then(Rank::from_char),
+                };
+                start_col = file.map(|file| file.index());
+                start_row = rank.map(|rank| rank.index());
             } else {
                 start_col = None;
                 start_row = None;
             }
 
-            if capture && self.en_passant.is_some() {
-                if piece == PieceType::Pawn && end_pos == self.en_passant.unwrap() {
+            if capture && piece == PieceType::Pawn && promotion.is_none() {
+                if self.en_passant == Some(end_pos) {
                     return Ok((
                         PieceType::Pawn,
                         (start_col, start_row),
@@ -690,14 +2044,95 @@ impl Game {
                         MoveType::EnPassant,
                     ));
                 }
+
+                // A pawn capturing onto an empty square of the en passant rank
+                // can only be an en passant capture
+                let en_passant_row = if self.is_white_turn { 5 } else { 2 };
+                if en_passant_suffix
+                    || (end_pos.row == en_passant_row && self.board.get_piece(&end_pos).is_none())
+                {
+                    return Err(MoveError::IllegalEnPassant {
+                        san: san.to_string(),
+                        to: end_pos,
+                    });
+                }
+            } else if en_passant_suffix {
+                return Err(invalid());
             }
 
-            return Ok((
+            Ok((
                 piece,
                 (start_col, start_row),
                 end_pos,
                 MoveType::Normal { capture, promotion },
-            ));
+            ))
+        }
+    }
+
+    /// Explains why a move can't be played, for beginner-friendly messages
+    ///
+    /// # Arguments
+    /// * `move_str`: The move, in any notation `move_piece` reads
+    ///
+    /// # Returns
+    /// Why the move is illegal, or `None` if it can be played, can't be read
+    /// or doesn't name a single piece
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::Position;
+    /// use chess_lab::errors::IllegalReason;
+    /// use chess_lab::logic::Game;
+    ///
+    /// let game = Game::from_fen("4k3/8/8/8/8/8/5r2/R3K3 w Q - 0 1");
+    ///
+    /// let f2 = Position::from_string("f2");
+    /// assert_eq!(game.explain_illegal("Kf1"), Some(IllegalReason::SelfCheck { from: f2 }));
+    /// assert_eq!(game.explain_illegal("Ra3"), None);
+    /// assert_eq!(game.explain_illegal("Rb3"), Some(IllegalReason::BadPattern));
+    /// assert_eq!(game.explain_illegal("Rf8"), Some(IllegalReason::WrongTurn));
+    /// assert_eq!(
+    ///     game.explain_illegal("Rg1"),
+    ///     Some(IllegalReason::BlockedPath { at: Position::from_string("e1") })
+    /// );
+    /// ```
+    ///
+    pub fn explain_illegal(&self, move_str: &str) -> Option<IllegalReason> {
+        if self.game_status != GameStatus::InProgress {
+            return None;
+        }
+        let notation = Notation::detect(move_str).unwrap_or(Notation::San);
+        let (piece_type, start_pos, end_pos, move_type) =
+            self.parse_move_with(move_str, notation).ok()?;
+        if let MoveType::Normal {
+            promotion: Some(promotion),
+            ..
+        } = move_type
+        {
+            if !self.promotion_policy.allows(promotion) {
+                return Some(IllegalReason::BadPattern);
+            }
+        }
+
+        let color = self.side_to_move();
+        if let (Some(col), Some(row)) = start_pos {
+            if self
+                .board
+                .get_piece(&Position::new(col, row))
+                .is_some_and(|piece| piece.color != color)
+            {
+                return Some(IllegalReason::WrongTurn);
+            }
+        }
+        let find =
+            |color| self.find_piece(move_str, piece_type, color, start_pos, &end_pos, &move_type);
+        match find(color) {
+            // The other side could play it, the player mixed up the turns
+            Err(MoveError::Illegal { .. }) if find(color.opposite()).is_ok() => {
+                Some(IllegalReason::WrongTurn)
+            }
+            Err(MoveError::Illegal { reason, .. }) => Some(reason),
+            _ => None,
         }
     }
 
@@ -719,56 +2154,434 @@ impl Game {
         end_pos: &Position,
         move_type: &MoveType,
     ) -> bool {
+        self.illegal_reason(piece, start_pos, end_pos, move_type)
+            .is_none()
+    }
+
+    /// Returns the moves of the side to move that follow the movement rules,
+    /// including the ones that leave (or, when castling, move the king
+    /// through) an attacked square
+    /// The moves don't carry check or checkmate flags
+    ///
+    /// # Returns
+    /// The pseudo-legal moves, grouped by the piece that moves
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let game = Game::from_fen("4k3/8/8/8/8/8/3r4/4K3 w - - 0 1");
+    /// let moves: Vec<String> = game
+    ///     .pseudo_legal_moves()
+    ///     .iter()
+    ///     .map(|mov| mov.to_string())
+    ///     .collect();
+    /// assert_eq!(moves, vec!["Kd1", "Kf1", "Kxd2", "Ke2", "Kf2"]);
+    /// ```
+    ///
+    pub fn pseudo_legal_moves(&self) -> Vec<Move> {
+        self.generate_moves(|reason| matches!(reason, None | Some(IllegalReason::SelfCheck { .. })))
+    }
+
+    /// Returns the legal moves of the side to move
+    /// The moves don't carry check or checkmate flags
+    ///
+    /// # Returns
+    /// The legal moves, grouped by the piece that moves
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// assert_eq!(Game::default().legal_moves().len(), 20);
+    ///
+    /// let game = Game::from_fen("4k3/8/8/8/8/8/3r4/4K3 w - - 0 1");
+    /// let moves: Vec<String> = game.legal_moves().iter().map(|mov| mov.to_string()).collect();
+    /// assert_eq!(moves, vec!["Kf1", "Kxd2"]);
+    /// ```
+    ///
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.generate_moves(|reason| reason.is_none())
+    }
+
+    /// Returns the legal moves of the side to move that give check, as hints
+    ///
+    /// # Returns
+    /// The checking moves, flagged as checks
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+    /// let checks: Vec<String> = game.checks_available().iter().map(|mov| mov.to_string()).collect();
+    /// assert_eq!(checks, vec!["Ra8+"]);
+    /// ```
+    ///
+    pub fn checks_available(&self) -> Vec<Move> {
+        let color = self.side_to_move();
+        self.legal_moves()
+            .into_iter()
+            .filter_map(|mut mov| {
+                let mut board = self.board.clone();
+                play_on(&mut board, &mov);
+                mov.check = board
+                    .find(PieceType::King, color.opposite())
+                    .first()
+                    .is_some_and(|king| board.is_attacked(*king, color));
+                mov.check.then_some(mov)
+            })
+            .collect()
+    }
+
+    /// Returns the legal captures of the side to move, en passant included,
+    /// as hints
+    ///
+    /// # Returns
+    /// The captures, the most valuable victim taken by the least valuable
+    /// attacker first (MVV-LVA)
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let game = Game::from_fen("4k3/8/8/1n1q4/2P5/8/8/3QK3 w - - 0 1");
+    /// let captures: Vec<String> = game
+    ///     .captures_available()
+    ///     .iter()
+    ///     .map(|mov| mov.to_string())
+    ///     .collect();
+    /// assert_eq!(captures, vec!["cxd5", "Qxd5", "cxb5"]);
+    /// ```
+    ///
+    pub fn captures_available(&self) -> Vec<Move> {
+        let mut captures: Vec<Move> = self
+            .legal_moves()
+            .into_iter()
+            .filter(|mov| mov.captured_piece.is_some())
+            .collect();
+        captures.sort_by_key(|mov| {
+            let victim = mov.captured_piece.map_or(0, |piece_type| piece_type.rank());
+            std::cmp::Reverse(victim * 8 + 7 - mov.piece.piece_type.rank())
+        });
+        captures
+    }
+
+    /// Finds the pieces of a color that are attacked and not defended, as
+    /// read from the attack maps, without looking at pins
+    ///
+    /// # Arguments
+    /// * `color`: The color of the pieces
+    ///
+    /// # Returns
+    /// The squares of the hanging pieces, kings left out
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::{Color, Position};
+    /// use chess_lab::logic::Game;
+    ///
+    /// let game = Game::from_fen("4k3/8/5n2/3p4/4P3/8/8/4K1N1 w - - 0 1");
+    /// assert_eq!(game.hanging_pieces(Color::White), vec![Position::from_string("e4")]);
+    /// assert!(game.hanging_pieces(Color::Black).is_empty());
+    /// ```
+    ///
+    pub fn hanging_pieces(&self, color: Color) -> Vec<Position> {
+        self.board
+            .find_all(color)
+            .into_iter()
+            .filter(|pos| {
+                if self.board.get_piece(pos).unwrap().piece_type == PieceType::King
+                    || !self.board.is_attacked(*pos, color.opposite())
+                {
+                    return false;
+                }
+                // A defender is a piece that could capture on the square once
+                // it's empty
+                let mut board = self.board.clone();
+                board.delete_piece(pos).unwrap();
+                !board.is_attacked(*pos, color)
+            })
+            .collect()
+    }
+
+    /// Finds out why a move is illegal
+    ///
+    /// # Arguments
+    /// * `piece`: The piece being moved
+    /// * `start_pos`: The starting position of the piece
+    /// * `end_pos`: The ending position of the piece
+    /// * `move_type`: The type of move being made
+    ///
+    /// # Returns
+    /// The reason why the move is illegal, or `None` if it's legal
+    ///
+    fn illegal_reason(
+        &self,
+        piece: &Piece,
+        start_pos: &Position,
+        end_pos: &Position,
+        move_type: &MoveType,
+    ) -> Option<IllegalReason> {
         if piece.piece_type != PieceType::Knight && piece.piece_type != PieceType::King {
-            if !linear_movement(start_pos, end_pos) && !diagonal_movement(start_pos, end_pos) {
-                return false;
+            if !piece_movement(piece, start_pos, end_pos) {
+                return Some(IllegalReason::BadPattern);
             }
-            if self.board.piece_between(start_pos, end_pos) {
-                return false;
+            if let Some(at) = self.board.first_piece_between(start_pos, end_pos) {
+                return Some(IllegalReason::BlockedPath { at });
+            }
+        }
+
+        if let MoveType::Castle { side } = move_type {
+            return self.castle_illegal_reason(piece, start_pos, end_pos, side);
+        }
+        if !piece_movement(piece, start_pos, end_pos) {
+            return Some(IllegalReason::BadPattern);
+        }
+        if let MoveType::Normal {
+            capture: false,
+            promotion: _,
+        } = move_type
+        {
+            // Pawns find out why they can't push below
+            if piece.piece_type != PieceType::Pawn && self.board.is_ocupied(end_pos) {
+                return Some(IllegalReason::BadPattern);
             }
         }
+        if let MoveType::Normal {
+            capture: true,
+            promotion: _,
+        } = move_type
+        {
+            if !self.board.is_ocupied(end_pos)
+                || self.board.get_piece(end_pos).unwrap().color == piece.color
+                || (piece.piece_type == PieceType::Pawn && start_pos.col == end_pos.col)
+            {
+                return Some(IllegalReason::BadPattern);
+            }
+        }
+        if piece.piece_type == PieceType::Pawn
+            && matches!(
+                move_type,
+                MoveType::Normal {
+                    capture: false,
+                    promotion: _
+                }
+            )
+        {
+            if let Some(reason) = self.pawn_push_illegal_reason(start_pos, end_pos) {
+                return Some(reason);
+            }
+        }
+
+        if self.capture_king {
+            return None;
+        }
+
+        let mut board = self.board.clone();
+        if board.move_piece(start_pos, end_pos).is_err() {
+            return Some(IllegalReason::BadPattern);
+        }
+        if let MoveType::EnPassant = move_type {
+            // The captured pawn can be shielding the king along the rank
+            board
+                .delete_piece(&Position::new(end_pos.col, start_pos.row))
+                .ok();
+        }
+
+        // Without a king the move can't leave it in check
+        let &king = board.find(PieceType::King, piece.color).first()?;
+        board
+            .attacker(king, piece.color.opposite())
+            .map(|from| IllegalReason::SelfCheck { from })
+    }
+
+    /// Finds out why a pawn can't move forward without capturing
+    ///
+    /// # Arguments
+    /// * `start_pos`: The starting position of the pawn
+    /// * `end_pos`: The ending position of the pawn
+    ///
+    /// # Returns
+    /// The reason why the pawn can't move, or `None` if the path is clear
+    ///
+    fn pawn_push_illegal_reason(
+        &self,
+        start_pos: &Position,
+        end_pos: &Position,
+    ) -> Option<IllegalReason> {
+        if start_pos.col != end_pos.col {
+            return Some(IllegalReason::BadPattern);
+        }
+        // A double push can't jump over the square in front of the pawn
+        let middle = Position::new(start_pos.col, (start_pos.row + end_pos.row) / 2);
+        if start_pos.row.abs_diff(end_pos.row) == 2 && self.board.is_ocupied(&middle) {
+            return Some(IllegalReason::BlockedPath { at: middle });
+        }
+        if self.board.is_ocupied(end_pos) {
+            return Some(IllegalReason::BlockedPath { at: *end_pos });
+        }
+        None
+    }
+
+    /// Generates the moves of the side to move whose illegal reason is
+    /// accepted by the filter
+    ///
+    /// # Arguments
+    /// * `accept`: Whether a move that is illegal for the given reason, or
+    ///   legal if it's `None`, is generated
+    ///
+    /// # Returns
+    /// The accepted moves, grouped by the piece that moves
+    ///
+    fn generate_moves<F>(&self, accept: F) -> Vec<Move>
+    where
+        F: Fn(Option<IllegalReason>) -> bool,
+    {
+        let candidates: Vec<(Piece, Position, Position, MoveType)> = self
+            .candidate_moves()
+            .into_iter()
+            .filter(|(piece, from, to, move_type)| {
+                accept(self.illegal_reason(piece, from, to, move_type))
+            })
+            .collect();
+
+        candidates
+            .iter()
+            .map(|(piece, from, to, move_type)| {
+                let captured_piece = match move_type {
+                    MoveType::EnPassant => Some(PieceType::Pawn),
+                    MoveType::Castle { .. } => None,
+                    _ => self.board.get_piece(to).map(|piece| piece.piece_type),
+                };
+                let rook_from = match move_type {
+                    MoveType::Castle { side } => self.castling_rook(piece.color, side),
+                    _ => None,
+                };
+
+                // Other pieces of the same type that can reach the square
+                let others: Vec<&Position> = candidates
+                    .iter()
+                    .filter(|(other, other_from, other_to, _)| {
+                        other == piece && other_to == to && other_from != from
+                    })
+                    .map(|(_, other_from, _, _)| other_from)
+                    .collect();
+                let ambiguity = if others.is_empty() || piece.piece_type == PieceType::Pawn {
+                    (false, false)
+                } else if others.iter().all(|other| other.col != from.col) {
+                    (true, false)
+                } else if others.iter().all(|other| other.row != from.row) {
+                    (false, true)
+                } else {
+                    (true, true)
+                };
+
+                Move::new(
+                    *piece,
+                    *from,
+                    *to,
+                    move_type.clone(),
+                    captured_piece,
+                    rook_from,
+                    ambiguity,
+                    false,
+                    false,
+                )
+            })
+            .collect()
+    }
+
+    /// Lists the moves of the side to move that could follow the movement
+    /// rules, before checking the paths and the safety of the king
+    ///
+    /// # Returns
+    /// The piece, the starting and ending positions and the type of every
+    /// candidate move
+    ///
+    fn candidate_moves(&self) -> Vec<(Piece, Position, Position, MoveType)> {
+        let color = self.side_to_move();
+        let promotions = [
+            PieceType::Knight,
+            PieceType::Bishop,
+            PieceType::Rook,
+            PieceType::Queen,
+        ];
 
-        if let MoveType::Castle { side } = move_type {
-            return self.is_castle_legal(piece, start_pos, end_pos, side);
-        }
-        if !piece_movement(piece, start_pos, end_pos) {
-            return false;
-        }
-        if let MoveType::Normal {
-            capture: true,
-            promotion: _,
-        } = move_type
-        {
-            if !self.board.is_ocupied(end_pos)
-                || self.board.get_piece(end_pos).unwrap().color == piece.color
-                || (piece.piece_type == PieceType::Pawn && start_pos.col == end_pos.col)
-            {
-                return false;
-            }
-        }
-        if piece.piece_type == PieceType::Pawn
-            && matches!(
-                move_type,
-                MoveType::Normal {
-                    capture: false,
-                    promotion: _
+        let mut candidates = Vec::new();
+        for from in self.board.find_all(color) {
+            let piece = self.board.get_piece(&from).unwrap();
+            for row in 0..8 {
+                for col in 0..8 {
+                    let to = Position::new(col, row);
+                    if to == from || !piece_movement(&piece, &from, &to) {
+                        continue;
+                    }
+                    let capture = self.board.is_ocupied(&to);
+
+                    if piece.piece_type == PieceType::Pawn {
+                        if Some(to) == self.en_passant && from.col != to.col {
+                            candidates.push((piece, from, to, MoveType::EnPassant));
+                        } else if to.rank() == Rank::First || to.rank() == Rank::Eighth {
+                            for promotion in promotions {
+                                if self.promotion_policy.allows(promotion) {
+                                    candidates.push((
+                                        piece,
+                                        from,
+                                        to,
+                                        MoveType::Normal {
+                                            capture,
+                                            promotion: Some(promotion),
+                                        },
+                                    ));
+                                }
+                            }
+                        } else {
+                            candidates.push((
+                                piece,
+                                from,
+                                to,
+                                MoveType::Normal {
+                                    capture,
+                                    promotion: None,
+                                },
+                            ));
+                        }
+                        continue;
+                    }
+
+                    candidates.push((
+                        piece,
+                        from,
+                        to,
+                        MoveType::Normal {
+                            capture,
+                            promotion: None,
+                        },
+                    ));
                 }
-            )
-        {
-            if self.board.get_piece(end_pos).is_some() || start_pos.col != end_pos.col {
-                return false;
             }
-        }
 
-        if self.capture_king {
-            return true;
+            if piece.piece_type == PieceType::King {
+                for side in [CastleType::KingSide, CastleType::QueenSide] {
+                    if self.castling_rook(color, &side).is_some() {
+                        let col = match side {
+                            CastleType::KingSide => 6,
+                            CastleType::QueenSide => 2,
+                        };
+                        if col != from.col {
+                            candidates.push((
+                                piece,
+                                from,
+                                Position::new(col, from.row),
+                                MoveType::Castle { side },
+                            ));
+                        }
+                    }
+                }
+            }
         }
-
-        let mut board = self.board.clone();
-        board.move_piece(start_pos, end_pos).unwrap();
-
-        let king = self.board.find(PieceType::King, piece.color)[0];
-        return !board.is_attacked(king, piece.color.opposite());
+        candidates
     }
 
     /// Returns whether the king is in check
@@ -793,22 +2606,17 @@ impl Game {
         if self.capture_king {
             return false;
         }
-        let color = if self.is_white_turn {
-            Color::White
-        } else {
-            Color::Black
-        };
+        let color = self.side_to_move();
 
-        if self
-            .board
-            .is_attacked(self.board.find(PieceType::King, color)[0], color.opposite())
-        {
-            return true;
-        }
-        false
+        self.board
+            .find(PieceType::King, color)
+            .first()
+            .is_some_and(|king| self.board.is_attacked(*king, color.opposite()))
     }
 
     /// Returns whether the king is in checkmate
+    /// In games where the king is captured, whether the king of the side to
+    /// move was captured
     ///
     /// # Returns
     /// Whether the king is in checkmate
@@ -832,13 +2640,9 @@ impl Game {
     ///
     pub fn checkmate(&self) -> bool {
         if self.capture_king {
-            let color = if self.is_white_turn {
-                Color::White
-            } else {
-                Color::Black
-            };
+            let color = self.side_to_move();
             let kings = self.board.find(PieceType::King, color);
-            return !kings.is_empty();
+            return kings.is_empty();
         }
         if !self.check() {
             return false;
@@ -872,6 +2676,86 @@ impl Game {
         !self.has_legal_moves()
     }
 
+    /// Returns whether the position is dead: no sequence of legal moves can
+    /// end the game by checkmate, following FIDE article 5.2.2
+    /// Besides the material that can never checkmate, like a lone minor
+    /// piece or bishops on squares of the same color, the positions reachable
+    /// from this one are searched, which detects locked pawn walls that the
+    /// kings can't cross. The search is bounded: a position with too many
+    /// reachable positions is not considered dead
+    ///
+    /// # Returns
+    /// Whether the position is dead
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let game = Game::from_fen("4k3/8/8/8/8/8/8/2B1K1B1 w - - 0 1");
+    /// assert!(game.is_dead_position());
+    ///
+    /// let game = Game::from_fen("4k3/7p/1p1p1p1P/pPpPpPp1/P1P1P1P1/8/8/4K3 w - - 0 1");
+    /// assert!(game.is_dead_position());
+    ///
+    /// assert!(!Game::default().is_dead_position());
+    /// ```
+    ///
+    pub fn is_dead_position(&self) -> bool {
+        if self.insufficient_material() {
+            return true;
+        }
+        if self.capture_king {
+            return false;
+        }
+
+        let mut seen = HashSet::from([self.get_fen_reduced()]);
+        let mut queue = VecDeque::from([self.clone()]);
+        while let Some(position) = queue.pop_front() {
+            for mov in position.legal_moves() {
+                let mut next = position.clone();
+                next.apply_move(&mov);
+                next.update_position(&mov);
+                if next.checkmate() {
+                    return false;
+                }
+                if seen.insert(next.get_fen_reduced()) {
+                    if seen.len() > DEAD_POSITION_SEARCH_LIMIT {
+                        return false;
+                    }
+                    queue.push_back(next);
+                }
+            }
+        }
+        true
+    }
+
+    /// Returns whether neither player has the material to checkmate, whatever
+    /// the position of the pieces
+    ///
+    /// # Returns
+    /// Whether only the kings remain, with at most a knight or any number of
+    /// bishops on squares of the same color
+    ///
+    fn insufficient_material(&self) -> bool {
+        let mut knights = 0;
+        let mut bishop_squares = HashSet::new();
+        for (pos, piece) in self.board.pieces() {
+            match piece.piece_type {
+                PieceType::King => {}
+                PieceType::Knight => knights += 1,
+                PieceType::Bishop => {
+                    bishop_squares.insert((pos.col + pos.row).is_multiple_of(2));
+                }
+                _ => return false,
+            }
+        }
+        match knights {
+            0 => bishop_squares.len() <= 1,
+            1 => bishop_squares.is_empty(),
+            _ => false,
+        }
+    }
+
     /// Ends the game and sets the winner to the opposite of the color that resigned
     ///
     /// # Arguments
@@ -936,9 +2820,181 @@ impl Game {
         self.game_status = GameStatus::Draw(DrawReason::Agreement);
     }
 
+    /// Asks to take back the last move of a player, as done in rated play
+    /// If the opponent already replied, accepting takes back both moves
+    /// The request is dropped as soon as a move is played, undone or redone
+    ///
+    /// # Arguments
+    /// * `color`: The color of the player asking
+    ///
+    /// # Returns
+    /// The request, or `None` if the game is over, another request is pending
+    /// or the player has no move among the last two to take back
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::{Color, TakebackEvent};
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    /// game.move_piece("e5").unwrap();
+    ///
+    /// assert_eq!(game.request_takeback(Color::White), Some(TakebackEvent::Requested { color: Color::White }));
+    /// assert_eq!(game.pending_takeback(), Some(Color::White));
+    /// assert_eq!(
+    ///     game.accept_takeback(),
+    ///     Some(TakebackEvent::Accepted { color: Color::White, plies: 2 })
+    /// );
+    /// assert_eq!(game.fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    /// assert_eq!(game.pgn(), "*");
+    /// ```
+    ///
+    pub fn request_takeback(&mut self, color: Color) -> Option<TakebackEvent> {
+        if !self.is_ongoing() || self.takeback_request.is_some() {
+            return None;
+        }
+        let plies = self
+            .history
+            .line_moves()
+            .iter()
+            .rev()
+            .take(2)
+            .position(|mov| mov.piece.color == color)?
+            + 1;
+        self.takeback_request = Some((color, plies));
+        Some(TakebackEvent::Requested { color })
+    }
+
+    /// Returns the player waiting for an answer to a takeback request
+    ///
+    /// # Returns
+    /// The color of the player that asked, or `None` if there is no request
+    ///
+    pub fn pending_takeback(&self) -> Option<Color> {
+        self.takeback_request.map(|(color, _)| color)
+    }
+
+    /// Accepts the pending takeback request, undoing every move from the last
+    /// move of the player that asked and removing them from the history
+    ///
+    /// # Returns
+    /// The accepted request with the number of half moves taken back, or
+    /// `None` if there is no request or the current line is shorter than it
+    ///
+    pub fn accept_takeback(&mut self) -> Option<TakebackEvent> {
+        let (color, plies) = self.takeback_request.take()?;
+        self.history.unshare();
+        let nodes = self.history.line_nodes();
+        if plies > nodes.len() {
+            return None;
+        }
+        let first = Rc::clone(&nodes[nodes.len() - plies]);
+        for _ in 0..plies {
+            self.undo();
+        }
+        self.history.remove_next(&first);
+        Some(TakebackEvent::Accepted {
+            color,
+            plies: plies as u32,
+        })
+    }
+
+    /// Declines the pending takeback request
+    ///
+    /// # Returns
+    /// The declined request, or `None` if there is no request
+    ///
+    pub fn decline_takeback(&mut self) -> Option<TakebackEvent> {
+        let (color, _) = self.takeback_request.take()?;
+        Some(TakebackEvent::Declined { color })
+    }
+
+    /// Returns the changes of the status of the game along the current line,
+    /// with the ply and the reason of each one
+    /// The line is replayed from the starting position, so the repeated
+    /// position or the halfmove clock behind an automatic draw can be checked,
+    /// and a status set without a move, like a resignation, is listed last
+    ///
+    /// # Returns
+    /// The changes of the status, from the first one
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::{DrawReason, GameStatus, StatusTrigger};
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// for mov in ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8"] {
+    ///     game.move_piece(mov).unwrap();
+    /// }
+    ///
+    /// let changes = game.status_history();
+    /// assert_eq!(changes.len(), 1);
+    /// assert_eq!(changes[0].ply, 8);
+    /// assert_eq!(changes[0].san, Some("Ng8".to_string()));
+    /// assert_eq!(changes[0].to, GameStatus::Draw(DrawReason::ThreefoldRepetition));
+    /// assert_eq!(
+    ///     changes[0].trigger,
+    ///     StatusTrigger::Repetition {
+    ///         key: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -".to_string(),
+    ///         count: 3,
+    ///     }
+    /// );
+    /// ```
+    ///
+    pub fn status_history(&self) -> Vec<StatusChange> {
+        let mut replay = self.replay();
+        let moves = self.history_vec();
+        let mut changes = Vec::new();
+
+        for (ply, mov) in moves.iter().enumerate() {
+            let from = replay.game_status;
+            if from != GameStatus::InProgress || replay.make_move(mov).is_err() {
+                break;
+            }
+            if replay.game_status == from {
+                continue;
+            }
+
+            let trigger = match replay.game_status {
+                GameStatus::Draw(DrawReason::ThreefoldRepetition) => {
+                    let key = replay.get_fen_reduced();
+                    StatusTrigger::Repetition {
+                        count: replay.prev_positions[&key],
+                        key,
+                    }
+                }
+                GameStatus::Draw(DrawReason::FiftyMoveRule) => StatusTrigger::FiftyMoveRule {
+                    halfmove_clock: replay.halfmove_clock,
+                },
+                _ => StatusTrigger::Position,
+            };
+            changes.push(StatusChange {
+                ply: ply as u32 + 1,
+                san: Some(mov.to_string()),
+                from,
+                to: replay.game_status,
+                trigger,
+            });
+        }
+
+        if self.game_status != replay.game_status {
+            changes.push(StatusChange {
+                ply: moves.len() as u32,
+                san: None,
+                from: replay.game_status,
+                to: self.game_status,
+                trigger: StatusTrigger::Declared,
+            });
+        }
+        changes
+    }
+
     /// Finds the position of a piece that matches the given criteria to move
     ///
     /// # Arguments
+    /// * `move_str`: The move as it was given, used in the errors
     /// * `piece`: The type of piece to find
     /// * `color`: The color of the piece to find
     /// * `start_pos`: The criteria for the starting position of the piece to find
@@ -951,6 +3007,7 @@ impl Game {
     ///
     fn find_piece(
         &self,
+        move_str: &str,
         piece: PieceType,
         color: Color,
         start_pos: (Option<u8>, Option<u8>),
@@ -978,26 +3035,41 @@ impl Game {
             .collect();
 
         let mut valid_positions = Vec::new();
+        let mut reason = IllegalReason::BadPattern;
         for pos in positions {
-            if self.is_legal(
+            match self.illegal_reason(
                 &Piece {
                     color,
                     piece_type: piece,
                 },
                 &pos,
-                &end_pos,
-                &move_type,
+                end_pos,
+                move_type,
             ) {
-                valid_positions.push(pos);
+                None => valid_positions.push(pos),
+                // Report the candidate that got the furthest
+                Some(self_check @ IllegalReason::SelfCheck { .. }) => reason = self_check,
+                Some(blocked @ IllegalReason::BlockedPath { .. })
+                    if reason == IllegalReason::BadPattern =>
+                {
+                    reason = blocked
+                }
+                Some(_) => {}
             }
         }
 
-        if valid_positions.len() == 0 {
-            return Err(MoveError::Illegal);
-        } else if valid_positions.len() == 1 {
-            return Ok(valid_positions[0]);
-        } else {
-            return Err(MoveError::Ambiguous);
+        match valid_positions.len() {
+            0 => Err(MoveError::Illegal {
+                san: move_str.to_string(),
+                to: *end_pos,
+                reason,
+            }),
+            1 => Ok(valid_positions[0]),
+            _ => Err(MoveError::Ambiguous {
+                san: move_str.to_string(),
+                to: *end_pos,
+                candidates: valid_positions,
+            }),
         }
     }
 
@@ -1037,9 +3109,9 @@ impl Game {
                                 color,
                                 piece_type: piece,
                             },
-                            &pos,
-                            &end_pos,
-                            &move_type,
+                            pos,
+                            end_pos,
+                            move_type,
                         )
                     })
                     .count();
@@ -1055,9 +3127,9 @@ impl Game {
                                 color,
                                 piece_type: piece,
                             },
-                            &pos,
-                            &end_pos,
-                            &move_type,
+                            pos,
+                            end_pos,
+                            move_type,
                         )
                     })
                     .count();
@@ -1074,9 +3146,9 @@ impl Game {
                                 color,
                                 piece_type: piece,
                             },
-                            &pos,
-                            &end_pos,
-                            &move_type,
+                            pos,
+                            end_pos,
+                            move_type,
                         )
                     })
                     .count()
@@ -1090,9 +3162,9 @@ impl Game {
                                 color,
                                 piece_type: piece,
                             },
-                            &pos,
-                            &end_pos,
-                            &move_type,
+                            pos,
+                            end_pos,
+                            move_type,
                         )
                     })
                     .count()
@@ -1102,142 +3174,730 @@ impl Game {
         }
     }
 
-    /// Checks if castling is legal
+    /// Finds out why castling is illegal
+    ///
+    /// # Arguments
+    /// * `piece`: The king piece to castle
+    /// * `start_pos`: The starting position of the king piece
+    /// * `end_pos`: The ending position of the king piece
+    /// * `side`: The side to castle
+    ///
+    /// # Returns
+    /// The reason why the castling is illegal, or `None` if it's legal
+    ///
+    fn castle_illegal_reason(
+        &self,
+        piece: &Piece,
+        start_pos: &Position,
+        end_pos: &Position,
+        side: &CastleType,
+    ) -> Option<IllegalReason> {
+        assert!(piece.piece_type == PieceType::King);
+        if start_pos.row != end_pos.row {
+            return Some(IllegalReason::BadPattern);
+        }
+
+        let index = castling_index(piece.color, side);
+        if self.castling_rights & (0b1000 >> index) == 0 {
+            return Some(IllegalReason::BadPattern);
+        }
+        let Some(rook) = self.castling_rook(piece.color, side) else {
+            return Some(IllegalReason::BadPattern);
+        };
+        let rook_to = castled_rook(side, start_pos.rank());
+
+        // The squares the king and the rook cross must be empty, but only
+        // the ones the king crosses must be safe
+        let cols = [start_pos.col, end_pos.col, rook.col, rook_to.col];
+        let (first, last) = (*cols.iter().min()?, *cols.iter().max()?);
+        let crossed = |col| Position::new(col, start_pos.row);
+        let mut path: Vec<u8> = (first..=last).collect();
+        if side == &CastleType::QueenSide {
+            path.reverse();
+        }
+        for &col in &path {
+            let square = crossed(col);
+            if square != *start_pos && square != rook && self.board.is_ocupied(&square) {
+                return Some(IllegalReason::BlockedPath { at: square });
+            }
+        }
+
+        let mut board = self.board.clone();
+        board.delete_piece(&rook).ok();
+        let (first, last) = (
+            start_pos.col.min(end_pos.col),
+            start_pos.col.max(end_pos.col),
+        );
+        (first..=last).find_map(|col| {
+            board
+                .attacker(crossed(col), piece.color.opposite())
+                .map(|from| IllegalReason::SelfCheck { from })
+        })
+    }
+
+    /// Checks if there are legal moves for the current player
+    ///
+    /// # Returns
+    /// A boolean indicating if there are legal moves for the current player
+    ///
+    fn has_legal_moves(&self) -> bool {
+        self.candidate_moves()
+            .iter()
+            .any(|(piece, from, to, move_type)| self.is_legal(piece, from, to, move_type))
+    }
+
+    /// Reports the reasons why the position can't be reached in a legal game
+    ///
+    /// # Returns
+    /// The issues found, empty if the position is valid
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::{Color, Position, ValidationIssue};
+    /// use chess_lab::logic::Game;
+    ///
+    /// assert!(Game::default().validate().is_empty());
+    ///
+    /// let game = Game::from_fen("P3k3/8/8/8/8/8/8/8 w - - 0 1");
+    /// assert_eq!(
+    ///     game.validate(),
+    ///     vec![
+    ///         ValidationIssue::MissingKing(Color::White),
+    ///         ValidationIssue::PawnOnBackRank(Position::from_string("a8")),
+    ///     ]
+    /// );
+    /// ```
+    ///
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = material_issues(&self.board);
+
+        let color = self.side_to_move();
+        let kings = (
+            self.board.find(PieceType::King, color),
+            self.board.find(PieceType::King, color.opposite()),
+        );
+        if let ([king], [opponent_king]) = (kings.0.as_slice(), kings.1.as_slice()) {
+            let in_check = self.board.is_attacked(*king, color.opposite());
+            if self.board.is_attacked(*opponent_king, color) {
+                issues.push(if in_check {
+                    ValidationIssue::BothKingsInCheck
+                } else {
+                    ValidationIssue::OpponentInCheck
+                });
+            }
+        }
+
+        if let Some(en_passant) = self.en_passant {
+            let (pushed_row, from_row) = if self.is_white_turn { (4, 6) } else { (3, 1) };
+            let pushed = self
+                .board
+                .get_piece(&Position::new(en_passant.col, pushed_row));
+            if en_passant.row != (pushed_row + from_row) / 2
+                || pushed != Some(Piece::new(color.opposite(), PieceType::Pawn))
+                || self.board.is_ocupied(&en_passant)
+                || self
+                    .board
+                    .is_ocupied(&Position::new(en_passant.col, from_row))
+            {
+                issues.push(ValidationIssue::InvalidEnPassant(en_passant));
+            }
+        }
+
+        let castling_valid = [
+            (0b1000, Color::White, CastleType::KingSide),
+            (0b0100, Color::White, CastleType::QueenSide),
+            (0b0010, Color::Black, CastleType::KingSide),
+            (0b0001, Color::Black, CastleType::QueenSide),
+        ]
+        .iter()
+        .all(|(bit, color, side)| {
+            self.castling_rights & bit == 0 || self.castling_rook(*color, side).is_some()
+        });
+        if !castling_valid {
+            issues.push(ValidationIssue::InvalidCastlingRights);
+        }
+
+        if self.halfmove_clock > 100 || (self.en_passant.is_some() && self.halfmove_clock != 0) {
+            issues.push(ValidationIssue::HalfmoveClockOutOfRange(
+                self.halfmove_clock,
+            ));
+        }
+        if self.fullmove_number == 0 {
+            issues.push(ValidationIssue::InvalidFullmoveNumber);
+        }
+
+        issues
+    }
+
+    /// Returns how many times the current position occurred in the current
+    /// line, counting this one
+    /// Positions are compared as for the threefold repetition rule, so with the
+    /// standard rules and a count of two the next repetition draws the game
+    ///
+    /// # Returns
+    /// The number of times the position occurred, at least 1
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// assert_eq!(game.repetition_count(), 1);
+    ///
+    /// for mov in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+    ///     game.move_piece(mov).unwrap();
+    /// }
+    /// assert_eq!(game.repetition_count(), 2);
+    ///
+    /// game.undo();
+    /// assert_eq!(game.repetition_count(), 1);
+    /// ```
+    ///
+    pub fn repetition_count(&self) -> u32 {
+        self.prev_positions
+            .get(&self.get_fen_reduced())
+            .copied()
+            .unwrap_or(1)
+    }
+
+    /// Returns the number of plies since the last irreversible move: a pawn
+    /// move, a capture or a move that lost castling rights
+    /// No position before an irreversible move can occur again, so only the
+    /// positions since then can be repeated. Unlike the halfmove clock, which
+    /// the fifty-move rule counts, losing castling rights also resets it
+    ///
+    /// # Returns
+    /// The number of plies, adding the halfmove clock of the starting position
+    /// if no irreversible move was played since
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w Q - 7 40");
+    /// assert_eq!(game.plies_since_irreversible(), 7);
+    ///
+    /// game.move_piece("Kd1").unwrap();
+    /// game.move_piece("Kd7").unwrap();
+    /// assert_eq!(game.plies_since_irreversible(), 1);
+    /// assert_eq!(game.halfmove_clock, 9);
+    ///
+    /// game.move_piece("e4").unwrap();
+    /// assert_eq!(game.plies_since_irreversible(), 0);
+    /// ```
+    ///
+    pub fn plies_since_irreversible(&self) -> u32 {
+        let nodes = self.history.line_nodes();
+        let mut castling_rights = self.castling_rights;
+        for (plies, node) in nodes.iter().rev().enumerate() {
+            let node = node.borrow();
+            let irreversible = node.mov.piece.piece_type == PieceType::Pawn
+                || matches!(
+                    node.mov.move_type,
+                    MoveType::Normal { capture: true, .. } | MoveType::EnPassant
+                )
+                || node.castling_rights != castling_rights;
+            if irreversible {
+                return plies as u32;
+            }
+            castling_rights = node.castling_rights;
+        }
+
+        let start_clock = self
+            .start_position
+            .split(' ')
+            .nth(4)
+            .and_then(|clock| clock.parse().ok())
+            .unwrap_or(0);
+        nodes.len() as u32 + start_clock
+    }
+
+    /// Returns a hash of the position, ignoring the halfmove clock and fullmove number
+    /// The hash is stable across runs and platforms, so it can be persisted
     ///
-    /// # Arguments
-    /// * `piece`: The king piece to castle
-    /// * `start_pos`: The starting position of the king piece
-    /// * `end_pos`: The ending position of the king piece
-    /// * `side`: The side to castle
+    /// # Returns
+    /// The 64-bit FNV-1a hash of the position
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// let start = game.position_hash();
+    ///
+    /// game.move_piece("Nf3").unwrap();
+    /// assert_ne!(game.position_hash(), start);
+    ///
+    /// game.move_piece("Nf6").unwrap();
+    /// game.move_piece("Ng1").unwrap();
+    /// game.move_piece("Ng8").unwrap();
+    /// assert_eq!(game.position_hash(), start);
+    /// ```
+    ///
+    pub fn position_hash(&self) -> u64 {
+        fnv1a(&self.get_fen_reduced())
+    }
+
+    /// Returns an identifier of the game, to recognize the same game coming
+    /// from different sources
+    /// Only the players, the date, the result, the starting position and the
+    /// moves of the main line are hashed, so the other tags, the comments and
+    /// the variations don't change it. Tags are compared ignoring case and
+    /// extra spaces, and unknown values (`?`, `*`) count as missing
+    /// Like `position_hash`, the identifier is stable across runs and platforms
     ///
     /// # Returns
-    /// A boolean indicating if the castling is legal
+    /// The 64-bit FNV-1a hash of the game
     ///
-    fn is_castle_legal(
-        &self,
-        piece: &Piece,
-        start_pos: &Position,
-        end_pos: &Position,
-        side: &CastleType,
-    ) -> bool {
-        assert!(piece.piece_type == PieceType::King);
-        if start_pos.row != end_pos.row {
-            return false;
+    /// # Example
+    /// ```
+    /// use chess_lab::parsing::{parse_pgn_with_options, PgnParseOptions};
+    ///
+    /// let options = PgnParseOptions::lenient();
+    /// let game = parse_pgn_with_options(
+    ///     "[Event \"Casual\"]\n[White \"Morphy, Paul\"]\n1. e4 { Best } e5 (1... c5) *",
+    ///     &options,
+    /// )
+    /// .unwrap();
+    /// let same = parse_pgn_with_options(
+    ///     "[Site \"?\"]\n[White \"morphy,  paul\"]\n[Result \"*\"]\n1. e4 e5 *",
+    ///     &options,
+    /// )
+    /// .unwrap();
+    /// let other = parse_pgn_with_options("[White \"Morphy, Paul\"]\n1. e4 c5 *", &options).unwrap();
+    ///
+    /// assert_eq!(game.canonical_id(), same.canonical_id());
+    /// assert_ne!(game.canonical_id(), other.canonical_id());
+    /// ```
+    ///
+    pub fn canonical_id(&self) -> u64 {
+        let tag = |value: &Option<String>| {
+            let value = value
+                .as_deref()
+                .unwrap_or_default()
+                .split_whitespace()
+                .collect::<Vec<&str>>()
+                .join(" ")
+                .to_lowercase();
+            if value.chars().all(|c| matches!(c, '?' | '.' | '*')) {
+                String::new()
+            } else {
+                value
+            }
+        };
+
+        let mut fields = vec![
+            tag(&self.history.white),
+            tag(&self.history.black),
+            tag(&self.history.date),
+            tag(&self.history.result),
+            self.start_position.clone(),
+        ];
+        let mut next = self.history.root_nodes().first().map(Rc::clone);
+        while let Some(node) = next {
+            fields.push(node.borrow().uci());
+            next = node.borrow().lines.first().map(Rc::clone);
         }
+        fnv1a(&fields.join("\n"))
+    }
 
-        match side {
-            CastleType::KingSide => {
-                if piece.color == Color::White && self.castling_rights & 0b1000 == 0 {
-                    return false;
-                } else if piece.color == Color::Black && self.castling_rights & 0b0010 == 0 {
-                    return false;
-                }
+    /// Gives the FEN string of the position withouth the halfmove clock and fullmove number
+    /// to be used as position identifier
+    /// Following the FIDE rules, two positions are the same only if the same
+    /// moves are possible in them: the en passant square is kept only if an
+    /// en passant capture is legal, and the castling rights only if the king
+    /// and the rook are still in place
+    ///
+    /// # Returns
+    /// The FEN string of the position withouth the halfmove clock and fullmove number
+    ///
+    fn get_fen_reduced(&self) -> String {
+        let fen = self.fen_with_style(FenStyle::XFen);
+        fen.split(' ').take(4).collect::<Vec<&str>>().join(" ")
+    }
 
-                for col in start_pos.col + 0..end_pos.col + 1 {
-                    let new_pos = Position::new(col, start_pos.row);
-                    if (&new_pos != start_pos && self.board.is_ocupied(&new_pos))
-                        || self
-                            .board
-                            .is_attacked(Position::new(col, start_pos.row), piece.color.opposite())
-                    {
-                        return false;
-                    }
-                }
-                return true;
+    /// Writes the castling rights of a FEN in the given style
+    /// Apart from the standard style, the rights whose king or rook left
+    /// their squares are dropped
+    ///
+    /// # Arguments
+    /// * `style`: The style of the FEN
+    ///
+    /// # Returns
+    /// The castling field, `-` if no right is left
+    ///
+    fn castling_field(&self, style: FenStyle) -> String {
+        let castling: String = [
+            ('K', 0b1000, Color::White, CastleType::KingSide),
+            ('Q', 0b0100, Color::White, CastleType::QueenSide),
+            ('k', 0b0010, Color::Black, CastleType::KingSide),
+            ('q', 0b0001, Color::Black, CastleType::QueenSide),
+        ]
+        .into_iter()
+        .filter(|(_, bit, _, _)| self.castling_rights & bit != 0)
+        .filter_map(|(c, _, color, side)| {
+            if style == FenStyle::Standard {
+                return Some(c);
             }
-            CastleType::QueenSide => {
-                if piece.color == Color::White && self.castling_rights & 0b0100 == 0 {
-                    return false;
-                } else if piece.color == Color::Black && self.castling_rights & 0b0001 == 0 {
-                    return false;
-                }
-
-                for col in start_pos.col - 0..end_pos.col + 1 {
-                    if self.board.is_ocupied(&Position::new(col, start_pos.row))
-                        || self
-                            .board
-                            .is_attacked(Position::new(col, start_pos.row), piece.color.opposite())
-                    {
-                        return false;
-                    }
-                }
-                return true;
+            let rook = self.castling_rook(color, &side)?;
+            // X-FEN only names the file when an outer rook is on the side
+            if style == FenStyle::XFen && self.outermost_rook(color, &side) == Some(rook) {
+                return Some(c);
             }
+            let file = (b'a' + rook.col) as char;
+            Some(match color {
+                Color::White => file.to_ascii_uppercase(),
+                Color::Black => file,
+            })
+        })
+        .collect();
+
+        if castling.is_empty() {
+            String::from("-")
+        } else {
+            castling
         }
     }
 
-    /// Checks if there are legal moves for the current player
+    /// Finds the rook a color castles with on a side: the rook on the file
+    /// given by the FEN, the outermost one of the side by default
+    ///
+    /// # Arguments
+    /// * `color`: The color of the pieces
+    /// * `side`: The side to castle to
     ///
     /// # Returns
-    /// A boolean indicating if there are legal moves for the current player
+    /// The position of the rook, or `None` if the king or the rook aren't
+    /// on the back rank
     ///
-    fn has_legal_moves(&self) -> bool {
-        let color = if self.is_white_turn {
-            Color::White
-        } else {
-            Color::Black
-        };
+    fn castling_rook(&self, color: Color, side: &CastleType) -> Option<Position> {
+        let file = self.castling_files[castling_index(color, side)]?;
+        self.back_rank_rooks(color, side)
+            .into_iter()
+            .find(|rook| rook.col == file)
+    }
 
-        for piece_pos in self.board.find_all(color) {
-            let piece = self.board.get_piece(&piece_pos).unwrap();
-            for col in 0..8 {
-                for row in 0..8 {
-                    let end_pos = Position::new(col, row);
+    /// Finds the outermost rook of a color on the back rank, on a side of
+    /// the king
+    ///
+    /// # Arguments
+    /// * `color`: The color of the pieces
+    /// * `side`: The side of the king
+    ///
+    /// # Returns
+    /// The position of the rook, or `None` if there is none
+    ///
+    fn outermost_rook(&self, color: Color, side: &CastleType) -> Option<Position> {
+        let rooks = self.back_rank_rooks(color, side).into_iter();
+        match side {
+            CastleType::KingSide => rooks.max_by_key(|rook| rook.col),
+            CastleType::QueenSide => rooks.min_by_key(|rook| rook.col),
+        }
+    }
 
-                    let mut board = self.board.clone();
-                    if !board.can_capture(&piece_pos, &end_pos) {
-                        continue;
+    /// Finds the rooks of a color on the back rank, on a side of the king
+    ///
+    /// # Arguments
+    /// * `color`: The color of the pieces
+    /// * `side`: The side of the king
+    ///
+    /// # Returns
+    /// The positions of the rooks, empty if the king isn't on the back rank
+    ///
+    fn back_rank_rooks(&self, color: Color, side: &CastleType) -> Vec<Position> {
+        let back_rank = match color {
+            Color::White => 0,
+            Color::Black => 7,
+        };
+        let Some(king) = self
+            .board
+            .find(PieceType::King, color)
+            .into_iter()
+            .find(|king| king.row == back_rank)
+        else {
+            return Vec::new();
+        };
+        self.board
+            .find(PieceType::Rook, color)
+            .into_iter()
+            .filter(|rook| {
+                rook.row == back_rank
+                    && match side {
+                        CastleType::KingSide => rook.col > king.col,
+                        CastleType::QueenSide => rook.col < king.col,
                     }
+            })
+            .collect()
+    }
 
-                    board.move_piece(&piece_pos, &end_pos).unwrap();
+    /// Checks if the player to move can legally capture en passant
+    ///
+    /// # Arguments
+    /// * `en_passant`: The en passant square
+    ///
+    /// # Returns
+    /// Whether a pawn of the player to move can legally capture on the square
+    ///
+    fn en_passant_legal(&self, en_passant: &Position) -> bool {
+        let color = self.side_to_move();
+        let pawn = Piece::new(color, PieceType::Pawn);
+        self.board
+            .find(PieceType::Pawn, color)
+            .iter()
+            .any(|pos| self.is_legal(&pawn, pos, en_passant, &MoveType::EnPassant))
+    }
+}
 
-                    let king = board.find(PieceType::King, piece.color)[0];
-                    if !board.is_attacked(king, piece.color.opposite()) {
-                        return true;
-                    }
-                }
+/// Finds the issues of the pieces on a board that make it unreachable: the
+/// number of kings, pawns and promoted pieces of each color and the pawns on
+/// the back ranks
+///
+/// # Arguments
+/// * `board`: The board
+///
+/// # Returns
+/// The issues found, empty if the pieces are valid
+///
+pub(crate) fn material_issues(board: &Board) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    for color in [Color::White, Color::Black] {
+        match board.find(PieceType::King, color).len() {
+            0 => issues.push(ValidationIssue::MissingKing(color)),
+            1 => {}
+            _ => issues.push(ValidationIssue::TooManyKings(color)),
+        }
+
+        let pawns = board.find(PieceType::Pawn, color).len();
+        if pawns > 8 {
+            issues.push(ValidationIssue::TooManyPawns(color));
+        }
+        let promoted: usize = [
+            (PieceType::Queen, 1),
+            (PieceType::Rook, 2),
+            (PieceType::Bishop, 2),
+            (PieceType::Knight, 2),
+        ]
+        .iter()
+        .map(|(piece_type, initial)| {
+            board
+                .find(*piece_type, color)
+                .len()
+                .saturating_sub(*initial)
+        })
+        .sum();
+        if board.find_all(color).len() > 16 || pawns + promoted > 8 {
+            issues.push(ValidationIssue::TooManyPieces(color));
+        }
+    }
+
+    for color in [Color::White, Color::Black] {
+        for pawn in board.find(PieceType::Pawn, color) {
+            if pawn.row == 0 || pawn.row == 7 {
+                issues.push(ValidationIssue::PawnOnBackRank(pawn));
             }
         }
-        false
     }
 
-    /// Gives the FEN string of the position withouth the halfmove clock and fullmove number
-    /// to be used as position identifier
+    issues
+}
+
+/// Gives the index of a castling right, in the order of `KQkq`
+///
+/// # Arguments
+/// * `color`: The color that castles
+/// * `side`: The side to castle to
+///
+/// # Returns
+/// The index, from 0 for `K` to 3 for `q`
+///
+fn castling_index(color: Color, side: &CastleType) -> usize {
+    match (color, side) {
+        (Color::White, CastleType::KingSide) => 0,
+        (Color::White, CastleType::QueenSide) => 1,
+        (Color::Black, CastleType::KingSide) => 2,
+        (Color::Black, CastleType::QueenSide) => 3,
+    }
+}
+
+/// Gives the square a rook lands on after castling
+///
+/// # Arguments
+/// * `side`: The side of the castle
+/// * `rank`: The back rank of the castling color
+///
+/// # Returns
+/// The `f` square for a king side castle, the `d` square otherwise
+///
+fn castled_rook(side: &CastleType, rank: Rank) -> Position {
+    match side {
+        CastleType::KingSide => Position::from_file_rank(File::F, rank),
+        CastleType::QueenSide => Position::from_file_rank(File::D, rank),
+    }
+}
+
+/// Moves the king and the rook of a castle, lifting both first since in
+/// Chess960 either can land on the square the other one leaves
+///
+/// # Arguments
+/// * `board`: The board
+/// * `king`: The squares the king moves from and to
+/// * `rook`: The squares the rook moves from and to
+///
+fn castle_on(board: &mut Board, king: [Position; 2], rook: [Position; 2]) {
+    let king_piece = board.delete_piece(&king[0]).unwrap();
+    let rook_piece = board.delete_piece(&rook[0]).unwrap();
+    board.set_piece(king_piece, &king[1]).unwrap();
+    board.set_piece(rook_piece, &rook[1]).unwrap();
+}
+
+/// Plays a move on a board, without checking it
+///
+/// # Arguments
+/// * `board`: The board, with the position before the move
+/// * `mov`: The move
+///
+fn play_on(board: &mut Board, mov: &Move) {
+    if let MoveType::Castle { side } = &mov.move_type {
+        let rook_to = castled_rook(side, mov.to.rank());
+        castle_on(board, [mov.from, mov.to], [mov.rook_from.unwrap(), rook_to]);
+        return;
+    }
+    board.move_piece(&mov.from, &mov.to).unwrap();
+
+    match &mov.move_type {
+        MoveType::EnPassant => {
+            board
+                .delete_piece(&Position::new(mov.to.col, mov.from.row))
+                .unwrap();
+        }
+        MoveType::Normal {
+            capture: _,
+            promotion: Some(piece_type),
+        } => {
+            board.delete_piece(&mov.to).unwrap();
+            board
+                .set_piece(Piece::new(mov.piece.color, *piece_type), &mov.to)
+                .unwrap();
+        }
+        _ => {}
+    }
+}
+
+/// Hashes a text with the 64-bit FNV-1a function, which is stable across runs
+/// and platforms
+///
+/// # Arguments
+/// * `text`: The text
+///
+/// # Returns
+/// The hash of the text
+///
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl Display for Game {
+    /// Draws the board in ASCII, from White's side, followed by the side to
+    /// move, the castling rights, the en passant square and the counters
     ///
     /// # Returns
-    /// The FEN string of the position withouth the halfmove clock and fullmove number
+    /// The drawing of the game
     ///
-    fn get_fen_reduced(&self) -> String {
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Game;
+    ///
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    ///
+    /// assert_eq!(
+    ///     game.to_string(),
+    ///     "8 r n b q k b n r\n\
+    ///      7 p p p p p p p p\n\
+    ///      6 . . . . . . . .\n\
+    ///      5 . . . . . . . .\n\
+    ///      4 . . . . P . . .\n\
+    ///      3 . . . . . . . .\n\
+    ///      2 P P P P . P P P\n\
+    ///      1 R N B Q K B N R\n\
+    ///      \x20 a b c d e f g h\n\
+    ///      Black to move\n\
+    ///      Castling: KQkq\n\
+    ///      En passant: -\n\
+    ///      Halfmove clock: 0, fullmove number: 1"
+    /// );
+    /// ```
+    ///
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        for row in (0..8).rev() {
+            write!(f, "{}", row + 1)?;
+            for col in 0..8 {
+                match self.board.get_piece(&Position::new(col, row)) {
+                    Some(piece) => write!(f, " {}", piece)?,
+                    None => write!(f, " .")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "  a b c d e f g h")?;
+
         let fen = self.fen();
-        let mut fen_parts: Vec<&str> = fen.split_whitespace().collect();
-        fen_parts.pop();
-        fen_parts.pop();
-        fen_parts.join(" ")
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        let side = match self.side_to_move() {
+            Color::White => "White",
+            Color::Black => "Black",
+        };
+        writeln!(f, "{} to move", side)?;
+        writeln!(f, "Castling: {}", fields[2])?;
+        writeln!(f, "En passant: {}", fields[3])?;
+        write!(
+            f,
+            "Halfmove clock: {}, fullmove number: {}",
+            self.halfmove_clock, self.fullmove_number
+        )
     }
 }
 
-impl ToString for Game {
-    /// Convert the game to a FEN string
-    ///
-    /// # Returns
-    /// The FEN string
+impl Debug for Game {
+    /// Shows the position, the status and the number of moves played, instead
+    /// of the whole board and history
     ///
     /// # Example
     /// ```
     /// use chess_lab::logic::Game;
     ///
-    /// let game = Game::default();
-    /// assert_eq!(game.fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    /// let mut game = Game::default();
+    /// game.move_piece("e4").unwrap();
+    ///
+    /// assert_eq!(
+    ///     format!("{:?}", game),
+    ///     "Game { fen: \"rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1\", game_status: InProgress, plies: 1 }"
+    /// );
     /// ```
     ///
-    fn to_string(&self) -> String {
-        self.fen()
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        f.debug_struct("Game")
+            .field("fen", &self.fen())
+            .field("game_status", &self.game_status)
+            .field("plies", &self.history.line_nodes().len())
+            .finish()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Game;
+    use super::{Game, RuleSet};
+    use crate::{
+        constants::{
+            Color, DrawReason, FenStyle, GameStatus, MoveType, Odds, Position, StatusChange,
+            StatusTrigger, TakebackEvent, ValidationIssue, WinReason,
+        },
+        errors::{IllegalReason, MoveError},
+        parsing::{parse_pgn_with_options, PgnParseOptions},
+    };
 
     #[test]
     fn test_fen() {
@@ -1301,6 +3961,255 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_wrong_color() {
+        let mut game = Game::default();
+        assert_eq!(
+            game.move_piece_coords(&Position::from_string("e7"), &Position::from_string("e5")),
+            Err(MoveError::WrongColor {
+                san: "e7e5".to_string(),
+                color: Color::Black,
+            })
+        );
+        assert_eq!(
+            game.move_piece("Ng8-f6"),
+            Err(MoveError::WrongColor {
+                san: "Ng8-f6".to_string(),
+                color: Color::Black,
+            })
+        );
+        assert!(matches!(
+            game.move_piece("b8c6"),
+            Err(MoveError::WrongColor { .. })
+        ));
+
+        game.move_piece("e2e4").unwrap();
+        assert_eq!(game.side_to_move(), Color::Black);
+        assert!(matches!(
+            game.move_piece("d2d4"),
+            Err(MoveError::WrongColor {
+                color: Color::White,
+                ..
+            })
+        ));
+        game.move_piece("e7e5").unwrap();
+    }
+
+    #[test]
+    fn test_pawn_path() {
+        let mut game = Game::from_fen("4k3/8/8/8/8/4n3/4P3/4K3 w - - 0 1");
+        for mov in ["e4", "e3"] {
+            assert_eq!(
+                game.move_piece(mov),
+                Err(MoveError::Illegal {
+                    san: mov.to_string(),
+                    to: Position::from_string(mov),
+                    reason: IllegalReason::BlockedPath {
+                        at: Position::from_string("e3"),
+                    },
+                })
+            );
+        }
+        assert!(matches!(
+            game.move_piece("exd3"),
+            Err(MoveError::Illegal {
+                reason: IllegalReason::BadPattern,
+                ..
+            })
+        ));
+        game.move_piece("exf3").unwrap_err();
+        assert_eq!(game.legal_moves().len(), 2);
+
+        let mut game = Game::from_fen("4k3/8/8/8/8/4p3/4P3/4K3 w - - 0 1");
+        assert!(game.move_piece("e4").is_err());
+        assert_eq!(game.legal_moves().len(), 2);
+    }
+
+    #[test]
+    fn test_legal_moves() {
+        let game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        let moves: Vec<String> = game.legal_moves().iter().map(|m| m.to_string()).collect();
+        assert!(moves.contains(&"O-O".to_string()));
+        assert!(moves.contains(&"O-O-O".to_string()));
+        assert_eq!(moves.len(), 26);
+
+        // A piece between the rook and the king blocks castling, an attacked
+        // b-file doesn't
+        let game = Game::from_fen("1r2k2r/8/8/8/8/8/8/RN2K2R w KQk - 0 1");
+        let moves: Vec<String> = game.legal_moves().iter().map(|m| m.to_string()).collect();
+        assert!(!moves.contains(&"O-O-O".to_string()));
+        let game = Game::from_fen("1r2k2r/8/8/8/8/8/8/R3K2R w KQk - 0 1");
+        let moves: Vec<String> = game.legal_moves().iter().map(|m| m.to_string()).collect();
+        assert!(moves.contains(&"O-O-O".to_string()));
+        let game = Game::from_fen("3rk2r/8/8/8/8/8/8/R3K2R w KQk - 0 1");
+        let moves: Vec<String> = game.legal_moves().iter().map(|m| m.to_string()).collect();
+        assert!(!moves.contains(&"O-O-O".to_string()));
+        assert!(game
+            .pseudo_legal_moves()
+            .iter()
+            .any(|m| m.to_string() == "O-O-O"));
+
+        // Promotions, en passant and disambiguation
+        let game = Game::from_fen("7k/1P6/8/3pP3/8/8/8/2N1K1N1 w - d6 0 1");
+        let moves: Vec<String> = game.legal_moves().iter().map(|m| m.to_string()).collect();
+        for mov in ["b8=N", "b8=Q", "e5xd6", "e6", "Nce2", "Nge2", "Nb3"] {
+            assert!(moves.contains(&mov.to_string()), "{} missing", mov);
+        }
+
+        // The king can't move, but the pawn can
+        let game = Game::from_fen("7k/8/8/8/8/8/P1q5/K7 w - - 0 1");
+        let moves: Vec<String> = game.legal_moves().iter().map(|m| m.to_string()).collect();
+        assert_eq!(moves, vec!["a3", "a4"]);
+        assert!(!game.stalemate());
+    }
+
+    #[test]
+    fn test_status_history() {
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 98 60");
+        assert!(game.status_history().is_empty());
+
+        game.move_piece("Ra2").unwrap();
+        game.move_piece("Kd8").unwrap();
+        let changes = game.status_history();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].ply, 2);
+        assert_eq!(
+            changes[0].trigger,
+            StatusTrigger::FiftyMoveRule {
+                halfmove_clock: 100
+            }
+        );
+        assert_eq!(changes[0].from, GameStatus::InProgress);
+
+        let mut game = Game::from_fen("7k/8/5Q2/8/8/8/8/K7 w - - 0 1");
+        game.move_piece("Qf7").unwrap();
+        assert_eq!(
+            game.status_history(),
+            vec![StatusChange {
+                ply: 1,
+                san: Some("Qf7".to_string()),
+                from: GameStatus::InProgress,
+                to: GameStatus::Draw(DrawReason::Stalemate),
+                trigger: StatusTrigger::Position,
+            }]
+        );
+
+        let mut game = Game::default();
+        game.move_piece("e4").unwrap();
+        game.resign(Color::Black);
+        let changes = game.status_history();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].ply, 1);
+        assert_eq!(changes[0].san, None);
+        assert_eq!(changes[0].trigger, StatusTrigger::Declared);
+    }
+
+    #[test]
+    fn test_fork() {
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+        assert_eq!(game.fork().pgn(), game.pgn());
+
+        game.move_piece("O-O").unwrap();
+        game.history.set_comment(Some("Safe".to_string()));
+        game.move_piece("Kd7").unwrap();
+        game.move_piece("Rd1").unwrap();
+        game.undo();
+
+        let mut analysis = game.fork();
+        assert_eq!(analysis.fen(), game.fen());
+        assert_eq!(analysis.prev_positions, game.prev_positions);
+        analysis.history.set_comment(Some("Active".to_string()));
+        analysis.undo();
+        analysis.history.set_comment(None);
+        analysis.redo();
+        analysis.move_piece("Re1").unwrap();
+
+        assert!(game.pgn().ends_with("1. O-O { Safe } Kd7 2. Rd1+ *"));
+        assert!(analysis
+            .pgn()
+            .ends_with("1. O-O Kd7 { Active } 2. Rd1+ (2. Re1) *"));
+
+        // Changes to the original don't reach the fork either
+        let fork = game.fork();
+        game.redo();
+        game.history.set_comment(Some("Check".to_string()));
+        assert!(fork.pgn().ends_with("1. O-O { Safe } Kd7 2. Rd1+ *"));
+    }
+
+    #[test]
+    fn test_make_move() {
+        for fen in [
+            "r1bqk2r/pp2bppp/2n1pn2/2ppP3/3P4/2PB1N2/PP1N1PPP/R1BQK2R w KQkq d6 0 8",
+            "r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1",
+        ] {
+            let mut game = Game::from_fen(fen);
+            for mov in game.legal_moves() {
+                let mut expected = Game::from_fen(fen);
+                expected.move_piece(&mov.to_string()).unwrap();
+
+                game.make_move(&mov).unwrap();
+                assert_eq!(game.fen(), expected.fen(), "{}", mov);
+                game.undo();
+                assert_eq!(game.fen(), fen);
+            }
+        }
+
+        // Replaying undone moves follows the line instead of repeating it
+        let mut game = Game::default();
+        for _ in 0..2 {
+            game.move_piece("e4").unwrap();
+            game.move_piece("e5").unwrap();
+            game.undo();
+            game.undo();
+        }
+        game.redo();
+        game.redo();
+        assert_eq!(game.pgn(), "1. e4 e5 *");
+
+        let mut game = Game::default();
+        let mov = game.legal_moves()[0].clone();
+        game.move_piece("e4").unwrap();
+        assert!(matches!(
+            game.make_move(&mov),
+            Err(MoveError::WrongColor { .. })
+        ));
+        let mut capture = game.legal_moves()[0].clone();
+        capture.move_type = MoveType::Normal {
+            capture: true,
+            promotion: None,
+        };
+        assert!(matches!(
+            game.make_move(&capture),
+            Err(MoveError::Illegal { .. })
+        ));
+    }
+
+    #[test]
+    fn test_en_passant_suffix() {
+        let mut game = Game::from_fen("4k3/8/8/3pPp2/8/8/8/4K3 w - f6 0 1");
+        assert_eq!(
+            game.move_piece("exd6 e.p."),
+            Err(MoveError::IllegalEnPassant {
+                san: "exd6 e.p.".to_string(),
+                to: Position::from_string("d6"),
+            })
+        );
+        assert_eq!(
+            game.move_piece("exd6"),
+            Err(MoveError::IllegalEnPassant {
+                san: "exd6".to_string(),
+                to: Position::from_string("d6"),
+            })
+        );
+        assert!(matches!(
+            game.move_piece("Kd2 e.p."),
+            Err(MoveError::Invalid { .. })
+        ));
+
+        game.move_piece("exf6 e.p.").unwrap();
+        assert_eq!(game.fen(), "4k3/8/5P2/3p4/8/8/8/4K3 b - - 0 1");
+    }
+
     #[test]
     fn test_castle_rights() {
         let mut game = Game::default();
@@ -1572,7 +4481,54 @@ mod tests {
         let pgn = game.pgn();
         assert_eq!(
             pgn,
-            "1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 (6... O-O 7. c3 b5 8. Bc2) 7. Bb3 O-O 8. c3 d5"
+            "1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 (6... O-O 7. c3 b5 8. Bc2) 7. Bb3 O-O 8. c3 d5 *"
+        );
+    }
+
+    #[test]
+    fn test_pgn_setup_tags() {
+        let fen = "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1";
+        let mut game = Game::from_fen(fen);
+        game.move_piece("e4").unwrap();
+        assert_eq!(
+            game.pgn(),
+            format!("[SetUp \"1\"]\n[FEN \"{}\"]\n1. e4 *", fen)
+        );
+
+        game.setup_tags = false;
+        assert_eq!(game.pgn(), "1. e4 *");
+
+        let mut game = Game::from_fen(&Game::default().fen());
+        game.move_piece("e4").unwrap();
+        assert_eq!(game.pgn(), "1. e4 *");
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut game = Game::default();
+        game.move_piece("e4").unwrap();
+        game.move_piece("e5").unwrap();
+        let snapshot = game.snapshot();
+
+        game.move_piece("Nf3").unwrap();
+        game.move_piece("Nc6").unwrap();
+        game.move_piece("Bb5").unwrap();
+        game.restore(&snapshot);
+
+        assert_eq!(
+            game.fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"
+        );
+        assert_eq!(game.prev_positions.len(), 3);
+
+        game.move_piece("d4").unwrap();
+        assert_eq!(game.pgn(), "1. e4 e5 2. Nf3 (2. d4) Nc6 3. Bb5 *");
+
+        game.restore(&snapshot);
+        game.redo();
+        assert_eq!(
+            game.fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
         );
     }
 
@@ -1585,6 +4541,14 @@ mod tests {
         assert!(game.check());
     }
 
+    #[test]
+    fn test_king_escapes_check() {
+        let mut game = Game::from_fen("4k3/8/8/5q2/8/8/8/1K6 w - - 0 1");
+        assert!(game.check());
+        game.move_piece("Ka1").unwrap();
+        assert_eq!(game.fen(), "4k3/8/8/5q2/8/8/8/K7 b - - 1 1");
+    }
+
     #[test]
     fn test_checkmate() {
         let mut game = Game::default();
@@ -1598,9 +4562,584 @@ mod tests {
         assert!(game.checkmate());
     }
 
+    #[test]
+    fn test_explain_illegal() {
+        let square = Position::from_string;
+        let game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K1NR w KQkq - 0 1");
+        assert_eq!(
+            game.explain_illegal("O-O"),
+            Some(IllegalReason::BlockedPath { at: square("g1") })
+        );
+
+        let game = Game::from_fen("r3k2r/8/8/8/8/3N4/3P1r2/R3K2R w KQkq - 0 1");
+        assert_eq!(
+            game.explain_illegal("O-O"),
+            Some(IllegalReason::SelfCheck { from: square("f2") })
+        );
+        assert_eq!(game.explain_illegal("O-O-O"), None);
+        assert_eq!(
+            game.explain_illegal("d4"),
+            Some(IllegalReason::BlockedPath { at: square("d3") })
+        );
+        assert_eq!(game.explain_illegal("e8g8"), Some(IllegalReason::WrongTurn));
+        assert_eq!(game.explain_illegal("Nz9"), None);
+    }
+
+    #[test]
+    fn test_hints() {
+        let game = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K2R w K d6 0 2");
+        let captures = game.captures_available();
+        assert_eq!(captures.len(), 1);
+        assert_eq!(captures[0].move_type, MoveType::EnPassant);
+
+        // Castling and discovered checks are found too
+        let game = Game::from_fen("5k2/8/8/8/8/8/8/4K2R w K - 0 1");
+        let checks: Vec<String> = game
+            .checks_available()
+            .iter()
+            .map(|mov| mov.to_string())
+            .collect();
+        assert_eq!(checks, vec!["Rf1+", "Rh8+", "O-O+"]);
+        assert!(game.checks_available().iter().all(|mov| mov.check));
+
+        // The pawn on d4 defends the one on c3, which attacks the bishop
+        let game = Game::from_fen("4k3/8/8/8/3p4/2p5/1B6/4K3 w - - 0 1");
+        assert!(game.hanging_pieces(Color::Black).is_empty());
+        assert_eq!(
+            game.hanging_pieces(Color::White),
+            vec![Position::from_string("b2")]
+        );
+    }
+
     #[test]
     fn test_stalemate() {
         let game = Game::from_fen("8/8/8/8/8/4KQ2/8/4k3 b - - 0 1");
         assert!(game.stalemate());
     }
+
+    #[test]
+    fn test_move_errors() {
+        let mut game = Game::default();
+        assert_eq!(
+            game.move_piece("e9"),
+            Err(MoveError::Invalid {
+                san: String::from("e9")
+            })
+        );
+        assert_eq!(
+            game.move_piece("Bc4"),
+            Err(MoveError::Illegal {
+                san: String::from("Bc4"),
+                to: Position::from_string("c4"),
+                reason: IllegalReason::BlockedPath {
+                    at: Position::from_string("e2"),
+                },
+            })
+        );
+        assert_eq!(
+            game.move_piece("Nd4"),
+            Err(MoveError::Illegal {
+                san: String::from("Nd4"),
+                to: Position::from_string("d4"),
+                reason: IllegalReason::BadPattern,
+            })
+        );
+
+        let mut game = Game::from_fen("4k3/8/8/8/1N3N2/8/4r3/4K3 w - - 0 1");
+        assert_eq!(
+            game.move_piece("Kf2"),
+            Err(MoveError::Illegal {
+                san: String::from("Kf2"),
+                to: Position::from_string("f2"),
+                reason: IllegalReason::SelfCheck {
+                    from: Position::from_string("e2"),
+                },
+            })
+        );
+
+        let mut game = Game::from_fen("4k3/8/8/8/1N3N2/8/8/4K3 w - - 0 1");
+        let err = game.move_piece("Nd3").unwrap_err();
+        assert_eq!(
+            err,
+            MoveError::Ambiguous {
+                san: String::from("Nd3"),
+                to: Position::from_string("d3"),
+                candidates: vec![Position::from_string("b4"), Position::from_string("f4")],
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "ambiguous move Nd3: 2 pieces can move to d3"
+        );
+    }
+
+    #[test]
+    fn test_repetition_key() {
+        // No pawn can capture en passant
+        let game = Game::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1");
+        let without = Game::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1");
+        assert_eq!(game.position_hash(), without.position_hash());
+
+        // The capture would expose the king along the rank
+        let game = Game::from_fen("8/8/8/8/k2pP2R/8/8/4K3 b - e3 0 1");
+        let without = Game::from_fen("8/8/8/8/k2pP2R/8/8/4K3 b - - 0 1");
+        assert_eq!(game.position_hash(), without.position_hash());
+
+        // The capture is legal
+        let game = Game::from_fen("8/8/8/8/k2pP3/8/8/4K3 b - e3 0 1");
+        let without = Game::from_fen("8/8/8/8/k2pP3/8/8/4K3 b - - 0 1");
+        assert_ne!(game.position_hash(), without.position_hash());
+
+        // The rook needed to castle is gone
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w KQ - 0 1");
+        let without = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1");
+        assert_eq!(game.position_hash(), without.position_hash());
+    }
+
+    #[test]
+    fn test_repetition_with_en_passant_square() {
+        let mut game =
+            Game::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+        for mov in ["Nf6", "Nf3", "Ng8", "Ng1", "Nf6", "Nf3", "Ng8"] {
+            assert_eq!(game.move_piece(mov), Ok(GameStatus::InProgress));
+        }
+        assert_eq!(
+            game.move_piece("Ng1"),
+            Ok(GameStatus::Draw(DrawReason::ThreefoldRepetition))
+        );
+    }
+
+    #[test]
+    fn test_en_passant_exposing_king() {
+        let mut game = Game::from_fen("8/8/8/8/k2pP2R/8/8/4K3 b - e3 0 1");
+        assert!(matches!(
+            game.move_piece("dxe3"),
+            Err(MoveError::Illegal {
+                reason: IllegalReason::SelfCheck {
+                    from: Position { col: 7, row: 3 },
+                },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_validate() {
+        let game = Game::from_fen("4k3/8/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQ e3 0 1");
+        assert_eq!(game.validate(), vec![]);
+
+        let game = Game::from_fen("4k3/4R3/8/8/8/8/8/4K2r w K e3 3 1");
+        assert_eq!(
+            game.validate(),
+            vec![
+                ValidationIssue::BothKingsInCheck,
+                ValidationIssue::InvalidEnPassant(Position::from_string("e3")),
+                ValidationIssue::InvalidCastlingRights,
+                ValidationIssue::HalfmoveClockOutOfRange(3),
+            ]
+        );
+
+        let game = Game::from_fen("4k3/4R3/8/8/8/8/8/4K3 w - - 0 1");
+        assert_eq!(game.validate(), vec![ValidationIssue::OpponentInCheck]);
+
+        let game = Game::from_fen("qqk5/8/8/8/8/pppppppp/p7/K7 b - - 0 1");
+        assert_eq!(
+            game.validate(),
+            vec![
+                ValidationIssue::TooManyPawns(Color::Black),
+                ValidationIssue::TooManyPieces(Color::Black),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fen_counters() {
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 99999999999 0");
+        assert_eq!(game.halfmove_clock, u32::MAX);
+        assert_eq!(game.fullmove_number, 0);
+        assert_eq!(
+            game.validate(),
+            vec![
+                ValidationIssue::HalfmoveClockOutOfRange(u32::MAX),
+                ValidationIssue::InvalidFullmoveNumber,
+            ]
+        );
+
+        let game = Game::try_from_fen_repaired("4k3/8/8/8/4P3/8/8/4K3 b - e3 7 12").unwrap();
+        assert_eq!(game.fen(), "4k3/8/8/8/4P3/8/8/4K3 b - e3 0 12");
+        assert_eq!(game.start_position, game.fen());
+    }
+
+    #[test]
+    fn test_fen_styles() {
+        // The b-pawn can't be taken en passant and the h-rook has moved
+        let game = Game::from_fen("r3k1r1/8/8/1p6/8/8/8/R3K2R w KQkq b6 0 1");
+        assert_eq!(
+            game.fen_with_style(FenStyle::Standard),
+            "r3k1r1/8/8/1p6/8/8/8/R3K2R w KQkq b6 0 1"
+        );
+        assert_eq!(
+            game.fen_with_style(FenStyle::XFen),
+            "r3k1r1/8/8/1p6/8/8/8/R3K2R w KQkq - 0 1"
+        );
+        assert_eq!(
+            game.fen_with_style(FenStyle::ShredderFen),
+            "r3k1r1/8/8/1p6/8/8/8/R3K2R w HAga - 0 1"
+        );
+
+        // Rook files round trip through the standard letters
+        for fen in [
+            "rk2r3/8/8/8/8/8/8/RK2R3 w EAea - 0 1",
+            "1r2k1r1/8/8/8/8/8/8/1R2K1R1 b GBgb - 3 9",
+            "4k3/8/8/8/8/8/8/R3K3 w A - 0 1",
+        ] {
+            let game = Game::from_fen(fen);
+            assert_eq!(game.fen_with_style(FenStyle::ShredderFen), fen);
+            let standard = Game::from_fen(&game.fen_with_style(FenStyle::XFen));
+            assert_eq!(standard.fen_with_style(FenStyle::ShredderFen), fen);
+        }
+
+        let game = Game::from_fen("1r2k1r1/8/8/8/8/8/8/1R2K1R1 w GBgb - 0 1");
+        assert_eq!(game.fen(), "1r2k1r1/8/8/8/8/8/8/1R2K1R1 w KQkq - 0 1");
+        assert!(game.validate().is_empty());
+
+        // A rook file needs a king on the back rank, on another file
+        assert!(Game::try_from_fen("4k3/8/8/8/8/8/4K3/R7 w A - 0 1").is_err());
+        assert!(Game::try_from_fen("4k3/8/8/8/8/8/8/4K3 w E - 0 1").is_err());
+
+        // An inner rook keeps its file, and is the one that castles
+        let mut game = Game::from_fen("6k1/8/8/8/8/8/8/5KRR w G - 0 1");
+        assert_eq!(
+            game.fen_with_style(FenStyle::XFen),
+            "6k1/8/8/8/8/8/8/5KRR w G - 0 1"
+        );
+        assert_eq!(
+            game.fen_with_style(FenStyle::ShredderFen),
+            "6k1/8/8/8/8/8/8/5KRR w G - 0 1"
+        );
+        game.move_piece("O-O").unwrap();
+        assert_eq!(game.fen(), "6k1/8/8/8/8/8/8/5RKR b - - 1 1");
+        game.undo();
+        assert_eq!(game.fen(), "6k1/8/8/8/8/8/8/5KRR w K - 0 1");
+        game.move_piece("Rh2").unwrap();
+        assert_eq!(
+            game.fen_with_style(FenStyle::XFen),
+            "6k1/8/8/8/8/8/7R/5KR1 b K - 1 1"
+        );
+    }
+
+    #[test]
+    fn test_redo_keeps_history() {
+        let mut game = Game::from_fen("r3k2r/1PP5/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1");
+        for mov in ["exd6", "O-O", "bxa8=Q", "Kg7", "c8=N"] {
+            game.move_piece(mov).unwrap();
+        }
+        game.history
+            .set_comment(Some(String::from("Underpromotion")));
+        let fen = game.fen();
+        let pgn = game.pgn();
+
+        for _ in 0..5 {
+            game.undo();
+        }
+        assert_eq!(game.fen(), "r3k2r/1PP5/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1");
+        for _ in 0..5 {
+            game.redo();
+        }
+        assert_eq!(game.fen(), fen);
+        assert_eq!(game.pgn(), pgn);
+        assert_eq!(
+            game.history.get_comment(),
+            Some(String::from("Underpromotion"))
+        );
+    }
+
+    #[test]
+    fn test_undo_repetition_count() {
+        let mut game = Game::default();
+        for _ in 0..3 {
+            assert_eq!(game.move_piece("Nf3"), Ok(GameStatus::InProgress));
+            game.undo();
+        }
+        assert_eq!(game.prev_positions.len(), 1);
+
+        for mov in ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1"] {
+            game.move_piece(mov).unwrap();
+        }
+        game.undo();
+        game.redo();
+        assert_eq!(
+            game.move_piece("Ng8"),
+            Ok(GameStatus::Draw(DrawReason::ThreefoldRepetition))
+        );
+    }
+
+    #[test]
+    fn test_repetition_counters() {
+        let mut game = Game::from_fen("r3k3/8/8/3p4/8/8/4P3/4K2R w Kq - 0 1");
+        for mov in ["Rh2", "Ra7", "Rh1", "Ra8"] {
+            game.move_piece(mov).unwrap();
+        }
+        // The rooks came back, but the castling rights didn't
+        assert_eq!(game.repetition_count(), 1);
+        assert_eq!(game.plies_since_irreversible(), 2);
+        assert_eq!(game.halfmove_clock, 4);
+
+        for mov in ["Rh2", "Ra7", "Rh1", "Ra8"] {
+            game.move_piece(mov).unwrap();
+        }
+        assert_eq!(game.repetition_count(), 2);
+        assert_eq!(game.plies_since_irreversible(), 6);
+
+        game.move_piece("e4").unwrap();
+        game.move_piece("dxe4").unwrap();
+        assert_eq!(game.plies_since_irreversible(), 0);
+        game.undo();
+        assert_eq!(game.plies_since_irreversible(), 0);
+        game.undo();
+        assert_eq!(game.plies_since_irreversible(), 6);
+        assert_eq!(game.repetition_count(), 2);
+    }
+
+    #[test]
+    fn test_rule_set() {
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+        game.rules = RuleSet::standard().with_move_limit(Some(4));
+        for mov in ["Ra2", "Kd7", "Ra1"] {
+            assert_eq!(game.move_piece(mov), Ok(GameStatus::InProgress));
+        }
+        assert_eq!(
+            game.move_piece("Ke8"),
+            Ok(GameStatus::Draw(DrawReason::FiftyMoveRule))
+        );
+        // Replays keep the rules of the game
+        assert_eq!(game.status_history()[0].ply, 4);
+
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80");
+        game.rules = game
+            .rules
+            .with_move_limit(None)
+            .with_repetition_limit(Some(2));
+        assert_eq!(game.move_piece("Ra2"), Ok(GameStatus::InProgress));
+        assert_eq!(game.move_piece("Kd7"), Ok(GameStatus::InProgress));
+        assert_eq!(game.move_piece("Ra1"), Ok(GameStatus::InProgress));
+        assert_eq!(
+            game.move_piece("Ke8"),
+            Ok(GameStatus::Draw(DrawReason::ThreefoldRepetition))
+        );
+    }
+
+    #[test]
+    fn test_status_at_ply() {
+        let mut game = Game::from_fen("k7/8/1Q6/8/8/8/8/K7 w - - 0 1");
+        game.move_piece("Qc7").unwrap();
+        assert!(game.status_at_ply(1).unwrap().stalemate);
+        game.goto_ply(0);
+        game.move_piece("Qb7+").unwrap();
+        game.move_piece("Kxb7").unwrap();
+        assert!(game.pgn().ends_with("1. Qc7 (1. Qb7+ Kxb7) *"));
+
+        // The check flags of a variation are kept on its moves
+        let status = game.status_at_ply(1).unwrap();
+        assert!(status.check && !status.checkmate && !status.stalemate);
+        assert_eq!(status.game_status, GameStatus::InProgress);
+
+        // The end of a line that isn't the current one is replayed
+        game.goto_ply(0);
+        assert_eq!(
+            game.status_at_ply(1).unwrap().game_status,
+            GameStatus::Draw(DrawReason::Stalemate)
+        );
+        assert_eq!(game.status_at_ply(2), None);
+
+        let mut game = Game::new("4k3/p7/8/8/8/8/8/4K2R w K - 0 1", true);
+        for mov in ["Rh8", "a6"] {
+            assert_eq!(game.move_piece(mov), Ok(GameStatus::InProgress));
+        }
+        assert_eq!(
+            game.move_piece("Rxe8"),
+            Ok(GameStatus::WhiteWins(WinReason::Checkmate))
+        );
+        assert!(game.status_at_ply(3).unwrap().checkmate);
+    }
+
+    #[test]
+    fn test_with_odds() {
+        let mut game = Game::with_odds(Color::Black, &[Odds::Pawn, Odds::Queen]);
+        assert_eq!(
+            game.start_position,
+            "rnb1kbnr/ppppp1pp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+        );
+        assert_eq!(
+            game.history.handicap.as_deref(),
+            Some("Black gives pawn and queen odds")
+        );
+        assert!(game.validate().is_empty());
+
+        game.history.white_clock = Some(String::from("300+3"));
+        game.history.black_clock = Some(String::from("120+3"));
+        game.move_piece("e4").unwrap();
+        let parsed = parse_pgn_with_options(&game.pgn(), &PgnParseOptions::lenient()).unwrap();
+        assert_eq!(parsed.start_position, game.start_position);
+        assert_eq!(parsed.history.white_clock, game.history.white_clock);
+        assert_eq!(parsed.history.black_clock, game.history.black_clock);
+        assert_eq!(parsed.history.handicap, game.history.handicap);
+
+        let game = Game::with_odds(Color::White, &[]);
+        assert_eq!(game.start_position, Game::default().start_position);
+        assert_eq!(game.history.handicap, None);
+    }
+
+    #[test]
+    fn test_takeback() {
+        let mut game = Game::default();
+        assert_eq!(game.request_takeback(Color::White), None);
+
+        game.move_piece("e4").unwrap();
+        assert_eq!(game.request_takeback(Color::Black), None);
+        assert_eq!(
+            game.request_takeback(Color::White),
+            Some(TakebackEvent::Requested {
+                color: Color::White
+            })
+        );
+        assert_eq!(game.request_takeback(Color::White), None);
+        assert_eq!(
+            game.decline_takeback(),
+            Some(TakebackEvent::Declined {
+                color: Color::White
+            })
+        );
+        assert_eq!(game.pending_takeback(), None);
+        assert_eq!(game.accept_takeback(), None);
+
+        game.move_piece("e5").unwrap();
+        game.move_piece("Nf3").unwrap();
+        // A request is dropped by the next move
+        game.request_takeback(Color::Black).unwrap();
+        game.move_piece("Nc6").unwrap();
+        assert_eq!(game.pending_takeback(), None);
+        assert_eq!(game.accept_takeback(), None);
+
+        // At most the last two moves are taken back, and they leave the history
+        game.undo();
+        game.request_takeback(Color::Black).unwrap();
+        assert_eq!(
+            game.accept_takeback(),
+            Some(TakebackEvent::Accepted {
+                color: Color::Black,
+                plies: 2
+            })
+        );
+        assert_eq!(
+            game.fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"
+        );
+        assert_eq!(game.history.node_count(), 1);
+        game.move_piece("c5").unwrap();
+        assert_eq!(game.pgn(), "1. e4 c5 *");
+
+        // Undoing drops the request too
+        game.request_takeback(Color::Black).unwrap();
+        game.undo();
+        game.move_piece("d5").unwrap();
+        assert_eq!(game.accept_takeback(), None);
+
+        // Going to another ply or restoring a snapshot drops the request too
+        for cached in [false, true] {
+            if cached {
+                game.enable_position_cache();
+            }
+            game.request_takeback(Color::Black).unwrap();
+            assert!(game.goto_ply(1));
+            assert_eq!(game.accept_takeback(), None);
+            assert!(game.goto_ply(2));
+        }
+        game.undo();
+        let snapshot = game.snapshot();
+        game.redo();
+        game.request_takeback(Color::Black).unwrap();
+        game.restore(&snapshot);
+        assert_eq!(game.accept_takeback(), None);
+        assert_eq!(game.history_vec().len(), 1);
+        game.redo();
+
+        // A request longer than the current line is not accepted
+        game.request_takeback(Color::Black).unwrap();
+        game.takeback_request = Some((Color::Black, 3));
+        assert_eq!(game.accept_takeback(), None);
+        assert_eq!(game.history_vec().len(), 2);
+
+        game.resign(Color::Black);
+        assert_eq!(game.request_takeback(Color::White), None);
+    }
+
+    #[test]
+    fn test_dead_position() {
+        for fen in [
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/4KN2 w - - 0 1",
+            "4kb2/8/8/8/8/8/8/2B1K3 b - - 0 1",
+            // The kings can't cross the wall and no pawn can capture
+            "4k3/7p/1p1p1p1P/pPpPpPp1/P1P1P1P1/8/8/4K3 b - - 0 1",
+        ] {
+            assert!(Game::from_fen(fen).is_dead_position(), "{fen}");
+        }
+
+        for fen in [
+            "4k3/8/8/8/8/8/8/3NKN2 w - - 0 1",
+            "4kb2/8/8/8/8/8/8/3BK3 w - - 0 1",
+            "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+            // The pawn on h5 opens the wall
+            "4k3/8/1p1p1p2/pPpPpPpp/P1P1P1P1/8/8/4K3 w - - 0 1",
+        ] {
+            assert!(!Game::from_fen(fen).is_dead_position(), "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_position_cache() {
+        let mut game = Game::default();
+        for mov in ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8"] {
+            game.move_piece(mov).unwrap();
+        }
+        game.goto_ply(2);
+        game.move_piece("e4").unwrap();
+        game.move_piece("d5").unwrap();
+
+        let mut cached = game.clone();
+        cached.enable_position_cache();
+        cached.move_piece("exd5").unwrap();
+        game.move_piece("exd5").unwrap();
+
+        // Every ply reached from the cache matches the replay, and going back
+        // to the start continues along the main line
+        for ply in [5, 3, 1, 4, 0, 5, 2, 6] {
+            assert!(game.goto_ply(ply));
+            assert!(cached.goto_ply(ply));
+            assert_eq!(cached.fen(), game.fen());
+            assert_eq!(cached.fen_at_ply(ply), Some(game.fen()));
+            assert_eq!(cached.prev_positions, game.prev_positions);
+            assert_eq!(cached.game_status, game.game_status);
+            assert_eq!(cached.last_move(), game.last_move());
+        }
+        assert!(!cached.goto_ply(9));
+
+        assert!(game.goto_ply(8));
+        assert!(cached.goto_ply(8));
+        assert_eq!(
+            cached.game_status,
+            GameStatus::Draw(DrawReason::ThreefoldRepetition)
+        );
+        assert_eq!(cached.prev_positions, game.prev_positions);
+
+        cached.disable_position_cache();
+        assert!(!cached.position_cache_enabled());
+        assert!(cached
+            .history
+            .line_nodes()
+            .iter()
+            .all(|node| node.borrow().position.is_none()));
+        assert_eq!(cached.fen_at_ply(4), game.fen_at_ply(4));
+    }
 }
@@ -1,6 +1,14 @@
 pub(crate) mod board;
+mod correspondence;
 mod game;
 mod pieces;
+mod promotion;
+mod rules;
+mod study;
 
+pub use board::*;
 pub use game::*;
 pub use pieces::*;
+pub use promotion::*;
+pub use rules::*;
+pub use study::*;
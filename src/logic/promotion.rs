@@ -0,0 +1,218 @@
+use std::{
+    fmt::{Debug, Error, Formatter},
+    rc::Rc,
+};
+
+use crate::constants::{Color, PieceType};
+
+/// A function that picks the piece a pawn promotes to, given the color of the
+/// pawn and the allowed pieces, or `None` to cancel the move
+pub type PromotionCallback = Rc<dyn Fn(Color, &[PieceType]) -> Option<PieceType>>;
+
+/// Decides which piece a pawn promotes to when it's moved with
+/// `Game::move_piece_coords`, and which pieces are allowed at all
+///
+/// # Example
+/// ```
+/// use std::rc::Rc;
+///
+/// use chess_lab::constants::{Position, PieceType};
+/// use chess_lab::logic::{Game, PromotionPolicy};
+///
+/// let mut game = Game::from_fen("8/4P3/8/8/8/8/8/k1K5 w - - 0 1");
+/// game.promotion_policy = PromotionPolicy::ask(Rc::new(|_, _| Some(PieceType::Knight)));
+///
+/// game.move_piece_coords(&Position::from_string("e7"), &Position::from_string("e8")).unwrap();
+///
+/// assert_eq!(game.fen(), "4N3/8/8/8/8/8/8/k1K5 b - - 0 1");
+/// ```
+///
+#[derive(Clone)]
+pub struct PromotionPolicy {
+    allowed: Vec<PieceType>,
+    callback: Option<PromotionCallback>,
+}
+
+impl Default for PromotionPolicy {
+    /// Creates the default policy, which always promotes to a queen
+    ///
+    /// # Returns
+    /// The auto-queen policy
+    ///
+    fn default() -> PromotionPolicy {
+        PromotionPolicy::auto_queen()
+    }
+}
+
+impl PromotionPolicy {
+    /// Creates a policy that always promotes to a queen, any piece is still
+    /// allowed when given explicitly in SAN
+    ///
+    /// # Returns
+    /// The auto-queen policy
+    ///
+    pub fn auto_queen() -> PromotionPolicy {
+        PromotionPolicy {
+            allowed: vec![
+                PieceType::Queen,
+                PieceType::Rook,
+                PieceType::Bishop,
+                PieceType::Knight,
+            ],
+            callback: None,
+        }
+    }
+
+    /// Creates a policy that asks which piece to promote to every time
+    ///
+    /// # Arguments
+    /// * `callback`: The function called with the color of the pawn and the
+    ///   allowed pieces, returning `None` cancels the move
+    ///
+    /// # Returns
+    /// The policy
+    ///
+    pub fn ask(callback: PromotionCallback) -> PromotionPolicy {
+        PromotionPolicy {
+            callback: Some(callback),
+            ..PromotionPolicy::auto_queen()
+        }
+    }
+
+    /// Restricts the pieces a pawn can promote to
+    /// Without a callback, the first allowed piece is chosen
+    ///
+    /// # Arguments
+    /// * `allowed`: The allowed pieces, in order of preference
+    ///
+    /// # Returns
+    /// The policy with the restriction applied
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::PieceType;
+    /// use chess_lab::logic::{Game, PromotionPolicy};
+    ///
+    /// let mut game = Game::from_fen("8/4P3/8/8/8/8/8/k1K5 w - - 0 1");
+    /// game.promotion_policy =
+    ///     PromotionPolicy::auto_queen().with_allowed(&[PieceType::Rook, PieceType::Knight]);
+    ///
+    /// assert!(game.move_piece("e8=Q").is_err());
+    /// assert!(game.move_piece("e8=N").is_ok());
+    /// ```
+    ///
+    pub fn with_allowed(mut self, allowed: &[PieceType]) -> PromotionPolicy {
+        self.allowed = allowed.to_vec();
+        self
+    }
+
+    /// Gets the pieces a pawn can promote to
+    ///
+    /// # Returns
+    /// The allowed pieces, in order of preference
+    ///
+    pub fn allowed(&self) -> &[PieceType] {
+        &self.allowed
+    }
+
+    /// Checks if a pawn can promote to a piece
+    ///
+    /// # Arguments
+    /// * `piece_type`: The piece to promote to
+    ///
+    /// # Returns
+    /// Whether the piece is allowed
+    ///
+    pub fn allows(&self, piece_type: PieceType) -> bool {
+        self.allowed.contains(&piece_type)
+    }
+
+    /// Picks the piece a pawn promotes to
+    ///
+    /// # Arguments
+    /// * `color`: The color of the promoting pawn
+    ///
+    /// # Returns
+    /// The chosen piece, or `None` if the promotion was cancelled
+    ///
+    pub(crate) fn choose(&self, color: Color) -> Option<PieceType> {
+        match &self.callback {
+            Some(callback) => callback(color, &self.allowed),
+            None => self.allowed.first().copied(),
+        }
+    }
+}
+
+impl Debug for PromotionPolicy {
+    /// Formats the policy, without the callback
+    ///
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        f.debug_struct("PromotionPolicy")
+            .field("allowed", &self.allowed)
+            .field("ask", &self.callback.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::PromotionPolicy;
+    use crate::{
+        constants::{Color, PieceType, Position},
+        errors::MoveError,
+        logic::Game,
+    };
+
+    #[test]
+    fn test_auto_queen() {
+        let mut game = Game::from_fen("8/4P3/8/8/8/8/8/k1K5 w - - 0 1");
+        game.move_piece_coords(&Position::from_string("e7"), &Position::from_string("e8"))
+            .unwrap();
+        assert_eq!(game.fen(), "4Q3/8/8/8/8/8/8/k1K5 b - - 0 1");
+        assert_eq!(game.history.get_move().unwrap().to_string(), "e8=Q");
+    }
+
+    #[test]
+    fn test_ask() {
+        let asked = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&asked);
+        let mut game = Game::from_fen("3r4/4P3/8/8/8/8/8/k1K5 w - - 0 1");
+        game.promotion_policy = PromotionPolicy::ask(Rc::new(move |color, allowed| {
+            assert_eq!(color, Color::White);
+            assert_eq!(allowed.len(), 4);
+            counter.set(counter.get() + 1);
+            None
+        }));
+
+        let from = Position::from_string("e7");
+        let to = Position::from_string("d8");
+        assert_eq!(
+            game.move_piece_coords(&from, &to),
+            Err(MoveError::PromotionCancelled {
+                san: String::from("e7xd8")
+            })
+        );
+        assert_eq!(asked.get(), 1);
+
+        game.promotion_policy = PromotionPolicy::ask(Rc::new(|_, _| Some(PieceType::Rook)));
+        game.move_piece_coords(&from, &to).unwrap();
+        assert_eq!(game.history.get_move().unwrap().to_string(), "exd8=R");
+    }
+
+    #[test]
+    fn test_restricted() {
+        let mut game = Game::from_fen("8/4P3/8/8/8/8/8/k1K5 w - - 0 1");
+        game.promotion_policy = PromotionPolicy::ask(Rc::new(|_, _| Some(PieceType::Queen)))
+            .with_allowed(&[PieceType::Knight]);
+        assert!(game
+            .move_piece_coords(&Position::from_string("e7"), &Position::from_string("e8"))
+            .is_err());
+
+        game.promotion_policy = PromotionPolicy::auto_queen().with_allowed(&[PieceType::Knight]);
+        game.move_piece_coords(&Position::from_string("e7"), &Position::from_string("e8"))
+            .unwrap();
+        assert_eq!(game.fen(), "4N3/8/8/8/8/8/8/k1K5 b - - 0 1");
+    }
+}
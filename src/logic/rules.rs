@@ -0,0 +1,116 @@
+/// The draw rules a game applies after every move: how many repetitions of a
+/// position and how many plies without a capture or a pawn move end the game
+/// Variants and servers differ, so either rule can be adjusted or disabled
+///
+/// # Fields
+/// * `repetition_limit`: The number of times a position must occur to draw
+///   the game, or `None` to never draw by repetition
+/// * `move_limit`: The halfmove clock that draws the game, or `None` to never
+///   draw by the move count
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::GameStatus;
+/// use chess_lab::logic::{Game, RuleSet};
+///
+/// let mut game = Game::default();
+/// game.rules = RuleSet::standard().with_repetition_limit(None);
+/// for _ in 0..3 {
+///     for mov in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+///         game.move_piece(mov).unwrap();
+///     }
+/// }
+///
+/// assert_eq!(game.repetition_count(), 4);
+/// assert_eq!(game.game_status, GameStatus::InProgress);
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuleSet {
+    pub repetition_limit: Option<u32>,
+    pub move_limit: Option<u32>,
+}
+
+impl Default for RuleSet {
+    /// Creates the standard rules
+    ///
+    /// # Returns
+    /// The standard rules
+    ///
+    fn default() -> RuleSet {
+        RuleSet::standard()
+    }
+}
+
+impl RuleSet {
+    /// Creates the rules of standard chess, as applied automatically: a draw
+    /// on the third repetition or after 50 moves by each side without a
+    /// capture or a pawn move
+    ///
+    /// # Returns
+    /// The standard rules
+    ///
+    pub const fn standard() -> RuleSet {
+        RuleSet {
+            repetition_limit: Some(3),
+            move_limit: Some(100),
+        }
+    }
+
+    /// Changes the number of repetitions that draws the game
+    ///
+    /// # Arguments
+    /// * `limit`: The number of times a position must occur, or `None` to
+    ///   disable the rule
+    ///
+    /// # Returns
+    /// The rules with the new limit
+    ///
+    pub const fn with_repetition_limit(self, limit: Option<u32>) -> RuleSet {
+        RuleSet {
+            repetition_limit: limit,
+            ..self
+        }
+    }
+
+    /// Changes the halfmove clock that draws the game
+    ///
+    /// # Arguments
+    /// * `limit`: The number of plies without a capture or a pawn move, or
+    ///   `None` to disable the rule
+    ///
+    /// # Returns
+    /// The rules with the new limit
+    ///
+    pub const fn with_move_limit(self, limit: Option<u32>) -> RuleSet {
+        RuleSet {
+            move_limit: limit,
+            ..self
+        }
+    }
+
+    /// Checks if a position is drawn by repetition
+    ///
+    /// # Arguments
+    /// * `repetitions`: The number of times the position occurred
+    ///
+    /// # Returns
+    /// Whether the rules draw the game
+    ///
+    pub fn is_repetition_draw(&self, repetitions: u32) -> bool {
+        self.repetition_limit
+            .is_some_and(|limit| repetitions >= limit)
+    }
+
+    /// Checks if a position is drawn by the move count
+    ///
+    /// # Arguments
+    /// * `halfmove_clock`: The plies since the last capture or pawn move
+    ///
+    /// # Returns
+    /// Whether the rules draw the game
+    ///
+    pub fn is_move_limit_draw(&self, halfmove_clock: u32) -> bool {
+        self.move_limit.is_some_and(|limit| halfmove_clock >= limit)
+    }
+}
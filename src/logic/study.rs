@@ -0,0 +1,340 @@
+use crate::{
+    constants::pgn::format_tag,
+    errors::PgnError,
+    parsing::{PgnParseOptions, PgnReader},
+};
+
+use super::{Game, STARTING_FEN};
+
+/// Tags that are read into the fields of a game, a chapter or a study, so
+/// they are not kept as extra tags
+const KNOWN_TAGS: [&str; 22] = [
+    "Event",
+    "Site",
+    "Date",
+    "Round",
+    "White",
+    "Black",
+    "Result",
+    "Variant",
+    "WhiteElo",
+    "BlackElo",
+    "TimeControl",
+    "Termination",
+    "WhiteClock",
+    "BlackClock",
+    "Handicap",
+    "EventDate",
+    "UTCDate",
+    "UTCTime",
+    "SetUp",
+    "FEN",
+    "StudyName",
+    "ChapterName",
+];
+
+/// A chapter of a study: a named game with its own extra tags
+///
+/// # Fields
+/// * `name`: The name of the chapter
+/// * `game`: The game of the chapter, with its moves, variations and comments
+/// * `tags`: The tags of the chapter that the game has no field for (like `ChapterURL`)
+///
+#[derive(Debug, Clone)]
+pub struct Chapter {
+    pub name: String,
+    pub game: Game,
+    pub tags: Vec<(String, String)>,
+}
+
+/// A study, as exported by Lichess: an ordered list of chapters sharing a
+/// name and some metadata, written as one PGN game per chapter
+///
+/// # Fields
+/// * `name`: The name of the study
+/// * `tags`: The tags shared by every chapter (like `Annotator`)
+/// * `chapters`: The chapters, in order
+///
+/// # Example
+/// ```
+/// use chess_lab::logic::{Game, Study};
+///
+/// let mut study = Study::new("Openings");
+/// let mut game = Game::default();
+/// game.move_piece("e4").unwrap();
+/// study.add_chapter("King's pawn", game);
+///
+/// let pgn = study.pgn();
+/// assert!(pgn.starts_with("[Event \"Openings: King's pawn\"]\n"));
+///
+/// let parsed = Study::from_pgn(&pgn).unwrap();
+/// assert_eq!(parsed.name, "Openings");
+/// assert_eq!(parsed.chapters[0].name, "King's pawn");
+/// assert_eq!(parsed.pgn(), pgn);
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct Study {
+    pub name: String,
+    pub tags: Vec<(String, String)>,
+    pub chapters: Vec<Chapter>,
+}
+
+impl Study {
+    /// Creates an empty study
+    ///
+    /// # Arguments
+    /// * `name`: The name of the study
+    ///
+    /// # Returns
+    /// A study without chapters
+    ///
+    pub fn new(name: &str) -> Study {
+        Study {
+            name: name.to_string(),
+            tags: Vec::new(),
+            chapters: Vec::new(),
+        }
+    }
+
+    /// Parses a multi-chapter study PGN, every game being a chapter
+    /// The Seven Tag Roster is not required, as Lichess doesn't write it
+    ///
+    /// The names are read from the `StudyName` and `ChapterName` tags, or from
+    /// an `Event` tag like `Study: Chapter`. The extra tags that every chapter
+    /// has with the same value become the tags of the study
+    ///
+    /// # Arguments
+    /// * `pgn`: The PGN text
+    ///
+    /// # Returns
+    /// The study, or an error if any of its chapters is not valid
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Study;
+    ///
+    /// let pgn = "[Event \"Endgames: Lucena\"]\n[Annotator \"me\"]\n[FEN \"1K1k4/1P6/8/8/8/8/r7/2R5 w - - 0 1\"]\n\n1. Rd1+ *\n\n[Event \"Endgames: Philidor\"]\n[Annotator \"me\"]\n[FEN \"4k3/8/r7/4PK2/8/8/8/1R6 b - - 0 1\"]\n\n1... Rb6 *\n";
+    /// let study = Study::from_pgn(pgn).unwrap();
+    ///
+    /// assert_eq!(study.name, "Endgames");
+    /// assert_eq!(study.tags, vec![("Annotator".to_string(), "me".to_string())]);
+    /// assert_eq!(study.chapters.len(), 2);
+    /// assert_eq!(study.chapters[1].name, "Philidor");
+    /// ```
+    ///
+    pub fn from_pgn(pgn: &str) -> Result<Study, PgnError> {
+        let options = PgnParseOptions {
+            allow_missing_tags: true,
+            ..PgnParseOptions::default()
+        };
+        let mut reader = PgnReader::with_options(pgn.as_bytes(), options);
+        let mut study = Study::new("");
+
+        while let Some(result) = reader.next_with_tags() {
+            let (tags, game) = result?;
+            let tag = |name: &str| {
+                tags.iter()
+                    .find(|(tag, _)| tag == name)
+                    .map(|(_, value)| value.clone())
+            };
+            let event = tag("Event").unwrap_or_default();
+            let (event_study, event_chapter) = match event.split_once(": ") {
+                Some((study, chapter)) => (study.to_string(), chapter.to_string()),
+                None => (String::new(), event.clone()),
+            };
+
+            if study.chapters.is_empty() {
+                study.name = tag("StudyName").unwrap_or(event_study);
+            }
+            let name = tag("ChapterName")
+                .or(Some(event_chapter).filter(|name| !name.is_empty()))
+                .unwrap_or(format!("Chapter {}", study.chapters.len() + 1));
+            let tags = tags
+                .into_iter()
+                .filter(|(tag, _)| !KNOWN_TAGS.contains(&tag.as_str()))
+                .collect();
+
+            study.chapters.push(Chapter { name, game, tags });
+        }
+
+        if let Some((first, rest)) = study.chapters.split_first() {
+            study.tags = first
+                .tags
+                .iter()
+                .filter(|tag| rest.iter().all(|chapter| chapter.tags.contains(tag)))
+                .cloned()
+                .collect();
+        }
+        for chapter in study.chapters.iter_mut() {
+            chapter.tags.retain(|tag| !study.tags.contains(tag));
+        }
+
+        Ok(study)
+    }
+
+    /// Adds a chapter at the end of the study
+    ///
+    /// # Arguments
+    /// * `name`: The name of the chapter
+    /// * `game`: The game of the chapter
+    ///
+    pub fn add_chapter(&mut self, name: &str, game: Game) {
+        self.chapters.push(Chapter {
+            name: name.to_string(),
+            game,
+            tags: Vec::new(),
+        });
+    }
+
+    /// Gets the first chapter with the given name
+    ///
+    /// # Arguments
+    /// * `name`: The name of the chapter
+    ///
+    /// # Returns
+    /// The chapter, if any
+    ///
+    pub fn chapter(&self, name: &str) -> Option<&Chapter> {
+        self.chapters.iter().find(|chapter| chapter.name == name)
+    }
+
+    /// Moves a chapter to another place, shifting the chapters in between
+    ///
+    /// # Arguments
+    /// * `from`: The index of the chapter to move
+    /// * `to`: The index the chapter ends at
+    ///
+    /// # Panics
+    /// Panics if any of the indices is out of bounds
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::{Game, Study};
+    ///
+    /// let mut study = Study::new("Study");
+    /// study.add_chapter("A", Game::default());
+    /// study.add_chapter("B", Game::default());
+    /// study.add_chapter("C", Game::default());
+    ///
+    /// study.move_chapter(2, 0);
+    /// let names: Vec<_> = study.chapters.iter().map(|c| c.name.as_str()).collect();
+    /// assert_eq!(names, vec!["C", "A", "B"]);
+    /// ```
+    ///
+    pub fn move_chapter(&mut self, from: usize, to: usize) {
+        assert!(to < self.chapters.len(), "Chapter index out of bounds");
+        let chapter = self.chapters.remove(from);
+        self.chapters.insert(to, chapter);
+    }
+
+    /// Returns the PGN of the study, one game per chapter separated by blank lines
+    /// Every chapter gets an `Event` tag like `Study: Chapter`, the `StudyName`
+    /// and `ChapterName` tags, the tags of the study and its own tags
+    ///
+    /// # Returns
+    /// The PGN of the study
+    ///
+    pub fn pgn(&self) -> String {
+        self.chapters
+            .iter()
+            .map(|chapter| self.chapter_pgn(chapter))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Returns the PGN of a single chapter
+    ///
+    /// # Arguments
+    /// * `chapter`: The chapter to write
+    ///
+    /// # Returns
+    /// The PGN of the chapter, ending with a line break
+    ///
+    fn chapter_pgn(&self, chapter: &Chapter) -> String {
+        let game = &chapter.game;
+        let history = &game.history;
+        let result = history.result.clone().unwrap_or(String::from("*"));
+
+        let mut tags: Vec<(String, String)> = history
+            .tag_fields()
+            .into_iter()
+            .filter_map(|(tag, value)| {
+                let value = match tag {
+                    "Event" => Some(format!("{}: {}", self.name, chapter.name)),
+                    "Result" => Some(result.clone()),
+                    _ => value,
+                };
+                Some((tag.to_string(), value?))
+            })
+            .collect();
+        if game.start_position != STARTING_FEN {
+            tags.push((String::from("SetUp"), String::from("1")));
+            tags.push((String::from("FEN"), game.start_position.clone()));
+        }
+        tags.extend(self.tags.iter().cloned());
+        tags.push((String::from("StudyName"), self.name.clone()));
+        tags.push((String::from("ChapterName"), chapter.name.clone()));
+        tags.extend(chapter.tags.iter().cloned());
+
+        let mut pgn = String::new();
+        for (tag, value) in tags {
+            pgn.push_str(&format_tag(&tag, &value));
+        }
+        pgn.push('\n');
+
+        let moves = history.pgn_moves_with(&|line| match line.comment_text() {
+            Some(comment) => format!("{} {{ {} }}", line.san(), comment),
+            None => line.san(),
+        });
+        let moves = moves.trim_end();
+        if !moves.is_empty() {
+            pgn.push_str(moves);
+            pgn.push(' ');
+        }
+        pgn.push_str(&result);
+        pgn.push('\n');
+        pgn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Study;
+    use crate::logic::Game;
+
+    #[test]
+    fn test_study_round_trip() {
+        let pgn = "[Event \"Study: Main\"]\n[Site \"https://lichess.org/study/abc/1\"]\n[Result \"*\"]\n[Annotator \"someone\"]\n[StudyName \"Study\"]\n[ChapterName \"Main\"]\n[ChapterURL \"https://lichess.org/study/abc/1\"]\n\n1. e4 { [%csl Gd5] Best } e5 (1... c5) 2. Nf3 *\n\n[Event \"Study: Side\"]\n[Result \"*\"]\n[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 w - - 0 1\"]\n[Annotator \"someone\"]\n[StudyName \"Study\"]\n[ChapterName \"Side\"]\n[ChapterURL \"https://lichess.org/study/abc/2\"]\n\n1. e4 *\n";
+        let study = Study::from_pgn(pgn).unwrap();
+
+        assert_eq!(study.name, "Study");
+        assert_eq!(
+            study.tags,
+            vec![("Annotator".to_string(), "someone".to_string())]
+        );
+        assert_eq!(study.chapter("Side").unwrap().tags.len(), 1);
+        assert_eq!(
+            study.chapter("Side").unwrap().game.fen(),
+            "4k3/8/8/8/4P3/8/8/4K3 b - - 0 1"
+        );
+        assert_eq!(study.pgn(), pgn);
+    }
+
+    #[test]
+    fn test_study_names() {
+        let study =
+            Study::from_pgn("[Event \"Lone\"]\n\n1. d4 *\n\n[Site \"?\"]\n\n1. c4 *\n").unwrap();
+        assert_eq!(study.name, "");
+        assert_eq!(study.chapters[0].name, "Lone");
+        assert_eq!(study.chapters[1].name, "Chapter 2");
+
+        let mut study = Study::new("Empty");
+        assert_eq!(study.pgn(), "");
+        study.add_chapter("A", Game::default());
+        assert_eq!(
+            study.pgn(),
+            "[Event \"Empty: A\"]\n[Result \"*\"]\n[StudyName \"Empty\"]\n[ChapterName \"A\"]\n\n*\n"
+        );
+    }
+}
@@ -0,0 +1,534 @@
+use std::{fmt, io::BufRead, time::Duration};
+
+use crate::{
+    constants::{parse_clock_time, pgn::format_tag, Color, GameStatus, PieceType, Position},
+    errors::PgnError,
+    logic::{Game, Piece, RuleSet},
+};
+
+use super::{PgnParseOptions, PgnReader, Token, Tokenizer};
+
+/// One of the two boards of a bughouse game
+///
+/// # Variants
+/// * `A`: The first board, written with `A`/`a` in the move numbers
+/// * `B`: The second board, written with `B`/`b` in the move numbers
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BughouseBoard {
+    A,
+    B,
+}
+
+/// A move of a bughouse game
+///
+/// # Fields
+/// * `board`: The board the move is played on
+/// * `color`: The color of the player that moves
+/// * `number`: The move number on that board
+/// * `san`: The move in algebraic notation, drops are written like `P@e4`
+/// * `clock`: The time left on the player's clock after the move, if known
+/// * `comment`: The comment of the move, if any
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct BughouseMove {
+    pub board: BughouseBoard,
+    pub color: Color,
+    pub number: u32,
+    pub san: String,
+    pub clock: Option<Duration>,
+    pub comment: Option<String>,
+}
+
+impl BughouseMove {
+    /// Gets the move number in BPGN notation, like `12A.` or `12b.`
+    ///
+    /// # Returns
+    /// The move number, with the letter of the board in uppercase for white
+    /// and in lowercase for black
+    ///
+    pub fn move_number(&self) -> String {
+        let letter = match (self.board, self.color) {
+            (BughouseBoard::A, Color::White) => 'A',
+            (BughouseBoard::A, Color::Black) => 'a',
+            (BughouseBoard::B, Color::White) => 'B',
+            (BughouseBoard::B, Color::Black) => 'b',
+        };
+        format!("{}{}.", self.number, letter)
+    }
+}
+
+impl fmt::Display for BughouseMove {
+    /// Formats the move as BPGN movetext: its number, the move, its clock and
+    /// its comment
+    ///
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.move_number(), self.san)?;
+        if let Some(clock) = self.clock {
+            let millis = format!("{:03}", clock.subsec_millis());
+            let fraction = match millis.trim_end_matches('0') {
+                "" => "0",
+                fraction => fraction,
+            };
+            write!(f, " {{{}.{}}}", clock.as_secs(), fraction)?;
+        }
+        if let Some(comment) = &self.comment {
+            write!(f, " {{{}}}", comment)?;
+        }
+        Ok(())
+    }
+}
+
+/// A bughouse game, as read from a BPGN file
+/// The moves of both boards are kept interleaved in the order they were
+/// played
+///
+/// # Fields
+/// * `tags`: The tag pairs, in the order they appear (`WhiteA`, `BlackB`, ...)
+/// * `moves`: The moves of both boards, in the order they were played
+/// * `result`: The game termination marker
+///
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BughouseGame {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<BughouseMove>,
+    pub result: Option<String>,
+}
+
+impl BughouseGame {
+    /// Gets the value of a tag
+    ///
+    /// # Arguments
+    /// * `name`: The name of the tag
+    ///
+    /// # Returns
+    /// The value of the first tag with that name, if any
+    ///
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Gets the moves played on one of the boards
+    ///
+    /// # Arguments
+    /// * `board`: The board
+    ///
+    /// # Returns
+    /// The moves of the board, in the order they were played
+    ///
+    pub fn board_moves(&self, board: BughouseBoard) -> Vec<&BughouseMove> {
+        self.moves.iter().filter(|mov| mov.board == board).collect()
+    }
+
+    /// Writes the game as BPGN
+    ///
+    /// # Returns
+    /// The tag pairs followed by the movetext of both boards
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::parsing::parse_bpgn;
+    ///
+    /// let bpgn = "[WhiteA \"alice\"]\n\n1A. e4 {179.5} 1B. d4 {178.0} 1a. Nc6 {177.25} 0-1";
+    /// let game = parse_bpgn(bpgn).unwrap();
+    ///
+    /// assert_eq!(game.bpgn(), bpgn);
+    /// ```
+    ///
+    pub fn bpgn(&self) -> String {
+        let mut bpgn = String::new();
+        for (name, value) in &self.tags {
+            bpgn.push_str(&format_tag(name, value));
+        }
+        if !self.tags.is_empty() {
+            bpgn.push('\n');
+        }
+
+        let mut movetext: Vec<String> = self.moves.iter().map(|mov| mov.to_string()).collect();
+        movetext.push(self.result.clone().unwrap_or(String::from("*")));
+        bpgn.push_str(&movetext.join(" "));
+        bpgn
+    }
+}
+
+/// Parses a bughouse game in BPGN, the format of the FICS bughouse archives
+/// Only the first game of the input is read
+///
+/// Every move is preceded by its number and board (`1A.` for white on board
+/// A, `1a.` for black on board A, `1B.`/`1b.` for board B), and is played on
+/// its board from the starting position to check it. Drops must land on an
+/// empty square without leaving the king in check, the pieces in hand are
+/// not tracked. The clock of a move is read from a comment right after it
+/// holding only the seconds left with a decimal point, as FICS writes them
+/// (`{179.9}`), or a `[%clk 0:02:59.9]` command; other comments are kept as
+/// comments
+///
+/// # Arguments
+/// * `bpgn`: The BPGN text
+///
+/// # Returns
+/// The game, or an error if the BPGN is not valid
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use chess_lab::parsing::{parse_bpgn, BughouseBoard};
+///
+/// let game = parse_bpgn(
+///     "[WhiteA \"alice\"]
+/// [BlackA \"bob\"]
+/// [WhiteB \"carol\"]
+/// [BlackB \"dave\"]
+///
+/// 1A. e4 {179.9} 1B. d4 {179.8} 1a. d5 {178.2} 2A. exd5 {177.1} 1b. P@h3 {Nice} 1-0",
+/// )
+/// .unwrap();
+///
+/// assert_eq!(game.tag("WhiteB"), Some("carol"));
+/// assert_eq!(game.moves.len(), 5);
+/// assert_eq!(game.board_moves(BughouseBoard::B)[1].san, "P@h3");
+/// assert_eq!(game.moves[2].clock, Some(Duration::from_millis(178200)));
+/// assert_eq!(game.result, Some("1-0".to_string()));
+/// ```
+///
+pub fn parse_bpgn(bpgn: &str) -> Result<BughouseGame, PgnError> {
+    let options = PgnParseOptions::default();
+    let mut tokenizer = Tokenizer::new(bpgn, &options)?;
+    let mut game = BughouseGame::default();
+
+    let mut token = tokenizer.next_token()?;
+    while let Some(Token::Tag(name, value)) = token {
+        game.tags.push((name, value));
+        token = tokenizer.next_token()?;
+    }
+
+    let new_board = || {
+        let mut board = Game::default();
+        board.rules = RuleSet::standard()
+            .with_repetition_limit(None)
+            .with_move_limit(None);
+        board
+    };
+    let mut boards = [new_board(), new_board()];
+    let mut number = None;
+    while let Some(current) = token {
+        match current {
+            Token::Comment(comment) => {
+                if let Some(mov) = game.moves.last_mut() {
+                    match parse_clock(&comment) {
+                        Some(clock) if mov.clock.is_none() => mov.clock = Some(clock),
+                        _ => {
+                            mov.comment = Some(match mov.comment.take() {
+                                Some(prev) => format!("{} {}", prev, comment),
+                                None => comment,
+                            });
+                        }
+                    }
+                }
+            }
+            Token::Nag => {}
+            Token::Move(symbol) => match parse_move_number(&symbol) {
+                Some(parsed) => number = Some(parsed),
+                None => {
+                    let (number, board, color) = number.take().ok_or(PgnError::InvalidToken)?;
+                    let index = match board {
+                        BughouseBoard::A => 0,
+                        BughouseBoard::B => 1,
+                    };
+                    play_on_board(&mut boards[index], color, &symbol)?;
+                    game.moves.push(BughouseMove {
+                        board,
+                        color,
+                        number,
+                        san: symbol,
+                        clock: None,
+                        comment: None,
+                    });
+                }
+            },
+            Token::Result(result) => {
+                game.result = Some(result);
+                break;
+            }
+            Token::Tag(_, _) | Token::MoveNumber | Token::VariationStart | Token::VariationEnd => {
+                return Err(PgnError::InvalidToken)
+            }
+        }
+        token = tokenizer.next_token()?;
+    }
+
+    if number.is_some() {
+        return Err(PgnError::InvalidToken);
+    }
+    if game.result.is_none() {
+        return Err(PgnError::MissingResult);
+    }
+    Ok(game)
+}
+
+/// Plays a move of a bughouse game on its board, drops included
+///
+/// # Arguments
+/// * `game`: The game of the board
+/// * `color`: The color of the player that moves
+/// * `san`: The move, drops written like `P@e4`
+///
+/// # Returns
+/// Ok if the move was played, or an error if it's not legal on the board
+///
+fn play_on_board(game: &mut Game, color: Color, san: &str) -> Result<(), PgnError> {
+    if game.side_to_move() != color {
+        return Err(PgnError::InvalidMove);
+    }
+    match san.split_once('@') {
+        Some((piece, square)) => drop_piece(game, color, piece, square)?,
+        None => {
+            game.move_piece(san).map_err(|_| PgnError::InvalidMove)?;
+        }
+    }
+    // A mate on one board can still be stopped with a drop, and draws don't
+    // end a bughouse game
+    game.game_status = GameStatus::InProgress;
+    Ok(())
+}
+
+/// Drops a piece from the hand of a player on an empty square
+///
+/// # Arguments
+/// * `game`: The game of the board
+/// * `color`: The color of the player that drops the piece
+/// * `piece`: The letter of the piece
+/// * `square`: The square, with an optional check or mate mark
+///
+/// # Returns
+/// Ok if the piece was dropped, or an error if the drop is not legal
+///
+fn drop_piece(game: &mut Game, color: Color, piece: &str, square: &str) -> Result<(), PgnError> {
+    let piece_type = match piece {
+        "P" => PieceType::Pawn,
+        "N" => PieceType::Knight,
+        "B" => PieceType::Bishop,
+        "R" => PieceType::Rook,
+        "Q" => PieceType::Queen,
+        _ => return Err(PgnError::InvalidMove),
+    };
+    let square: Position = square
+        .trim_end_matches(['+', '#'])
+        .parse()
+        .map_err(|_| PgnError::InvalidMove)?;
+    if piece_type == PieceType::Pawn && (square.row == 0 || square.row == 7) {
+        return Err(PgnError::InvalidMove);
+    }
+
+    game.board
+        .set_piece(Piece::new(color, piece_type), &square)
+        .map_err(|_| PgnError::InvalidMove)?;
+    let in_check = game
+        .board
+        .find(PieceType::King, color)
+        .into_iter()
+        .any(|king| game.board.is_attacked(king, color.opposite()));
+    if in_check {
+        return Err(PgnError::InvalidMove);
+    }
+
+    game.is_white_turn = !game.is_white_turn;
+    game.en_passant = None;
+    if color == Color::Black {
+        game.fullmove_number += 1;
+    }
+    Ok(())
+}
+
+/// Reads the clock of a BPGN move from its comment
+///
+/// # Arguments
+/// * `comment`: The comment after the move
+///
+/// # Returns
+/// The time left, or `None` if the comment is not a clock: the seconds with
+/// a decimal point (`179.9`) or a `[%clk]` command alone
+///
+fn parse_clock(comment: &str) -> Option<Duration> {
+    let comment = comment.trim();
+    if let Some(clock) = comment
+        .strip_prefix("[%clk ")
+        .and_then(|clock| clock.strip_suffix(']'))
+    {
+        return parse_clock_time(clock.trim());
+    }
+
+    let (seconds, fraction) = comment.split_once('.')?;
+    let digits = |text: &str| !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit());
+    if !digits(seconds) || !digits(fraction) || fraction.len() > 3 {
+        return None;
+    }
+    let millis = format!("{:0<3}", fraction).parse::<u64>().ok()?;
+    Some(Duration::from_secs(seconds.parse().ok()?) + Duration::from_millis(millis))
+}
+
+/// Parses a BPGN move number, like `12A.` or `12b.`
+///
+/// # Arguments
+/// * `symbol`: The symbol read from the movetext
+///
+/// # Returns
+/// The move number, the board and the color that moves, or `None` if the
+/// symbol is not a move number
+///
+fn parse_move_number(symbol: &str) -> Option<(u32, BughouseBoard, Color)> {
+    let symbol = symbol.trim_end_matches('.');
+    let (number, letter) = symbol.split_at(symbol.len().checked_sub(1)?);
+    let (board, color) = match letter {
+        "A" => (BughouseBoard::A, Color::White),
+        "a" => (BughouseBoard::A, Color::Black),
+        "B" => (BughouseBoard::B, Color::White),
+        "b" => (BughouseBoard::B, Color::Black),
+        _ => return None,
+    };
+    Some((number.parse().ok()?, board, color))
+}
+
+/// Reads the bughouse games of a BPGN stream one by one, like the FICS
+/// bughouse archives
+///
+/// # Example
+/// ```
+/// use chess_lab::parsing::BpgnReader;
+///
+/// let bpgn = "[Event \"A\"]\n\n1A. e4 1-0\n\n[Event \"B\"]\n\n1B. d4 *\n";
+/// let games: Vec<_> = BpgnReader::new(bpgn.as_bytes())
+///     .map(|game| game.unwrap())
+///     .collect();
+///
+/// assert_eq!(games.len(), 2);
+/// assert_eq!(games[1].tag("Event"), Some("B"));
+/// ```
+///
+pub struct BpgnReader<R: BufRead> {
+    reader: PgnReader<R>,
+    done: bool,
+}
+
+impl<R: BufRead> BpgnReader<R> {
+    /// Creates a reader over a BPGN stream
+    ///
+    /// # Arguments
+    /// * `reader`: The source of the BPGN text
+    ///
+    /// # Returns
+    /// The BPGN reader
+    ///
+    pub fn new(reader: R) -> BpgnReader<R> {
+        BpgnReader {
+            reader: PgnReader::new(reader),
+            done: false,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for BpgnReader<R> {
+    type Item = Result<BughouseGame, PgnError>;
+
+    /// Reads and parses the next game of the stream
+    ///
+    /// # Returns
+    /// The next game, or an error if it can't be read or parsed. Parse errors
+    /// don't stop the reader, the following games can still be read
+    ///
+    fn next(&mut self) -> Option<Result<BughouseGame, PgnError>> {
+        if self.done {
+            return None;
+        }
+        match self.reader.next_game_text() {
+            Ok(Some(text)) => Some(parse_bpgn(&text)),
+            Ok(None) => None,
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{parse_bpgn, parse_move_number, BughouseBoard};
+    use crate::{constants::Color, errors::PgnError};
+
+    #[test]
+    fn test_parse_move_number() {
+        assert_eq!(
+            parse_move_number("12b."),
+            Some((12, BughouseBoard::B, Color::Black))
+        );
+        assert_eq!(
+            parse_move_number("1A."),
+            Some((1, BughouseBoard::A, Color::White))
+        );
+        assert_eq!(parse_move_number("e4"), None);
+        assert_eq!(parse_move_number("A."), None);
+        assert_eq!(parse_move_number("."), None);
+    }
+
+    #[test]
+    fn test_bpgn_round_trip() {
+        let bpgn = "[Event \"FICS rated bughouse game\"]\n[WhiteA \"a\"]\n\n1A. e4 {180.0} 1B. Nf3 {179.9} {C:hi} 1a. e5 {179.125} 2A. Q@h5 {176.0} *";
+        let game = parse_bpgn(bpgn).unwrap();
+
+        assert_eq!(game.moves[1].comment, Some("C:hi".to_string()));
+        assert_eq!(game.moves[3].number, 2);
+        assert_eq!(game.moves[3].san, "Q@h5");
+        assert_eq!(game.moves[2].clock, Some(Duration::from_millis(179125)));
+        assert_eq!(game.board_moves(BughouseBoard::A).len(), 3);
+        assert_eq!(game.bpgn(), bpgn);
+    }
+
+    #[test]
+    fn test_bpgn_errors() {
+        assert_eq!(parse_bpgn("1. e4 *"), Err(PgnError::InvalidToken));
+        assert_eq!(parse_bpgn("e4 *"), Err(PgnError::InvalidToken));
+        assert_eq!(parse_bpgn("1A. e4 (1A. d4) *"), Err(PgnError::InvalidToken));
+        assert_eq!(parse_bpgn("1A. e4 1a. *"), Err(PgnError::InvalidToken));
+        assert_eq!(parse_bpgn("1A. e4 1a. e5"), Err(PgnError::MissingResult));
+        assert_eq!(
+            parse_bpgn("1A. e4 {inf} *").unwrap().moves[0].comment,
+            Some("inf".to_string())
+        );
+    }
+
+    #[test]
+    fn test_bpgn_validation() {
+        for bpgn in [
+            "1A. e5 *",
+            "1a. e5 *",
+            "1A. e4 1B. e4 2A. e5 *",
+            "1A. P@e2 *",
+            "1A. P@e8 *",
+            "1A. K@e4 *",
+            // The drop leaves the king in check from the queen
+            "1A. f3 1a. e5 2A. g4 2a. Qh4+ 3A. N@a3 *",
+        ] {
+            assert_eq!(parse_bpgn(bpgn), Err(PgnError::InvalidMove), "{bpgn}");
+        }
+
+        // A check can be blocked with a drop, even if it mates on a board
+        // alone
+        let game = parse_bpgn("1A. f3 1a. e5 2A. g4 2a. Qh4# 3A. P@g3 {[%clk 0:02:55.5]} *");
+        assert_eq!(
+            game.unwrap().moves[4].clock,
+            Some(Duration::from_millis(175_500))
+        );
+
+        // Only explicit clocks are read
+        let game = parse_bpgn("1A. e4 {42} 1a. e5 {1e2} *").unwrap();
+        assert_eq!(game.moves[0].clock, None);
+        assert_eq!(game.moves[0].comment, Some("42".to_string()));
+        assert_eq!(game.moves[1].comment, Some("1e2".to_string()));
+    }
+}
@@ -0,0 +1,338 @@
+use std::fmt::{Display, Error, Formatter};
+
+use crate::{
+    constants::Position,
+    errors::FenError,
+    logic::{Game, Piece},
+};
+
+/// A FEN with the fields variants add to it, as Lichess writes them: the
+/// Crazyhouse holdings in brackets after the board
+/// (`.../RNBQKBNR[Qp] w KQkq - 0 1`) and the checks each side still has to
+/// give in Three-check after the en passant square (`... w KQkq - 3+2 0 1`)
+///
+/// # Fields
+/// * `fen`: The FEN without the variant fields, as read by `Game::try_from_fen`
+/// * `holdings`: The pieces in hand of both players, in the order they are
+///   written, or `None` if the variant has no holdings
+/// * `promoted`: The squares of the Crazyhouse pieces marked with `~` as
+///   promoted, which go back to the hand as pawns when captured
+/// * `remaining_checks`: The checks White and Black still have to give, or
+///   `None` if the variant doesn't count them
+///
+/// # Example
+/// ```
+/// use chess_lab::parsing::VariantFen;
+///
+/// let fen = "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 3+3 0 2";
+/// let variant_fen = VariantFen::parse(fen, "Three-check").unwrap();
+///
+/// assert_eq!(variant_fen.remaining_checks, Some((3, 3)));
+/// assert_eq!(variant_fen.game().unwrap().fullmove_number, 2);
+/// assert_eq!(variant_fen.to_string(), fen);
+/// assert!(VariantFen::parse(fen, "Standard").is_err());
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariantFen {
+    pub fen: String,
+    pub holdings: Option<Vec<Piece>>,
+    pub promoted: Vec<Position>,
+    pub remaining_checks: Option<(u8, u8)>,
+}
+
+impl VariantFen {
+    /// Parses a FEN with the fields of a variant
+    /// Crazyhouse holdings can also be written as a ninth rank, and Three-check
+    /// counters as the checks already given at the end (`... 0 1 +1+0`).
+    /// The `~` marks of promoted pieces are kept apart from the board
+    ///
+    /// # Arguments
+    /// * `fen`: The FEN
+    /// * `variant`: The variant, as written in the `Variant` tag. The
+    ///   holdings are read for `Crazyhouse` and the checks for `Three-check`,
+    ///   missing fields meaning empty hands and no checks given
+    ///
+    /// # Returns
+    /// The FEN split into its fields, or an error if it doesn't have the
+    /// fields of the variant
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::logic::Piece;
+    /// use chess_lab::parsing::VariantFen;
+    ///
+    /// let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R/Pp w KQkq - 0 4";
+    /// let variant_fen = VariantFen::parse(fen, "Crazyhouse").unwrap();
+    ///
+    /// assert_eq!(variant_fen.holdings, Some(vec![Piece::from_fen('P'), Piece::from_fen('p')]));
+    /// assert_eq!(
+    ///     variant_fen.to_string(),
+    ///     "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R[Pp] w KQkq - 0 4"
+    /// );
+    /// ```
+    ///
+    pub fn parse(fen: &str, variant: &str) -> Result<VariantFen, FenError> {
+        let mut fields: Vec<&str> = fen.split(' ').collect();
+        let (crazyhouse, three_check) = match variant.to_lowercase().as_str() {
+            "crazyhouse" => (true, false),
+            "three-check" | "threecheck" | "3-check" => (false, true),
+            _ => (false, false),
+        };
+
+        let mut holdings = None;
+        let mut promoted = Vec::new();
+        let board;
+        if crazyhouse {
+            let (placement, hand) = split_holdings(fields[0])?;
+            holdings = Some(
+                hand.chars()
+                    .map(|c| match c {
+                        'P' | 'N' | 'B' | 'R' | 'Q' | 'p' | 'n' | 'b' | 'r' | 'q' => {
+                            Ok(Piece::from_fen(c))
+                        }
+                        _ => Err(FenError::Invalid),
+                    })
+                    .collect::<Result<Vec<Piece>, FenError>>()?,
+            );
+            (board, promoted) = split_promoted(placement)?;
+            fields[0] = &board;
+        }
+
+        let mut remaining_checks = None;
+        if three_check {
+            remaining_checks = Some((3, 3));
+            if fields.len() == 7 && fields[4].contains('+') {
+                remaining_checks = Some(parse_checks(fields.remove(4), false)?);
+            } else if fields.len() == 7 && fields[6].starts_with('+') {
+                remaining_checks = Some(parse_checks(&fields.pop().unwrap()[1..], true)?);
+            }
+        }
+
+        let fen = fields.join(" ");
+        Game::try_from_fen(&fen)?;
+        Ok(VariantFen {
+            fen,
+            holdings,
+            promoted,
+            remaining_checks,
+        })
+    }
+
+    /// Creates the game of the FEN, without the variant fields
+    ///
+    /// # Returns
+    /// The game, or an error if the FEN is invalid
+    ///
+    pub fn game(&self) -> Result<Game, FenError> {
+        Game::try_from_fen(&self.fen)
+    }
+}
+
+impl Display for VariantFen {
+    /// Writes the FEN with the variant fields, as Lichess does
+    ///
+    /// # Arguments
+    /// * `f`: The formatter
+    ///
+    /// # Returns
+    /// A result indicating if the FEN was written
+    ///
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        let mut fields: Vec<String> = self.fen.split(' ').map(String::from).collect();
+        if !self.promoted.is_empty() {
+            fields[0] = mark_promoted(&fields[0], &self.promoted);
+        }
+        if let Some(holdings) = &self.holdings {
+            let hand: String = holdings.iter().map(|piece| piece.to_string()).collect();
+            fields[0] = format!("{}[{}]", fields[0], hand);
+        }
+        if let Some((white, black)) = self.remaining_checks {
+            fields.insert(fields.len().min(4), format!("{}+{}", white, black));
+        }
+        write!(f, "{}", fields.join(" "))
+    }
+}
+
+/// Splits the holdings off the board of a Crazyhouse FEN
+///
+/// # Arguments
+/// * `board`: The board field, with the holdings in brackets or as a ninth
+///   rank, or without them
+///
+/// # Returns
+/// The board and the holdings, or an error if the brackets aren't closed
+///
+fn split_holdings(board: &str) -> Result<(&str, &str), FenError> {
+    if let Some((placement, hand)) = board.split_once('[') {
+        let hand = hand.strip_suffix(']').ok_or(FenError::Invalid)?;
+        return Ok((placement, hand));
+    }
+    if board.matches('/').count() == 8 {
+        return Ok(board.rsplit_once('/').unwrap());
+    }
+    Ok((board, ""))
+}
+
+/// Takes the `~` marks of promoted pieces off the board of a Crazyhouse FEN
+///
+/// # Arguments
+/// * `placement`: The board field, without the holdings
+///
+/// # Returns
+/// The board without the marks and the squares of the marked pieces, or an
+/// error if a mark doesn't follow a piece on the board
+///
+fn split_promoted(placement: &str) -> Result<(String, Vec<Position>), FenError> {
+    let mut promoted = Vec::new();
+    for (rank, pieces) in placement.split('/').enumerate() {
+        let mut col = 0u8;
+        let mut last = None;
+        for c in pieces.chars() {
+            match c {
+                '~' => promoted.push(last.take().ok_or(FenError::Invalid)?),
+                '1'..='8' => {
+                    col += c as u8 - b'0';
+                    last = None;
+                }
+                _ => {
+                    last = (col < 8 && rank < 8).then(|| Position::new(col, 7 - rank as u8));
+                    col += 1;
+                }
+            }
+        }
+    }
+    Ok((placement.replace('~', ""), promoted))
+}
+
+/// Writes the `~` marks of promoted pieces back on the board of a FEN
+///
+/// # Arguments
+/// * `placement`: The board field
+/// * `promoted`: The squares of the promoted pieces
+///
+/// # Returns
+/// The board with a mark after every promoted piece
+///
+fn mark_promoted(placement: &str, promoted: &[Position]) -> String {
+    let mut marked = String::new();
+    for (rank, pieces) in placement.split('/').enumerate() {
+        if rank > 0 {
+            marked.push('/');
+        }
+        let mut col = 0u8;
+        for c in pieces.chars() {
+            marked.push(c);
+            match c.to_digit(10) {
+                Some(empty) => col += empty as u8,
+                None => {
+                    let square = (col < 8 && rank < 8).then(|| Position::new(col, 7 - rank as u8));
+                    if square.is_some_and(|square| promoted.contains(&square)) {
+                        marked.push('~');
+                    }
+                    col += 1;
+                }
+            }
+        }
+    }
+    marked
+}
+
+/// Parses the checks of a Three-check FEN, written as `white+black`
+///
+/// # Arguments
+/// * `checks`: The counters
+/// * `given`: Whether the counters are the checks given instead of the ones
+///   left to give
+///
+/// # Returns
+/// The checks White and Black still have to give, or an error if the
+/// counters aren't between 0 and 3
+///
+fn parse_checks(checks: &str, given: bool) -> Result<(u8, u8), FenError> {
+    let counter = |count: &str| match count.parse::<u8>() {
+        Ok(count) if count <= 3 && given => Ok(3 - count),
+        Ok(count) if count <= 3 => Ok(count),
+        _ => Err(FenError::Invalid),
+    };
+    let (white, black) = checks.split_once('+').ok_or(FenError::Invalid)?;
+    Ok((counter(white)?, counter(black)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VariantFen;
+    use crate::{constants::Position, errors::FenError, logic::Piece};
+
+    #[test]
+    fn test_variant_fen() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+        let crazyhouse = VariantFen::parse(
+            "rnbqkb1r/ppp1pppp/5n2/8/8/8/PPPP1PPP/RNBQKBNR[Pp] w KQkq - 0 3",
+            "Crazyhouse",
+        )
+        .unwrap();
+        assert_eq!(
+            crazyhouse.holdings,
+            Some(vec![Piece::from_fen('P'), Piece::from_fen('p')])
+        );
+        assert_eq!(crazyhouse.remaining_checks, None);
+
+        // Missing fields are empty hands and no checks given
+        let crazyhouse = VariantFen::parse(start, "crazyhouse").unwrap();
+        assert_eq!(crazyhouse.holdings, Some(vec![]));
+        assert_eq!(
+            crazyhouse.to_string(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1"
+        );
+        let three_check = VariantFen::parse(start, "Three-check").unwrap();
+        assert_eq!(three_check.remaining_checks, Some((3, 3)));
+
+        let three_check = VariantFen::parse(&format!("{} +2+0", start), "Three-check").unwrap();
+        assert_eq!(three_check.remaining_checks, Some((1, 3)));
+        assert_eq!(
+            three_check.to_string(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 1+3 0 1"
+        );
+
+        // Promoted pieces are marked apart from the board
+        let fen = "4k2Q~/8/8/8/8/8/8/1n~2K3[] b - - 0 40";
+        let crazyhouse = VariantFen::parse(fen, "Crazyhouse").unwrap();
+        assert_eq!(crazyhouse.fen, "4k2Q/8/8/8/8/8/8/1n2K3 b - - 0 40");
+        assert_eq!(
+            crazyhouse.promoted,
+            vec![Position::from_string("h8"), Position::from_string("b1")]
+        );
+        assert_eq!(crazyhouse.to_string(), fen);
+
+        for (fen, variant) in [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[K] w KQkq - 0 1",
+                "Crazyhouse",
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[Q w KQkq - 0 1",
+                "Crazyhouse",
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1",
+                "Standard",
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 4+3 0 1",
+                "Three-check",
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3+3 0 1",
+                "Crazyhouse",
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/~RNBQKBNR[] w KQkq - 0 1",
+                "Crazyhouse",
+            ),
+        ] {
+            assert_eq!(VariantFen::parse(fen, variant), Err(FenError::Invalid));
+        }
+    }
+}
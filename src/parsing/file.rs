@@ -0,0 +1,257 @@
+#[cfg(feature = "tokio")]
+use std::io::Read;
+use std::{fs::File, path::Path};
+
+use crate::{errors::PgnError, logic::Game};
+
+#[cfg(feature = "tokio")]
+use super::parse_pgn_with_options;
+use super::{PgnParseOptions, PgnReader};
+
+/// Loads the first game of a PGN file, decompressing it if needed as done by
+/// [`PgnReader::with_decompression`]
+///
+/// # Arguments
+/// * `path`: The file to read
+/// * `options`: The options used to parse the game
+///
+/// # Returns
+/// The first game of the file, or an error if the file can't be read, has no
+/// games or the first one can't be parsed
+///
+/// # Example
+/// ```
+/// use chess_lab::parsing::{load_pgn, PgnParseOptions};
+///
+/// let path = std::env::temp_dir().join("chess_lab_load_pgn_example.pgn");
+/// std::fs::write(&path, "1. e4 e5 *\n\n1. d4 d5 *\n").unwrap();
+///
+/// let game = load_pgn(&path, &PgnParseOptions::lenient()).unwrap();
+/// assert_eq!(game.history_vec().len(), 2);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+///
+pub fn load_pgn(path: &Path, options: &PgnParseOptions) -> Result<Game, PgnError> {
+    let file = File::open(path).map_err(|_| PgnError::ReadError)?;
+    PgnReader::with_decompression(file, options.clone())?
+        .next()
+        .unwrap_or(Err(PgnError::Empty))
+}
+
+/// Loads all the games of a PGN file, decompressing it if needed as done by
+/// [`PgnReader::with_decompression`]
+///
+/// # Arguments
+/// * `path`: The file to read
+/// * `options`: The options used to parse every game
+///
+/// # Returns
+/// The games of the file, or the first error found reading or parsing them
+///
+pub fn load_all_pgn(path: &Path, options: &PgnParseOptions) -> Result<Vec<Game>, PgnError> {
+    let file = File::open(path).map_err(|_| PgnError::ReadError)?;
+    PgnReader::with_decompression(file, options.clone())?.collect()
+}
+
+/// Loads the first game of a PGN file without blocking the async runtime: the
+/// file is read, decompressed and split on the blocking thread pool
+///
+/// # Arguments
+/// * `path`: The file to read
+/// * `options`: The options used to parse the game
+///
+/// # Returns
+/// The first game of the file, or an error as in [`load_pgn`]
+///
+#[cfg(feature = "tokio")]
+pub async fn load_pgn_async(
+    path: impl AsRef<Path>,
+    options: &PgnParseOptions,
+) -> Result<Game, PgnError> {
+    let file = open_async(path.as_ref()).await?;
+    let texts = split_games(file, options, Some(1)).await?;
+    match texts.first() {
+        Some(text) => parse_pgn_with_options(text, options),
+        None => Err(PgnError::Empty),
+    }
+}
+
+/// Loads all the games of a PGN file without blocking the async runtime: the
+/// file is read, decompressed and split on the blocking thread pool
+///
+/// # Arguments
+/// * `path`: The file to read
+/// * `options`: The options used to parse every game
+///
+/// # Returns
+/// The games of the file, or an error as in [`load_all_pgn`]
+///
+#[cfg(feature = "tokio")]
+pub async fn load_all_pgn_async(
+    path: impl AsRef<Path>,
+    options: &PgnParseOptions,
+) -> Result<Vec<Game>, PgnError> {
+    let file = open_async(path.as_ref()).await?;
+    let texts = split_games(file, options, None).await?;
+    parse_games(texts, options).await
+}
+
+/// Downloads a PGN over HTTP and parses all its games
+///
+/// # Arguments
+/// * `url`: The address of the PGN
+/// * `options`: The options used to parse every game
+///
+/// # Returns
+/// The games of the PGN, or an error if it can't be downloaded (including
+/// responses with an error status) or parsed
+///
+#[cfg(feature = "tokio")]
+pub async fn from_url(url: &str, options: &PgnParseOptions) -> Result<Vec<Game>, PgnError> {
+    let text = reqwest::get(url)
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|_| PgnError::ReadError)?
+        .text()
+        .await
+        .map_err(|_| PgnError::ReadError)?;
+
+    let texts = split_games(std::io::Cursor::new(text), options, None).await?;
+    parse_games(texts, options).await
+}
+
+/// Opens a file on the blocking thread pool
+///
+/// # Arguments
+/// * `path`: The file to open
+///
+/// # Returns
+/// The file, or a read error
+///
+#[cfg(feature = "tokio")]
+async fn open_async(path: &Path) -> Result<File, PgnError> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || File::open(path))
+        .await
+        .map_err(|_| PgnError::ReadError)?
+        .map_err(|_| PgnError::ReadError)
+}
+
+/// Reads, decompresses and splits a PGN into the texts of its games on the
+/// blocking thread pool
+/// The games themselves are parsed by the caller, a [`Game`] can't be sent
+/// back from another thread
+///
+/// # Arguments
+/// * `source`: The PGN to read
+/// * `options`: The options of the reader
+/// * `limit`: The most games to read, all of them if `None`
+///
+/// # Returns
+/// The texts of the games, or the first error found reading them
+///
+#[cfg(feature = "tokio")]
+async fn split_games<R: Read + Send + 'static>(
+    source: R,
+    options: &PgnParseOptions,
+    limit: Option<usize>,
+) -> Result<Vec<String>, PgnError> {
+    let options = options.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut reader = PgnReader::with_decompression(source, options)?;
+        let mut texts = Vec::new();
+        while limit.is_none_or(|limit| texts.len() < limit) {
+            match reader.next_game_text()? {
+                Some(text) => texts.push(text),
+                None => break,
+            }
+        }
+        Ok(texts)
+    })
+    .await
+    .map_err(|_| PgnError::ReadError)?
+}
+
+/// Parses the texts of some games, yielding to the runtime after each one so
+/// a long file doesn't hold the thread
+///
+/// # Arguments
+/// * `texts`: The texts of the games
+/// * `options`: The options used to parse every game
+///
+/// # Returns
+/// The games, or the first error found parsing them
+///
+#[cfg(feature = "tokio")]
+async fn parse_games(texts: Vec<String>, options: &PgnParseOptions) -> Result<Vec<Game>, PgnError> {
+    let mut games = Vec::with_capacity(texts.len());
+    for text in texts {
+        games.push(parse_pgn_with_options(&text, options)?);
+        tokio::task::yield_now().await;
+    }
+    Ok(games)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{load_all_pgn, load_pgn};
+    use crate::{errors::PgnError, parsing::PgnParseOptions};
+
+    #[test]
+    fn test_load_pgn() {
+        let path = std::env::temp_dir().join(format!("chess_lab_load_{}.pgn", std::process::id()));
+        let options = PgnParseOptions::lenient();
+
+        fs::write(&path, "").unwrap();
+        assert_eq!(load_pgn(&path, &options).err(), Some(PgnError::Empty));
+        assert_eq!(
+            load_all_pgn(&path, &options).map(|games| games.len()),
+            Ok(0)
+        );
+
+        fs::write(
+            &path,
+            "[Event \"A\"]\n\n1. e4 *\n\n[Event \"B\"]\n\n1. d4 *\n\n[Event \"C\"]\n\n1. e5 *\n",
+        )
+        .unwrap();
+        assert!(load_pgn(&path, &options)
+            .unwrap()
+            .pgn()
+            .ends_with("\n1. e4 *"));
+        assert_eq!(
+            load_all_pgn(&path, &options).err(),
+            Some(PgnError::InvalidMove)
+        );
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(load_pgn(&path, &options).err(), Some(PgnError::ReadError));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_load_pgn_async() {
+        use super::{load_all_pgn_async, load_pgn_async};
+        use crate::export::{save_pgn_async, SaveMode};
+
+        let path =
+            std::env::temp_dir().join(format!("chess_lab_load_async_{}.pgn", std::process::id()));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            save_pgn_async(&path, "[Event \"A\"]\n1. e4 *", SaveMode::Overwrite)
+                .await
+                .unwrap();
+            save_pgn_async(&path, "[Event \"B\"]\n1. d4 *", SaveMode::Append)
+                .await
+                .unwrap();
+            let options = PgnParseOptions::lenient();
+            let game = load_pgn_async(&path, &options).await.unwrap();
+            assert!(game.pgn().ends_with("\n1. e4 *"));
+            assert_eq!(load_all_pgn_async(&path, &options).await.unwrap().len(), 2);
+        });
+        fs::remove_file(&path).unwrap();
+    }
+}
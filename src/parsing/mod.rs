@@ -0,0 +1,17 @@
+mod bpgn;
+mod fen;
+mod file;
+mod notation;
+mod pgn;
+mod reader;
+mod validate;
+mod variant;
+
+pub use bpgn::*;
+pub use fen::*;
+pub use file::*;
+pub use notation::*;
+pub use pgn::*;
+pub use reader::*;
+pub use validate::*;
+pub use variant::*;
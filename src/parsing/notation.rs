@@ -0,0 +1,356 @@
+use regex::Regex;
+
+use crate::{
+    constants::{CastleType, MoveType, PieceType, Position},
+    errors::{IllegalReason, MoveError},
+    logic::{Game, ParsedMove},
+};
+
+/// The notations a move can be written in
+///
+/// # Variants
+/// * `San`: Standard algebraic notation (`e4`, `Nf3`, `exd5`, `e8=Q`, `O-O`),
+///   en passant captures may end with `e.p.` (`exd6 e.p.`)
+/// * `LongAlgebraic`: Long algebraic notation, with both squares (`e2-e4`,
+///   `Ng1-f3`, `e4xd5`, `e7-e8=Q`), castling is written as in SAN
+/// * `Smith`: Smith notation, with both squares and the captured piece
+///   (`e2e4`, `e4d5p`, `e5d6E`, `e1g1c`, `e7d8rQ`)
+/// * `Uci`: UCI notation, with both squares and the promotion piece in
+///   lowercase (`e2e4`, `e4d5`, `e1g1`, `a7a8q`)
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notation {
+    San,
+    LongAlgebraic,
+    Smith,
+    Uci,
+}
+
+impl Notation {
+    /// Detects the notation a move is written in
+    /// Moves written with just both squares, like `e2e4` or `e4d5`, and a
+    /// lowercase promotion piece are read as UCI, so captures, castling and
+    /// promotions are taken from the board
+    ///
+    /// # Arguments
+    /// * `move_str`: The move
+    ///
+    /// # Returns
+    /// The notation of the move, or `None` if it doesn't follow any of them
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::parsing::Notation;
+    ///
+    /// assert_eq!(Notation::detect("Nf3"), Some(Notation::San));
+    /// assert_eq!(Notation::detect("Ng1-f3"), Some(Notation::LongAlgebraic));
+    /// assert_eq!(Notation::detect("e1g1c"), Some(Notation::Smith));
+    /// assert_eq!(Notation::detect("a7a8q"), Some(Notation::Uci));
+    /// assert_eq!(Notation::detect("Nf9"), None);
+    /// ```
+    ///
+    pub fn detect(move_str: &str) -> Option<Notation> {
+        let san =
+            Regex::new(r"^([NBRQK]?[a-h]?[1-8]?x?[a-h][1-8](=[NBRQ])?( ?e\.p\.)?|O-O(-O)?)[+#]?$")
+                .unwrap();
+        let long = Regex::new(r"^[NBRQK]?[a-h][1-8][-x][a-h][1-8](=?[NBRQ])?[+#]?$").unwrap();
+        let smith = Regex::new(r"^[a-h][1-8][a-h][1-8][pnbrqkEcC]?[NBRQ]?$").unwrap();
+        let uci = Regex::new(r"^[a-h][1-8][a-h][1-8][nbrq]?$").unwrap();
+
+        if uci.is_match(move_str) {
+            Some(Notation::Uci)
+        } else if smith.is_match(move_str) {
+            Some(Notation::Smith)
+        } else if san.is_match(move_str) {
+            Some(Notation::San)
+        } else if long.is_match(move_str) {
+            Some(Notation::LongAlgebraic)
+        } else {
+            None
+        }
+    }
+}
+
+/// Parses a move in long algebraic notation
+/// Castling is parsed as SAN, and a king moving two squares is read as castling
+///
+/// # Arguments
+/// * `game`: The game the move is played in
+/// * `move_str`: The move
+///
+/// # Returns
+/// The parsed move, or an error if the move is not in long algebraic notation
+///
+pub(crate) fn parse_long_algebraic(game: &Game, move_str: &str) -> Result<ParsedMove, MoveError> {
+    if move_str.starts_with('O') {
+        return game.parse_move_with(move_str, Notation::San);
+    }
+
+    let re = Regex::new(r"^([NBRQK])?([a-h][1-8])([-x])([a-h][1-8])(=?([NBRQ]))?[+#]?$").unwrap();
+    let caps = re.captures(move_str).ok_or(MoveError::Invalid {
+        san: move_str.to_string(),
+    })?;
+
+    let piece = caps
+        .get(1)
+        .and_then(|piece| piece.as_str().chars().next())
+        .and_then(PieceType::from_char)
+        .unwrap_or(PieceType::Pawn);
+    let from = Position::from_string(&caps[2]);
+    let to = Position::from_string(&caps[4]);
+    let capture = &caps[3] == "x";
+    let promotion = caps
+        .get(6)
+        .and_then(|promotion| promotion.as_str().chars().next())
+        .and_then(PieceType::from_char);
+
+    if promotion.is_some() && piece != PieceType::Pawn {
+        return Err(MoveError::Invalid {
+            san: move_str.to_string(),
+        });
+    }
+    Ok((
+        piece,
+        (Some(from.col), Some(from.row)),
+        to,
+        move_type(game, piece, &from, &to, capture, promotion),
+    ))
+}
+
+/// Parses a move in Smith notation
+/// The piece is the one on the starting square, and a capture must name the
+/// captured piece
+///
+/// # Arguments
+/// * `game`: The game the move is played in
+/// * `move_str`: The move
+///
+/// # Returns
+/// The parsed move, or an error if the move is not in Smith notation or the
+/// starting square is empty
+///
+pub(crate) fn parse_smith(game: &Game, move_str: &str) -> Result<ParsedMove, MoveError> {
+    let re = Regex::new(r"^([a-h][1-8])([a-h][1-8])([pnbrqkEcC])?([NBRQ])?$").unwrap();
+    let caps = re.captures(move_str).ok_or(MoveError::Invalid {
+        san: move_str.to_string(),
+    })?;
+
+    let from = Position::from_string(&caps[1]);
+    let to = Position::from_string(&caps[2]);
+    let piece = match game.board.get_piece(&from) {
+        Some(piece) => piece.piece_type,
+        None => {
+            return Err(MoveError::Illegal {
+                san: move_str.to_string(),
+                to,
+                reason: IllegalReason::BadPattern,
+            })
+        }
+    };
+    let promotion = caps
+        .get(4)
+        .and_then(|promotion| promotion.as_str().chars().next())
+        .and_then(PieceType::from_char);
+
+    let move_type = match caps.get(3).map(|suffix| suffix.as_str()) {
+        Some("c") => MoveType::Castle {
+            side: CastleType::KingSide,
+        },
+        Some("C") => MoveType::Castle {
+            side: CastleType::QueenSide,
+        },
+        Some("E") => MoveType::EnPassant,
+        Some(_) => MoveType::Normal {
+            capture: true,
+            promotion,
+        },
+        None => MoveType::Normal {
+            capture: false,
+            promotion,
+        },
+    };
+    if promotion.is_some() && !matches!(move_type, MoveType::Normal { .. }) {
+        return Err(MoveError::Invalid {
+            san: move_str.to_string(),
+        });
+    }
+    Ok((piece, (Some(from.col), Some(from.row)), to, move_type))
+}
+
+/// Parses a move in UCI notation
+/// The piece is the one on the starting square, a move to a square of the
+/// opponent is a capture, and a king moving two squares castles
+///
+/// # Arguments
+/// * `game`: The game the move is played in
+/// * `move_str`: The move
+///
+/// # Returns
+/// The parsed move, or an error if the move is not in UCI notation or the
+/// starting square is empty
+///
+pub(crate) fn parse_uci(game: &Game, move_str: &str) -> Result<ParsedMove, MoveError> {
+    let re = Regex::new(r"^([a-h][1-8])([a-h][1-8])([nbrq])?$").unwrap();
+    let caps = re.captures(move_str).ok_or(MoveError::Invalid {
+        san: move_str.to_string(),
+    })?;
+
+    let from = Position::from_string(&caps[1]);
+    let to = Position::from_string(&caps[2]);
+    let piece = match game.board.get_piece(&from) {
+        Some(piece) => piece,
+        None => {
+            return Err(MoveError::Illegal {
+                san: move_str.to_string(),
+                to,
+                reason: IllegalReason::BadPattern,
+            })
+        }
+    };
+    let promotion = caps
+        .get(3)
+        .and_then(|promotion| promotion.as_str().chars().next())
+        .and_then(|promotion| PieceType::from_char(promotion.to_ascii_uppercase()));
+    let capture = game
+        .board
+        .get_piece(&to)
+        .is_some_and(|captured| captured.color != piece.color)
+        || (piece.piece_type == PieceType::Pawn && from.col != to.col);
+
+    if promotion.is_some() && piece.piece_type != PieceType::Pawn {
+        return Err(MoveError::Invalid {
+            san: move_str.to_string(),
+        });
+    }
+    Ok((
+        piece.piece_type,
+        (Some(from.col), Some(from.row)),
+        to,
+        move_type(game, piece.piece_type, &from, &to, capture, promotion),
+    ))
+}
+
+/// Infers the type of a move written with both squares
+///
+/// # Arguments
+/// * `game`: The game the move is played in
+/// * `piece`: The type of the moving piece
+/// * `from`: The starting square
+/// * `to`: The ending square
+/// * `capture`: Whether the move is written as a capture
+/// * `promotion`: The promotion piece, if any
+///
+/// # Returns
+/// Castling for a king moving two squares, en passant for a pawn capturing on
+/// the en passant square, and a normal move otherwise
+///
+fn move_type(
+    game: &Game,
+    piece: PieceType,
+    from: &Position,
+    to: &Position,
+    capture: bool,
+    promotion: Option<PieceType>,
+) -> MoveType {
+    match piece {
+        PieceType::King if from.row == to.row && from.col.abs_diff(to.col) == 2 => {
+            MoveType::Castle {
+                side: if to.col > from.col {
+                    CastleType::KingSide
+                } else {
+                    CastleType::QueenSide
+                },
+            }
+        }
+        PieceType::Pawn if capture && Some(*to) == game.en_passant => MoveType::EnPassant,
+        _ => MoveType::Normal { capture, promotion },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Notation;
+    use crate::{errors::MoveError, logic::Game};
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Notation::detect("e2e4"), Some(Notation::Uci));
+        assert_eq!(Notation::detect("a7a8q"), Some(Notation::Uci));
+        assert_eq!(Notation::detect("e2xe4"), Some(Notation::San));
+        assert_eq!(Notation::detect("exd8=Q+"), Some(Notation::San));
+        assert_eq!(Notation::detect("O-O-O"), Some(Notation::San));
+        assert_eq!(Notation::detect("exd6 e.p."), Some(Notation::San));
+        assert_eq!(Notation::detect("e7xd8Q"), Some(Notation::LongAlgebraic));
+        assert_eq!(Notation::detect("e2-e4"), Some(Notation::LongAlgebraic));
+        assert_eq!(Notation::detect("e4d5p"), Some(Notation::Smith));
+        assert_eq!(Notation::detect("e7e8Q"), Some(Notation::Smith));
+        assert_eq!(Notation::detect("e2-e9"), None);
+        assert_eq!(Notation::detect("Pe2e4q"), None);
+    }
+
+    #[test]
+    fn test_long_algebraic() {
+        let mut game = Game::default();
+        for mov in [
+            "e2-e4", "d7-d5", "e4xd5", "Qd8xd5", "Ng1-f3", "Bc8-g4", "Bf1-e2",
+        ] {
+            game.move_piece(mov).unwrap();
+        }
+        game.move_piece_with("Nb8-c6", Notation::LongAlgebraic)
+            .unwrap();
+        game.move_piece("Ke1-g1").unwrap();
+        assert_eq!(
+            game.pgn(),
+            "1. e4 d5 2. exd5 Qxd5 3. Nf3 Bg4 4. Be2 Nc6 5. O-O *"
+        );
+
+        assert!(matches!(
+            game.move_piece_with("Qd5-d1", Notation::San),
+            Err(MoveError::Invalid { .. })
+        ));
+        assert!(matches!(
+            game.move_piece("Nc6-e4"),
+            Err(MoveError::Illegal { .. })
+        ));
+    }
+
+    #[test]
+    fn test_uci() {
+        let mut game = Game::from_fen("r3k3/1P6/8/3pP3/8/8/8/R3K2R w KQq d6 0 1");
+        game.setup_tags = false;
+        for mov in ["e5d6", "a8d8", "e1g1", "d8d6", "b7b8q"] {
+            game.move_piece(mov).unwrap();
+        }
+        assert_eq!(game.pgn(), "1. e5xd6 Rd8 2. O-O Rxd6 3. b8=Q+ *");
+
+        assert!(matches!(
+            game.move_piece("e8e7q"),
+            Err(MoveError::Invalid { .. })
+        ));
+        assert!(matches!(
+            game.move_piece_with("e8f8", Notation::Uci),
+            Err(MoveError::Illegal { .. })
+        ));
+    }
+
+    #[test]
+    fn test_smith() {
+        let mut game = Game::from_fen("r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1");
+        game.setup_tags = false;
+        game.move_piece("e5d6E").unwrap();
+        game.move_piece("e8g8c").unwrap();
+        game.move_piece("b7a8rN").unwrap();
+        game.move_piece("g8g7").unwrap();
+        game.move_piece_with("e1c1C", Notation::Smith).unwrap();
+        assert_eq!(game.pgn(), "1. e5xd6 O-O 2. bxa8=N Kg7 3. O-O-O *");
+
+        assert!(matches!(
+            game.move_piece("e4e5"),
+            Err(MoveError::Illegal { .. })
+        ));
+        assert!(matches!(
+            game.move_piece_with("d6d7cQ", Notation::Smith),
+            Err(MoveError::Invalid { .. })
+        ));
+    }
+}
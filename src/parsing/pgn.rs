@@ -0,0 +1,892 @@
+use std::{iter::Peekable, str::Chars};
+
+use crate::{
+    constants::{extract_annotations, extract_node_data, pgn::is_result, GameStatus},
+    errors::PgnError,
+    logic::Game,
+};
+
+/// Options that control how permissive the PGN parser is
+///
+/// # Fields
+/// * `allow_missing_tags`: Accept games without the Seven Tag Roster, or with
+///   a `Result` tag that is not a game termination marker
+/// * `allow_missing_result`: Accept movetext without a game termination marker
+/// * `allow_zero_castling`: Accept castling written with zeros (`0-0`, `0-0-0`)
+/// * `allow_en_passant_suffix`: Accept an `e.p.` suffix after en passant captures
+/// * `allow_bom`: Skip a byte order mark at the start of the input
+/// * `allow_unterminated_comment`: Close a comment left open at the end of the input
+/// * `allow_non_ascii_tags`: Accept tag values with characters outside ASCII,
+///   and backslashes that escape neither a quote nor a backslash, which are
+///   kept as written
+/// * `max_variation_depth`: The deepest nesting of variations accepted, or
+///   `None` for no limit
+/// * `max_variation_width`: The most variations accepted on a single move,
+///   or `None` for no limit
+/// * `max_plies`: The most moves accepted, counting the variations, or
+///   `None` for no limit
+/// * `max_tags`: The most tag pairs accepted, or `None` for no limit
+/// * `max_comment_size`: The longest comment accepted, in bytes, or `None`
+///   for no limit
+///
+/// The limits protect servers from untrusted uploads, and are not set by
+/// `strict` nor `lenient`: [`PgnParseOptions::limited`] sets them all
+///
+/// Game termination markers inside comments (`{ 1-0 was agreed }`) never end
+/// the game, in any mode
+///
+/// # Example
+/// ```
+/// use chess_lab::parsing::{parse_pgn_with_options, PgnParseOptions};
+///
+/// let pgn = "\u{feff}1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. 0-0";
+///
+/// assert!(parse_pgn_with_options(pgn, &PgnParseOptions::strict()).is_err());
+/// assert!(parse_pgn_with_options(pgn, &PgnParseOptions::lenient()).is_ok());
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnParseOptions {
+    pub allow_missing_tags: bool,
+    pub allow_missing_result: bool,
+    pub allow_zero_castling: bool,
+    pub allow_en_passant_suffix: bool,
+    pub allow_bom: bool,
+    pub allow_unterminated_comment: bool,
+    pub allow_non_ascii_tags: bool,
+    pub max_variation_depth: Option<usize>,
+    pub max_variation_width: Option<usize>,
+    pub max_plies: Option<usize>,
+    pub max_tags: Option<usize>,
+    pub max_comment_size: Option<usize>,
+}
+
+impl Default for PgnParseOptions {
+    /// Creates the default options, which follow the PGN standard strictly
+    ///
+    /// # Returns
+    /// The strict options
+    ///
+    fn default() -> PgnParseOptions {
+        PgnParseOptions::strict()
+    }
+}
+
+impl PgnParseOptions {
+    /// Creates options that only accept PGN following the standard
+    ///
+    /// # Returns
+    /// The strict options
+    ///
+    pub fn strict() -> PgnParseOptions {
+        PgnParseOptions {
+            allow_missing_tags: false,
+            allow_missing_result: false,
+            allow_zero_castling: false,
+            allow_en_passant_suffix: false,
+            allow_bom: false,
+            allow_unterminated_comment: false,
+            allow_non_ascii_tags: false,
+            max_variation_depth: None,
+            max_variation_width: None,
+            max_plies: None,
+            max_tags: None,
+            max_comment_size: None,
+        }
+    }
+
+    /// Creates options that tolerate the quirks of the PGN produced by
+    /// Chess.com and other GUIs
+    ///
+    /// # Returns
+    /// The lenient options
+    ///
+    pub fn lenient() -> PgnParseOptions {
+        PgnParseOptions {
+            allow_missing_tags: true,
+            allow_missing_result: true,
+            allow_zero_castling: true,
+            allow_en_passant_suffix: true,
+            allow_bom: true,
+            allow_unterminated_comment: true,
+            allow_non_ascii_tags: true,
+            max_variation_depth: None,
+            max_variation_width: None,
+            max_plies: None,
+            max_tags: None,
+            max_comment_size: None,
+        }
+    }
+
+    /// Creates strict options with limits suited to untrusted uploads: 2000
+    /// moves, 8 levels of variations with 8 variations per move, 64 tags and
+    /// comments of 4 KiB
+    ///
+    /// # Returns
+    /// The limited options
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::errors::PgnError;
+    /// use chess_lab::parsing::{parse_pgn_with_options, PgnParseOptions};
+    ///
+    /// let options = PgnParseOptions {
+    ///     allow_missing_tags: true,
+    ///     ..PgnParseOptions::limited()
+    /// };
+    /// let pgn = format!("1. e4 {{ {} }} *", "spam ".repeat(1000));
+    ///
+    /// assert_eq!(parse_pgn_with_options(&pgn, &options).err(), Some(PgnError::LimitExceeded));
+    /// assert!(parse_pgn_with_options("1. e4 { fine } *", &options).is_ok());
+    /// ```
+    ///
+    pub fn limited() -> PgnParseOptions {
+        PgnParseOptions {
+            max_variation_depth: Some(8),
+            max_variation_width: Some(8),
+            max_plies: Some(2000),
+            max_tags: Some(64),
+            max_comment_size: Some(4096),
+            ..PgnParseOptions::strict()
+        }
+    }
+}
+
+/// A token of a PGN file
+///
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Token {
+    Tag(String, String),
+    Comment(String),
+    VariationStart,
+    VariationEnd,
+    MoveNumber,
+    Nag,
+    Move(String),
+    Result(String),
+}
+
+/// Splits a PGN text into tokens
+///
+pub(super) struct Tokenizer<'a> {
+    chars: Peekable<Chars<'a>>,
+    options: &'a PgnParseOptions,
+    line_start: bool,
+}
+
+impl<'a> Tokenizer<'a> {
+    /// Creates a tokenizer over a PGN text
+    ///
+    /// # Arguments
+    /// * `pgn`: The PGN text
+    /// * `options`: The parse options
+    ///
+    /// # Returns
+    /// The tokenizer, or an error if the text starts with a byte order mark
+    /// that the options don't allow
+    ///
+    pub(super) fn new(
+        pgn: &'a str,
+        options: &'a PgnParseOptions,
+    ) -> Result<Tokenizer<'a>, PgnError> {
+        let pgn = match pgn.strip_prefix('\u{feff}') {
+            Some(_) if !options.allow_bom => return Err(PgnError::InvalidToken),
+            Some(pgn) => pgn,
+            None => pgn,
+        };
+        Ok(Tokenizer {
+            chars: pgn.chars().peekable(),
+            options,
+            line_start: true,
+        })
+    }
+
+    /// Reads the next token
+    ///
+    /// # Returns
+    /// The next token, `None` at the end of the input, or an error if the
+    /// input is malformed
+    ///
+    pub(super) fn next_token(&mut self) -> Result<Option<Token>, PgnError> {
+        loop {
+            let c = match self.chars.peek() {
+                Some(c) => *c,
+                None => return Ok(None),
+            };
+
+            if c == '\n' {
+                self.chars.next();
+                self.line_start = true;
+                continue;
+            }
+            if c.is_whitespace() {
+                self.chars.next();
+                continue;
+            }
+            if c == '%' && self.line_start {
+                self.skip_line();
+                continue;
+            }
+            self.line_start = false;
+
+            return match c {
+                '[' => {
+                    self.chars.next();
+                    self.read_tag().map(Some)
+                }
+                '{' => {
+                    self.chars.next();
+                    self.read_brace_comment().map(Some)
+                }
+                ';' => {
+                    self.chars.next();
+                    let comment = self.skip_line();
+                    Ok(Some(Token::Comment(comment.trim().to_string())))
+                }
+                '(' => {
+                    self.chars.next();
+                    Ok(Some(Token::VariationStart))
+                }
+                ')' => {
+                    self.chars.next();
+                    Ok(Some(Token::VariationEnd))
+                }
+                '$' => {
+                    self.chars.next();
+                    if self.read_while(|c| c.is_ascii_digit()).is_empty() {
+                        return Err(PgnError::InvalidToken);
+                    }
+                    Ok(Some(Token::Nag))
+                }
+                '*' => {
+                    self.chars.next();
+                    Ok(Some(Token::Result(String::from("*"))))
+                }
+                _ => self.read_symbol().map(Some),
+            };
+        }
+    }
+
+    /// Reads characters while they match a predicate
+    ///
+    /// # Arguments
+    /// * `predicate`: The condition the characters must meet
+    ///
+    /// # Returns
+    /// The characters read
+    ///
+    fn read_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let mut result = String::new();
+        while let Some(c) = self.chars.peek() {
+            if !predicate(*c) {
+                break;
+            }
+            result.push(*c);
+            self.chars.next();
+        }
+        result
+    }
+
+    /// Skips the rest of the current line
+    ///
+    /// # Returns
+    /// The skipped text
+    ///
+    fn skip_line(&mut self) -> String {
+        let line = self.read_while(|c| c != '\n');
+        self.chars.next();
+        self.line_start = true;
+        line
+    }
+
+    /// Reads a tag pair, after its opening bracket
+    ///
+    /// # Returns
+    /// The tag token, or an error if the tag is malformed
+    ///
+    fn read_tag(&mut self) -> Result<Token, PgnError> {
+        self.read_while(|c| c.is_whitespace());
+        let name = self.read_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        if name.is_empty() {
+            return Err(PgnError::InvalidTag);
+        }
+        self.read_while(|c| c.is_whitespace());
+        if self.chars.next() != Some('"') {
+            return Err(PgnError::InvalidTag);
+        }
+
+        let mut value = String::new();
+        loop {
+            match self.chars.next() {
+                Some('\\') => match self.chars.peek() {
+                    Some('\\' | '"') => value.extend(self.chars.next()),
+                    Some(_) if self.options.allow_non_ascii_tags => value.push('\\'),
+                    _ => return Err(PgnError::InvalidTag),
+                },
+                Some('"') => break,
+                Some('\n') | None => return Err(PgnError::InvalidTag),
+                Some(c) if !c.is_ascii() && !self.options.allow_non_ascii_tags => {
+                    return Err(PgnError::InvalidTag)
+                }
+                Some(c) => value.push(c),
+            }
+        }
+
+        self.read_while(|c| c.is_whitespace());
+        if self.chars.next() != Some(']') {
+            return Err(PgnError::InvalidTag);
+        }
+        Ok(Token::Tag(name, value))
+    }
+
+    /// Reads a brace comment, after its opening brace
+    ///
+    /// # Returns
+    /// The comment token, or an error if the comment is never closed
+    ///
+    fn read_brace_comment(&mut self) -> Result<Token, PgnError> {
+        let comment = self.read_while(|c| c != '}');
+        if self.chars.next().is_none() && !self.options.allow_unterminated_comment {
+            return Err(PgnError::UnterminatedComment);
+        }
+        Ok(Token::Comment(
+            comment.split_whitespace().collect::<Vec<&str>>().join(" "),
+        ))
+    }
+
+    /// Reads a symbol: a move number, a move, a termination marker or a
+    /// suffix annotation
+    ///
+    /// # Returns
+    /// The token, or an error if the symbol is not valid
+    ///
+    fn read_symbol(&mut self) -> Result<Token, PgnError> {
+        let first = *self.chars.peek().unwrap();
+
+        if first.is_ascii_digit() {
+            let digits = self.read_while(|c| c.is_ascii_digit());
+            if self.chars.peek() == Some(&'.') {
+                self.read_while(|c| c == '.');
+                return Ok(Token::MoveNumber);
+            }
+            let rest = self.read_while(is_symbol_char);
+            let symbol = format!("{}{}", digits, rest);
+            return match symbol.as_str() {
+                "1-0" | "0-1" | "1/2-1/2" => Ok(Token::Result(symbol)),
+                _ => self.read_move(symbol),
+            };
+        }
+        if first == '!' || first == '?' {
+            self.read_while(|c| c == '!' || c == '?');
+            return Ok(Token::Nag);
+        }
+
+        let symbol = self.read_while(is_symbol_char);
+        if symbol.is_empty() {
+            return Err(PgnError::InvalidToken);
+        }
+        self.read_move(symbol)
+    }
+
+    /// Normalizes a move symbol, removing its suffix annotations
+    ///
+    /// # Arguments
+    /// * `symbol`: The symbol read from the input
+    ///
+    /// # Returns
+    /// The move token, or an error if the symbol uses a notation the options
+    /// don't allow
+    ///
+    fn read_move(&mut self, symbol: String) -> Result<Token, PgnError> {
+        let mut mov = symbol.trim_end_matches(['!', '?']).to_string();
+
+        if let Some(stripped) = mov.strip_suffix("e.p.") {
+            if !self.options.allow_en_passant_suffix {
+                return Err(PgnError::InvalidToken);
+            }
+            mov = stripped.to_string();
+        }
+        if mov.is_empty() {
+            return if self.options.allow_en_passant_suffix && symbol == "e.p." {
+                Ok(Token::Nag)
+            } else {
+                Err(PgnError::InvalidToken)
+            };
+        }
+        if mov.starts_with("0-0") {
+            if !self.options.allow_zero_castling {
+                return Err(PgnError::InvalidToken);
+            }
+            mov = mov.replace('0', "O");
+        }
+        Ok(Token::Move(mov))
+    }
+}
+
+/// Checks if a character can be part of a PGN symbol
+///
+/// # Arguments
+/// * `c`: The character to check
+///
+/// # Returns
+/// Whether the character can be part of a symbol
+///
+fn is_symbol_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "_+#=:-/.!?@".contains(c)
+}
+
+/// Parses a PGN game following the PGN standard
+/// Only the first game of the input is read
+///
+/// # Arguments
+/// * `pgn`: The PGN text
+///
+/// # Returns
+/// The game, or an error if the PGN is not valid
+///
+/// # Example
+/// ```
+/// use chess_lab::parsing::parse_pgn;
+///
+/// let game = parse_pgn(
+///     "[Event \"Casual game\"]
+/// [Site \"?\"]
+/// [Date \"2024.06.27\"]
+/// [Round \"1\"]
+/// [White \"Alice\"]
+/// [Black \"Bob\"]
+/// [Result \"*\"]
+///
+/// 1. e4 e5 2. Nf3 (2. Bc4 Nf6) 2... Nc6 *",
+/// )
+/// .unwrap();
+///
+/// assert_eq!(game.history.white, Some("Alice".to_string()));
+/// assert_eq!(game.fen(), "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+/// ```
+///
+pub fn parse_pgn(pgn: &str) -> Result<Game, PgnError> {
+    parse_pgn_with_options(pgn, &PgnParseOptions::default())
+}
+
+/// Parses a PGN game with the given options
+/// Only the first game of the input is read
+///
+/// # Arguments
+/// * `pgn`: The PGN text
+/// * `options`: The options that control which deviations from the standard are accepted
+///
+/// # Returns
+/// The game, or an error if the PGN is not valid
+///
+/// # Example
+/// ```
+/// use chess_lab::parsing::{parse_pgn_with_options, PgnParseOptions};
+///
+/// let game = parse_pgn_with_options(
+///     "1. e4 d5 2. e5 f5 3. exf6 e.p. {1-0 was not played here}",
+///     &PgnParseOptions::lenient(),
+/// )
+/// .unwrap();
+///
+/// assert_eq!(game.fen(), "rnbqkbnr/ppp1p1pp/5P2/3p4/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3");
+/// assert_eq!(game.history.result, None);
+/// ```
+///
+pub fn parse_pgn_with_options(pgn: &str, options: &PgnParseOptions) -> Result<Game, PgnError> {
+    parse_pgn_with_tags(pgn, options).map(|(_, game)| game)
+}
+
+/// The tag pairs of a game, in the order they appear, and the game itself
+pub(crate) type TaggedGame = (Vec<(String, String)>, Game);
+
+/// Parses a PGN game with the given options, keeping its tag pairs
+/// Tags that the game has no field for (like `Annotator`) are only kept here
+///
+/// # Arguments
+/// * `pgn`: The PGN text
+/// * `options`: The options that control which deviations from the standard are accepted
+///
+/// # Returns
+/// The tag pairs, in the order they appear, and the game, or an error if the
+/// PGN is not valid
+///
+pub(crate) fn parse_pgn_with_tags(
+    pgn: &str,
+    options: &PgnParseOptions,
+) -> Result<TaggedGame, PgnError> {
+    let mut tokenizer = Tokenizer::new(pgn, options)?;
+    let mut tags = Vec::new();
+
+    let mut token = tokenizer.next_token()?;
+    while let Some(Token::Tag(name, value)) = token {
+        if options.max_tags.is_some_and(|max| tags.len() >= max) {
+            return Err(PgnError::LimitExceeded);
+        }
+        tags.push((name, value));
+        token = tokenizer.next_token()?;
+    }
+
+    let mut game = game_from_tags(&tags, options)?;
+    // Draws by repetition and move count are claimed, games often go on
+    // after them, so they don't stop the moves from being read
+    let rules = game.rules;
+    game.rules = rules.with_repetition_limit(None).with_move_limit(None);
+    let mut variations = Vec::new();
+    let mut can_comment = false;
+    let mut result = None;
+    let mut plies = 0;
+
+    while let Some(current) = token {
+        match current {
+            Token::Tag(_, _) => return Err(PgnError::InvalidToken),
+            Token::Comment(comment) => {
+                if options
+                    .max_comment_size
+                    .is_some_and(|max| comment.len() > max)
+                {
+                    return Err(PgnError::LimitExceeded);
+                }
+                if can_comment {
+                    let (comment, arrows, highlights) = extract_annotations(&comment);
+                    let (comment, data) = extract_node_data(&comment);
+                    for (key, value) in data {
+                        game.history.set_node_data(&key, Some(value));
+                    }
+                    for arrow in arrows {
+                        game.history.add_arrow(arrow.from, arrow.to, arrow.color);
+                    }
+                    for highlight in highlights {
+                        game.history
+                            .add_highlight(highlight.square, highlight.color);
+                    }
+                    if !comment.is_empty() {
+                        let comment = match game.history.get_comment() {
+                            Some(prev) => format!("{} {}", prev, comment),
+                            None => comment,
+                        };
+                        game.history.set_comment(Some(comment));
+                    }
+                }
+            }
+            Token::VariationStart => {
+                if game.history.get_move().is_none() {
+                    return Err(PgnError::UnbalancedVariation);
+                }
+                if options
+                    .max_variation_depth
+                    .is_some_and(|max| variations.len() >= max)
+                {
+                    return Err(PgnError::LimitExceeded);
+                }
+                variations.push(game.snapshot());
+                game.undo();
+                if options
+                    .max_variation_width
+                    .is_some_and(|max| game.history.all_next_moves().len() > max)
+                {
+                    return Err(PgnError::LimitExceeded);
+                }
+                can_comment = false;
+            }
+            Token::VariationEnd => {
+                let snapshot = variations.pop().ok_or(PgnError::UnbalancedVariation)?;
+                game.restore(&snapshot);
+                can_comment = false;
+            }
+            Token::MoveNumber | Token::Nag => {}
+            Token::Move(mov) => {
+                plies += 1;
+                if options.max_plies.is_some_and(|max| plies > max) {
+                    return Err(PgnError::LimitExceeded);
+                }
+                // A finished game ignores moves instead of playing them
+                if game.game_status != GameStatus::InProgress {
+                    return Err(PgnError::InvalidMove);
+                }
+                game.move_piece(&mov).map_err(|_| PgnError::InvalidMove)?;
+                can_comment = true;
+            }
+            Token::Result(res) => {
+                if !variations.is_empty() {
+                    return Err(PgnError::UnbalancedVariation);
+                }
+                result = Some(res);
+                break;
+            }
+        }
+        token = tokenizer.next_token()?;
+    }
+
+    if !variations.is_empty() {
+        return Err(PgnError::UnbalancedVariation);
+    }
+    game.rules = rules;
+    match result {
+        Some(result) if game.history.result.is_none() => game.history.result = Some(result),
+        Some(_) => {}
+        None if !options.allow_missing_result => return Err(PgnError::MissingResult),
+        None => {}
+    }
+
+    Ok((tags, game))
+}
+
+/// Creates the game described by the tag section of a PGN
+///
+/// # Arguments
+/// * `tags`: The tag pairs, in the order they appear
+/// * `options`: The parse options
+///
+/// # Returns
+/// The game at its starting position with the metadata set, or an error if
+/// a required tag is missing or the FEN tag is invalid
+///
+fn game_from_tags(tags: &[(String, String)], options: &PgnParseOptions) -> Result<Game, PgnError> {
+    let tag = |name: &str| {
+        tags.iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.clone())
+    };
+
+    if !options.allow_missing_tags {
+        for name in ["Event", "Site", "Date", "Round", "White", "Black", "Result"] {
+            if tag(name).is_none() {
+                return Err(PgnError::MissingTag);
+            }
+        }
+        if tag("Result").is_some_and(|result| !is_result(&result)) {
+            return Err(PgnError::InvalidTag);
+        }
+    }
+
+    let mut game = match tag("FEN") {
+        Some(fen) => Game::try_from_fen(&fen).map_err(|_| PgnError::InvalidFen)?,
+        None => Game::default(),
+    };
+
+    game.history.event = tag("Event");
+    game.history.site = tag("Site");
+    game.history.date = tag("Date");
+    game.history.round = tag("Round");
+    game.history.white = tag("White");
+    game.history.black = tag("Black");
+    game.history.result = tag("Result");
+    game.history.variant = tag("Variant");
+    game.history.white_elo = tag("WhiteElo").and_then(|elo| elo.parse().ok());
+    game.history.black_elo = tag("BlackElo").and_then(|elo| elo.parse().ok());
+    game.history.time_control = tag("TimeControl");
+    game.history.termination = tag("Termination");
+    game.history.white_clock = tag("WhiteClock");
+    game.history.black_clock = tag("BlackClock");
+    game.history.handicap = tag("Handicap");
+    game.history.event_date = tag("EventDate");
+    game.history.utc_date = tag("UTCDate");
+    game.history.utc_time = tag("UTCTime");
+
+    Ok(game)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_pgn, parse_pgn_with_options, PgnParseOptions};
+    use crate::{errors::PgnError, logic::RuleSet};
+
+    const HEADER: &str = "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"?\"]\n[Black \"?\"]\n[Result \"*\"]\n\n";
+
+    #[test]
+    fn test_parse_pgn() {
+        let game = parse_pgn(&format!(
+            "{}1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 {{ Morphy }} 4. Ba4 Nf6 5. O-O Be7 *",
+            HEADER
+        ))
+        .unwrap();
+        assert_eq!(
+            game.fen(),
+            "r1bqk2r/1pppbppp/p1n2n2/4p3/B3P3/5N2/PPPP1PPP/RNBQ1RK1 w kq - 4 6"
+        );
+        assert_eq!(game.history.result, Some("*".to_string()));
+    }
+
+    #[test]
+    fn test_parse_pgn_variations() {
+        let game = parse_pgn(&format!(
+            "{}1. e4 e5 (1... c5 2. Nf3 (2. c3) 2... d6) 2. Nf3 *",
+            HEADER
+        ))
+        .unwrap();
+        assert_eq!(
+            game.pgn(),
+            "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"?\"]\n[Black \"?\"]\n[Result \"*\"]\n1. e4 e5 (1... c5 2. Nf3 (2. c3) d6) 2. Nf3 *"
+        );
+    }
+
+    #[test]
+    fn test_parse_pgn_variation_limits() {
+        let pgn = format!(
+            "{}1. e4 e5 (1... c5 2. Nf3 (2. c3) 2... d6) (1... e6) 2. Nf3 *",
+            HEADER
+        );
+        let limited = |depth, width| {
+            let options = PgnParseOptions {
+                max_variation_depth: depth,
+                max_variation_width: width,
+                ..PgnParseOptions::default()
+            };
+            parse_pgn_with_options(&pgn, &options).map(|game| game.history.node_count())
+        };
+
+        assert_eq!(limited(None, None), Ok(8));
+        assert_eq!(limited(Some(2), Some(2)), Ok(8));
+        assert_eq!(limited(Some(1), None), Err(PgnError::LimitExceeded));
+        assert_eq!(limited(None, Some(1)), Err(PgnError::LimitExceeded));
+    }
+
+    #[test]
+    fn test_parse_pgn_limits() {
+        let pgn = format!("{}1. e4 {{ good }} e5 (1... c5) 2. Nf3 *", HEADER);
+        let parse = |options: PgnParseOptions| parse_pgn_with_options(&pgn, &options);
+
+        assert!(parse(PgnParseOptions::limited()).is_ok());
+        for options in [
+            PgnParseOptions {
+                max_plies: Some(3),
+                ..PgnParseOptions::default()
+            },
+            PgnParseOptions {
+                max_tags: Some(6),
+                ..PgnParseOptions::default()
+            },
+            PgnParseOptions {
+                max_comment_size: Some(3),
+                ..PgnParseOptions::default()
+            },
+        ] {
+            assert_eq!(parse(options).err(), Some(PgnError::LimitExceeded));
+        }
+        assert!(parse(PgnParseOptions {
+            max_plies: Some(4),
+            max_tags: Some(7),
+            max_comment_size: Some(4),
+            ..PgnParseOptions::default()
+        })
+        .is_ok());
+    }
+
+    #[test]
+    fn test_parse_pgn_annotations() {
+        let game = parse_pgn(&format!(
+            "{}1. e4 {{ [%csl Gd5][%cal Gg1f3,Rd1h5] Open game }} e5 {{ [%cal Bd8h4] }} *",
+            HEADER
+        ))
+        .unwrap();
+        assert_eq!(game.history.get_comment(), None);
+        assert_eq!(game.history.get_arrows().len(), 1);
+        assert!(game
+            .pgn()
+            .ends_with("1. e4 { [%csl Gd5][%cal Gg1f3,Rd1h5] Open game } e5 { [%cal Bd8h4] } *"));
+    }
+
+    #[test]
+    fn test_parse_pgn_past_draw() {
+        // The threefold repetition isn't claimed, the game goes on to mate
+        let pgn = HEADER.replace("[Result \"*\"]", "[Result \"1-0\"]");
+        let game = parse_pgn(&format!(
+            "{}1. Nf3 Nf6 2. Ng1 Ng8 3. Nf3 Nf6 4. Ng1 Ng8 5. e4 e5 6. Bc4 Nc6 7. Qh5 Nf6 8. Qxf7# 1-0",
+            pgn
+        ))
+        .unwrap();
+        assert_eq!(game.history.line_moves().len(), 15);
+        assert_eq!(game.last_move().unwrap().to_string(), "Qxf7#");
+        assert_eq!(game.rules, RuleSet::standard());
+    }
+
+    #[test]
+    fn test_parse_pgn_fen_tag() {
+        let game = parse_pgn(&format!(
+            "[FEN \"4k3/8/8/8/8/8/4P3/4K3 w - - 0 1\"]\n[SetUp \"1\"]\n{}1. e4 Kd7 *",
+            HEADER
+        ))
+        .unwrap();
+        assert_eq!(game.fen(), "8/3k4/8/8/4P3/8/8/4K3 w - - 1 2");
+    }
+
+    #[test]
+    fn test_parse_pgn_errors() {
+        assert_eq!(parse_pgn("1. e4 e5 *").err(), Some(PgnError::MissingTag));
+        assert_eq!(
+            parse_pgn(&format!("{}1. e4 e5", HEADER)).err(),
+            Some(PgnError::MissingResult)
+        );
+        assert_eq!(
+            parse_pgn(&format!("{}1. e4 e4 *", HEADER)).err(),
+            Some(PgnError::InvalidMove)
+        );
+        assert_eq!(
+            parse_pgn(&format!("{}1. e4 (1. d4 *", HEADER)).err(),
+            Some(PgnError::UnbalancedVariation)
+        );
+        assert_eq!(
+            parse_pgn(&format!("{}1. f3 e5 2. g4 Qh4# 3. e4 *", HEADER)).err(),
+            Some(PgnError::InvalidMove)
+        );
+        assert_eq!(
+            parse_pgn(&format!("{}1. e4 {{ open *", HEADER)).err(),
+            Some(PgnError::UnterminatedComment)
+        );
+        assert_eq!(
+            parse_pgn("[Event \"?]\n*").err(),
+            Some(PgnError::InvalidTag)
+        );
+        let pgn = HEADER.replace("[Result \"*\"]", "[Result \"1-0 (time)\"]");
+        assert_eq!(
+            parse_pgn(&format!("{}1. e4 *", pgn)).err(),
+            Some(PgnError::InvalidTag)
+        );
+        assert!(
+            parse_pgn_with_options(&format!("{}1. e4 *", pgn), &PgnParseOptions::lenient()).is_ok()
+        );
+    }
+
+    #[test]
+    fn test_tag_escaping() {
+        let pgn = format!(
+            "{}[Annotator \"\\\"Magnus\\\" C:\\\\games\"]\n\n*",
+            HEADER.replace("[Event \"?\"]", "[Event \"The \\\"Big\\\" Open\"]")
+        );
+        let game = parse_pgn(&pgn).unwrap();
+        assert_eq!(game.history.event, Some("The \"Big\" Open".to_string()));
+        assert!(game
+            .pgn()
+            .starts_with("[Event \"The \\\"Big\\\" Open\"]\n[Site \"?\"]"));
+
+        // Non-ASCII characters and unknown escapes are only read leniently
+        for tag in ["[White \"Café\"]", "[White \"C:\\games\"]"] {
+            let pgn = format!("{}{}\n\n*", HEADER, tag);
+            assert_eq!(parse_pgn(&pgn).err(), Some(PgnError::InvalidTag));
+            assert!(parse_pgn_with_options(&pgn, &PgnParseOptions::lenient()).is_ok());
+        }
+        let game = parse_pgn_with_options(
+            "[White \"C:\\games\"]\n[Black \"Müller\"]\n*",
+            &PgnParseOptions::lenient(),
+        )
+        .unwrap();
+        assert_eq!(game.history.white, Some("C:\\games".to_string()));
+        assert_eq!(game.history.black, Some("Müller".to_string()));
+        assert!(game.pgn().starts_with("[White \"C:\\\\games\"]"));
+    }
+
+    #[test]
+    fn test_parse_pgn_lenient() {
+        let pgn = "\u{feff}[Event \"Live Chess\"]\n[Site \"Chess.com\"]\n\n1. e4 e5 2. Nf3 Nc6 3. Bc4 Nf6 4. 0-0 {Result \"1-0\" is not here} Nxe4 {unterminated";
+        assert!(parse_pgn_with_options(pgn, &PgnParseOptions::strict()).is_err());
+
+        let game = parse_pgn_with_options(pgn, &PgnParseOptions::lenient()).unwrap();
+        assert_eq!(
+            game.fen(),
+            "r1bqkb1r/pppp1ppp/2n5/4p3/2B1n3/5N2/PPPP1PPP/RNBQ1RK1 w kq - 0 5"
+        );
+        assert_eq!(game.history.result, None);
+        assert_eq!(game.history.get_comment(), Some("unterminated".to_string()));
+    }
+}
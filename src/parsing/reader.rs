@@ -0,0 +1,302 @@
+use std::io::{BufRead, BufReader, Read};
+
+use crate::{errors::PgnError, logic::Game};
+
+use super::{parse_pgn_with_tags, PgnParseOptions, TaggedGame};
+
+/// Reads the games of a PGN stream one by one
+///
+/// The stream is split into games at the first tag pair that follows a
+/// movetext, so big collections can be processed without loading them into
+/// memory at once
+///
+/// # Example
+/// ```
+/// use chess_lab::parsing::{PgnParseOptions, PgnReader};
+///
+/// let pgn = "[Event \"A\"]\n\n1. e4 e5 *\n\n[Event \"B\"]\n\n1. d4 *\n";
+/// let reader = PgnReader::with_options(pgn.as_bytes(), PgnParseOptions::lenient());
+///
+/// let games: Vec<_> = reader.map(|game| game.unwrap()).collect();
+///
+/// assert_eq!(games.len(), 2);
+/// assert_eq!(games[1].history.event, Some("B".to_string()));
+/// ```
+///
+pub struct PgnReader<R: BufRead> {
+    reader: R,
+    options: PgnParseOptions,
+    next_line: Option<String>,
+    done: bool,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    /// Creates a reader that parses the games following the PGN standard
+    ///
+    /// # Arguments
+    /// * `reader`: The source of the PGN text
+    ///
+    /// # Returns
+    /// The PGN reader
+    ///
+    pub fn new(reader: R) -> PgnReader<R> {
+        PgnReader::with_options(reader, PgnParseOptions::default())
+    }
+
+    /// Creates a reader that parses the games with the given options
+    ///
+    /// # Arguments
+    /// * `reader`: The source of the PGN text
+    /// * `options`: The options used to parse every game
+    ///
+    /// # Returns
+    /// The PGN reader
+    ///
+    pub fn with_options(reader: R, options: PgnParseOptions) -> PgnReader<R> {
+        PgnReader {
+            reader,
+            options,
+            next_line: None,
+            done: false,
+        }
+    }
+
+    /// Reads the text of the next game
+    ///
+    /// # Returns
+    /// The text of the game, `None` at the end of the stream, or an error if
+    /// the stream can't be read
+    ///
+    pub(super) fn next_game_text(&mut self) -> Result<Option<String>, PgnError> {
+        let mut text = self.next_line.take().unwrap_or_default();
+        let mut in_movetext = false;
+        let mut in_comment = false;
+
+        loop {
+            let mut line = String::new();
+            if self
+                .reader
+                .read_line(&mut line)
+                .map_err(|_| PgnError::ReadError)?
+                == 0
+            {
+                self.done = true;
+                break;
+            }
+
+            let trimmed = line.trim_start_matches('\u{feff}').trim();
+            let is_tag = !in_comment && trimmed.starts_with('[') && !trimmed.starts_with("[%");
+            if is_tag && in_movetext {
+                self.next_line = Some(line);
+                break;
+            }
+            if in_comment || (!trimmed.is_empty() && !is_tag && !trimmed.starts_with('%')) {
+                in_movetext = true;
+                in_comment = ends_in_comment(trimmed, in_comment);
+            }
+            text.push_str(&line);
+        }
+
+        if text.trim().is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(text))
+        }
+    }
+
+    /// Reads and parses the next game of the stream, keeping its tag pairs
+    ///
+    /// # Returns
+    /// The tag pairs and the next game, or an error if it can't be read or parsed
+    ///
+    pub(crate) fn next_with_tags(&mut self) -> Option<Result<TaggedGame, PgnError>> {
+        if self.done && self.next_line.is_none() {
+            return None;
+        }
+        match self.next_game_text() {
+            Ok(Some(text)) => Some(parse_pgn_with_tags(&text, &self.options)),
+            Ok(None) => None,
+            Err(err) => {
+                self.done = true;
+                self.next_line = None;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Finds whether a line of movetext leaves a `{}` comment open, so a line
+/// starting with `[` inside it isn't taken for the tags of the next game
+///
+/// # Arguments
+/// * `line`: The line of movetext
+/// * `in_comment`: Whether the line starts inside a comment
+///
+/// # Returns
+/// Whether the line ends inside a comment
+///
+fn ends_in_comment(line: &str, mut in_comment: bool) -> bool {
+    for c in line.chars() {
+        match c {
+            '}' if in_comment => in_comment = false,
+            '{' if !in_comment => in_comment = true,
+            ';' if !in_comment => return false,
+            _ => {}
+        }
+    }
+    in_comment
+}
+
+/// The first bytes of a gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// The first bytes of a zstd frame
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+impl PgnReader<Box<dyn BufRead>> {
+    /// Creates a reader that decompresses the stream if needed
+    /// The format is detected from the first bytes: gzip streams are read with
+    /// the `gzip` feature, zstd streams with the `zstd` feature, and anything
+    /// else as plain text
+    ///
+    /// # Arguments
+    /// * `reader`: The source of the PGN, compressed or not
+    /// * `options`: The options used to parse every game
+    ///
+    /// # Returns
+    /// The PGN reader, or an error if the stream can't be read or is
+    /// compressed in a format whose feature isn't enabled
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::parsing::{PgnParseOptions, PgnReader};
+    ///
+    /// let pgn = "[Event \"A\"]\n\n1. e4 e5 *\n";
+    /// let reader =
+    ///     PgnReader::with_decompression(pgn.as_bytes(), PgnParseOptions::lenient()).unwrap();
+    ///
+    /// assert_eq!(reader.count(), 1);
+    /// ```
+    ///
+    pub fn with_decompression<R: Read + 'static>(
+        reader: R,
+        options: PgnParseOptions,
+    ) -> Result<PgnReader<Box<dyn BufRead>>, PgnError> {
+        let mut reader = BufReader::new(reader);
+        let start = reader.fill_buf().map_err(|_| PgnError::ReadError)?;
+
+        let reader: Box<dyn BufRead> = if start.starts_with(&GZIP_MAGIC) {
+            #[cfg(feature = "gzip")]
+            {
+                Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(reader)))
+            }
+            #[cfg(not(feature = "gzip"))]
+            return Err(PgnError::UnsupportedCompression);
+        } else if start.starts_with(&ZSTD_MAGIC) {
+            #[cfg(feature = "zstd")]
+            {
+                Box::new(BufReader::new(
+                    zstd::stream::read::Decoder::with_buffer(reader)
+                        .map_err(|_| PgnError::ReadError)?,
+                ))
+            }
+            #[cfg(not(feature = "zstd"))]
+            return Err(PgnError::UnsupportedCompression);
+        } else {
+            Box::new(reader)
+        };
+        Ok(PgnReader::with_options(reader, options))
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<Game, PgnError>;
+
+    /// Reads and parses the next game of the stream
+    ///
+    /// # Returns
+    /// The next game, or an error if it can't be read or parsed. Parse errors
+    /// don't stop the reader, the following games can still be read
+    ///
+    fn next(&mut self) -> Option<Result<Game, PgnError>> {
+        self.next_with_tags()
+            .map(|result| result.map(|(_, game)| game))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PgnReader;
+    use crate::errors::PgnError;
+
+    #[test]
+    fn test_pgn_reader() {
+        let pgn = "[Event \"A\"]\n[Site \"?\"]\n[Date \"?\"]\n[Round \"?\"]\n[White \"?\"]\n[Black \"?\"]\n[Result \"1-0\"]\n\n1. e4 { [%clk 0:03:00]\n[%emt 0:00:01] } e5 1-0\n\n[Event \"B\"]\n\n1. e5 *\n\n[Event \"C\"]\n[Site \"?\"]\n[Date \"?\"]\n[Round \"?\"]\n[White \"?\"]\n[Black \"?\"]\n[Result \"*\"]\n\n1. d4 *";
+        let games: Vec<_> = PgnReader::new(pgn.as_bytes()).collect();
+
+        assert_eq!(games.len(), 3);
+        assert_eq!(
+            games[0].as_ref().unwrap().history.result,
+            Some("1-0".to_string())
+        );
+        assert_eq!(games[1].as_ref().err(), Some(&PgnError::MissingTag));
+        assert_eq!(
+            games[2].as_ref().unwrap().history.event,
+            Some("C".to_string())
+        );
+    }
+
+    #[test]
+    fn test_pgn_reader_decompression() {
+        use crate::parsing::PgnParseOptions;
+
+        let pgn = "[Event \"A\"]\n\n1. e4 e5 *\n\n[Event \"B\"]\n\n1. d4 *\n";
+        let count = |bytes: Vec<u8>| {
+            PgnReader::with_decompression(std::io::Cursor::new(bytes), PgnParseOptions::lenient())
+                .map(|reader| reader.filter(|game| game.is_ok()).count())
+        };
+        assert_eq!(count(pgn.as_bytes().to_vec()), Ok(2));
+
+        let gzip = vec![0x1f, 0x8b, 0x08, 0x00];
+        #[cfg(not(feature = "gzip"))]
+        assert_eq!(count(gzip), Err(PgnError::UnsupportedCompression));
+        #[cfg(feature = "gzip")]
+        {
+            use std::io::Write;
+
+            assert!(count(gzip).is_ok());
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(pgn.as_bytes()).unwrap();
+            assert_eq!(count(encoder.finish().unwrap()), Ok(2));
+        }
+
+        #[cfg(not(feature = "zstd"))]
+        assert_eq!(
+            count(vec![0x28, 0xb5, 0x2f, 0xfd]),
+            Err(PgnError::UnsupportedCompression)
+        );
+        #[cfg(feature = "zstd")]
+        assert_eq!(count(zstd::encode_all(pgn.as_bytes(), 0).unwrap()), Ok(2));
+    }
+
+    #[test]
+    fn test_pgn_reader_comments() {
+        use crate::parsing::PgnParseOptions;
+
+        let pgn = "[Event \"A\"]\n\n1. e4 { quoted from\n[Event \"B\"] } e5 ; not a { comment\n2. Nf3 *\n\n[Event \"C\"]\n\n1. d4 *\n";
+        let games: Vec<_> =
+            PgnReader::with_options(pgn.as_bytes(), PgnParseOptions::lenient()).collect();
+
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].as_ref().unwrap().history_vec().len(), 3);
+        assert_eq!(
+            games[1].as_ref().unwrap().history.event,
+            Some("C".to_string())
+        );
+    }
+
+    #[test]
+    fn test_pgn_reader_empty() {
+        assert_eq!(PgnReader::new("\n\n".as_bytes()).count(), 0);
+    }
+}
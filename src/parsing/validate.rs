@@ -0,0 +1,117 @@
+use crate::{
+    errors::{FenError, MoveError, PgnError},
+    logic::Game,
+};
+
+use super::{parse_pgn_with_options, Notation, PgnParseOptions};
+
+/// Checks a FEN string without keeping the game, for inputs that only need
+/// to be accepted or rejected
+///
+/// # Arguments
+/// * `fen`: The FEN string
+///
+/// # Returns
+/// Ok if the FEN can be loaded, or the error `Game::try_from_fen` gives
+///
+/// # Example
+/// ```
+/// use chess_lab::errors::FenError;
+/// use chess_lab::parsing::validate_fen;
+///
+/// assert_eq!(validate_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1"), Ok(()));
+/// assert_eq!(validate_fen("4k3/8/8/8/8/8/8/4K3 w - -"), Err(FenError::Invalid));
+/// ```
+///
+pub fn validate_fen(fen: &str) -> Result<(), FenError> {
+    Game::try_from_fen(fen).map(|_| ())
+}
+
+/// Checks that a move in SAN can be played in a game, without playing it
+///
+/// # Arguments
+/// * `game`: The game the move would be played in
+/// * `san`: The move, in standard algebraic notation
+///
+/// # Returns
+/// Ok if the move is legal, or the error `Game::move_piece_with` gives
+///
+/// # Example
+/// ```
+/// use chess_lab::logic::Game;
+/// use chess_lab::parsing::validate_san;
+///
+/// let game = Game::default();
+/// assert_eq!(validate_san(&game, "Nf3"), Ok(()));
+/// assert!(validate_san(&game, "Nf4").is_err());
+/// assert!(validate_san(&game, "g1f3").is_err());
+/// assert_eq!(game.fen(), Game::default().fen());
+/// ```
+///
+pub fn validate_san(game: &Game, san: &str) -> Result<(), MoveError> {
+    game.clone().move_piece_with(san, Notation::San).map(|_| ())
+}
+
+/// Checks a PGN game without keeping it
+/// Use `PgnParseOptions::limited` for text coming from untrusted sources
+///
+/// # Arguments
+/// * `pgn`: The PGN text
+/// * `options`: The options that control which deviations from the standard
+///   are accepted
+///
+/// # Returns
+/// Ok if the first game of the text can be parsed, or the error
+/// `parse_pgn_with_options` gives
+///
+/// # Example
+/// ```
+/// use chess_lab::errors::PgnError;
+/// use chess_lab::parsing::{validate_pgn, PgnParseOptions};
+///
+/// let options = PgnParseOptions::limited();
+/// assert_eq!(validate_pgn("1. e4 e5 2. Nf3 *", &options), Err(PgnError::MissingTag));
+///
+/// let options = PgnParseOptions {
+///     allow_missing_tags: true,
+///     ..PgnParseOptions::limited()
+/// };
+/// assert_eq!(validate_pgn("1. e4 e5 2. Nf3 *", &options), Ok(()));
+/// assert!(validate_pgn("1. e4 e5 2. Ke3 *", &options).is_err());
+/// ```
+///
+pub fn validate_pgn(pgn: &str, options: &PgnParseOptions) -> Result<(), PgnError> {
+    parse_pgn_with_options(pgn, options).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_fen, validate_pgn, validate_san};
+    use crate::{logic::Game, parsing::PgnParseOptions};
+
+    #[test]
+    fn test_validate_fuzz_regressions() {
+        // Positions without a king used to panic while writing the FEN back
+        assert_eq!(
+            validate_fen("r3k2r/8/8/3pP3/8/8/8/R3Q2R w KQkq d6 0 1"),
+            Ok(())
+        );
+        let mut game = Game::from_fen("r3k2r/8/8/3pP3/8/8/8/R3Q2R w KQkq d6 0 1");
+        assert!(!game.legal_moves().is_empty());
+        game.move_piece("Rb1").unwrap();
+        game.move_piece("Rxh1").unwrap();
+        assert_eq!(game.fen(), "r3k3/8/8/3pP3/8/8/8/1R2Q2r w KQq - 0 2");
+
+        // A move written without `x` onto an occupied square used to panic
+        let game = Game::from_fen("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1");
+        assert!(validate_san(&game, "Ra8").is_err());
+        assert_eq!(validate_san(&game, "Rxa8"), Ok(()));
+
+        // Capturing a rook takes the castling right of its owner
+        let mut game = Game::from_fen("r3k2r/8/8/8/8/8/8/B6K w kq - 0 1");
+        game.move_piece("Bxh8").unwrap();
+        assert_eq!(game.castling_rights, 0b0001);
+
+        assert!(validate_pgn("1. e4 e5 (1... c5 {", &PgnParseOptions::lenient()).is_err());
+    }
+}
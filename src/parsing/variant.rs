@@ -0,0 +1,262 @@
+use std::rc::Rc;
+
+use crate::{
+    constants::{BoxedVariant, PgnVariant},
+    errors::PgnError,
+    logic::Game,
+    variants::Armageddon,
+};
+
+use super::{
+    pgn::{Token, Tokenizer},
+    PgnParseOptions,
+};
+
+/// Parses a PGN game as a variant, checking its moves with the rules of the
+/// variant
+///
+/// # Arguments
+/// * `pgn`: The PGN text
+/// * `options`: The options that control which deviations from the standard
+///   are accepted
+///
+/// # Returns
+/// The game, or an error if the PGN is not valid, its `Variant` tag names
+/// another variant or a move breaks the rules of the variant
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::Variant;
+/// use chess_lab::errors::PgnError;
+/// use chess_lab::parsing::{parse_pgn_as, PgnParseOptions};
+/// use chess_lab::variants::Armageddon;
+///
+/// let options = PgnParseOptions::lenient();
+/// let game = parse_pgn_as::<Armageddon>("[BlackClock \"180\"]\n1. e4 e5 *", &options).unwrap();
+///
+/// assert_eq!(game.game().history.white_clock, Some(String::from("300")));
+/// assert_eq!(game.game().history.black_clock, Some(String::from("180")));
+/// assert_eq!(
+///     parse_pgn_as::<Armageddon>("[Variant \"Atomic\"]\n1. e4 *", &options).err(),
+///     Some(PgnError::UnsupportedVariant(String::from("Atomic")))
+/// );
+/// ```
+///
+pub fn parse_pgn_as<V: PgnVariant>(pgn: &str, options: &PgnParseOptions) -> Result<V, PgnError> {
+    if let Some(name) = variant_tag(pgn, options)? {
+        if !is_tag_of::<V>(&name) {
+            return Err(PgnError::UnsupportedVariant(name));
+        }
+    }
+    read_as(pgn, options)
+}
+
+/// Parses a PGN game as the variant named by its `Variant` tag
+/// Games without the tag are standard games
+///
+/// # Arguments
+/// * `pgn`: The PGN text
+/// * `options`: The options that control which deviations from the standard
+///   are accepted
+///
+/// # Returns
+/// The game, or an error if the PGN is not valid or the variant isn't
+/// supported
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::Variant;
+/// use chess_lab::errors::PgnError;
+/// use chess_lab::parsing::{parse_pgn_dynamic, PgnParseOptions};
+///
+/// let options = PgnParseOptions::lenient();
+/// let game = parse_pgn_dynamic("[Variant \"armageddon\"]\n1. d4 *", &options).unwrap();
+/// assert_eq!(game.name(), "Armageddon");
+///
+/// let game = parse_pgn_dynamic("1. d4 *", &options).unwrap();
+/// assert_eq!(game.name(), "Standard");
+///
+/// assert_eq!(
+///     parse_pgn_dynamic("[Variant \"Crazyhouse\"]\n1. e4 d5 2. exd5 Qxd5 3. N@f3 *", &options).err(),
+///     Some(PgnError::UnsupportedVariant(String::from("Crazyhouse")))
+/// );
+/// ```
+///
+pub fn parse_pgn_dynamic(pgn: &str, options: &PgnParseOptions) -> Result<BoxedVariant, PgnError> {
+    match variant_tag(pgn, options)? {
+        None => Ok(Box::new(read_as::<Game>(pgn, options)?)),
+        Some(name) if is_tag_of::<Game>(&name) => Ok(Box::new(read_as::<Game>(pgn, options)?)),
+        Some(name) if is_tag_of::<Armageddon>(&name) => {
+            Ok(Box::new(read_as::<Armageddon>(pgn, options)?))
+        }
+        Some(name) => Err(PgnError::UnsupportedVariant(name)),
+    }
+}
+
+/// Reads the `Variant` tag of a PGN game, before its moves are parsed
+///
+/// # Arguments
+/// * `pgn`: The PGN text
+/// * `options`: The parse options
+///
+/// # Returns
+/// The value of the tag, if any, or an error if the tag section is invalid
+///
+fn variant_tag(pgn: &str, options: &PgnParseOptions) -> Result<Option<String>, PgnError> {
+    let mut tokenizer = Tokenizer::new(pgn, options)?;
+    while let Some(Token::Tag(name, value)) = tokenizer.next_token()? {
+        if name == "Variant" {
+            return Ok(Some(value));
+        }
+    }
+    Ok(None)
+}
+
+/// Checks if a value of the `Variant` tag names a variant
+///
+/// # Arguments
+/// * `name`: The value of the tag
+///
+/// # Returns
+/// Whether the value is one of the tags of the variant, ignoring case
+///
+fn is_tag_of<V: PgnVariant>(name: &str) -> bool {
+    V::TAGS.iter().any(|tag| tag.eq_ignore_ascii_case(name))
+}
+
+/// Parses a PGN game with the standard rules and plays all its lines again
+/// on the variant, with the draw rules of the variant
+/// As with the standard rules, the moves after an automatic draw are kept
+///
+/// # Arguments
+/// * `pgn`: The PGN text
+/// * `options`: The parse options
+///
+/// # Returns
+/// The variant, or an error if the PGN is not valid or a move of any line
+/// breaks the rules of the variant
+///
+fn read_as<V: PgnVariant>(pgn: &str, options: &PgnParseOptions) -> Result<V, PgnError> {
+    let mut game = super::parse_pgn_with_options(pgn, options)?;
+    game.rules = V::RULES;
+
+    let mut start = game.replay();
+    start.rules = V::RULES.with_repetition_limit(None).with_move_limit(None);
+    let mut replay = V::from_game(start);
+
+    // The nodes are walked depth first, taking back the moves of a line
+    // before playing the next one from its ply
+    let mut played = 0;
+    let mut nodes: Vec<_> = game
+        .history
+        .root_nodes()
+        .iter()
+        .rev()
+        .map(|node| (Rc::clone(node), 0))
+        .collect();
+    while let Some((node, ply)) = nodes.pop() {
+        for _ in ply..played {
+            replay.undo();
+        }
+        let node = node.borrow();
+        replay
+            .move_piece(&node.mov.to_string())
+            .map_err(|_| PgnError::InvalidMove)?;
+        played = ply + 1;
+        nodes.extend(
+            node.lines
+                .iter()
+                .rev()
+                .map(|line| (Rc::clone(line), ply + 1)),
+        );
+    }
+    Ok(V::from_game(game))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_pgn_as, parse_pgn_dynamic};
+    use crate::{
+        constants::{GameStatus, PgnVariant, Variant},
+        errors::{MoveError, PgnError},
+        logic::{Game, RuleSet},
+        parsing::PgnParseOptions,
+    };
+
+    /// A variant where the kings can't move, stricter than standard chess
+    struct StillKings(Game);
+
+    impl Variant for StillKings {
+        fn game(&self) -> &Game {
+            &self.0
+        }
+
+        fn move_piece(&mut self, move_str: &str) -> Result<GameStatus, MoveError> {
+            if move_str.starts_with('K') || move_str.starts_with('O') {
+                return Err(MoveError::Invalid {
+                    san: move_str.to_string(),
+                });
+            }
+            self.0.move_piece(move_str)
+        }
+
+        fn undo(&mut self) {
+            self.0.undo()
+        }
+
+        fn redo(&mut self) {
+            self.0.redo()
+        }
+    }
+
+    impl PgnVariant for StillKings {
+        const TAGS: &'static [&'static str] = &["Still kings"];
+        const RULES: RuleSet = RuleSet::standard().with_move_limit(None);
+
+        fn from_game(game: Game) -> StillKings {
+            StillKings(game)
+        }
+    }
+
+    #[test]
+    fn test_parse_pgn_as_variant() {
+        let options = PgnParseOptions::lenient();
+
+        let game = parse_pgn_as::<StillKings>(
+            "[Variant \"Still Kings\"]\n1. e4 e5 2. Nf3 (2. Nc3) *",
+            &options,
+        )
+        .unwrap();
+        assert_eq!(game.game().history.line_moves().len(), 3);
+        assert_eq!(game.rules(), RuleSet::standard().with_move_limit(None));
+        let game = parse_pgn_as::<Game>("1. e4 *", &options).unwrap();
+        assert_eq!(game.rules, RuleSet::standard());
+
+        // The variations are checked against the variant as well
+        assert_eq!(
+            parse_pgn_as::<StillKings>("1. e4 e5 2. Ke2 *", &options).err(),
+            Some(PgnError::InvalidMove)
+        );
+        assert_eq!(
+            parse_pgn_as::<StillKings>("1. e4 e5 (1... d5 2. Nc3 (2. Ke2)) 2. Nf3 *", &options)
+                .err(),
+            Some(PgnError::InvalidMove)
+        );
+        assert!(parse_pgn_as::<Game>("[Variant \"Chess960\"]\n1. e4 *", &options).is_ok());
+        assert_eq!(
+            parse_pgn_as::<Game>("[Variant \"Still kings\"]\n1. e4 *", &options).err(),
+            Some(PgnError::UnsupportedVariant(String::from("Still kings")))
+        );
+        assert!(parse_pgn_as::<Game>("[Variant \"From Position\"]\n1. e4 *", &options).is_ok());
+
+        // Games without the tag are read with the standard rules
+        assert_eq!(
+            parse_pgn_dynamic("1. e4 e5 2. Ke3 *", &options).err(),
+            Some(PgnError::InvalidMove)
+        );
+        assert_eq!(
+            parse_pgn_dynamic("[Variant \"Still kings\"]\n1. e4 *", &options).err(),
+            Some(PgnError::UnsupportedVariant(String::from("Still kings")))
+        );
+    }
+}
@@ -0,0 +1,22 @@
+//! The types needed for typical usage, to be glob imported
+//!
+//! # Example
+//! ```
+//! use chess_lab::prelude::*;
+//!
+//! let mut game = Game::default();
+//! game.move_piece("e4").unwrap();
+//!
+//! assert_eq!(game.side_to_move(), Color::Black);
+//! assert_eq!(game.game_status, GameStatus::InProgress);
+//! assert_eq!(
+//!     game.board.get_piece(&Position::from_string("e4")).unwrap().piece_type,
+//!     PieceType::Pawn
+//! );
+//! ```
+
+pub use crate::{
+    constants::{Color, GameStatus, Move, PieceType, Position, Variant},
+    logic::{Board, Game},
+    variants::StandardChess,
+};
@@ -0,0 +1,193 @@
+use std::io::BufRead;
+
+use crate::errors::PuzzleError;
+
+use super::Puzzle;
+
+/// The first column of the header of the Lichess puzzle CSV file
+const HEADER: &str = "PuzzleId";
+
+impl Puzzle {
+    /// Parses a line of the Lichess puzzle CSV file
+    /// The columns are `PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,
+    /// NbPlays,Themes,GameUrl,OpeningTags`, of which only the id, the FEN,
+    /// the moves, the rating and the themes are kept
+    ///
+    /// # Arguments
+    /// * `record`: The line
+    ///
+    /// # Returns
+    /// The puzzle, or an error if the line is malformed
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::puzzles::Puzzle;
+    ///
+    /// let puzzle = Puzzle::from_lichess_csv(
+    ///     "00sHx,q3k1nr/1pp1nQpp/3p4/1P2p3/4P3/B1PP1b2/B5PP/5K2 b k - 0 17,e8d7 a2e6 d7d8 f7f8,1760,80,83,72,mate mateIn2 middlegame short,https://lichess.org/yyznGmXs/black#34,Italian_Game Italian_Game_Classical_Variation",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(puzzle.id, "00sHx");
+    /// assert_eq!(puzzle.moves, ["e8d7", "a2e6", "d7d8", "f7f8"]);
+    /// assert_eq!(puzzle.rating, 1760);
+    /// assert_eq!(puzzle.themes, ["mate", "mateIn2", "middlegame", "short"]);
+    /// ```
+    ///
+    pub fn from_lichess_csv(record: &str) -> Result<Puzzle, PuzzleError> {
+        let invalid = || PuzzleError::InvalidRecord(record.to_string());
+        let columns: Vec<&str> = record.trim_end().split(',').collect();
+        if columns.len() < 8 {
+            return Err(invalid());
+        }
+
+        let words = |column: &str| -> Vec<String> {
+            column.split_whitespace().map(str::to_string).collect()
+        };
+        let puzzle = Puzzle {
+            id: columns[0].to_string(),
+            fen: columns[1].to_string(),
+            moves: words(columns[2]),
+            rating: columns[3].parse().map_err(|_| invalid())?,
+            themes: words(columns[7]),
+        };
+        if puzzle.id.is_empty() || puzzle.fen.is_empty() || puzzle.moves.is_empty() {
+            return Err(invalid());
+        }
+        Ok(puzzle)
+    }
+}
+
+/// Reads the puzzles of the Lichess puzzle CSV file one by one
+///
+/// The header line, if any, and empty lines are skipped, so the dump can be
+/// processed without loading it into memory at once
+///
+/// # Example
+/// ```
+/// use chess_lab::puzzles::LichessPuzzleReader;
+///
+/// let csv = "PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags\n\
+///     00008,r6k/pp2r2p/4Rp1Q/3p4/8/1N1P2R1/PqP2bPP/7K b - - 0 24,f2g3 e6e7 b2b1 b3c1 b1c1 h6c1,1913,75,94,6230,crushing hangingPiece long middlegame,https://lichess.org/787zsVup/black#48,\n";
+///
+/// let puzzles: Vec<_> = LichessPuzzleReader::new(csv.as_bytes())
+///     .map(|puzzle| puzzle.unwrap())
+///     .collect();
+///
+/// assert_eq!(puzzles.len(), 1);
+/// assert_eq!(puzzles[0].id, "00008");
+/// assert!(puzzles[0].game().is_ok());
+/// ```
+///
+pub struct LichessPuzzleReader<R: BufRead> {
+    reader: R,
+    done: bool,
+}
+
+impl<R: BufRead> LichessPuzzleReader<R> {
+    /// Creates a reader of the Lichess puzzle CSV file
+    ///
+    /// # Arguments
+    /// * `reader`: The source of the CSV text
+    ///
+    /// # Returns
+    /// The puzzle reader
+    ///
+    pub fn new(reader: R) -> LichessPuzzleReader<R> {
+        LichessPuzzleReader {
+            reader,
+            done: false,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for LichessPuzzleReader<R> {
+    type Item = Result<Puzzle, PuzzleError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => self.done = true,
+                Ok(_) => {
+                    let line = line.trim();
+                    if line.is_empty() || line.split(',').next() == Some(HEADER) {
+                        continue;
+                    }
+                    return Some(Puzzle::from_lichess_csv(line));
+                }
+                Err(_) => {
+                    self.done = true;
+                    return Some(Err(PuzzleError::ReadError));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Parses the puzzles of a Lichess puzzle CSV file
+///
+/// # Arguments
+/// * `csv`: The CSV text
+///
+/// # Returns
+/// The puzzles, or the error of the first malformed line
+///
+pub fn parse_lichess_csv(csv: &str) -> Result<Vec<Puzzle>, PuzzleError> {
+    LichessPuzzleReader::new(csv.as_bytes()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_lichess_csv, LichessPuzzleReader};
+    use crate::{errors::PuzzleError, puzzles::Puzzle};
+
+    #[test]
+    fn test_lichess_csv() {
+        let csv = "PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags\n\
+            000aY,r4rk1/pp3ppp/2n1b3/q1pp2B1/8/P1Q2NP1/1PP1PP1P/2KR3R w - - 0 15,g5e7 a5c3 b2c3 c6e7,1445,75,87,459,advantage master middlegame short,https://lichess.org/iihZGl6t#29,\n\
+            \n\
+            00sHx,q3k1nr/1pp1nQpp/3p4/1P2p3/4P3/B1PP1b2/B5PP/5K2 b k - 0 17,e8d7 a2e6 d7d8 f7f8,1760,80,83,72,mate mateIn2 middlegame short,https://lichess.org/yyznGmXs/black#34,Italian_Game Italian_Game_Classical_Variation\n";
+        let puzzles = parse_lichess_csv(csv).unwrap();
+        assert_eq!(puzzles.len(), 2);
+        assert_eq!(
+            puzzles[0].themes,
+            ["advantage", "master", "middlegame", "short"]
+        );
+
+        // The game starts at the move of the solver
+        let mut game = puzzles[1].game().unwrap();
+        assert_eq!(game.history.line_moves().len(), 1);
+        assert_eq!(game.last_move().unwrap().to_string(), "Kd7");
+        assert!(game.is_white_turn);
+        for _ in 0..3 {
+            game.redo();
+        }
+        assert_eq!(game.last_move().unwrap().to_string(), "Qf8#");
+
+        let mut reader = LichessPuzzleReader::new(
+            "00sHx,8/8/8/8/8/8/8/8 w - - 0 1,e2e4,high,0,0,0,\n".as_bytes(),
+        );
+        assert!(matches!(
+            reader.next(),
+            Some(Err(PuzzleError::InvalidRecord(_)))
+        ));
+        assert!(reader.next().is_none());
+        assert!(matches!(
+            Puzzle::from_lichess_csv("00sHx,,e2e4,1500"),
+            Err(PuzzleError::InvalidRecord(_))
+        ));
+
+        let puzzle =
+            Puzzle::from_lichess_csv("00sHx,4k3/8/8/8/8/8/8/4K3 w - - 0 1,e2e4,1500,0,0,0,,,")
+                .unwrap();
+        assert_eq!(
+            puzzle.game().err(),
+            Some(PuzzleError::IllegalMove {
+                ply: 0,
+                mov: String::from("e2e4")
+            })
+        );
+    }
+}
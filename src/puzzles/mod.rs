@@ -0,0 +1,117 @@
+mod lichess;
+mod themes;
+mod verify;
+
+pub use lichess::*;
+pub use themes::*;
+pub use verify::*;
+
+use crate::{constants::Move, errors::PuzzleError, logic::Game};
+
+/// A puzzle, as published in the Lichess puzzle database
+///
+/// # Fields
+/// * `id`: The identifier of the puzzle
+/// * `fen`: The position before the move of the opponent that sets up the
+///   puzzle
+/// * `moves`: The moves in UCI: the move of the opponent, then the solution,
+///   where the solver and the opponent take turns
+/// * `rating`: The rating of the puzzle
+/// * `themes`: The themes of the puzzle, such as `fork` or `mateIn2`
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Puzzle {
+    pub id: String,
+    pub fen: String,
+    pub moves: Vec<String>,
+    pub rating: u32,
+    pub themes: Vec<String>,
+}
+
+impl Puzzle {
+    /// Creates a game to play the puzzle back, with every move recorded
+    ///
+    /// # Returns
+    /// The game at the position the solver starts from, after the move of
+    /// the opponent, with the solution left to redo, or an error if the FEN
+    /// is not valid or a move can't be played
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::puzzles::Puzzle;
+    ///
+    /// let puzzle = Puzzle {
+    ///     fen: String::from("6k1/p4ppp/8/8/8/8/5PPP/4R1K1 b - - 0 1"),
+    ///     moves: vec![String::from("a7a6"), String::from("e1e8")],
+    ///     ..Puzzle::default()
+    /// };
+    ///
+    /// let mut game = puzzle.game().unwrap();
+    /// assert_eq!(game.fen(), "6k1/5ppp/p7/8/8/8/5PPP/4R1K1 w - - 0 2");
+    /// game.redo();
+    /// assert_eq!(game.last_move().unwrap().to_string(), "Re8#");
+    /// ```
+    ///
+    pub fn game(&self) -> Result<Game, PuzzleError> {
+        let mut game = self.replay()?;
+        game.goto_ply(self.moves.len().min(1));
+        Ok(game)
+    }
+
+    /// Plays the moves of the puzzle from its position
+    ///
+    /// # Returns
+    /// The game before the first move and the moves as played, with their
+    /// check flags, or an error if a move can't be played
+    ///
+    fn play(&self) -> Result<(Game, Vec<Move>), PuzzleError> {
+        let game = self.replay()?;
+        Ok((Game::from_fen(&self.fen), game.history.line_moves()))
+    }
+
+    /// Plays every move of the puzzle from its position
+    ///
+    /// # Returns
+    /// The game after the last move, or an error if the FEN is not valid or
+    /// a move can't be played
+    ///
+    fn replay(&self) -> Result<Game, PuzzleError> {
+        let mut game = Game::try_from_fen(&self.fen).map_err(|_| PuzzleError::InvalidFen)?;
+        for (ply, uci) in self.moves.iter().enumerate() {
+            let illegal = || PuzzleError::IllegalMove {
+                ply,
+                mov: uci.clone(),
+            };
+            let mov = game
+                .legal_moves()
+                .into_iter()
+                .find(|mov| mov.uci() == *uci)
+                .ok_or_else(illegal)?;
+            game.make_move(&mov).map_err(|_| illegal())?;
+        }
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::Puzzle;
+
+    /// Builds a puzzle from its FEN and its moves in UCI, for the tests of
+    /// the puzzle modules
+    ///
+    /// # Arguments
+    /// * `fen`: The position of the puzzle
+    /// * `moves`: The moves of the puzzle
+    ///
+    /// # Returns
+    /// The puzzle
+    ///
+    pub(crate) fn puzzle(fen: &str, moves: &[&str]) -> Puzzle {
+        Puzzle {
+            fen: fen.to_string(),
+            moves: moves.iter().map(|mov| mov.to_string()).collect(),
+            ..Puzzle::default()
+        }
+    }
+}
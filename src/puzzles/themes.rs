@@ -0,0 +1,283 @@
+use crate::{
+    constants::{Color, PieceType, Position},
+    logic::{Board, Piece},
+};
+
+use super::Puzzle;
+
+/// Detects the themes of a puzzle from its solution, with the names of the
+/// Lichess puzzle database: `mate`, `mateInN`, `backRankMate`,
+/// `smotheredMate`, `fork`, `pin` and the length of the solution (`oneMove`,
+/// `short`, `long` or `veryLong`)
+///
+/// # Arguments
+/// * `puzzle`: The puzzle
+///
+/// # Returns
+/// The themes found, empty if a move of the puzzle can't be played
+///
+/// # Example
+/// ```
+/// use chess_lab::puzzles::{themes, Puzzle};
+///
+/// let puzzle = Puzzle {
+///     fen: String::from("2r3k1/p4ppp/8/8/8/8/4RPPP/4R1K1 b - - 0 1"),
+///     moves: ["a7a6", "e2e8", "c8e8", "e1e8"].map(String::from).to_vec(),
+///     ..Puzzle::default()
+/// };
+/// assert_eq!(themes(&puzzle), ["mate", "mateIn2", "backRankMate", "short"]);
+/// ```
+///
+pub fn themes(puzzle: &Puzzle) -> Vec<String> {
+    let Ok((mut game, moves)) = puzzle.play() else {
+        return Vec::new();
+    };
+
+    let mut themes = Vec::new();
+    let (mut fork, mut pin) = (false, false);
+    for (ply, mov) in moves.iter().enumerate() {
+        if game.make_move(mov).is_err() {
+            return Vec::new();
+        }
+        if ply % 2 == 1 && !mov.checkmate {
+            // A check answered by taking the checking piece isn't a fork
+            let recaptured =
+                mov.check && moves.get(ply + 1).is_some_and(|reply| reply.to == mov.to);
+            fork |= !recaptured && is_fork(&game.board, &mov.to);
+            pin |= is_pin(&game.board, &mov.to);
+        }
+    }
+
+    let solver_moves = moves.len() / 2;
+    if game.checkmate() {
+        themes.push(String::from("mate"));
+        themes.push(format!("mateIn{}", solver_moves));
+        let king = game.board.find(PieceType::King, game.side_to_move())[0];
+        if is_back_rank_mate(&game.board, king) {
+            themes.push(String::from("backRankMate"));
+        }
+        if is_smothered_mate(&game.board, king) {
+            themes.push(String::from("smotheredMate"));
+        }
+    }
+    if fork {
+        themes.push(String::from("fork"));
+    }
+    if pin {
+        themes.push(String::from("pin"));
+    }
+    themes.push(String::from(match solver_moves {
+        0 | 1 => "oneMove",
+        2 => "short",
+        3 => "long",
+        _ => "veryLong",
+    }));
+    themes
+}
+
+/// Checks if the piece that just moved attacks two pieces that can't all be
+/// saved: the king, pieces worth more than it or pieces left undefended
+/// A piece that can be taken for free doesn't fork
+///
+/// # Arguments
+/// * `board`: The board after the move
+/// * `square`: The square the piece moved to
+///
+/// # Returns
+/// Whether the move is a fork
+///
+fn is_fork(board: &Board, square: &Position) -> bool {
+    let piece = board.get_piece(square).unwrap();
+    if board.is_attacked(*square, piece.color.opposite()) && !defended(board, square) {
+        return false;
+    }
+    let targets = board
+        .find_all(piece.color.opposite())
+        .into_iter()
+        .filter(|target| board.can_capture(square, target))
+        .filter(|target| {
+            let attacked = board.get_piece(target).unwrap().piece_type;
+            attacked == PieceType::King
+                || attacked.rank() > piece.piece_type.rank()
+                || !defended(board, target)
+        })
+        .count();
+    targets >= 2
+}
+
+/// Checks if the piece that just moved pins a piece to the king or to a more
+/// valuable piece behind it
+///
+/// # Arguments
+/// * `board`: The board after the move
+/// * `square`: The square the piece moved to
+///
+/// # Returns
+/// Whether the move pins a piece
+///
+fn is_pin(board: &Board, square: &Position) -> bool {
+    let piece = board.get_piece(square).unwrap();
+    if !matches!(
+        piece.piece_type,
+        PieceType::Bishop | PieceType::Rook | PieceType::Queen
+    ) {
+        return false;
+    }
+
+    board
+        .find_all(piece.color.opposite())
+        .into_iter()
+        .filter(|target| board.can_capture(square, target))
+        .any(|pinned| {
+            let pinned_rank = board.get_piece(&pinned).unwrap().piece_type.rank();
+            behind(board, square, &pinned).is_some_and(|behind| {
+                behind.color != piece.color
+                    && behind.piece_type != PieceType::Pawn
+                    && behind.piece_type.rank() > pinned_rank
+            })
+        })
+}
+
+/// Finds the first piece behind another one, looking from a square
+///
+/// # Arguments
+/// * `board`: The board
+/// * `from`: The square looked from
+/// * `square`: The square of the piece in front
+///
+/// # Returns
+/// The first piece past `square` on the line from `from`, if any
+///
+fn behind(board: &Board, from: &Position, square: &Position) -> Option<Piece> {
+    let step = |from: u8, to: u8| to.cmp(&from) as i8;
+    let step = (step(from.col, square.col), step(from.row, square.row));
+    let mut next = square.checked_add(step);
+    while let Some(square) = next {
+        if let Some(piece) = board.get_piece(&square) {
+            return Some(piece);
+        }
+        next = square.checked_add(step);
+    }
+    None
+}
+
+/// Checks if a piece is defended by a piece of its own color
+///
+/// # Arguments
+/// * `board`: The board
+/// * `square`: The square of the piece
+///
+/// # Returns
+/// Whether a piece of the same color could capture on the square
+///
+fn defended(board: &Board, square: &Position) -> bool {
+    let color = board.get_piece(square).unwrap().color;
+    let mut board = board.clone();
+    board.delete_piece(square).unwrap();
+    board.is_attacked(*square, color)
+}
+
+/// Returns the squares around a king
+///
+/// # Arguments
+/// * `king`: The square of the king
+///
+/// # Returns
+/// The squares next to the king, on the board
+///
+fn around(king: Position) -> impl Iterator<Item = Position> {
+    (-1..=1)
+        .flat_map(|col| (-1..=1).map(move |row| (col, row)))
+        .filter(|&step| step != (0, 0))
+        .filter_map(move |step| king.checked_add(step))
+}
+
+/// Checks if a checkmate is a back rank mate: the king is mated on its first
+/// rank by a rook or a queen, walled in by its own pieces
+///
+/// # Arguments
+/// * `board`: The board of the checkmate
+/// * `king`: The square of the mated king
+///
+/// # Returns
+/// Whether the mate is a back rank mate
+///
+fn is_back_rank_mate(board: &Board, king: Position) -> bool {
+    let color = board.get_piece(&king).unwrap().color;
+    let (back_rank, forward) = match color {
+        Color::White => (0, 1),
+        Color::Black => (7, 6),
+    };
+    king.row == back_rank
+        && board
+            .attacker(king, color.opposite())
+            .is_some_and(|checker| {
+                checker.row == back_rank
+                    && matches!(
+                        board.get_piece(&checker).unwrap().piece_type,
+                        PieceType::Rook | PieceType::Queen
+                    )
+            })
+        && around(king)
+            .filter(|square| square.row == forward)
+            .all(|square| {
+                board
+                    .get_piece(&square)
+                    .is_some_and(|piece| piece.color == color)
+            })
+}
+
+/// Checks if a checkmate is a smothered mate: the king is mated by a knight,
+/// surrounded by its own pieces
+///
+/// # Arguments
+/// * `board`: The board of the checkmate
+/// * `king`: The square of the mated king
+///
+/// # Returns
+/// Whether the mate is a smothered mate
+///
+fn is_smothered_mate(board: &Board, king: Position) -> bool {
+    let color = board.get_piece(&king).unwrap().color;
+    board
+        .attacker(king, color.opposite())
+        .is_some_and(|checker| board.get_piece(&checker).unwrap().piece_type == PieceType::Knight)
+        && around(king).all(|square| {
+            board
+                .get_piece(&square)
+                .is_some_and(|piece| piece.color == color)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::themes;
+    use crate::puzzles::tests::puzzle;
+
+    #[test]
+    fn test_themes() {
+        let smothered = puzzle("6rk/p5pp/8/6N1/8/8/8/6K1 b - - 0 1", &["a7a6", "g5f7"]);
+        assert_eq!(
+            themes(&smothered),
+            ["mate", "mateIn1", "smotheredMate", "oneMove"]
+        );
+
+        let fork = puzzle("r3k3/7p/8/1N6/8/8/8/4K3 b - - 0 1", &["h7h6", "b5c7"]);
+        assert_eq!(themes(&fork), ["fork", "oneMove"]);
+
+        let pin = puzzle("4k3/7p/2n5/8/8/8/8/4KB2 b - - 0 1", &["h7h6", "f1b5"]);
+        assert_eq!(themes(&pin), ["pin", "oneMove"]);
+
+        // A knight that can be taken for free doesn't fork
+        let fork = puzzle("r3k3/7p/3b4/1N6/8/8/8/4K3 b - - 0 1", &["h7h6", "b5c7"]);
+        assert_eq!(themes(&fork), ["oneMove"]);
+        assert!(themes(&puzzle("r3k3/7p/8/1N6/8/8/8/4K3 b - - 0 1", &["b5c7"])).is_empty());
+
+        // A check that is answered by taking the checking piece doesn't fork
+        let check = puzzle(
+            "r3k3/1q5p/1P6/1N6/8/8/8/4K3 b - - 0 1",
+            &["h7h6", "b5c7", "b7c7"],
+        );
+        assert_eq!(themes(&check), ["oneMove"]);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::{
+    analysis::{forces_mate, Engine},
+    constants::Move,
+    errors::PuzzleError,
+    logic::Game,
+};
+
+use super::Puzzle;
+
+/// The smallest lead, in centipawns, the move of the solution needs over the
+/// second best engine move to be the only winning move
+const UNIQUE_MARGIN: i32 = 200;
+
+/// Checks that the solution of a puzzle is forced: every move of the solver
+/// is the only one that wins
+/// Solutions that end in mate are checked with an exhaustive search, where
+/// any mate is accepted on the last move, as Lichess does. The others need
+/// an engine, whose best move must be the one of the solution and lead the
+/// second best by 200 centipawns
+///
+/// # Arguments
+/// * `puzzle`: The puzzle
+/// * `engine`: The engine to check the solutions that don't mate, if any
+///
+/// # Returns
+/// Ok if the solution is forced, or the first problem found
+///
+/// # Example
+/// ```
+/// use chess_lab::errors::PuzzleError;
+/// use chess_lab::puzzles::{verify, Puzzle};
+///
+/// let mut puzzle = Puzzle {
+///     fen: String::from("2r3k1/p4ppp/8/8/8/8/4RPPP/4R1K1 b - - 0 1"),
+///     moves: ["a7a6", "e2e8", "c8e8", "e1e8"].map(String::from).to_vec(),
+///     ..Puzzle::default()
+/// };
+/// assert_eq!(verify(&puzzle, None), Ok(()));
+///
+/// puzzle.moves[2] = String::from("c8c1");
+/// assert_eq!(
+///     verify(&puzzle, None),
+///     Err(PuzzleError::IllegalMove { ply: 2, mov: String::from("c8c1") })
+/// );
+/// ```
+///
+pub fn verify(puzzle: &Puzzle, engine: Option<&mut dyn Engine>) -> Result<(), PuzzleError> {
+    let (mut game, moves) = puzzle.play()?;
+    if moves.len() % 2 != 0 || moves.is_empty() {
+        return Err(PuzzleError::Incomplete);
+    }
+
+    let mates = moves.last().is_some_and(|mov| mov.checkmate);
+    let mut engine = match engine {
+        Some(engine) => Some(engine),
+        None if mates => None,
+        None => return Err(PuzzleError::Unverifiable),
+    };
+
+    for (ply, mov) in moves.iter().enumerate() {
+        if ply % 2 == 1 {
+            let solver_moves = (moves.len() - ply).div_ceil(2);
+            if mates {
+                verify_mate(&mut game, ply, mov, solver_moves)?;
+            } else if let Some(engine) = engine.as_deref_mut() {
+                verify_engine_move(engine, &game, ply, mov)?;
+            }
+        }
+        game.make_move(mov).map_err(|_| PuzzleError::IllegalMove {
+            ply,
+            mov: mov.uci(),
+        })?;
+    }
+    Ok(())
+}
+
+/// Checks a move of a solution that ends in mate
+///
+/// # Arguments
+/// * `game`: The game before the move
+/// * `ply`: The index of the move in the puzzle
+/// * `mov`: The move
+/// * `solver_moves`: The moves of the solver left, this one included
+///
+/// # Returns
+/// Ok if the move is the only one that mates in time
+///
+fn verify_mate(
+    game: &mut Game,
+    ply: usize,
+    mov: &Move,
+    solver_moves: usize,
+) -> Result<(), PuzzleError> {
+    if !forces_mate(game, mov, solver_moves) {
+        return Err(PuzzleError::NotWinning { ply });
+    }
+    if solver_moves == 1 {
+        return Ok(());
+    }
+    for alternative in game.legal_moves() {
+        if alternative.uci() != mov.uci() && forces_mate(game, &alternative, solver_moves) {
+            return Err(PuzzleError::NotForced {
+                ply,
+                alternative: alternative.uci(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Checks a move of a solution against the best moves of an engine
+///
+/// # Arguments
+/// * `engine`: The engine
+/// * `game`: The game before the move
+/// * `ply`: The index of the move in the puzzle
+/// * `mov`: The move
+///
+/// # Returns
+/// Ok if the move is the best engine move, far ahead of the second one
+///
+fn verify_engine_move(
+    engine: &mut dyn Engine,
+    game: &Game,
+    ply: usize,
+    mov: &Move,
+) -> Result<(), PuzzleError> {
+    let is_move = |engine_move: &str| {
+        engine_move == mov.uci()
+            || engine_move.trim_end_matches(['+', '#'])
+                == mov.to_string().trim_end_matches(['+', '#'])
+    };
+
+    let best = engine.best_moves(game, 2);
+    match best.as_slice() {
+        [first, ..] if !is_move(&first.mov) => Err(PuzzleError::NotWinning { ply }),
+        [first, second, ..] if first.score - second.score < UNIQUE_MARGIN => {
+            Err(PuzzleError::NotForced {
+                ply,
+                alternative: second.mov.clone(),
+            })
+        }
+        [] => Err(PuzzleError::Unverifiable),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::verify;
+    use crate::{
+        analysis::{Engine, EngineMove},
+        errors::PuzzleError,
+        logic::Game,
+        puzzles::tests::puzzle,
+    };
+
+    /// An engine that always suggests the same moves
+    struct Fixed(Vec<EngineMove>);
+
+    impl Engine for Fixed {
+        fn best_moves(&mut self, _game: &Game, count: usize) -> Vec<EngineMove> {
+            self.0.iter().take(count).cloned().collect()
+        }
+    }
+
+    #[test]
+    fn test_verify_mate() {
+        let back_rank = "2r3k1/p4ppp/8/8/8/8/4RPPP/4R1K1 b - - 0 1";
+        assert_eq!(
+            verify(&puzzle(back_rank, &["a7a6", "e2e8", "c8e8"]), None),
+            Err(PuzzleError::Incomplete)
+        );
+        // The defence blunders into a mate the solution didn't force
+        assert_eq!(
+            verify(&puzzle(back_rank, &["a7a6", "h2h3", "c8c2", "e2e8"]), None),
+            Err(PuzzleError::NotWinning { ply: 1 })
+        );
+
+        let doubled = "2r3k1/p4ppp/8/8/8/8/3RRPPP/3RR1K1 b - - 0 1";
+        assert!(matches!(
+            verify(&puzzle(doubled, &["a7a6", "e2e8", "c8e8", "e1e8"]), None),
+            Err(PuzzleError::NotForced { ply: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_with_engine() {
+        let fork = puzzle("r3k3/7p/8/1N6/8/8/8/4K3 b - - 0 1", &["h7h6", "b5c7"]);
+        assert_eq!(verify(&fork, None), Err(PuzzleError::Unverifiable));
+
+        let engine_move = |mov: &str, score| EngineMove {
+            mov: mov.to_string(),
+            score,
+        };
+        let mut engine = Fixed(vec![engine_move("Nc7+", 500), engine_move("Kd2", 0)]);
+        assert_eq!(verify(&fork, Some(&mut engine)), Ok(()));
+
+        let mut engine = Fixed(vec![engine_move("b5c7", 500), engine_move("Nd6+", 400)]);
+        assert_eq!(
+            verify(&fork, Some(&mut engine)),
+            Err(PuzzleError::NotForced {
+                ply: 1,
+                alternative: String::from("Nd6+")
+            })
+        );
+
+        let mut engine = Fixed(vec![engine_move("Nd6+", 500)]);
+        assert_eq!(
+            verify(&fork, Some(&mut engine)),
+            Err(PuzzleError::NotWinning { ply: 1 })
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use crate::constants::{Color, GameStatus};
+
+use super::score;
+
+/// Returns the expected score of a player against an opponent
+///
+/// # Arguments
+/// * `rating`: The Elo rating of the player
+/// * `opponent`: The Elo rating of the opponent
+///
+/// # Returns
+/// The expected score, between 0 and 1
+///
+/// # Example
+/// ```
+/// use chess_lab::rating::expected_score;
+///
+/// assert_eq!(expected_score(1500.0, 1500.0), 0.5);
+/// assert!((expected_score(1900.0, 1500.0) - 0.909).abs() < 0.001);
+/// ```
+///
+pub fn expected_score(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+/// Updates the Elo ratings of both players after a game
+///
+/// # Arguments
+/// * `white`: The rating of the white player
+/// * `black`: The rating of the black player
+/// * `status`: The status of the game
+/// * `k_factor`: The largest change of a rating after a single game
+///
+/// # Returns
+/// The new ratings of white and black, or `None` if the game is still in
+/// progress
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::{GameStatus, WinReason};
+/// use chess_lab::rating::update_elo;
+///
+/// let status = GameStatus::BlackWins(WinReason::Resignation);
+/// assert_eq!(update_elo(1500.0, 1500.0, status, 32.0), Some((1484.0, 1516.0)));
+/// assert_eq!(update_elo(1500.0, 1500.0, GameStatus::InProgress, 32.0), None);
+/// ```
+///
+pub fn update_elo(white: f64, black: f64, status: GameStatus, k_factor: f64) -> Option<(f64, f64)> {
+    let change = k_factor * (score(status, Color::White)? - expected_score(white, black));
+    Some((white + change, black - change))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expected_score, update_elo};
+    use crate::constants::{DrawReason, GameStatus, WinReason};
+
+    #[test]
+    fn test_update_elo() {
+        assert!(
+            (expected_score(1500.0, 1700.0) + expected_score(1700.0, 1500.0) - 1.0).abs() < 1e-9
+        );
+
+        // The favourite loses rating on a draw
+        let (white, black) = update_elo(
+            1700.0,
+            1500.0,
+            GameStatus::Draw(DrawReason::Agreement),
+            20.0,
+        )
+        .unwrap();
+        assert!((white - 1694.8).abs() < 0.1);
+        assert!((black - 1505.2).abs() < 0.1);
+
+        let (white, black) = update_elo(
+            1700.0,
+            1500.0,
+            GameStatus::WhiteWins(WinReason::Checkmate),
+            20.0,
+        )
+        .unwrap();
+        assert!((white - 1704.8).abs() < 0.1);
+        assert_eq!(white + black, 3200.0);
+    }
+}
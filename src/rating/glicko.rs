@@ -0,0 +1,236 @@
+use std::f64::consts::PI;
+
+use crate::constants::{Color, GameStatus};
+
+use super::score;
+
+/// The factor between the Glicko and the Glicko-2 scales
+const SCALE: f64 = 173.7178;
+
+/// The precision of the volatility iteration
+const EPSILON: f64 = 0.000_001;
+
+/// A Glicko-2 rating
+///
+/// # Fields
+/// * `rating`: The rating, on the same scale as Elo
+/// * `deviation`: How uncertain the rating is, 350 for a new player
+/// * `volatility`: How erratic the results of the player are
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glicko2Rating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+impl Default for Glicko2Rating {
+    /// Creates the rating of a new player: 1500, with a deviation of 350 and
+    /// a volatility of 0.06
+    ///
+    /// # Returns
+    /// The default rating
+    ///
+    fn default() -> Glicko2Rating {
+        Glicko2Rating {
+            rating: 1500.0,
+            deviation: 350.0,
+            volatility: 0.06,
+        }
+    }
+}
+
+impl Glicko2Rating {
+    /// Updates the rating with the games of a rating period, following
+    /// Glickman's description of Glicko-2
+    /// A period without games only increases the deviation
+    ///
+    /// # Arguments
+    /// * `results`: The rating of the opponent and the score of every game
+    /// * `tau`: How much the volatility can change, usually between 0.3 and
+    ///   1.2
+    ///
+    /// # Returns
+    /// The rating after the period
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::rating::Glicko2Rating;
+    ///
+    /// let opponent = |rating, deviation| Glicko2Rating { rating, deviation, volatility: 0.06 };
+    /// let player = Glicko2Rating { rating: 1500.0, deviation: 200.0, volatility: 0.06 };
+    ///
+    /// let updated = player.update(
+    ///     &[(opponent(1400.0, 30.0), 1.0), (opponent(1550.0, 100.0), 0.0), (opponent(1700.0, 300.0), 0.0)],
+    ///     0.5,
+    /// );
+    /// assert_eq!(updated.rating.round(), 1464.0);
+    /// assert_eq!(updated.deviation.round(), 152.0);
+    /// ```
+    ///
+    pub fn update(&self, results: &[(Glicko2Rating, f64)], tau: f64) -> Glicko2Rating {
+        let mu = (self.rating - 1500.0) / SCALE;
+        let phi = self.deviation / SCALE;
+
+        if results.is_empty() {
+            return Glicko2Rating {
+                deviation: (phi.powi(2) + self.volatility.powi(2)).sqrt() * SCALE,
+                ..*self
+            };
+        }
+
+        let mut variance = 0.0;
+        let mut improvement = 0.0;
+        for (opponent, score) in results {
+            let g = g((opponent.deviation) / SCALE);
+            let expected = 1.0 / (1.0 + (-g * (mu - (opponent.rating - 1500.0) / SCALE)).exp());
+            variance += g.powi(2) * expected * (1.0 - expected);
+            improvement += g * (score - expected);
+        }
+        let variance = 1.0 / variance;
+        let delta = variance * improvement;
+
+        let volatility = volatility(phi, self.volatility, variance, delta, tau);
+        let phi_star = (phi.powi(2) + volatility.powi(2)).sqrt();
+        let new_phi = 1.0 / (1.0 / phi_star.powi(2) + 1.0 / variance).sqrt();
+        let new_mu = mu + new_phi.powi(2) * improvement;
+
+        Glicko2Rating {
+            rating: new_mu * SCALE + 1500.0,
+            deviation: new_phi * SCALE,
+            volatility,
+        }
+    }
+}
+
+/// Updates the Glicko-2 ratings of both players after a game, taken as a
+/// rating period of its own
+///
+/// # Arguments
+/// * `white`: The rating of the white player
+/// * `black`: The rating of the black player
+/// * `status`: The status of the game
+/// * `tau`: How much the volatility can change
+///
+/// # Returns
+/// The new ratings of white and black, or `None` if the game is still in
+/// progress
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::{GameStatus, WinReason};
+/// use chess_lab::rating::{update_glicko2, Glicko2Rating};
+///
+/// let status = GameStatus::WhiteWins(WinReason::Checkmate);
+/// let (white, black) =
+///     update_glicko2(Glicko2Rating::default(), Glicko2Rating::default(), status, 0.5).unwrap();
+/// assert!(white.rating > 1500.0 && black.rating < 1500.0);
+/// assert!(white.deviation < 350.0);
+/// ```
+///
+pub fn update_glicko2(
+    white: Glicko2Rating,
+    black: Glicko2Rating,
+    status: GameStatus,
+    tau: f64,
+) -> Option<(Glicko2Rating, Glicko2Rating)> {
+    Some((
+        white.update(&[(black, score(status, Color::White)?)], tau),
+        black.update(&[(white, score(status, Color::Black)?)], tau),
+    ))
+}
+
+/// Reduces the impact of a game by the deviation of the opponent
+///
+/// # Arguments
+/// * `phi`: The deviation of the opponent, on the Glicko-2 scale
+///
+/// # Returns
+/// The weight of the game
+///
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi.powi(2) / PI.powi(2)).sqrt()
+}
+
+/// Computes the new volatility with the Illinois algorithm
+///
+/// # Arguments
+/// * `phi`: The deviation of the player, on the Glicko-2 scale
+/// * `sigma`: The volatility of the player
+/// * `variance`: The estimated variance of the rating from the games
+/// * `delta`: The estimated improvement of the rating from the games
+/// * `tau`: How much the volatility can change
+///
+/// # Returns
+/// The new volatility
+///
+fn volatility(phi: f64, sigma: f64, variance: f64, delta: f64, tau: f64) -> f64 {
+    let a = sigma.powi(2).ln();
+    let f = |x: f64| {
+        let ex = x.exp();
+        ex * (delta.powi(2) - phi.powi(2) - variance - ex)
+            / (2.0 * (phi.powi(2) + variance + ex).powi(2))
+            - (x - a) / tau.powi(2)
+    };
+
+    let mut lower = a;
+    let mut upper = if delta.powi(2) > phi.powi(2) + variance {
+        (delta.powi(2) - phi.powi(2) - variance).ln()
+    } else {
+        let mut k = 1.0;
+        while f(a - k * tau) < 0.0 {
+            k += 1.0;
+        }
+        a - k * tau
+    };
+
+    let (mut f_lower, mut f_upper) = (f(lower), f(upper));
+    while (upper - lower).abs() > EPSILON {
+        let c = lower + (lower - upper) * f_lower / (f_upper - f_lower);
+        let f_c = f(c);
+        if f_c * f_upper <= 0.0 {
+            lower = upper;
+            f_lower = f_upper;
+        } else {
+            f_lower /= 2.0;
+        }
+        upper = c;
+        f_upper = f_c;
+    }
+    (lower / 2.0).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Glicko2Rating;
+
+    #[test]
+    fn test_glicko2_update() {
+        // The example of Glickman's paper
+        let player = Glicko2Rating {
+            rating: 1500.0,
+            deviation: 200.0,
+            volatility: 0.06,
+        };
+        let opponent = |rating, deviation| Glicko2Rating {
+            rating,
+            deviation,
+            volatility: 0.06,
+        };
+        let updated = player.update(
+            &[
+                (opponent(1400.0, 30.0), 1.0),
+                (opponent(1550.0, 100.0), 0.0),
+                (opponent(1700.0, 300.0), 0.0),
+            ],
+            0.5,
+        );
+        assert!((updated.rating - 1464.06).abs() < 0.01);
+        assert!((updated.deviation - 151.52).abs() < 0.01);
+        assert!((updated.volatility - 0.05999).abs() < 0.00001);
+
+        let idle = player.update(&[], 0.5);
+        assert_eq!(idle.rating, 1500.0);
+        assert!((idle.deviation - 200.27).abs() < 0.01);
+    }
+}
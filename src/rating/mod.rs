@@ -0,0 +1,42 @@
+mod elo;
+mod glicko;
+
+pub use elo::*;
+pub use glicko::*;
+
+use crate::constants::{Color, GameStatus};
+
+/// Returns the score of a player in a finished game
+///
+/// # Arguments
+/// * `status`: The status of the game
+/// * `color`: The color of the player
+///
+/// # Returns
+/// 1 for a win, 0.5 for a draw and 0 for a loss, or `None` if the game is
+/// still in progress
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::{Color, DrawReason, GameStatus, WinReason};
+/// use chess_lab::rating::score;
+///
+/// let status = GameStatus::WhiteWins(WinReason::Checkmate);
+/// assert_eq!(score(status, Color::White), Some(1.0));
+/// assert_eq!(score(status, Color::Black), Some(0.0));
+/// assert_eq!(score(GameStatus::Draw(DrawReason::Stalemate), Color::Black), Some(0.5));
+/// assert_eq!(score(GameStatus::InProgress, Color::White), None);
+/// ```
+///
+pub fn score(status: GameStatus, color: Color) -> Option<f64> {
+    let white = match status {
+        GameStatus::WhiteWins(_) => 1.0,
+        GameStatus::BlackWins(_) => 0.0,
+        GameStatus::Draw(_) => 0.5,
+        GameStatus::InProgress => return None,
+    };
+    match color {
+        Color::White => Some(white),
+        Color::Black => Some(1.0 - white),
+    }
+}
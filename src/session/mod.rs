@@ -0,0 +1,3 @@
+mod simul;
+
+pub use simul::*;
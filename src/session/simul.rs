@@ -0,0 +1,300 @@
+use crate::{
+    constants::{Color, GameStatus},
+    errors::SessionError,
+    logic::Game,
+    rating::score,
+};
+
+/// A board of a simultaneous exhibition
+///
+/// # Fields
+/// * `opponent`: The name of the player facing the giver
+/// * `game`: The game played on the board
+///
+#[derive(Debug, Clone)]
+pub struct SimulBoard {
+    pub opponent: String,
+    pub game: Game,
+}
+
+/// A simultaneous exhibition: one player, the giver, against several
+/// opponents at once
+///
+/// The giver walks along the boards, so the boards are visited in
+/// [`rotation`](Simul::rotation), starting after the last board the giver
+/// moved on
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::Color;
+/// use chess_lab::session::Simul;
+///
+/// let opponents = vec!["Anna".to_string(), "Ben".to_string(), "Cleo".to_string()];
+/// let mut simul = Simul::new("Magnus", Color::White, opponents);
+///
+/// let board = simul.next_board().unwrap();
+/// simul.play(board, "e4").unwrap();
+/// assert_eq!(simul.next_board(), Some(1));
+///
+/// // Anna answers while the giver plays on the other boards
+/// simul.play(0, "c5").unwrap();
+/// simul.play(1, "d4").unwrap();
+/// simul.play(2, "c4").unwrap();
+/// assert_eq!(simul.next_board(), Some(0));
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct Simul {
+    pub giver: String,
+    pub giver_color: Color,
+    boards: Vec<SimulBoard>,
+    position: usize,
+}
+
+impl Simul {
+    /// Creates a simultaneous exhibition from the starting position on every
+    /// board
+    ///
+    /// # Arguments
+    /// * `giver`: The name of the giver
+    /// * `giver_color`: The color the giver plays on every board
+    /// * `opponents`: The names of the opponents, one per board
+    ///
+    /// # Returns
+    /// The simul, with the giver at the first board
+    ///
+    pub fn new(giver: &str, giver_color: Color, opponents: Vec<String>) -> Simul {
+        let mut simul = Simul {
+            giver: giver.to_string(),
+            giver_color,
+            boards: Vec::new(),
+            position: 0,
+        };
+        for opponent in opponents {
+            simul.add_board(&opponent, Game::default());
+        }
+        simul
+    }
+
+    /// Adds a board to the simul, setting the `Event`, `White` and `Black`
+    /// tags of its game
+    ///
+    /// # Arguments
+    /// * `opponent`: The name of the opponent
+    /// * `game`: The game of the board, which can start from any position
+    ///
+    /// # Returns
+    /// The index of the new board
+    ///
+    pub fn add_board(&mut self, opponent: &str, mut game: Game) -> usize {
+        game.history.event = Some(format!("{} simul", self.giver));
+        let (white, black) = match self.giver_color {
+            Color::White => (self.giver.as_str(), opponent),
+            Color::Black => (opponent, self.giver.as_str()),
+        };
+        game.history.white = Some(white.to_string());
+        game.history.black = Some(black.to_string());
+        self.boards.push(SimulBoard {
+            opponent: opponent.to_string(),
+            game,
+        });
+        self.boards.len() - 1
+    }
+
+    /// Returns the boards of the simul
+    ///
+    /// # Returns
+    /// The boards, in the order the giver walks them
+    ///
+    pub fn boards(&self) -> &[SimulBoard] {
+        &self.boards
+    }
+
+    /// Returns the game of a board, to resign, offer draws or read it
+    ///
+    /// # Arguments
+    /// * `board`: The index of the board
+    ///
+    /// # Returns
+    /// The game, or `None` if the board doesn't exist
+    ///
+    pub fn game_mut(&mut self, board: usize) -> Option<&mut Game> {
+        self.boards.get_mut(board).map(|board| &mut board.game)
+    }
+
+    /// Plays a move on a board, by the giver or by the opponent, depending on
+    /// the side to move
+    /// After a move of the giver, the rotation continues at the next board
+    ///
+    /// # Arguments
+    /// * `board`: The index of the board
+    /// * `mov`: The move, in any notation accepted by `Game::move_piece`
+    ///
+    /// # Returns
+    /// The status of the game after the move, or an error if the board
+    /// doesn't exist or the move can't be played
+    ///
+    pub fn play(&mut self, board: usize, mov: &str) -> Result<GameStatus, SessionError> {
+        let game = &mut self
+            .boards
+            .get_mut(board)
+            .ok_or(SessionError::UnknownBoard(board))?
+            .game;
+        let giver_moves = game.side_to_move() == self.giver_color;
+        let status = game.move_piece(mov)?;
+        if giver_moves {
+            self.position = (board + 1) % self.boards.len();
+        }
+        Ok(status)
+    }
+
+    /// Returns the boards where the giver has to move, in the order they are
+    /// reached from the current position of the giver
+    ///
+    /// # Returns
+    /// An iterator over the indexes and games of the boards waiting for the
+    /// giver, going around the boards once
+    ///
+    pub fn rotation(&self) -> Rotation<'_> {
+        Rotation {
+            simul: self,
+            next: self.position,
+            remaining: self.boards.len(),
+        }
+    }
+
+    /// Returns the next board where the giver has to move
+    ///
+    /// # Returns
+    /// The index of the board, or `None` if every board is waiting for its
+    /// opponent or finished
+    ///
+    pub fn next_board(&self) -> Option<usize> {
+        self.rotation().next().map(|(board, _)| board)
+    }
+
+    /// Returns whether every game of the simul is over
+    ///
+    /// # Returns
+    /// Whether no game is in progress
+    ///
+    pub fn is_finished(&self) -> bool {
+        self.boards.iter().all(|board| !board.game.is_ongoing())
+    }
+
+    /// Returns the score of the finished games
+    ///
+    /// # Returns
+    /// The points of the giver and the points of all the opponents together
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::Color;
+    /// use chess_lab::session::Simul;
+    ///
+    /// let mut simul = Simul::new("Judit", Color::Black, vec!["A".to_string(), "B".to_string()]);
+    /// simul.game_mut(0).unwrap().resign(Color::White);
+    /// simul.game_mut(1).unwrap().set_draw_by_agreement();
+    ///
+    /// assert!(simul.is_finished());
+    /// assert_eq!(simul.score(), (1.5, 0.5));
+    /// ```
+    ///
+    pub fn score(&self) -> (f64, f64) {
+        self.boards
+            .iter()
+            .filter_map(|board| score(board.game.game_status, self.giver_color))
+            .fold((0.0, 0.0), |(giver, opponents), points| {
+                (giver + points, opponents + 1.0 - points)
+            })
+    }
+
+    /// Returns the PGN of every game of the simul, as a database
+    ///
+    /// # Returns
+    /// The games, by board, separated by blank lines
+    ///
+    pub fn pgn(&self) -> String {
+        self.boards
+            .iter()
+            .map(|board| board.game.pgn())
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    }
+}
+
+/// An iterator over the boards waiting for the giver, created by
+/// `Simul::rotation`
+///
+pub struct Rotation<'a> {
+    simul: &'a Simul,
+    next: usize,
+    remaining: usize,
+}
+
+impl<'a> Iterator for Rotation<'a> {
+    type Item = (usize, &'a Game);
+
+    /// Returns the next board waiting for the giver
+    ///
+    /// # Returns
+    /// The index and the game of the board, or `None` once every board was
+    /// visited
+    ///
+    fn next(&mut self) -> Option<(usize, &'a Game)> {
+        while self.remaining > 0 {
+            let board = self.next;
+            self.next = (self.next + 1) % self.simul.boards.len();
+            self.remaining -= 1;
+
+            let game = &self.simul.boards[board].game;
+            if game.is_ongoing() && game.side_to_move() == self.simul.giver_color {
+                return Some((board, game));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Simul;
+    use crate::{constants::Color, errors::SessionError, logic::Game};
+
+    #[test]
+    fn test_simul_rotation() {
+        let mut simul = Simul::new("Giver", Color::Black, vec![]);
+        assert_eq!(simul.next_board(), None);
+        assert!(simul.is_finished());
+
+        simul.add_board("A", Game::default());
+        simul.add_board("B", Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1"));
+        simul.add_board("C", Game::default());
+        assert_eq!(simul.next_board(), None);
+
+        assert_eq!(simul.play(3, "e4"), Err(SessionError::UnknownBoard(3)));
+        assert!(matches!(simul.play(1, "Ra9"), Err(SessionError::Move(_))));
+        simul.play(1, "Ra7").unwrap();
+        simul.play(2, "d4").unwrap();
+        let boards: Vec<usize> = simul.rotation().map(|(board, _)| board).collect();
+        assert_eq!(boards, vec![1, 2]);
+
+        simul.play(1, "Kd8").unwrap();
+        assert_eq!(simul.next_board(), Some(2));
+        simul.play(0, "e4").unwrap();
+        let boards: Vec<usize> = simul.rotation().map(|(board, _)| board).collect();
+        assert_eq!(boards, vec![2, 0]);
+
+        simul.game_mut(2).unwrap().resign(Color::Black);
+        assert_eq!(simul.next_board(), Some(0));
+        assert_eq!(simul.score(), (0.0, 1.0));
+        assert!(!simul.is_finished());
+
+        let pgn = simul.pgn();
+        let games: Vec<&str> = pgn.split("\n\n").collect();
+        assert_eq!(games.len(), 3);
+        assert!(games[0].starts_with("[Event \"Giver simul\"]\n[White \"A\"]\n[Black \"Giver\"]"));
+        assert!(games[1].ends_with("1. Ra7 Kd8 *"));
+        assert!(games[2].ends_with("1. d4 1-0"));
+    }
+}
@@ -0,0 +1,503 @@
+mod pairing;
+mod standings;
+
+use std::collections::HashSet;
+
+pub use standings::Standing;
+
+use crate::{
+    constants::{Color, GameStatus},
+    errors::TournamentError,
+    logic::Game,
+    rating::score,
+};
+
+use pairing::{round_robin_round, swiss_round};
+use standings::{scores, standings};
+
+/// How the players of a tournament are paired
+///
+/// # Variants
+/// * `RoundRobin`: Every player meets every other one once
+/// * `Swiss`: Players with similar scores meet, without rematches when
+///   possible
+///     - `rounds`: The number of rounds
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PairingSystem {
+    RoundRobin,
+    Swiss { rounds: u32 },
+}
+
+/// A board of a round
+///
+/// # Fields
+/// * `board`: The number of the board, from 1
+/// * `white`: The index of the player with the white pieces
+/// * `black`: The index of the player with the black pieces, or `None` if
+///   `white` gets a bye
+/// * `result`: The final status of the game, or `None` if it wasn't recorded
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pairing {
+    pub board: u32,
+    pub white: usize,
+    pub black: Option<usize>,
+    pub result: Option<GameStatus>,
+}
+
+impl Pairing {
+    /// Returns whether the board is a bye
+    ///
+    /// # Returns
+    /// Whether the player has no opponent in the round
+    ///
+    pub fn is_bye(&self) -> bool {
+        self.black.is_none()
+    }
+
+    /// Returns the score of the white player
+    ///
+    /// # Returns
+    /// The points of white, a full point for a bye, or `None` if the result
+    /// isn't known yet
+    ///
+    pub fn white_score(&self) -> Option<f64> {
+        if self.is_bye() {
+            return Some(1.0);
+        }
+        score(self.result?, Color::White)
+    }
+}
+
+/// A tournament: its players, the pairings of every round and their results
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::Color;
+/// use chess_lab::tournament::{PairingSystem, Tournament};
+///
+/// let players = vec!["Anna".to_string(), "Ben".to_string(), "Cleo".to_string()];
+/// let mut tournament = Tournament::new("Club championship", players, PairingSystem::RoundRobin)
+///     .unwrap();
+///
+/// while tournament.pair_next_round().is_ok() {
+///     let round = tournament.rounds().len() as u32;
+///     for (board, mut game) in tournament.round_games(round).into_iter().enumerate() {
+///         game.resign(Color::Black);
+///         tournament.record(round, board as u32 + 1, &game).unwrap();
+///     }
+/// }
+///
+/// assert_eq!(tournament.rounds().len(), 3);
+/// let standings = tournament.standings();
+/// assert_eq!(standings.iter().map(|standing| standing.points).sum::<f64>(), 6.0);
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct Tournament {
+    pub event: String,
+    players: Vec<String>,
+    system: PairingSystem,
+    rounds: Vec<Vec<Pairing>>,
+}
+
+impl Tournament {
+    /// Creates a tournament with no rounds paired yet
+    ///
+    /// # Arguments
+    /// * `event`: The name of the tournament, written in the `Event` tag
+    /// * `players`: The names of the players, in seeding order
+    /// * `system`: How the players are paired
+    ///
+    /// # Returns
+    /// The tournament, or an error if there are less than two players
+    ///
+    pub fn new(
+        event: &str,
+        players: Vec<String>,
+        system: PairingSystem,
+    ) -> Result<Tournament, TournamentError> {
+        if players.len() < 2 {
+            return Err(TournamentError::NotEnoughPlayers);
+        }
+        Ok(Tournament {
+            event: event.to_string(),
+            players,
+            system,
+            rounds: Vec::new(),
+        })
+    }
+
+    /// Returns the names of the players
+    ///
+    /// # Returns
+    /// The players, in seeding order
+    ///
+    pub fn players(&self) -> &[String] {
+        &self.players
+    }
+
+    /// Returns the pairings of the rounds paired so far
+    ///
+    /// # Returns
+    /// The boards of every round, from the first one
+    ///
+    pub fn rounds(&self) -> &[Vec<Pairing>] {
+        &self.rounds
+    }
+
+    /// Returns the number of rounds of the tournament
+    ///
+    /// # Returns
+    /// The rounds of a round robin, or the rounds set for a Swiss tournament
+    ///
+    pub fn total_rounds(&self) -> u32 {
+        match self.system {
+            PairingSystem::RoundRobin => (self.players.len() - 1 + self.players.len() % 2) as u32,
+            PairingSystem::Swiss { rounds } => rounds,
+        }
+    }
+
+    /// Pairs the next round, with the bye on the last board
+    /// A Swiss round needs every result of the previous one, since players
+    /// are paired by score. The player with a bye gets a full point
+    ///
+    /// # Returns
+    /// The boards of the new round, or an error if every round was paired or
+    /// the previous Swiss round isn't finished
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::errors::TournamentError;
+    /// use chess_lab::tournament::{PairingSystem, Tournament};
+    ///
+    /// let players = (1..=4).map(|n| format!("Player {}", n)).collect();
+    /// let mut tournament = Tournament::new("Open", players, PairingSystem::Swiss { rounds: 3 })
+    ///     .unwrap();
+    ///
+    /// assert_eq!(tournament.pair_next_round().unwrap().len(), 2);
+    /// assert_eq!(tournament.pair_next_round(), Err(TournamentError::RoundNotFinished));
+    /// ```
+    ///
+    pub fn pair_next_round(&mut self) -> Result<&[Pairing], TournamentError> {
+        let round = self.rounds.len();
+        if round as u32 >= self.total_rounds() {
+            return Err(TournamentError::NoRoundsLeft);
+        }
+
+        let mut boards = match self.system {
+            PairingSystem::RoundRobin => round_robin_round(self.players.len(), round),
+            PairingSystem::Swiss { .. } => self.swiss_boards()?,
+        };
+        // The bye goes last, so the games of a round are numbered like its boards
+        boards.sort_by_key(|(_, black)| black.is_none());
+        self.rounds.push(
+            boards
+                .into_iter()
+                .enumerate()
+                .map(|(board, (white, black))| Pairing {
+                    board: board as u32 + 1,
+                    white,
+                    black,
+                    result: None,
+                })
+                .collect(),
+        );
+        Ok(self.rounds.last().unwrap())
+    }
+
+    /// Pairs the next Swiss round from the scores so far
+    ///
+    /// # Returns
+    /// The white and black player of every board, the bye last, or an error
+    /// if a result of the previous round is missing
+    ///
+    fn swiss_boards(&self) -> Result<Vec<(usize, Option<usize>)>, TournamentError> {
+        if self
+            .rounds
+            .iter()
+            .flatten()
+            .any(|pairing| pairing.white_score().is_none())
+        {
+            return Err(TournamentError::RoundNotFinished);
+        }
+
+        let points = scores(self.players.len(), &self.rounds);
+        let mut ranking: Vec<usize> = (0..self.players.len()).collect();
+        ranking.sort_by(|a, b| points[*b].total_cmp(&points[*a]).then(a.cmp(b)));
+
+        let mut met = HashSet::new();
+        let mut had_bye = HashSet::new();
+        // Whites minus blacks, and the color of the last game, of each player
+        let mut balance = vec![0i32; self.players.len()];
+        let mut last_white = vec![None; self.players.len()];
+        for pairing in self.rounds.iter().flatten() {
+            match pairing.black {
+                Some(black) => {
+                    met.insert((pairing.white.min(black), pairing.white.max(black)));
+                    balance[pairing.white] += 1;
+                    balance[black] -= 1;
+                    last_white[pairing.white] = Some(true);
+                    last_white[black] = Some(false);
+                }
+                None => {
+                    had_bye.insert(pairing.white);
+                }
+            }
+        }
+
+        let (pairs, bye) = swiss_round(&ranking, &met, &had_bye);
+        let mut boards: Vec<(usize, Option<usize>)> = pairs
+            .into_iter()
+            .map(|(higher, lower)| {
+                let lower_gets_white = balance[lower] < balance[higher]
+                    || (balance[lower] == balance[higher]
+                        && last_white[lower] == Some(false)
+                        && last_white[higher] != Some(false));
+                if lower_gets_white {
+                    (lower, Some(higher))
+                } else {
+                    (higher, Some(lower))
+                }
+            })
+            .collect();
+        boards.extend(bye.map(|player| (player, None)));
+        Ok(boards)
+    }
+
+    /// Records the result of a game of the tournament
+    /// The `White` and `Black` tags of the game, when set, must name the
+    /// players of the board
+    ///
+    /// # Arguments
+    /// * `round`: The number of the round, from 1
+    /// * `board`: The number of the board, from 1
+    /// * `game`: The finished game
+    ///
+    /// # Returns
+    /// Ok if the result was recorded, or an error if there is no game on the
+    /// board, the game isn't over or it was played by other players
+    ///
+    pub fn record(&mut self, round: u32, board: u32, game: &Game) -> Result<(), TournamentError> {
+        if game.game_status == GameStatus::InProgress {
+            return Err(TournamentError::GameInProgress);
+        }
+        let pairing = self.pairing(round, board)?;
+        let names = [Some(pairing.white), pairing.black]
+            .map(|player| player.map(|player| self.players[player].as_str()));
+        for (tag, name) in [&game.history.white, &game.history.black]
+            .into_iter()
+            .zip(names)
+        {
+            if tag.as_deref().is_some_and(|tag| Some(tag) != name) {
+                return Err(TournamentError::WrongPlayers);
+            }
+        }
+        self.record_result(round, board, game.game_status)
+    }
+
+    /// Records the result of a board without the game, like a forfeit
+    ///
+    /// # Arguments
+    /// * `round`: The number of the round, from 1
+    /// * `board`: The number of the board, from 1
+    /// * `status`: The final status of the game
+    ///
+    /// # Returns
+    /// Ok if the result was recorded, or an error if there is no game on the
+    /// board or the status isn't final
+    ///
+    pub fn record_result(
+        &mut self,
+        round: u32,
+        board: u32,
+        status: GameStatus,
+    ) -> Result<(), TournamentError> {
+        if status == GameStatus::InProgress {
+            return Err(TournamentError::GameInProgress);
+        }
+        self.pairing(round, board)?;
+        self.rounds[round as usize - 1][board as usize - 1].result = Some(status);
+        Ok(())
+    }
+
+    /// Finds the pairing of a board that has a game
+    ///
+    /// # Arguments
+    /// * `round`: The number of the round, from 1
+    /// * `board`: The number of the board, from 1
+    ///
+    /// # Returns
+    /// The pairing, or an error if the board doesn't exist or is a bye
+    ///
+    fn pairing(&self, round: u32, board: u32) -> Result<&Pairing, TournamentError> {
+        self.rounds
+            .get((round as usize).wrapping_sub(1))
+            .and_then(|boards| boards.get((board as usize).wrapping_sub(1)))
+            .filter(|pairing| !pairing.is_bye())
+            .ok_or(TournamentError::UnknownGame { round, board })
+    }
+
+    /// Returns the games of a round with their tags filled in: the event, the
+    /// round, the players and the result, if recorded
+    /// Byes have no game
+    ///
+    /// # Arguments
+    /// * `round`: The number of the round, from 1
+    ///
+    /// # Returns
+    /// The games of the round, by board, or none if the round isn't paired
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::tournament::{PairingSystem, Tournament};
+    ///
+    /// let players = vec!["Anna".to_string(), "Ben".to_string()];
+    /// let mut tournament = Tournament::new("Match", players, PairingSystem::RoundRobin).unwrap();
+    /// tournament.pair_next_round().unwrap();
+    ///
+    /// assert_eq!(
+    ///     tournament.round_games(1)[0].pgn(),
+    ///     "[Event \"Match\"]\n[Round \"1\"]\n[White \"Ben\"]\n[Black \"Anna\"]\n*"
+    /// );
+    /// ```
+    ///
+    pub fn round_games(&self, round: u32) -> Vec<Game> {
+        let Some(boards) = self.rounds.get((round as usize).wrapping_sub(1)) else {
+            return Vec::new();
+        };
+        boards
+            .iter()
+            .filter_map(|pairing| {
+                let black = pairing.black?;
+                let mut game = Game::default();
+                game.history.event = Some(self.event.clone());
+                game.history.round = Some(round.to_string());
+                game.history.white = Some(self.players[pairing.white].clone());
+                game.history.black = Some(self.players[black].clone());
+                game.history.result = pairing.white_score().map(|score| {
+                    match score {
+                        1.0 => "1-0",
+                        0.0 => "0-1",
+                        _ => "1/2-1/2",
+                    }
+                    .to_string()
+                });
+                Some(game)
+            })
+            .collect()
+    }
+
+    /// Returns the standings of the tournament
+    ///
+    /// # Returns
+    /// The players ordered by points, with Buchholz and Sonneborn-Berger as
+    /// tie-breaks
+    ///
+    pub fn standings(&self) -> Vec<Standing> {
+        standings(&self.players, &self.rounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PairingSystem, Tournament};
+    use crate::{
+        constants::{Color, DrawReason, GameStatus, WinReason},
+        errors::TournamentError,
+        logic::Game,
+    };
+
+    fn players(count: usize) -> Vec<String> {
+        (0..count).map(|n| format!("P{}", n)).collect()
+    }
+
+    #[test]
+    fn test_record() {
+        assert_eq!(
+            Tournament::new("Solo", players(1), PairingSystem::RoundRobin).err(),
+            Some(TournamentError::NotEnoughPlayers)
+        );
+
+        let mut tournament =
+            Tournament::new("Open", players(3), PairingSystem::Swiss { rounds: 2 }).unwrap();
+        let round = tournament.pair_next_round().unwrap().to_vec();
+        assert_eq!(round.len(), 2);
+        assert!(round[1].is_bye());
+        assert_eq!(
+            (round[0].white, round[0].black, round[1].white),
+            (0, Some(1), 2)
+        );
+
+        let mut game = tournament.round_games(1).remove(0);
+        assert_eq!(
+            tournament.record(1, 1, &game),
+            Err(TournamentError::GameInProgress)
+        );
+        game.resign(Color::White);
+        assert_eq!(
+            tournament.record(1, 2, &game),
+            Err(TournamentError::UnknownGame { round: 1, board: 2 })
+        );
+        assert_eq!(
+            tournament.record(2, 1, &game),
+            Err(TournamentError::UnknownGame { round: 2, board: 1 })
+        );
+        game.history.black = Some("P2".to_string());
+        assert_eq!(
+            tournament.record(1, 1, &game),
+            Err(TournamentError::WrongPlayers)
+        );
+        game.history.black = None;
+        tournament.record(1, 1, &game).unwrap();
+
+        // P1 and P2 lead with a point, P2 had the bye and P1 played black
+        let round = tournament.pair_next_round().unwrap().to_vec();
+        assert_eq!((round[0].white, round[0].black), (1, Some(2)));
+        assert_eq!((round[1].white, round[1].black), (0, None));
+        tournament
+            .record_result(2, 1, GameStatus::Draw(DrawReason::Agreement))
+            .unwrap();
+        assert_eq!(
+            tournament.pair_next_round().err(),
+            Some(TournamentError::NoRoundsLeft)
+        );
+        assert!(tournament.round_games(2)[0].pgn().ends_with("1/2-1/2"));
+        assert!(tournament.round_games(3).is_empty());
+    }
+
+    #[test]
+    fn test_standings() {
+        let mut tournament =
+            Tournament::new("Club", players(4), PairingSystem::RoundRobin).unwrap();
+        while tournament.pair_next_round().is_ok() {
+            let round = tournament.rounds().len() as u32;
+            for pairing in tournament.rounds()[round as usize - 1].clone() {
+                // The lower index always wins, except P3 draws with P2
+                let status = match (pairing.white, pairing.black.unwrap()) {
+                    (3, 2) | (2, 3) => GameStatus::Draw(DrawReason::Agreement),
+                    (white, black) if white < black => GameStatus::WhiteWins(WinReason::Time),
+                    _ => GameStatus::BlackWins(WinReason::Time),
+                };
+                tournament
+                    .record(round, pairing.board, &{
+                        let mut game = Game::default();
+                        game.game_status = status;
+                        game
+                    })
+                    .unwrap();
+            }
+        }
+
+        let standings = tournament.standings();
+        let order: Vec<usize> = standings.iter().map(|standing| standing.player).collect();
+        assert_eq!(order, vec![0, 1, 2, 3]);
+        let points: Vec<f64> = standings.iter().map(|standing| standing.points).collect();
+        assert_eq!(points, vec![3.0, 2.0, 0.5, 0.5]);
+        // Everyone met everyone, so the Buchholz is the total minus the own score
+        assert_eq!(standings[0].buchholz, 3.0);
+        assert_eq!(standings[0].sonneborn_berger, 3.0);
+        assert_eq!(standings[2].sonneborn_berger, 0.25);
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+
+/// Pairs a round of a round robin with the circle method
+/// The last player stays in place while the others rotate, so every player
+/// meets every other one once over the rounds
+///
+/// # Arguments
+/// * `players`: The number of players
+/// * `round`: The index of the round, from 0
+///
+/// # Returns
+/// The white and black player of every board, with black `None` for the
+/// player that gets a bye when the number of players is odd
+///
+pub(crate) fn round_robin_round(players: usize, round: usize) -> Vec<(usize, Option<usize>)> {
+    let slots = players + players % 2;
+    let circle = slots - 1;
+    let player = |slot: usize| (slot < players).then_some(slot);
+
+    let mut boards = Vec::new();
+    let (fixed, first) = (circle, round % circle);
+    if round.is_multiple_of(2) {
+        boards.push((fixed, first));
+    } else {
+        boards.push((first, fixed));
+    }
+    for i in 1..slots / 2 {
+        boards.push(((round + i) % circle, (round + circle - i) % circle));
+    }
+
+    boards
+        .into_iter()
+        .filter_map(|(white, black)| match (player(white), player(black)) {
+            (Some(white), black) => Some((white, black)),
+            (None, Some(black)) => Some((black, None)),
+            (None, None) => None,
+        })
+        .collect()
+}
+
+/// Pairs a Swiss round
+///
+/// The players are ranked by score, and each one is paired with the highest
+/// ranked player left that it hasn't met yet, backtracking when the players
+/// left can't be paired without rematches. If there is no way to avoid them,
+/// the players are paired in order. With an odd number of players, the lowest
+/// ranked player without a bye gets one
+///
+/// # Arguments
+/// * `ranking`: The players, from the highest score to the lowest
+/// * `met`: The pairs of players that already played each other
+/// * `had_bye`: The players that already got a bye
+///
+/// # Returns
+/// The pairs of players, the higher ranked first, and the player that gets the
+/// bye, if any
+///
+pub(crate) fn swiss_round(
+    ranking: &[usize],
+    met: &HashSet<(usize, usize)>,
+    had_bye: &HashSet<usize>,
+) -> (Vec<(usize, usize)>, Option<usize>) {
+    let mut players = ranking.to_vec();
+    let bye = if players.len() % 2 == 1 {
+        let index = players
+            .iter()
+            .rposition(|player| !had_bye.contains(player))
+            .unwrap_or(players.len() - 1);
+        Some(players.remove(index))
+    } else {
+        None
+    };
+
+    let pairs = pair_without_rematches(&players, met)
+        .unwrap_or_else(|| players.chunks(2).map(|pair| (pair[0], pair[1])).collect());
+    (pairs, bye)
+}
+
+/// Pairs the players in rank order without rematches
+///
+/// # Arguments
+/// * `players`: The players left, from the highest ranked
+/// * `met`: The pairs of players that already played each other
+///
+/// # Returns
+/// The pairs, or `None` if every pairing has a rematch
+///
+fn pair_without_rematches(
+    players: &[usize],
+    met: &HashSet<(usize, usize)>,
+) -> Option<Vec<(usize, usize)>> {
+    let Some((&first, rest)) = players.split_first() else {
+        return Some(Vec::new());
+    };
+    for (index, &opponent) in rest.iter().enumerate() {
+        if met.contains(&(first.min(opponent), first.max(opponent))) {
+            continue;
+        }
+        let mut left = rest.to_vec();
+        left.remove(index);
+        if let Some(mut pairs) = pair_without_rematches(&left, met) {
+            pairs.insert(0, (first, opponent));
+            return Some(pairs);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{round_robin_round, swiss_round};
+
+    #[test]
+    fn test_round_robin() {
+        for players in 2..=9 {
+            let rounds = players - 1 + players % 2;
+            let mut met = HashSet::new();
+            for round in 0..rounds {
+                let boards = round_robin_round(players, round);
+                let mut seen = HashSet::new();
+                for (white, black) in boards {
+                    assert!(seen.insert(white));
+                    match black {
+                        Some(black) => {
+                            assert!(seen.insert(black));
+                            assert!(met.insert((white.min(black), white.max(black))));
+                        }
+                        None => assert_eq!(players % 2, 1),
+                    }
+                }
+                assert_eq!(seen.len(), players);
+            }
+            assert_eq!(met.len(), players * (players - 1) / 2);
+        }
+    }
+
+    #[test]
+    fn test_swiss_round() {
+        let met = HashSet::from([(0, 1), (2, 3)]);
+        let (pairs, bye) = swiss_round(&[0, 1, 2, 3], &met, &HashSet::new());
+        assert_eq!(pairs, vec![(0, 2), (1, 3)]);
+        assert_eq!(bye, None);
+
+        // Player 3 is left for the bye because 4 already had one
+        let (pairs, bye) = swiss_round(&[0, 1, 2, 3, 4], &met, &HashSet::from([4]));
+        assert_eq!(pairs, vec![(0, 2), (1, 4)]);
+        assert_eq!(bye, Some(3));
+
+        // Everyone already met, so the players are paired in order
+        let met = HashSet::from([(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+        let (pairs, _) = swiss_round(&[3, 2, 1, 0], &met, &HashSet::new());
+        assert_eq!(pairs, vec![(3, 2), (1, 0)]);
+    }
+}
@@ -0,0 +1,91 @@
+use super::Pairing;
+
+/// The standing of a player in a tournament
+///
+/// # Fields
+/// * `player`: The index of the player
+/// * `name`: The name of the player
+/// * `points`: The score, a point for a win or a bye and half a point for a
+///   draw
+/// * `buchholz`: The sum of the scores of the opponents
+/// * `sonneborn_berger`: The sum of the scores of the defeated opponents and
+///   half the scores of the drawn ones
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub player: usize,
+    pub name: String,
+    pub points: f64,
+    pub buchholz: f64,
+    pub sonneborn_berger: f64,
+}
+
+/// Computes the standings of a tournament from its finished games
+///
+/// # Arguments
+/// * `players`: The names of the players
+/// * `rounds`: The pairings of every round
+///
+/// # Returns
+/// The standings, ordered by points, then by Buchholz, then by
+/// Sonneborn-Berger and then by the order of the players
+///
+pub(crate) fn standings(players: &[String], rounds: &[Vec<Pairing>]) -> Vec<Standing> {
+    let points = scores(players.len(), rounds);
+
+    let mut standings: Vec<Standing> = players
+        .iter()
+        .enumerate()
+        .map(|(player, name)| Standing {
+            player,
+            name: name.clone(),
+            points: points[player],
+            buchholz: 0.0,
+            sonneborn_berger: 0.0,
+        })
+        .collect();
+
+    for pairing in rounds.iter().flatten() {
+        let (Some(black), Some(white_score)) = (pairing.black, pairing.white_score()) else {
+            continue;
+        };
+        for (player, opponent, score) in [
+            (pairing.white, black, white_score),
+            (black, pairing.white, 1.0 - white_score),
+        ] {
+            standings[player].buchholz += points[opponent];
+            standings[player].sonneborn_berger += score * points[opponent];
+        }
+    }
+
+    standings.sort_by(|a, b| {
+        b.points
+            .total_cmp(&a.points)
+            .then(b.buchholz.total_cmp(&a.buchholz))
+            .then(b.sonneborn_berger.total_cmp(&a.sonneborn_berger))
+            .then(a.player.cmp(&b.player))
+    });
+    standings
+}
+
+/// Adds up the points of every player
+///
+/// # Arguments
+/// * `players`: The number of players
+/// * `rounds`: The pairings of every round
+///
+/// # Returns
+/// The points of each player
+///
+pub(crate) fn scores(players: usize, rounds: &[Vec<Pairing>]) -> Vec<f64> {
+    let mut points = vec![0.0; players];
+    for pairing in rounds.iter().flatten() {
+        if let Some(score) = pairing.white_score() {
+            points[pairing.white] += score;
+            if let Some(black) = pairing.black {
+                points[black] += 1.0 - score;
+            }
+        }
+    }
+    points
+}
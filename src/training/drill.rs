@@ -0,0 +1,364 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    constants::{pgn::PgnLine, Color, Move},
+    errors::MoveError,
+    logic::Game,
+};
+
+/// The outcome of a move played in a drill
+///
+/// # Variants
+/// * `Correct`: The move is one of the expected moves
+///     - `reply`: The move played back by the opponent, if any
+/// * `Incorrect`: The move is not one of the expected moves, it is not played
+///     - `expected`: The moves that were expected
+/// * `Completed`: The move is correct and ends the line
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrillResult {
+    Correct { reply: Option<String> },
+    Incorrect { expected: Vec<String> },
+    Completed,
+}
+
+/// A wrong move played in a drill
+///
+/// # Fields
+/// * `fen`: The position where the move was played
+/// * `played`: The move that was played
+/// * `expected`: The moves that were expected
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrillMistake {
+    pub fen: String,
+    pub played: String,
+    pub expected: Vec<String>,
+}
+
+/// The progress of a drill
+///
+/// # Fields
+/// * `correct`: The number of correct moves
+/// * `mistakes`: The number of wrong moves
+/// * `completed`: Whether the end of the line has been reached
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DrillProgress {
+    pub correct: u32,
+    pub mistakes: u32,
+    pub completed: bool,
+}
+
+/// A drill over the move tree of a game, to train openings or lines
+///
+/// The player plays one of the colors and the opponent's moves are played
+/// automatically, following the main line. The moves of the player are checked
+/// against the moves of the tree: by default any variation is accepted, but the
+/// drill can be restricted to the main line
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::Color;
+/// use chess_lab::logic::Game;
+/// use chess_lab::parsing::{parse_pgn_with_options, PgnParseOptions};
+/// use chess_lab::training::{Drill, DrillResult};
+///
+/// let repertoire = parse_pgn_with_options(
+///     "1. e4 e5 (1... c5 2. Nf3) 2. Nf3 Nc6 3. Bb5 *",
+///     &PgnParseOptions::lenient(),
+/// )
+/// .unwrap();
+/// let mut drill = Drill::new(&repertoire, Color::White);
+///
+/// assert_eq!(drill.play("d4"), Ok(DrillResult::Incorrect { expected: vec!["e4".to_string()] }));
+/// assert_eq!(drill.play("e4"), Ok(DrillResult::Correct { reply: Some("e5".to_string()) }));
+/// assert_eq!(drill.play("Nf3"), Ok(DrillResult::Correct { reply: Some("Nc6".to_string()) }));
+/// assert_eq!(drill.play("Bb5"), Ok(DrillResult::Completed));
+///
+/// let progress = drill.progress();
+/// assert_eq!((progress.correct, progress.mistakes, progress.completed), (3, 1, true));
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct Drill {
+    roots: Vec<Rc<RefCell<PgnLine<Move>>>>,
+    start: Game,
+    color: Color,
+    mainline_only: bool,
+    game: Game,
+    node: Option<Rc<RefCell<PgnLine<Move>>>>,
+    correct: u32,
+    mistakes: Vec<DrillMistake>,
+}
+
+impl Drill {
+    /// Creates a drill over the move tree of a game, accepting any variation
+    /// If the player plays black, the first move of the main line is played
+    ///
+    /// # Arguments
+    /// * `game`: The game with the move tree to train
+    /// * `color`: The color played by the player
+    ///
+    /// # Returns
+    /// The drill, at the start position of the game
+    ///
+    pub fn new(game: &Game, color: Color) -> Drill {
+        let mut drill = Drill {
+            roots: game.history.root_nodes().to_vec(),
+            start: game.replay(),
+            color,
+            mainline_only: false,
+            game: game.replay(),
+            node: None,
+            correct: 0,
+            mistakes: Vec::new(),
+        };
+        drill.play_opponent();
+        drill
+    }
+
+    /// Restricts the drill to the main line, variations are not accepted
+    ///
+    /// # Returns
+    /// The drill, only accepting main line moves
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::constants::Color;
+    /// use chess_lab::parsing::{parse_pgn_with_options, PgnParseOptions};
+    /// use chess_lab::training::{Drill, DrillResult};
+    ///
+    /// let repertoire = parse_pgn_with_options(
+    ///     "1. e4 (1. d4) e5 *",
+    ///     &PgnParseOptions::lenient(),
+    /// )
+    /// .unwrap();
+    ///
+    /// let mut drill = Drill::new(&repertoire, Color::White).with_mainline_only();
+    /// assert_eq!(drill.play("d4"), Ok(DrillResult::Incorrect { expected: vec!["e4".to_string()] }));
+    ///
+    /// let mut drill = Drill::new(&repertoire, Color::White);
+    /// assert_eq!(drill.play("d4"), Ok(DrillResult::Completed));
+    /// ```
+    ///
+    pub fn with_mainline_only(mut self) -> Drill {
+        self.mainline_only = true;
+        self
+    }
+
+    /// Plays a move of the player, checking it against the expected moves
+    /// A correct move is played along with the reply of the opponent, a wrong
+    /// one is recorded as a mistake and not played
+    ///
+    /// # Arguments
+    /// * `move_str`: The move in algebraic notation
+    ///
+    /// # Returns
+    /// The outcome of the move, or an error if the move is not legal in the
+    /// current position or the line is already finished
+    ///
+    pub fn play(&mut self, move_str: &str) -> Result<DrillResult, MoveError> {
+        let expected = self.expected_nodes();
+        if expected.is_empty() {
+            return Err(MoveError::Finished {
+                san: move_str.to_string(),
+            });
+        }
+
+        let mut attempt = Game::from_fen(&self.game.fen());
+        attempt.move_piece(move_str)?;
+        let played = attempt.history.get_move().unwrap();
+
+        let found = expected.iter().find(|node| {
+            let mov = &node.borrow().mov;
+            mov.from == played.from && mov.to == played.to && mov.move_type == played.move_type
+        });
+        match found {
+            Some(node) => {
+                self.advance(node);
+                self.correct += 1;
+                match self.play_opponent() {
+                    Some(reply) if !self.expected_nodes().is_empty() => {
+                        Ok(DrillResult::Correct { reply: Some(reply) })
+                    }
+                    None if !self.expected_nodes().is_empty() => {
+                        Ok(DrillResult::Correct { reply: None })
+                    }
+                    _ => Ok(DrillResult::Completed),
+                }
+            }
+            None => {
+                let expected = self.expected_moves();
+                self.mistakes.push(DrillMistake {
+                    fen: self.game.fen(),
+                    played: played.to_string(),
+                    expected: expected.clone(),
+                });
+                Ok(DrillResult::Incorrect { expected })
+            }
+        }
+    }
+
+    /// Returns the moves the player is expected to play
+    ///
+    /// # Returns
+    /// The expected moves in algebraic notation, empty if the line is completed
+    ///
+    pub fn expected_moves(&self) -> Vec<String> {
+        self.expected_nodes()
+            .iter()
+            .map(|node| node.borrow().san())
+            .collect()
+    }
+
+    /// Returns the progress of the drill
+    ///
+    /// # Returns
+    /// The number of correct and wrong moves and whether the line is completed
+    ///
+    pub fn progress(&self) -> DrillProgress {
+        DrillProgress {
+            correct: self.correct,
+            mistakes: self.mistakes.len() as u32,
+            completed: self.expected_nodes().is_empty(),
+        }
+    }
+
+    /// Returns the wrong moves played so far
+    ///
+    /// # Returns
+    /// The mistakes, in the order they were played
+    ///
+    pub fn mistakes(&self) -> &[DrillMistake] {
+        &self.mistakes
+    }
+
+    /// Returns the game being played in the drill
+    ///
+    /// # Returns
+    /// The game, at the current position of the drill
+    ///
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Restarts the drill from the start position, clearing the progress
+    ///
+    pub fn restart(&mut self) {
+        self.game = self.start.clone();
+        self.node = None;
+        self.correct = 0;
+        self.mistakes.clear();
+        self.play_opponent();
+    }
+
+    /// Returns the nodes of the tree that can follow the current position
+    ///
+    /// # Returns
+    /// The following nodes, only the main line one if the drill is restricted
+    /// to it
+    ///
+    fn expected_nodes(&self) -> Vec<Rc<RefCell<PgnLine<Move>>>> {
+        let mut nodes = match &self.node {
+            Some(node) => node.borrow().lines.clone(),
+            None => self.roots.clone(),
+        };
+        if self.mainline_only {
+            nodes.truncate(1);
+        }
+        nodes
+    }
+
+    /// Plays the move of a node and moves the drill to it
+    ///
+    /// # Arguments
+    /// * `node`: The node to move to
+    ///
+    fn advance(&mut self, node: &Rc<RefCell<PgnLine<Move>>>) {
+        let mov = node.borrow().san();
+        self.game
+            .move_piece(&mov)
+            .expect("The moves of the tree are legal");
+        self.node = Some(Rc::clone(node));
+    }
+
+    /// Plays the main line move of the opponent, if it is the opponent's turn
+    ///
+    /// # Returns
+    /// The move played, if any
+    ///
+    fn play_opponent(&mut self) -> Option<String> {
+        let turn = if self.game.is_white_turn {
+            Color::White
+        } else {
+            Color::Black
+        };
+        if turn == self.color {
+            return None;
+        }
+        let node = self.expected_nodes().into_iter().next()?;
+        self.advance(&node);
+        let reply = node.borrow().san();
+        Some(reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Drill, DrillResult};
+    use crate::{
+        constants::Color,
+        errors::MoveError,
+        parsing::{parse_pgn_with_options, PgnParseOptions},
+    };
+
+    #[test]
+    fn test_drill_black() {
+        let repertoire = parse_pgn_with_options(
+            "1. e4 c5 (1... e5 2. Nf3) 2. Nf3 d6 *",
+            &PgnParseOptions::lenient(),
+        )
+        .unwrap();
+        let mut drill = Drill::new(&repertoire, Color::Black);
+
+        assert_eq!(drill.expected_moves(), vec!["c5", "e5"]);
+        assert_eq!(
+            drill.play("e5"),
+            Ok(DrillResult::Completed),
+            "the variation ends with the opponent's reply"
+        );
+        assert_eq!(
+            drill.game().fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+        );
+        assert_eq!(
+            drill.play("Nc6"),
+            Err(MoveError::Finished {
+                san: "Nc6".to_string()
+            })
+        );
+
+        drill.restart();
+        assert!(drill.play("Ke2").is_err());
+        assert_eq!(
+            drill.play("c6"),
+            Ok(DrillResult::Incorrect {
+                expected: vec!["c5".to_string(), "e5".to_string()]
+            })
+        );
+        assert_eq!(drill.mistakes()[0].played, "c6");
+        assert_eq!(
+            drill.play("c5"),
+            Ok(DrillResult::Correct {
+                reply: Some("Nf3".to_string())
+            })
+        );
+        assert_eq!(drill.play("d6"), Ok(DrillResult::Completed));
+
+        let progress = drill.progress();
+        assert_eq!(progress.correct, 2);
+        assert_eq!(progress.mistakes, 1);
+        assert!(progress.completed);
+    }
+}
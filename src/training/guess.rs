@@ -0,0 +1,290 @@
+use crate::{analysis::Engine, constants::Move, errors::MoveError, logic::Game};
+
+/// The points given to a guess that matches the played move or the best
+/// engine move
+const MAX_POINTS: u32 = 10;
+
+/// The loss, in centipawns, that costs one point
+const CENTIPAWNS_PER_POINT: i32 = 25;
+
+/// The number of engine moves a guess is compared against
+const ENGINE_MOVES: usize = 3;
+
+/// The score of a guess in a guess-the-move session
+///
+/// # Fields
+/// * `guess`: The guessed move
+/// * `played`: The move played in the game
+/// * `matched_played`: Whether the guess is the move played in the game
+/// * `engine_rank`: The position of the guess among the engine moves, if any
+/// * `loss`: The centipawns lost by the guess compared to the best engine
+///   move, if the guess is one of the engine moves
+/// * `points`: The points given to the guess
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuessScore {
+    pub guess: String,
+    pub played: String,
+    pub matched_played: bool,
+    pub engine_rank: Option<usize>,
+    pub loss: Option<i32>,
+    pub points: u32,
+}
+
+/// A guess-the-move session over the main line of a game
+///
+/// Created by `guess_the_move`, it goes through the main line position by
+/// position: at each one a move is guessed and scored, then the move of the
+/// game is played
+///
+pub struct GuessTheMove<'a, E: Engine> {
+    engine: &'a mut E,
+    moves: Vec<Move>,
+    game: Game,
+    scores: Vec<GuessScore>,
+}
+
+/// Starts a guess-the-move session ("solitaire chess") over the main line of a game
+///
+/// A guess that matches the played move gets the maximum points. A guess that
+/// matches one of the top engine moves gets the maximum points minus one point
+/// for every 25 centipawns it loses against the best engine move. Any other
+/// move gets no points
+///
+/// # Arguments
+/// * `game`: The game to guess the moves of
+/// * `engine`: The engine used to score the guesses
+///
+/// # Returns
+/// The session, at the start position of the game
+///
+/// # Example
+/// ```
+/// use chess_lab::analysis::{Engine, EngineMove};
+/// use chess_lab::logic::Game;
+/// use chess_lab::training::guess_the_move;
+///
+/// struct Book;
+///
+/// impl Engine for Book {
+///     fn best_moves(&mut self, _game: &Game, _count: usize) -> Vec<EngineMove> {
+///         vec![
+///             EngineMove { mov: String::from("e4"), score: 30 },
+///             EngineMove { mov: String::from("d4"), score: 5 },
+///         ]
+///     }
+/// }
+///
+/// let mut game = Game::default();
+/// game.move_piece("e4").unwrap();
+///
+/// let mut engine = Book;
+/// let mut session = guess_the_move(&game, &mut engine);
+///
+/// let score = session.guess("d4").unwrap();
+/// assert_eq!((score.engine_rank, score.loss, score.points), (Some(1), Some(25), 9));
+/// assert!(session.is_finished());
+/// assert_eq!(session.total_points(), 9);
+/// ```
+///
+pub fn guess_the_move<'a, E: Engine>(game: &Game, engine: &'a mut E) -> GuessTheMove<'a, E> {
+    let mut moves = Vec::new();
+    let mut node = game.history.root_nodes().first().cloned();
+    while let Some(line) = node {
+        moves.push(line.borrow().mov.clone());
+        node = line.borrow().lines.first().cloned();
+    }
+
+    GuessTheMove {
+        engine,
+        moves,
+        game: game.replay(),
+        scores: Vec::new(),
+    }
+}
+
+impl<E: Engine> GuessTheMove<'_, E> {
+    /// Returns the position where the next move has to be guessed
+    ///
+    /// # Returns
+    /// The game at the current position
+    ///
+    pub fn position(&self) -> &Game {
+        &self.game
+    }
+
+    /// Returns whether every move of the main line has been guessed
+    ///
+    /// # Returns
+    /// Whether the session is finished
+    ///
+    pub fn is_finished(&self) -> bool {
+        self.scores.len() >= self.moves.len()
+    }
+
+    /// Scores a guess at the current position, then plays the move of the game
+    ///
+    /// # Arguments
+    /// * `move_str`: The guessed move in algebraic notation
+    ///
+    /// # Returns
+    /// The score of the guess, or an error if the move is not legal or the
+    /// session is finished
+    ///
+    pub fn guess(&mut self, move_str: &str) -> Result<GuessScore, MoveError> {
+        let Some(played) = self.moves.get(self.scores.len()).cloned() else {
+            return Err(MoveError::Finished {
+                san: move_str.to_string(),
+            });
+        };
+        let guess = resolve(&self.game, move_str)?;
+
+        let engine_moves = self.engine.best_moves(&self.game, ENGINE_MOVES);
+        let best = engine_moves.first().map(|engine_move| engine_move.score);
+        let engine_rank = engine_moves.iter().position(|engine_move| {
+            resolve(&self.game, &engine_move.mov).is_ok_and(|mov| same_move(&mov, &guess))
+        });
+        let loss = engine_rank
+            .zip(best)
+            .map(|(rank, best)| (best - engine_moves[rank].score).max(0));
+
+        let matched_played = same_move(&played, &guess);
+        let points = if matched_played {
+            MAX_POINTS
+        } else {
+            loss.map_or(0, |loss| {
+                MAX_POINTS.saturating_sub((loss / CENTIPAWNS_PER_POINT) as u32)
+            })
+        };
+
+        let score = GuessScore {
+            guess: guess.to_string(),
+            played: played.to_string(),
+            matched_played,
+            engine_rank,
+            loss,
+            points,
+        };
+        self.game
+            .move_piece(&played.to_string())
+            .expect("The moves of the game are legal");
+        self.scores.push(score.clone());
+        Ok(score)
+    }
+
+    /// Returns the scores of the guesses made so far
+    ///
+    /// # Returns
+    /// The scores, in the order of the moves
+    ///
+    pub fn scores(&self) -> &[GuessScore] {
+        &self.scores
+    }
+
+    /// Returns the sum of the points of the guesses made so far
+    ///
+    /// # Returns
+    /// The total points
+    ///
+    pub fn total_points(&self) -> u32 {
+        self.scores.iter().map(|score| score.points).sum()
+    }
+}
+
+/// Plays a move on a copy of a position to get its full description
+///
+/// # Arguments
+/// * `game`: The game at the position
+/// * `move_str`: The move in algebraic notation
+///
+/// # Returns
+/// The move, or an error if it is not legal
+///
+fn resolve(game: &Game, move_str: &str) -> Result<Move, MoveError> {
+    let mut game = Game::from_fen(&game.fen());
+    game.move_piece(move_str)?;
+    Ok(game.history.get_move().unwrap())
+}
+
+/// Compares two moves of the same position
+///
+/// # Arguments
+/// * `a`: The first move
+/// * `b`: The second move
+///
+/// # Returns
+/// Whether both moves go from and to the same squares the same way
+///
+fn same_move(a: &Move, b: &Move) -> bool {
+    a.from == b.from && a.to == b.to && a.move_type == b.move_type
+}
+
+#[cfg(test)]
+mod tests {
+    use super::guess_the_move;
+    use crate::{
+        analysis::{Engine, EngineMove},
+        errors::MoveError,
+        logic::Game,
+    };
+
+    struct Fixed(Vec<EngineMove>);
+
+    impl Engine for Fixed {
+        fn best_moves(&mut self, _game: &Game, count: usize) -> Vec<EngineMove> {
+            self.0.iter().take(count).cloned().collect()
+        }
+    }
+
+    #[test]
+    fn test_guess_the_move() {
+        let mut game = Game::default();
+        game.move_piece("e4").unwrap();
+        game.move_piece("c5").unwrap();
+        game.undo();
+        game.move_piece("e5").unwrap();
+
+        let mut engine = Fixed(vec![
+            EngineMove {
+                mov: String::from("c5"),
+                score: 20,
+            },
+            EngineMove {
+                mov: String::from("e5"),
+                score: 10,
+            },
+            EngineMove {
+                mov: String::from("Nc6"),
+                score: -120,
+            },
+        ]);
+        let mut session = guess_the_move(&game, &mut engine);
+
+        let score = session.guess("e4").unwrap();
+        assert!(score.matched_played);
+        assert_eq!(score.engine_rank, None);
+        assert_eq!(score.points, 10);
+
+        assert!(session.guess("Ke2").is_err());
+        let score = session.guess("Nc6").unwrap();
+        assert_eq!(
+            score.played, "c5",
+            "the first move of the game stays the main line"
+        );
+        assert_eq!((score.engine_rank, score.loss), (Some(2), Some(140)));
+        assert_eq!(score.points, 5);
+
+        assert!(session.is_finished());
+        assert_eq!(session.total_points(), 15);
+        assert_eq!(
+            session.position().fen(),
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"
+        );
+        assert_eq!(
+            session.guess("Nf3"),
+            Err(MoveError::Finished {
+                san: String::from("Nf3")
+            })
+        );
+    }
+}
@@ -0,0 +1,6 @@
+mod drill;
+mod guess;
+pub mod srs;
+
+pub use drill::*;
+pub use guess::*;
@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::logic::Game;
+
+/// The ease factor of a new card
+const INITIAL_EASE: f64 = 2.5;
+
+/// The lowest ease factor a card can reach
+const MIN_EASE: f64 = 1.3;
+
+/// The highest review grade
+const MAX_GRADE: u8 = 5;
+
+/// The lowest review grade that counts as remembered
+const PASS_GRADE: u8 = 3;
+
+/// A position to review, with its spaced repetition state
+///
+/// # Fields
+/// * `hash`: The hash of the position (`Game::position_hash`)
+/// * `fen`: The FEN of the position
+/// * `repetitions`: The number of reviews in a row that were remembered
+/// * `interval`: The number of days until the next review
+/// * `ease`: The ease factor, how fast the interval grows
+/// * `due`: The day of the next review
+///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Card {
+    pub hash: u64,
+    pub fen: String,
+    pub repetitions: u32,
+    pub interval: u32,
+    pub ease: f64,
+    pub due: u64,
+}
+
+impl Card {
+    /// Creates a card that is due on the given day
+    ///
+    /// # Arguments
+    /// * `game`: The game at the position to review
+    /// * `today`: The current day
+    ///
+    /// # Returns
+    /// A new card
+    ///
+    pub fn new(game: &Game, today: u64) -> Card {
+        Card {
+            hash: game.position_hash(),
+            fen: game.fen(),
+            repetitions: 0,
+            interval: 0,
+            ease: INITIAL_EASE,
+            due: today,
+        }
+    }
+
+    /// Updates the card after a review, following the SM-2 algorithm
+    ///
+    /// # Arguments
+    /// * `grade`: How well the position was remembered, from 0 (blackout) to
+    ///   5 (perfect), greater values count as 5
+    /// * `today`: The day of the review
+    ///
+    pub fn review(&mut self, grade: u8, today: u64) {
+        let grade = grade.min(MAX_GRADE);
+        if grade >= PASS_GRADE {
+            self.interval = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval as f64 * self.ease).round() as u32,
+            };
+            self.repetitions += 1;
+        } else {
+            self.repetitions = 0;
+            self.interval = 1;
+        }
+
+        let miss = (MAX_GRADE - grade) as f64;
+        self.ease = (self.ease + 0.1 - miss * (0.08 + miss * 0.02)).max(MIN_EASE);
+        self.due = today + self.interval as u64;
+    }
+
+    /// Returns whether the card has to be reviewed
+    ///
+    /// # Arguments
+    /// * `today`: The current day
+    ///
+    /// # Returns
+    /// Whether the card is due on or before the given day
+    ///
+    pub fn is_due(&self, today: u64) -> bool {
+        self.due <= today
+    }
+}
+
+/// A review queue of positions scheduled with spaced repetition (SM-2)
+///
+/// Days are plain numbers chosen by the caller (for example, days since the
+/// Unix epoch), so the queue doesn't depend on the system clock. With the
+/// `serde` feature the queue can be serialized to persist it between sessions
+///
+/// # Example
+/// ```
+/// use chess_lab::logic::Game;
+/// use chess_lab::training::srs::ReviewQueue;
+///
+/// let mut queue = ReviewQueue::new();
+/// let mut game = Game::default();
+/// game.move_piece("e4").unwrap();
+///
+/// let hash = queue.add(&game, 100);
+/// assert_eq!(queue.due(100).len(), 1);
+///
+/// queue.review(hash, 5, 100);
+/// assert!(queue.due(100).is_empty());
+/// assert_eq!(queue.due(101)[0].fen, game.fen());
+///
+/// queue.review(hash, 4, 101);
+/// assert_eq!(queue.get(hash).unwrap().due, 107);
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ReviewQueue {
+    cards: HashMap<u64, Card>,
+}
+
+impl ReviewQueue {
+    /// Creates an empty review queue
+    ///
+    /// # Returns
+    /// The review queue
+    ///
+    pub fn new() -> ReviewQueue {
+        ReviewQueue::default()
+    }
+
+    /// Adds the current position of a game to the queue, due on the given day
+    /// Does nothing if the position is already in the queue
+    ///
+    /// # Arguments
+    /// * `game`: The game at the position to add
+    /// * `today`: The current day
+    ///
+    /// # Returns
+    /// The hash of the position, the key of its card
+    ///
+    pub fn add(&mut self, game: &Game, today: u64) -> u64 {
+        let hash = game.position_hash();
+        self.cards
+            .entry(hash)
+            .or_insert_with(|| Card::new(game, today));
+        hash
+    }
+
+    /// Reviews a position, scheduling its next review
+    ///
+    /// # Arguments
+    /// * `hash`: The hash of the position
+    /// * `grade`: How well the position was remembered, from 0 to 5
+    /// * `today`: The day of the review
+    ///
+    /// # Returns
+    /// The updated card, or `None` if the position is not in the queue
+    ///
+    pub fn review(&mut self, hash: u64, grade: u8, today: u64) -> Option<&Card> {
+        let card = self.cards.get_mut(&hash)?;
+        card.review(grade, today);
+        Some(card)
+    }
+
+    /// Returns the positions that have to be reviewed
+    ///
+    /// # Arguments
+    /// * `today`: The current day
+    ///
+    /// # Returns
+    /// The due cards, the most overdue first
+    ///
+    pub fn due(&self, today: u64) -> Vec<&Card> {
+        let mut due: Vec<&Card> = self
+            .cards
+            .values()
+            .filter(|card| card.is_due(today))
+            .collect();
+        due.sort_by_key(|card| (card.due, card.hash));
+        due
+    }
+
+    /// Gets the card of a position
+    ///
+    /// # Arguments
+    /// * `hash`: The hash of the position
+    ///
+    /// # Returns
+    /// The card, if the position is in the queue
+    ///
+    pub fn get(&self, hash: u64) -> Option<&Card> {
+        self.cards.get(&hash)
+    }
+
+    /// Removes a position from the queue
+    ///
+    /// # Arguments
+    /// * `hash`: The hash of the position
+    ///
+    /// # Returns
+    /// The card of the position, if it was in the queue
+    ///
+    pub fn remove(&mut self, hash: u64) -> Option<Card> {
+        self.cards.remove(&hash)
+    }
+
+    /// Returns the number of positions in the queue
+    ///
+    /// # Returns
+    /// The number of cards
+    ///
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    /// Returns whether the queue has no positions
+    ///
+    /// # Returns
+    /// Whether the queue is empty
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReviewQueue, MIN_EASE};
+    use crate::logic::Game;
+
+    #[test]
+    fn test_review_schedule() {
+        let mut queue = ReviewQueue::new();
+        let game = Game::default();
+        let hash = queue.add(&game, 0);
+        assert_eq!(queue.add(&game, 10), hash);
+        assert_eq!(queue.len(), 1);
+
+        let intervals: Vec<u32> = [5, 5, 5]
+            .iter()
+            .enumerate()
+            .map(|(day, grade)| queue.review(hash, *grade, day as u64).unwrap().interval)
+            .collect();
+        assert_eq!(intervals, vec![1, 6, 16]);
+
+        let card = queue.review(hash, 0, 3).unwrap();
+        assert_eq!((card.repetitions, card.interval, card.due), (0, 1, 4));
+
+        for _ in 0..10 {
+            queue.review(hash, 1, 4);
+        }
+        assert_eq!(queue.get(hash).unwrap().ease, MIN_EASE);
+        assert!(queue.remove(hash).is_some());
+        assert!(queue.is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_review_queue_serde() {
+        let mut queue = ReviewQueue::new();
+        let hash = queue.add(&Game::default(), 7);
+        queue.review(hash, 4, 7);
+
+        let json = serde_json::to_string(&queue).unwrap();
+        let restored: ReviewQueue = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, queue);
+    }
+}
@@ -0,0 +1,171 @@
+use crate::constants::Position;
+
+/// The eight directions a queen moves in, as steps of column and row
+pub const DIRECTIONS: [(i8, i8); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+];
+
+/// The squares from every square to the edge of the board, in every
+/// direction
+static RAYS: [[u64; 8]; 64] = rays();
+
+/// The squares strictly between every pair of squares on the same line
+static BETWEEN: [[u64; 64]; 64] = between_squares();
+
+/// Returns the squares from a position to the edge of the board
+///
+/// # Arguments
+/// * `from`: The position the ray starts at, which is not part of it
+/// * `direction`: One of the [`DIRECTIONS`]
+///
+/// # Returns
+/// The bitboard of the ray, empty if the direction is not a single step
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::Position;
+/// use chess_lab::utils::rays::ray;
+///
+/// let squares = Position::from_bitboard(ray(&Position::from_string("f6"), (1, 1)));
+/// let squares: Vec<String> = squares.iter().map(|pos| pos.to_string()).collect();
+///
+/// assert_eq!(squares, vec!["g7", "h8"]);
+/// assert_eq!(ray(&Position::from_string("f6"), (2, 1)), 0);
+/// ```
+///
+pub fn ray(from: &Position, direction: (i8, i8)) -> u64 {
+    match DIRECTIONS.iter().position(|step| *step == direction) {
+        Some(index) => RAYS[square(from)][index],
+        None => 0,
+    }
+}
+
+/// Returns the squares strictly between two positions on the same rank,
+/// file or diagonal
+///
+/// # Arguments
+/// * `a`: One of the positions
+/// * `b`: The other position
+///
+/// # Returns
+/// The bitboard of the squares between them, empty if they are not on the
+/// same line or are next to each other
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::Position;
+/// use chess_lab::utils::rays::between;
+///
+/// let a1 = Position::from_string("a1");
+/// let d4 = Position::from_string("d4");
+///
+/// assert_eq!(between(&a1, &d4), 0x40200);
+/// assert_eq!(between(&d4, &a1), 0x40200);
+/// assert_eq!(between(&a1, &Position::from_string("b3")), 0);
+/// ```
+///
+pub fn between(a: &Position, b: &Position) -> u64 {
+    BETWEEN[square(a)][square(b)]
+}
+
+/// Returns the index of a position in the tables
+///
+/// # Arguments
+/// * `pos`: The position
+///
+/// # Returns
+/// The index, the same as the bit of the position in a bitboard
+///
+fn square(pos: &Position) -> usize {
+    (pos.row * 8 + pos.col) as usize
+}
+
+/// Builds the table of rays
+///
+/// # Returns
+/// The rays of every square, by direction
+///
+const fn rays() -> [[u64; 8]; 64] {
+    let mut table = [[0; 8]; 64];
+    let mut from = 0;
+    while from < 64 {
+        let mut index = 0;
+        while index < 8 {
+            let (col_step, row_step) = DIRECTIONS[index];
+            let mut col = (from % 8) as i8 + col_step;
+            let mut row = (from / 8) as i8 + row_step;
+            while col >= 0 && col < 8 && row >= 0 && row < 8 {
+                table[from][index] |= 1 << (row * 8 + col);
+                col += col_step;
+                row += row_step;
+            }
+            index += 1;
+        }
+        from += 1;
+    }
+    table
+}
+
+/// Builds the table of the squares between two squares
+///
+/// # Returns
+/// The squares between every pair of squares
+///
+const fn between_squares() -> [[u64; 64]; 64] {
+    let mut table = [[0; 64]; 64];
+    let mut from = 0;
+    while from < 64 {
+        let mut index = 0;
+        while index < 8 {
+            let (col_step, row_step) = DIRECTIONS[index];
+            let mut col = (from % 8) as i8 + col_step;
+            let mut row = (from / 8) as i8 + row_step;
+            let mut squares = 0;
+            while col >= 0 && col < 8 && row >= 0 && row < 8 {
+                let to = (row * 8 + col) as usize;
+                table[from][to] = squares;
+                squares |= 1 << to;
+                col += col_step;
+                row += row_step;
+            }
+            index += 1;
+        }
+        from += 1;
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{between, ray, DIRECTIONS};
+    use crate::constants::Position;
+
+    #[test]
+    fn test_rays() {
+        let e4 = Position::from_string("e4");
+        let squares: usize = DIRECTIONS
+            .iter()
+            .map(|direction| ray(&e4, *direction).count_ones() as usize)
+            .sum();
+        assert_eq!(squares, 27);
+
+        // The squares between two squares are on the ray from one to the other
+        for to in ["e8", "h7", "h4", "h1", "e1", "b1", "a4", "a8"] {
+            let to = Position::from_string(to);
+            let direction = e4.direction(&to);
+            assert_eq!(
+                between(&e4, &to),
+                ray(&e4, direction) & !ray(&to, direction) & !to.to_bitboard()
+            );
+        }
+        assert_eq!(between(&e4, &Position::from_string("f6")), 0);
+        assert_eq!(between(&e4, &e4), 0);
+    }
+}
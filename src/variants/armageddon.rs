@@ -0,0 +1,231 @@
+use crate::{
+    constants::{Color, GameStatus, PgnVariant, Variant, WinReason},
+    errors::MoveError,
+    logic::Game,
+};
+
+/// The `Termination` tag of a drawn armageddon game
+const DRAW_ODDS_TERMINATION: &str = "Armageddon: draw counts as a win for Black";
+
+/// An armageddon game: a standard game where Black has less time on the clock
+/// but wins if the game is drawn
+///
+/// The wrapped game keeps the result played on the board, while
+/// [`status`](Armageddon::status) and the `Result` and `Termination` tags
+/// follow the armageddon rule
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::{GameStatus, Variant, WinReason};
+/// use chess_lab::variants::Armageddon;
+///
+/// let mut game = Armageddon::default();
+/// game.move_piece("e4").unwrap();
+/// game.move_piece("e5").unwrap();
+/// game.set_draw_by_agreement();
+///
+/// assert_eq!(game.status(), GameStatus::BlackWins(WinReason::DrawOdds));
+/// assert_eq!(
+///     game.pgn(),
+///     "[Result \"0-1\"]\n[WhiteClock \"300\"]\n[BlackClock \"240\"]\n[Termination \"Armageddon: draw counts as a win for Black\"]\n1. e4 e5 0-1"
+/// );
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct Armageddon {
+    game: Game,
+}
+
+impl Default for Armageddon {
+    /// Creates an armageddon game from the starting position, with 5 minutes
+    /// for White and 4 for Black
+    ///
+    /// # Returns
+    /// The armageddon game
+    ///
+    fn default() -> Armageddon {
+        Armageddon::new(Game::default(), "300", "240")
+    }
+}
+
+impl Armageddon {
+    /// Creates an armageddon game
+    ///
+    /// # Arguments
+    /// * `game`: The game to play
+    /// * `white_clock`: The time control of White, like `300+2`, written in
+    ///   the `WhiteClock` tag
+    /// * `black_clock`: The time control of Black, written in the
+    ///   `BlackClock` tag
+    ///
+    /// # Returns
+    /// The armageddon game
+    ///
+    pub fn new(game: Game, white_clock: &str, black_clock: &str) -> Armageddon {
+        let mut armageddon = Armageddon { game };
+        armageddon.game.history.white_clock = Some(white_clock.to_string());
+        armageddon.game.history.black_clock = Some(black_clock.to_string());
+        armageddon.update_tags();
+        armageddon
+    }
+
+    /// Returns the status of the game under the armageddon rule
+    ///
+    /// # Returns
+    /// The status of the game, with a draw turned into a win for Black
+    ///
+    pub fn status(&self) -> GameStatus {
+        armageddon_status(self.game.game_status)
+    }
+
+    /// Ends the game by resignation
+    ///
+    /// # Arguments
+    /// * `color`: The color of the player that resigns
+    ///
+    pub fn resign(&mut self, color: Color) {
+        self.game.resign(color);
+        self.update_tags();
+    }
+
+    /// Ends the game on time
+    ///
+    /// # Arguments
+    /// * `color`: The color of the player that lost on time
+    ///
+    pub fn set_lost_in_time(&mut self, color: Color) {
+        self.game.set_lost_in_time(color);
+        self.update_tags();
+    }
+
+    /// Ends the game by a draw due to agreement, which Black wins
+    pub fn set_draw_by_agreement(&mut self) {
+        self.game.set_draw_by_agreement();
+        self.update_tags();
+    }
+
+    /// Returns the wrapped game
+    ///
+    /// # Returns
+    /// The game, with the tags of the armageddon result
+    ///
+    pub fn into_game(self) -> Game {
+        self.game
+    }
+
+    /// Writes the `Result` and `Termination` tags of a drawn game, or clears
+    /// them once the draw is taken back
+    fn update_tags(&mut self) {
+        let history = &mut self.game.history;
+        if let GameStatus::Draw(_) = self.game.game_status {
+            history.result = Some(String::from("0-1"));
+            history.termination = Some(String::from(DRAW_ODDS_TERMINATION));
+        } else if history.termination.as_deref() == Some(DRAW_ODDS_TERMINATION) {
+            history.result = None;
+            history.termination = None;
+        }
+    }
+}
+
+impl Variant for Armageddon {
+    fn game(&self) -> &Game {
+        &self.game
+    }
+
+    fn name(&self) -> &str {
+        "Armageddon"
+    }
+
+    fn move_piece(&mut self, move_str: &str) -> Result<GameStatus, MoveError> {
+        let status = self.game.move_piece(move_str)?;
+        self.update_tags();
+        Ok(armageddon_status(status))
+    }
+
+    fn undo(&mut self) {
+        self.game.undo();
+        self.update_tags();
+    }
+
+    fn redo(&mut self) {
+        self.game.redo();
+        self.update_tags();
+    }
+}
+
+impl PgnVariant for Armageddon {
+    const TAGS: &'static [&'static str] = &["Armageddon"];
+
+    /// Creates an armageddon game, keeping the clocks of the `WhiteClock` and
+    /// `BlackClock` tags, 5 and 4 minutes if they are missing
+    fn from_game(game: Game) -> Armageddon {
+        let white_clock = game.history.white_clock.clone();
+        let black_clock = game.history.black_clock.clone();
+        Armageddon::new(
+            game,
+            white_clock.as_deref().unwrap_or("300"),
+            black_clock.as_deref().unwrap_or("240"),
+        )
+    }
+}
+
+/// Maps the status of a game to the armageddon rule
+///
+/// # Arguments
+/// * `status`: The status of the game
+///
+/// # Returns
+/// The same status, except for a draw, which is a win for Black
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::{DrawReason, GameStatus, WinReason};
+/// use chess_lab::variants::armageddon_status;
+///
+/// assert_eq!(
+///     armageddon_status(GameStatus::Draw(DrawReason::Stalemate)),
+///     GameStatus::BlackWins(WinReason::DrawOdds)
+/// );
+/// assert_eq!(armageddon_status(GameStatus::InProgress), GameStatus::InProgress);
+/// ```
+///
+pub fn armageddon_status(status: GameStatus) -> GameStatus {
+    match status {
+        GameStatus::Draw(_) => GameStatus::BlackWins(WinReason::DrawOdds),
+        status => status,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Armageddon;
+    use crate::{
+        constants::{DrawReason, GameStatus, Variant, WinReason},
+        logic::Game,
+    };
+
+    #[test]
+    fn test_armageddon_repetition() {
+        let mut game = Armageddon::new(Game::default(), "600+2", "480+2");
+        for mov in ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1"] {
+            assert_eq!(game.move_piece(mov), Ok(GameStatus::InProgress));
+        }
+        assert_eq!(
+            game.move_piece("Ng8"),
+            Ok(GameStatus::BlackWins(WinReason::DrawOdds))
+        );
+        assert_eq!(
+            game.game().game_status,
+            GameStatus::Draw(DrawReason::ThreefoldRepetition)
+        );
+        assert!(game.pgn().ends_with("4. Ng1 Ng8 0-1"));
+
+        game.undo();
+        assert_eq!(game.status(), GameStatus::InProgress);
+        assert_eq!(game.game().history.result, None);
+        assert_eq!(game.game().history.termination, None);
+        assert!(game
+            .pgn()
+            .starts_with("[WhiteClock \"600+2\"]\n[BlackClock \"480+2\"]\n"));
+    }
+}
@@ -0,0 +1,346 @@
+use std::{
+    fmt::{Display, Error, Formatter},
+    hash::{BuildHasher, Hasher, RandomState},
+};
+
+use crate::constants::PieceType;
+
+/// The placements of the two knights on the five squares left after placing
+/// the bishops and the queen, in the order of the Scharnagl numbering
+const KNIGHTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+/// A Chess960 starting position: the pieces of the back rank, from the a-file
+/// to the h-file. The bishops are on squares of opposite colors and the king
+/// is between the rooks
+///
+/// # Example
+/// ```
+/// use chess_lab::variants::Chess960;
+///
+/// let position = Chess960::from_position_number(518).unwrap();
+/// assert_eq!(position.to_string(), "RNBQKBNR");
+/// assert_eq!(position.position_number(), 518);
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chess960 {
+    back_rank: [PieceType; 8],
+}
+
+impl Chess960 {
+    /// Creates the starting position with the given Scharnagl number
+    ///
+    /// # Arguments
+    /// * `n`: The number of the position, from 0 to 959 (518 is the standard
+    ///   starting position)
+    ///
+    /// # Returns
+    /// The starting position, or `None` if the number is out of range
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::variants::Chess960;
+    ///
+    /// assert_eq!(Chess960::from_position_number(0).unwrap().to_string(), "BBQNNRKR");
+    /// assert_eq!(Chess960::from_position_number(959).unwrap().to_string(), "RKRNNQBB");
+    /// assert_eq!(Chess960::from_position_number(960), None);
+    /// ```
+    ///
+    pub fn from_position_number(n: u16) -> Option<Chess960> {
+        if n >= 960 {
+            return None;
+        }
+        let n = n as usize;
+        let mut back_rank: [Option<PieceType>; 8] = [None; 8];
+
+        // The light-squared bishop goes on b, d, f or h and the dark-squared
+        // one on a, c, e or g
+        back_rank[n % 4 * 2 + 1] = Some(PieceType::Bishop);
+        back_rank[n / 4 % 4 * 2] = Some(PieceType::Bishop);
+
+        let n = n / 16;
+        let empty = |back_rank: &[Option<PieceType>; 8]| -> Vec<usize> {
+            (0..8).filter(|col| back_rank[*col].is_none()).collect()
+        };
+        back_rank[empty(&back_rank)[n % 6]] = Some(PieceType::Queen);
+
+        let (first, second) = KNIGHTS[n / 6];
+        let squares = empty(&back_rank);
+        back_rank[squares[first]] = Some(PieceType::Knight);
+        back_rank[squares[second]] = Some(PieceType::Knight);
+
+        let squares = empty(&back_rank);
+        back_rank[squares[0]] = Some(PieceType::Rook);
+        back_rank[squares[1]] = Some(PieceType::King);
+        back_rank[squares[2]] = Some(PieceType::Rook);
+
+        Some(Chess960 {
+            back_rank: back_rank.map(|piece| piece.unwrap()),
+        })
+    }
+
+    /// Creates the starting position with the given back rank
+    ///
+    /// # Arguments
+    /// * `back_rank`: The pieces from the a-file to the h-file, in uppercase
+    ///   (`RNBQKBNR`)
+    ///
+    /// # Returns
+    /// The starting position, or `None` if the back rank doesn't follow the
+    /// Chess960 rules
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::variants::Chess960;
+    ///
+    /// let position = Chess960::from_back_rank("BBQNNRKR").unwrap();
+    /// assert_eq!(position.position_number(), 0);
+    ///
+    /// // The bishops are on squares of the same color
+    /// assert_eq!(Chess960::from_back_rank("BNBQKNRR"), None);
+    /// ```
+    ///
+    pub fn from_back_rank(back_rank: &str) -> Option<Chess960> {
+        let pieces: Vec<PieceType> = back_rank.chars().filter_map(PieceType::from_char).collect();
+        if pieces.len() != 8 || back_rank.chars().count() != 8 {
+            return None;
+        }
+        (0..960)
+            .filter_map(Chess960::from_position_number)
+            .find(|position| position.back_rank[..] == pieces[..])
+    }
+
+    /// Picks one of the 960 starting positions at random
+    ///
+    /// # Returns
+    /// A random starting position
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::variants::Chess960;
+    ///
+    /// let position = Chess960::random();
+    /// assert!(position.position_number() < 960);
+    /// ```
+    ///
+    pub fn random() -> Chess960 {
+        Chess960::random_seeded(RandomState::new().build_hasher().finish())
+    }
+
+    /// Picks one of the 960 starting positions from a seed, the same seed
+    /// always giving the same position
+    ///
+    /// # Arguments
+    /// * `seed`: The seed
+    ///
+    /// # Returns
+    /// The starting position picked by the seed
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::variants::Chess960;
+    ///
+    /// assert_eq!(Chess960::random_seeded(42), Chess960::random_seeded(42));
+    /// ```
+    ///
+    pub fn random_seeded(seed: u64) -> Chess960 {
+        // SplitMix64, so that close seeds give unrelated positions
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        Chess960::from_position_number((z % 960) as u16).unwrap()
+    }
+
+    /// Picks one of the 960 starting positions with the given random number
+    /// generator
+    ///
+    /// # Arguments
+    /// * `rng`: The random number generator
+    ///
+    /// # Returns
+    /// The starting position picked by the generator
+    ///
+    #[cfg(feature = "rand")]
+    pub fn random_with_rng<R: rand::Rng + ?Sized>(rng: &mut R) -> Chess960 {
+        Chess960::from_position_number(rng.gen_range(0..960)).unwrap()
+    }
+
+    /// Returns the Scharnagl number of the position
+    ///
+    /// # Returns
+    /// The number of the position, from 0 to 959
+    ///
+    pub fn position_number(&self) -> u16 {
+        let col = |piece: PieceType, parity: usize| {
+            (0..8)
+                .find(|col| col % 2 == parity && self.back_rank[*col] == piece)
+                .unwrap()
+        };
+        let light_bishop = col(PieceType::Bishop, 1) / 2;
+        let dark_bishop = col(PieceType::Bishop, 0) / 2;
+
+        let others: Vec<PieceType> = self
+            .back_rank
+            .iter()
+            .copied()
+            .filter(|piece| *piece != PieceType::Bishop)
+            .collect();
+        let queen = others
+            .iter()
+            .position(|piece| *piece == PieceType::Queen)
+            .unwrap();
+
+        let knights: Vec<usize> = others
+            .iter()
+            .filter(|piece| **piece != PieceType::Queen)
+            .enumerate()
+            .filter(|(_, piece)| **piece == PieceType::Knight)
+            .map(|(index, _)| index)
+            .collect();
+        let knights = KNIGHTS
+            .iter()
+            .position(|placement| *placement == (knights[0], knights[1]))
+            .unwrap();
+
+        (((knights * 6 + queen) * 4 + dark_bishop) * 4 + light_bishop) as u16
+    }
+
+    /// Returns the pieces of the back rank
+    ///
+    /// # Returns
+    /// The pieces from the a-file to the h-file
+    ///
+    pub fn back_rank(&self) -> [PieceType; 8] {
+        self.back_rank
+    }
+
+    /// Returns the FEN of the starting position, with both sides able to
+    /// castle on both sides
+    ///
+    /// # Returns
+    /// The FEN of the position
+    ///
+    /// # Example
+    /// ```
+    /// use chess_lab::variants::Chess960;
+    ///
+    /// let position = Chess960::from_position_number(0).unwrap();
+    /// assert_eq!(
+    ///     position.fen(),
+    ///     "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"
+    /// );
+    /// ```
+    ///
+    pub fn fen(&self) -> String {
+        format!(
+            "{}/pppppppp/8/8/8/8/PPPPPPPP/{} w KQkq - 0 1",
+            self.to_string().to_lowercase(),
+            self
+        )
+    }
+}
+
+impl Display for Chess960 {
+    /// Displays the back rank, from the a-file to the h-file, in uppercase
+    ///
+    /// # Arguments
+    /// * `f`: The formatter
+    ///
+    /// # Returns
+    /// A result indicating if the back rank was written
+    ///
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        for piece in self.back_rank {
+            write!(f, "{}", piece.to_char())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Chess960;
+    use crate::constants::PieceType;
+
+    #[test]
+    fn test_position_numbers() {
+        for n in 0..960 {
+            let position = Chess960::from_position_number(n).unwrap();
+            assert_eq!(position.position_number(), n);
+            assert_eq!(
+                Chess960::from_back_rank(&position.to_string()),
+                Some(position)
+            );
+
+            let back_rank = position.back_rank();
+            let bishops: Vec<usize> = (0..8)
+                .filter(|col| back_rank[*col] == PieceType::Bishop)
+                .collect();
+            assert_eq!(bishops.len(), 2);
+            assert_ne!(bishops[0] % 2, bishops[1] % 2);
+
+            let rooks: Vec<usize> = (0..8)
+                .filter(|col| back_rank[*col] == PieceType::Rook)
+                .collect();
+            let king = (0..8)
+                .find(|col| back_rank[*col] == PieceType::King)
+                .unwrap();
+            assert!(rooks[0] < king && king < rooks[1]);
+        }
+        assert_eq!(
+            Chess960::from_position_number(518).unwrap().to_string(),
+            "RNBQKBNR"
+        );
+    }
+
+    #[test]
+    fn test_random_seeded() {
+        let positions: Vec<u16> = (0..20)
+            .map(|seed| Chess960::random_seeded(seed).position_number())
+            .collect();
+        let again: Vec<u16> = (0..20)
+            .map(|seed| Chess960::random_seeded(seed).position_number())
+            .collect();
+        assert_eq!(positions, again);
+        assert!(positions.windows(2).any(|pair| pair[0] != pair[1]));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random_with_rng() {
+        use rand::rngs::mock::StepRng;
+
+        let mut first = StepRng::new(7, 13);
+        let mut second = StepRng::new(7, 13);
+        for _ in 0..10 {
+            assert_eq!(
+                Chess960::random_with_rng(&mut first),
+                Chess960::random_with_rng(&mut second)
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_back_rank() {
+        assert_eq!(Chess960::from_back_rank("RNBQKBN"), None);
+        assert_eq!(Chess960::from_back_rank("RNBQKBNRX"), None);
+        assert_eq!(Chess960::from_back_rank("RKNBQBNR"), None);
+        assert_eq!(Chess960::from_back_rank("rnbqkbnr"), None);
+        assert_eq!(
+            Chess960::from_back_rank("RNBKQBNR").map(|position| position.position_number()),
+            Some(534)
+        );
+    }
+}
@@ -0,0 +1,7 @@
+mod armageddon;
+mod chess960;
+mod standard;
+
+pub use armageddon::*;
+pub use chess960::*;
+pub use standard::*;
@@ -0,0 +1,18 @@
+use crate::logic::Game;
+
+/// Standard chess, under the FIDE laws
+/// A plain [`Game`] already follows them, so this is the same type, named
+/// like the other variants
+///
+/// # Example
+/// ```
+/// use chess_lab::constants::Variant;
+/// use chess_lab::variants::StandardChess;
+///
+/// let mut game = StandardChess::default();
+/// game.move_piece("e4").unwrap();
+///
+/// assert_eq!(game.name(), "Standard");
+/// ```
+///
+pub type StandardChess = Game;